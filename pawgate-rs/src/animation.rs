@@ -0,0 +1,264 @@
+//! Overlay opacity animation
+//!
+//! Computes the overlay's window alpha for a point in time; the tray loop
+//! applies it every frame while the keyboard is locked. Windows accessibility
+//! settings (reduced motion, high contrast) take precedence over the config.
+
+use crate::config::OverlayAnimation;
+use std::time::Duration;
+use windows::Win32::Foundation::BOOL;
+use windows::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTW};
+use windows::Win32::UI::WindowsAndMessaging::{
+    SystemParametersInfoW, SPI_GETCLIENTAREAANIMATION, SPI_GETHIGHCONTRAST,
+    SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS,
+};
+
+/// Peak alpha of the `Flash` animation
+const FLASH_ALPHA: u8 = 224;
+
+/// System-wide accessibility settings that affect the overlay
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemPreferences {
+    /// "Show animations in Windows" is on
+    pub animations_enabled: bool,
+    /// A high-contrast theme is active
+    pub high_contrast: bool,
+}
+
+impl Default for SystemPreferences {
+    fn default() -> Self {
+        Self {
+            animations_enabled: true,
+            high_contrast: false,
+        }
+    }
+}
+
+impl SystemPreferences {
+    /// Read the current settings; anything that can't be read keeps its default
+    pub fn query() -> Self {
+        let mut prefs = Self::default();
+        unsafe {
+            let mut animations = BOOL(1);
+            if SystemParametersInfoW(
+                SPI_GETCLIENTAREAANIMATION,
+                0,
+                Some(&mut animations as *mut BOOL as *mut std::ffi::c_void),
+                SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+            )
+            .is_ok()
+            {
+                prefs.animations_enabled = animations.as_bool();
+            }
+
+            let mut contrast = HIGHCONTRASTW {
+                cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+                ..Default::default()
+            };
+            if SystemParametersInfoW(
+                SPI_GETHIGHCONTRAST,
+                contrast.cbSize,
+                Some(&mut contrast as *mut HIGHCONTRASTW as *mut std::ffi::c_void),
+                SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+            )
+            .is_ok()
+            {
+                prefs.high_contrast = contrast.dwFlags.contains(HCF_HIGHCONTRASTON);
+            }
+        }
+        prefs
+    }
+}
+
+/// The animation to actually run, given the configured one and system settings
+///
+/// Reduced motion turns animation off, and so does high contrast, since
+/// fading would undercut the forced full opacity.
+pub fn effective_animation(configured: OverlayAnimation, prefs: SystemPreferences) -> OverlayAnimation {
+    if !prefs.animations_enabled || prefs.high_contrast {
+        OverlayAnimation::None
+    } else {
+        configured
+    }
+}
+
+/// The overlay alpha to use, given the configured one and system settings
+pub fn effective_alpha(configured: u8, prefs: SystemPreferences) -> u8 {
+    if prefs.high_contrast {
+        255
+    } else {
+        configured
+    }
+}
+
+/// How long to fade the overlay in or out, given the configured time and system settings
+///
+/// Fading is motion too, so it follows the same rules as [`effective_animation`].
+pub fn effective_fade(fade_ms: u32, prefs: SystemPreferences) -> Duration {
+    if !prefs.animations_enabled || prefs.high_contrast {
+        Duration::ZERO
+    } else {
+        Duration::from_millis(u64::from(fade_ms))
+    }
+}
+
+/// Window alpha `elapsed` into a fade from `from` to `to`
+///
+/// Eases in and out so the overlay doesn't start or stop abruptly. Before the
+/// fade starts this is `from`, and once `duration` has passed it's `to`.
+pub fn fade_alpha(from: u8, to: u8, elapsed: Duration, duration: Duration) -> u8 {
+    if elapsed >= duration {
+        return to;
+    }
+    let t = elapsed.as_secs_f32() / duration.as_secs_f32();
+    let eased = t * t * (3.0 - 2.0 * t);
+    let (from, to) = (f32::from(from), f32::from(to));
+    (from + (to - from) * eased).round() as u8
+}
+
+/// Window alpha `elapsed` after the lock engaged
+///
+/// `base` is the configured overlay alpha. `Pulse` oscillates between
+/// base/2 and base once per `period`; `Flash` starts at [`FLASH_ALPHA`] and
+/// fades down to base over one `period`, then holds.
+pub fn animation_alpha(
+    animation: OverlayAnimation,
+    base: u8,
+    elapsed: Duration,
+    period: Duration,
+) -> u8 {
+    if period.is_zero() {
+        return base;
+    }
+    let base_f = f32::from(base);
+
+    match animation {
+        OverlayAnimation::None => base,
+        OverlayAnimation::Pulse => {
+            let phase = (elapsed.as_secs_f32() % period.as_secs_f32()) / period.as_secs_f32();
+            // Cosine starts at full opacity and dips smoothly to half
+            let factor = 0.75 + 0.25 * (phase * std::f32::consts::TAU).cos();
+            (base_f * factor).round() as u8
+        }
+        OverlayAnimation::Flash => {
+            if elapsed >= period {
+                return base;
+            }
+            let peak = f32::from(FLASH_ALPHA.max(base));
+            let progress = elapsed.as_secs_f32() / period.as_secs_f32();
+            (peak + (base_f - peak) * progress).round() as u8
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PERIOD: Duration = Duration::from_millis(2000);
+
+    #[test]
+    fn test_none_is_constant() {
+        /// WHY: With animation off the overlay must look exactly as configured.
+        for ms in [0, 500, 1500, 10_000] {
+            let alpha = animation_alpha(OverlayAnimation::None, 100, Duration::from_millis(ms), PERIOD);
+            assert_eq!(alpha, 100);
+        }
+    }
+
+    #[test]
+    fn test_pulse_range() {
+        /// WHY: Pulse must swing between half and full opacity, starting at full.
+        let at = |ms| animation_alpha(OverlayAnimation::Pulse, 100, Duration::from_millis(ms), PERIOD);
+
+        assert_eq!(at(0), 100, "Starts at full opacity");
+        assert_eq!(at(1000), 50, "Half a period in, it's at half opacity");
+        assert_eq!(at(2000), 100, "A full period later it's back to full");
+        for ms in (0..4000).step_by(37) {
+            assert!((50..=100).contains(&at(ms)), "alpha {} out of range at {}ms", at(ms), ms);
+        }
+    }
+
+    #[test]
+    fn test_flash_fades_to_base() {
+        /// WHY: Flash is a one-off cue on lock, then the overlay settles to normal.
+        let at = |ms| animation_alpha(OverlayAnimation::Flash, 76, Duration::from_millis(ms), PERIOD);
+
+        assert_eq!(at(0), FLASH_ALPHA);
+        assert!(at(1000) < FLASH_ALPHA && at(1000) > 76, "Fading in between");
+        assert_eq!(at(2000), 76);
+        assert_eq!(at(60_000), 76);
+    }
+
+    #[test]
+    fn test_fade_alpha_endpoints_clamped() {
+        /// WHY: A fade must start exactly at its origin and land exactly on the target, even late.
+        let fade = Duration::from_millis(400);
+        let at = |ms| fade_alpha(0, 76, Duration::from_millis(ms), fade);
+
+        assert_eq!(at(0), 0, "Fade-in starts invisible");
+        assert_eq!(at(200), 38, "Halfway through it's at half opacity");
+        assert_eq!(at(400), 76);
+        assert_eq!(at(10_000), 76, "Overshooting the duration holds the target");
+
+        let out = |ms| fade_alpha(76, 0, Duration::from_millis(ms), fade);
+        assert_eq!(out(0), 76, "Fade-out starts from the current opacity");
+        assert_eq!(out(400), 0);
+        assert_eq!(out(10_000), 0);
+    }
+
+    #[test]
+    fn test_fade_alpha_is_monotonic() {
+        /// WHY: A fade-in that flickers darker part way through would look broken.
+        let fade = Duration::from_millis(300);
+        let mut previous = 0;
+        for ms in 0..=300 {
+            let alpha = fade_alpha(0, 255, Duration::from_millis(ms), fade);
+            assert!(alpha >= previous, "alpha dropped from {} to {} at {}ms", previous, alpha, ms);
+            previous = alpha;
+        }
+    }
+
+    #[test]
+    fn test_zero_fade_is_instant() {
+        /// WHY: The default fade_ms of 0 must show the overlay at full opacity immediately.
+        assert_eq!(fade_alpha(0, 76, Duration::ZERO, Duration::ZERO), 76);
+        assert_eq!(fade_alpha(76, 0, Duration::ZERO, Duration::ZERO), 0);
+    }
+
+    #[test]
+    fn test_reduced_motion_disables_animation() {
+        /// WHY: Users who turned off animations in Windows must never see the overlay pulse.
+        let reduced = SystemPreferences {
+            animations_enabled: false,
+            high_contrast: false,
+        };
+        assert_eq!(effective_animation(OverlayAnimation::Pulse, reduced), OverlayAnimation::None);
+        assert_eq!(effective_animation(OverlayAnimation::Flash, reduced), OverlayAnimation::None);
+        assert_eq!(effective_alpha(76, reduced), 76, "Reduced motion alone keeps the opacity");
+        assert_eq!(effective_fade(500, reduced), Duration::ZERO, "No fading either");
+
+        let normal = SystemPreferences::default();
+        assert_eq!(effective_animation(OverlayAnimation::Pulse, normal), OverlayAnimation::Pulse);
+        assert_eq!(effective_alpha(76, normal), 76);
+        assert_eq!(effective_fade(500, normal), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_high_contrast_forces_static_full_opacity() {
+        /// WHY: In high contrast the overlay must be fully visible, so it can't fade either.
+        let contrast = SystemPreferences {
+            animations_enabled: true,
+            high_contrast: true,
+        };
+        assert_eq!(effective_alpha(76, contrast), 255);
+        assert_eq!(effective_animation(OverlayAnimation::Pulse, contrast), OverlayAnimation::None);
+    }
+
+    #[test]
+    fn test_zero_period_disables_animation() {
+        /// WHY: A period of 0 from a hand-edited config must not divide by zero.
+        let alpha = animation_alpha(OverlayAnimation::Pulse, 80, Duration::from_millis(123), Duration::ZERO);
+        assert_eq!(alpha, 80);
+    }
+}
@@ -0,0 +1,177 @@
+//! Per-application rules for the hotkey and auto-lock
+//!
+//! A background thread polls the foreground window's process and applies the
+//! first matching `app_rules` entry: `disable` sets `AppState::app_disabled`,
+//! which the keyboard hook and idle monitor treat like a snooze, and
+//! `forcelock` locks when that app comes to the foreground.
+
+use crate::config::{AppRule, AppRuleAction};
+use crate::events::Trigger;
+use crate::AppState;
+use log::{debug, info};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use windows::core::PWSTR;
+use windows::Win32::Foundation::CloseHandle;
+use windows::Win32::System::Threading::{
+    OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+};
+use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowThreadProcessId};
+
+/// How often the foreground app is checked
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Action of the first rule matching `process`, or `Normal` if none does
+///
+/// `process` may be a bare executable name or a full path. Names compare
+/// case-insensitively, and ".exe" may be left off either side.
+pub fn matching_action(rules: &[AppRule], process: &str) -> AppRuleAction {
+    let process = exe_stem(process.rsplit(['\\', '/']).next().unwrap_or_default());
+    rules
+        .iter()
+        .find(|rule| exe_stem(&rule.app).eq_ignore_ascii_case(process))
+        .map(|rule| rule.action)
+        .unwrap_or_default()
+}
+
+/// `name` without surrounding whitespace and a trailing ".exe"
+fn exe_stem(name: &str) -> &str {
+    let name = name.trim();
+    match name.len().checked_sub(4) {
+        Some(split) if name.is_char_boundary(split) && name[split..].eq_ignore_ascii_case(".exe") => &name[..split],
+        _ => name,
+    }
+}
+
+/// Whether switching from `previous` to `current` should lock
+///
+/// WHY: Only the switch to a forcelock app locks, so unlocking while it
+/// stays in the foreground doesn't immediately lock again.
+pub fn should_force_lock(previous: AppRuleAction, current: AppRuleAction) -> bool {
+    current == AppRuleAction::ForceLock && previous != AppRuleAction::ForceLock
+}
+
+/// Run the foreground watcher until the app quits
+pub fn run_app_rule_monitor(state: Arc<AppState>, rules: Vec<AppRule>) {
+    info!("App rules enabled for {} app(s)", rules.len());
+    let mut previous = AppRuleAction::Normal;
+
+    while !state.should_quit.load(Ordering::SeqCst) {
+        std::thread::sleep(POLL_INTERVAL);
+
+        // Elevated or exiting processes can't be queried; treat them as unmatched
+        let action = foreground_process_name()
+            .map(|process| matching_action(&rules, &process))
+            .unwrap_or_default();
+        if action != previous {
+            debug!("Foreground app rule: {:?}", action);
+        }
+
+        state
+            .app_disabled
+            .store(action == AppRuleAction::Disable, Ordering::SeqCst);
+
+        // Forced locks are an auto-lock, so they pause while snoozed too
+        if should_force_lock(previous, action)
+            && !state.snoozed.load(Ordering::SeqCst)
+            && !state.locked.load(Ordering::SeqCst)
+        {
+            info!("Foreground app has a forcelock rule, locking");
+            state.set_locked(true, Trigger::AppRule);
+        }
+        previous = action;
+    }
+    state.app_disabled.store(false, Ordering::SeqCst);
+}
+
+/// Full path of the executable that owns the foreground window
+fn foreground_process_name() -> Option<String> {
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0.is_null() {
+            return None;
+        }
+
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        if pid == 0 {
+            return None;
+        }
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid).ok()?;
+        // MAX_PATH; longer paths just fail to match
+        let mut buffer = [0u16; 260];
+        let mut len = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, PWSTR(buffer.as_mut_ptr()), &mut len);
+        let _ = CloseHandle(process);
+        result.ok()?;
+
+        Some(String::from_utf16_lossy(&buffer[..len as usize]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> Vec<AppRule> {
+        vec![
+            AppRule {
+                app: "premiere.exe".to_string(),
+                action: AppRuleAction::Disable,
+            },
+            AppRule {
+                app: "WindowsTerminal".to_string(),
+                action: AppRuleAction::ForceLock,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_matching_rule_by_exe_name() {
+        /// WHY: Rules are written with the plain executable name users see in Task Manager.
+        assert_eq!(matching_action(&rules(), "premiere.exe"), AppRuleAction::Disable);
+        assert_eq!(matching_action(&rules(), "WindowsTerminal.exe"), AppRuleAction::ForceLock);
+    }
+
+    #[test]
+    fn test_matching_rule_from_full_path() {
+        /// WHY: Windows reports the full image path, which must still match the bare name.
+        assert_eq!(
+            matching_action(&rules(), r"C:\Program Files\Adobe\Premiere\Premiere.EXE"),
+            AppRuleAction::Disable
+        );
+    }
+
+    #[test]
+    fn test_unmatched_process_is_normal() {
+        /// WHY: Apps without a rule must leave the hotkey and auto-lock alone.
+        assert_eq!(matching_action(&rules(), "notepad.exe"), AppRuleAction::Normal);
+        assert_eq!(matching_action(&[], "premiere.exe"), AppRuleAction::Normal);
+        assert_eq!(matching_action(&rules(), "premiere.exe.bak"), AppRuleAction::Normal);
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        /// WHY: A predictable order lets users put a specific override first.
+        let mut rules = rules();
+        rules.insert(
+            0,
+            AppRule {
+                app: "PREMIERE".to_string(),
+                action: AppRuleAction::Normal,
+            },
+        );
+        assert_eq!(matching_action(&rules, "premiere.exe"), AppRuleAction::Normal);
+    }
+
+    #[test]
+    fn test_force_lock_only_on_switch() {
+        /// WHY: Unlocking inside a forcelock app must not lock again on the next poll.
+        assert!(should_force_lock(AppRuleAction::Normal, AppRuleAction::ForceLock));
+        assert!(should_force_lock(AppRuleAction::Disable, AppRuleAction::ForceLock));
+        assert!(!should_force_lock(AppRuleAction::ForceLock, AppRuleAction::ForceLock));
+        assert!(!should_force_lock(AppRuleAction::ForceLock, AppRuleAction::Normal));
+    }
+}
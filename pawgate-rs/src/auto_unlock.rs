@@ -0,0 +1,89 @@
+//! Timed unlock for trying PawGate out safely
+//!
+//! `pawgate test-lock <seconds>` (or the `auto_unlock_secs` setting) locks
+//! the keyboard and releases it again after a fixed time, whatever is typed.
+//! The tray loop owns an [`AutoUnlock`] and unlocks when it fires.
+
+use std::time::{Duration, Instant};
+
+/// Value of `AppState::test_lock_request` when no request is waiting
+pub const NO_REQUEST: u32 = 0;
+
+/// State machine for a deadline after which the keyboard unlocks
+#[derive(Debug, Default)]
+pub struct AutoUnlock {
+    deadline: Option<Instant>,
+}
+
+impl AutoUnlock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Unlock `duration` after `now`, replacing any running deadline
+    pub fn start(&mut self, now: Instant, duration: Duration) {
+        self.deadline = Some(now + duration);
+    }
+
+    /// Forget the deadline (the keyboard was unlocked some other way)
+    pub fn cancel(&mut self) {
+        self.deadline = None;
+    }
+
+    /// Time left before the unlock, or None if none is scheduled
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.deadline.map(|deadline| deadline.saturating_duration_since(now))
+    }
+
+    /// Advance the timer; returns true exactly once, when it's time to unlock
+    pub fn tick(&mut self, now: Instant) -> bool {
+        if self.remaining(now) == Some(Duration::ZERO) {
+            self.deadline = None;
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlocks_after_duration() {
+        /// WHY: A test lock must release the keyboard exactly when promised, and only once.
+        let start = Instant::now();
+        let mut timer = AutoUnlock::new();
+        timer.start(start, Duration::from_secs(5));
+
+        assert!(!timer.tick(start + Duration::from_millis(4999)));
+        assert_eq!(timer.remaining(start + Duration::from_secs(2)), Some(Duration::from_secs(3)));
+        assert!(timer.tick(start + Duration::from_secs(5)));
+        assert_eq!(timer.remaining(start + Duration::from_secs(5)), None);
+        assert!(!timer.tick(start + Duration::from_secs(6)), "Should fire only once");
+    }
+
+    #[test]
+    fn test_late_tick_still_unlocks() {
+        /// WHY: If the tray loop stalls past the deadline, the next frame must still unlock.
+        let start = Instant::now();
+        let mut timer = AutoUnlock::new();
+        timer.start(start, Duration::from_secs(1));
+        assert!(timer.tick(start + Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn test_cancel_and_restart() {
+        /// WHY: Unlocking early cancels the timer, and a new test lock gets its full time.
+        let start = Instant::now();
+        let mut timer = AutoUnlock::new();
+        timer.start(start, Duration::from_secs(5));
+        timer.cancel();
+        assert!(!timer.tick(start + Duration::from_secs(10)));
+
+        let restart = start + Duration::from_secs(10);
+        timer.start(restart, Duration::from_secs(5));
+        assert!(!timer.tick(restart + Duration::from_secs(4)));
+        assert!(timer.tick(restart + Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,216 @@
+//! Starting PawGate at login
+//!
+//! `pawgate autostart enable` writes a value under the per-user Run key
+//! pointing at the running executable; `disable` removes it and `status`
+//! reports whether it's there. No admin rights are needed for HKCU.
+
+use std::path::Path;
+use windows::core::HSTRING;
+use windows::Win32::Foundation::{ERROR_FILE_NOT_FOUND, ERROR_SUCCESS, WIN32_ERROR};
+use windows::Win32::System::Registry::{
+    RegDeleteKeyValueW, RegGetValueW, RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ, RRF_RT_REG_SZ,
+};
+
+/// Registry key (under HKEY_CURRENT_USER) whose values run at login
+pub const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+/// Name of PawGate's value under [`RUN_KEY`]
+pub const RUN_VALUE_NAME: &str = "PawGate";
+
+/// What `pawgate autostart` was asked to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutostartAction {
+    Enable,
+    Disable,
+    Status,
+}
+
+/// Storage for the Run value, so the dispatch logic can be tested without
+/// touching the real registry
+pub trait RunEntry {
+    /// The current command line, or None if there is no value
+    fn get(&self) -> Result<Option<String>, String>;
+    fn set(&mut self, command_line: &str) -> Result<(), String>;
+    /// Remove the value; removing a missing value is not an error
+    fn remove(&mut self) -> Result<(), String>;
+}
+
+/// Command line to register: the quoted executable, plus `--config` if a
+/// config file was given, so login starts the same setup
+pub fn command_line(exe: &Path, config: Option<&Path>) -> String {
+    // WHY: Quoting keeps paths with spaces ("C:\Program Files\...") intact
+    let mut line = format!("\"{}\"", exe.display());
+    if let Some(config) = config {
+        line.push_str(&format!(" --config \"{}\"", config.display()));
+    }
+    line
+}
+
+/// Carry out `action` against `entry`, returning the message to print
+///
+/// `command_line` is what `enable` registers and what `status` compares the
+/// existing value against.
+pub fn run(action: AutostartAction, entry: &mut impl RunEntry, command_line: &str) -> Result<String, String> {
+    match action {
+        AutostartAction::Enable => {
+            entry.set(command_line)?;
+            Ok(format!("PawGate will start at login ({})", command_line))
+        }
+        AutostartAction::Disable => {
+            entry.remove()?;
+            Ok("PawGate will no longer start at login".to_string())
+        }
+        AutostartAction::Status => Ok(match entry.get()? {
+            None => "Autostart is disabled".to_string(),
+            Some(existing) if existing == command_line => format!("Autostart is enabled ({})", existing),
+            // E.g. PawGate was moved since; `enable` again fixes it
+            Some(existing) => format!(
+                "Autostart is enabled, but runs a different command: {}\nRun \"pawgate autostart enable\" to point it here.",
+                existing
+            ),
+        }),
+    }
+}
+
+/// The real Run value under HKEY_CURRENT_USER
+pub struct RegistryRunEntry;
+
+impl RunEntry for RegistryRunEntry {
+    fn get(&self) -> Result<Option<String>, String> {
+        let key = HSTRING::from(RUN_KEY);
+        let name = HSTRING::from(RUN_VALUE_NAME);
+        unsafe {
+            // First ask for the size, then read the value
+            let mut size = 0u32;
+            let status = RegGetValueW(HKEY_CURRENT_USER, &key, &name, RRF_RT_REG_SZ, None, None, Some(&mut size));
+            if status == ERROR_FILE_NOT_FOUND {
+                return Ok(None);
+            }
+            check(status)?;
+
+            let mut buffer = vec![0u16; (size as usize).div_ceil(2)];
+            let status = RegGetValueW(
+                HKEY_CURRENT_USER,
+                &key,
+                &name,
+                RRF_RT_REG_SZ,
+                None,
+                Some(buffer.as_mut_ptr().cast()),
+                Some(&mut size),
+            );
+            check(status)?;
+
+            let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+            Ok(Some(String::from_utf16_lossy(&buffer[..len])))
+        }
+    }
+
+    fn set(&mut self, command_line: &str) -> Result<(), String> {
+        let data: Vec<u16> = command_line.encode_utf16().chain(std::iter::once(0)).collect();
+        let status = unsafe {
+            RegSetKeyValueW(
+                HKEY_CURRENT_USER,
+                &HSTRING::from(RUN_KEY),
+                &HSTRING::from(RUN_VALUE_NAME),
+                REG_SZ.0,
+                Some(data.as_ptr().cast()),
+                (data.len() * 2) as u32,
+            )
+        };
+        check(status)
+    }
+
+    fn remove(&mut self) -> Result<(), String> {
+        let status = unsafe {
+            RegDeleteKeyValueW(HKEY_CURRENT_USER, &HSTRING::from(RUN_KEY), &HSTRING::from(RUN_VALUE_NAME))
+        };
+        if status == ERROR_FILE_NOT_FOUND {
+            return Ok(());
+        }
+        check(status)
+    }
+}
+
+fn check(status: WIN32_ERROR) -> Result<(), String> {
+    if status == ERROR_SUCCESS {
+        Ok(())
+    } else {
+        Err(format!("registry error: {}", windows::core::Error::from(status.to_hresult()).message()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// A Run value held in memory
+    #[derive(Default)]
+    struct FakeEntry {
+        value: Option<String>,
+    }
+
+    impl RunEntry for FakeEntry {
+        fn get(&self) -> Result<Option<String>, String> {
+            Ok(self.value.clone())
+        }
+
+        fn set(&mut self, command_line: &str) -> Result<(), String> {
+            self.value = Some(command_line.to_string());
+            Ok(())
+        }
+
+        fn remove(&mut self) -> Result<(), String> {
+            self.value = None;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_command_line_quotes_paths() {
+        /// WHY: An unquoted path with spaces would run "C:\Program" at login.
+        let exe = PathBuf::from(r"C:\Program Files\PawGate\pawgate.exe");
+        assert_eq!(command_line(&exe, None), r#""C:\Program Files\PawGate\pawgate.exe""#);
+    }
+
+    #[test]
+    fn test_command_line_keeps_config_flag() {
+        /// WHY: Registering from `pawgate --config ...` should start that same setup at login.
+        let exe = PathBuf::from(r"C:\Tools\pawgate.exe");
+        let config = PathBuf::from(r"D:\setups\work.json");
+        assert_eq!(
+            command_line(&exe, Some(&config)),
+            r#""C:\Tools\pawgate.exe" --config "D:\setups\work.json""#
+        );
+    }
+
+    #[test]
+    fn test_enable_then_disable() {
+        /// WHY: Enable must write the command line and disable must remove it again.
+        let mut entry = FakeEntry::default();
+        let line = r#""C:\Tools\pawgate.exe""#;
+
+        run(AutostartAction::Enable, &mut entry, line).unwrap();
+        assert_eq!(entry.value.as_deref(), Some(line));
+
+        run(AutostartAction::Disable, &mut entry, line).unwrap();
+        assert_eq!(entry.value, None);
+        assert!(run(AutostartAction::Disable, &mut entry, line).is_ok(), "Disabling twice is fine");
+    }
+
+    #[test]
+    fn test_status_reports_state() {
+        /// WHY: Status must distinguish off, on, and on-but-pointing-elsewhere (a moved install).
+        let line = r#""C:\Tools\pawgate.exe""#;
+        let mut entry = FakeEntry::default();
+        assert_eq!(run(AutostartAction::Status, &mut entry, line).unwrap(), "Autostart is disabled");
+
+        entry.value = Some(line.to_string());
+        let status = run(AutostartAction::Status, &mut entry, line).unwrap();
+        assert!(status.starts_with("Autostart is enabled ("), "{}", status);
+
+        entry.value = Some(r#""C:\Old\pawgate.exe""#.to_string());
+        let status = run(AutostartAction::Status, &mut entry, line).unwrap();
+        assert!(status.contains("different command"), "{}", status);
+        assert!(status.contains(r"C:\Old\pawgate.exe"), "{}", status);
+    }
+}
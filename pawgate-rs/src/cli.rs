@@ -0,0 +1,603 @@
+//! Command-line interface
+//!
+//! With no arguments PawGate starts normally (tray icon + keyboard hook).
+//! Subcommands control an already running instance from scripts.
+
+use crate::autostart::AutostartAction;
+use crate::config::{describe_hotkey, parse_hotkey_sequence_detailed, Config};
+use std::path::PathBuf;
+
+/// What the process was asked to do
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// Start PawGate (no subcommand given)
+    Run,
+    /// Lock the running instance
+    Lock,
+    /// Unlock the running instance
+    Unlock,
+    /// Toggle the running instance's lock
+    Toggle,
+    /// Ignore the hotkey and auto-lock for this many minutes (0 = resume)
+    Snooze(u32),
+    /// Lock the running instance and unlock it after this many seconds
+    TestLock(u32),
+    /// Print the effective config (file + env overrides) as JSON
+    ConfigShow,
+    /// Print the resolved config file path
+    ConfigPath,
+    /// Print a JSON Schema for the config file
+    ConfigSchema,
+    /// Open the config file in its default editor
+    ConfigEdit,
+    /// Validate and save one config setting
+    ConfigSet { key: String, value: String },
+    /// Check whether a hotkey string parses, without changing anything
+    TestHotkey(String),
+    /// List the available config profiles
+    ProfileList,
+    /// Switch to the named config profile
+    ProfileUse(String),
+    /// Turn PawGate back on (sets `enabled` in the config)
+    Enable,
+    /// Turn PawGate off without uninstalling it (clears `enabled`)
+    Disable,
+    /// Register, unregister or check starting at login
+    Autostart(AutostartAction),
+    /// Print the lock session history with totals
+    Sessions,
+    /// Print usage
+    Help,
+}
+
+/// Reason the command line could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CliError {
+    /// The first argument isn't a known subcommand
+    UnknownCommand(String),
+    /// Extra arguments after a subcommand that takes none
+    UnexpectedArgument(String),
+    /// A subcommand is missing a required argument
+    MissingArgument(&'static str),
+    /// An argument has the wrong form (the argument, what was expected)
+    InvalidArgument(String, &'static str),
+}
+
+impl std::fmt::Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownCommand(cmd) => write!(f, "unknown command '{}'", cmd),
+            Self::UnexpectedArgument(arg) => write!(f, "unexpected argument '{}'", arg),
+            Self::MissingArgument(what) => write!(f, "missing {}", what),
+            Self::InvalidArgument(arg, expected) => {
+                write!(f, "invalid argument '{}': expected {}", arg, expected)
+            }
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Usage text printed by `pawgate help`
+pub const USAGE: &str = "\
+Usage: pawgate [--config <file>] [-v|-vv|-vvv] [COMMAND]
+
+With no command, starts PawGate in the system tray.
+
+Options:
+  --config <file>
+                Use this config file instead of ~/.pawgate/config.json
+  -v, -vv, -vvv Log to the console: lock changes and config loading, then
+                each hotkey press, then everything
+
+Commands:
+  lock          Lock the keyboard of the running instance
+  unlock        Unlock the keyboard of the running instance
+  toggle        Toggle the lock of the running instance
+  snooze <minutes>
+                Ignore the hotkey and auto-lock for a while (snooze 0 resumes)
+  test-lock <seconds>
+                Lock, then unlock automatically after that many seconds
+  config show   Print the effective config as JSON
+  config path   Print the config file location
+  config schema Print a JSON Schema for config.json
+  config edit   Open the config file in its default editor
+  config set <key> <value>
+                Validate and save one setting (e.g. config set opacity 0.5)
+  test-hotkey <combo>
+                Check how a hotkey string is parsed (e.g. test-hotkey ctrl+shift+f5)
+  profile list  List the config profiles in ~/.pawgate/profiles
+  profile use <name>
+                Switch to a profile; \"default\" goes back to config.json's settings
+  enable        Turn PawGate back on (takes effect at the next start)
+  disable       Keep PawGate from locking or watching hotkeys until enabled
+  autostart enable|disable|status
+                Start PawGate at login (or stop), or check whether it does
+  sessions      List recent lock sessions and the total time locked
+  help          Show this message";
+
+/// Remove `--config <file>` (or `--config=<file>`) from the arguments
+///
+/// Returns the file, if given, and the remaining arguments for
+/// [`parse_args`]. The option may appear before or after the subcommand.
+pub fn take_config_flag<I, S>(args: I) -> Result<(Option<PathBuf>, Vec<String>), CliError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut config = None;
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let arg = arg.as_ref();
+        if arg == "--config" {
+            let path = args.next().ok_or(CliError::MissingArgument("config file after --config"))?;
+            config = Some(PathBuf::from(path.as_ref()));
+        } else if let Some(path) = arg.strip_prefix("--config=") {
+            if path.is_empty() {
+                return Err(CliError::MissingArgument("config file after --config"));
+            }
+            config = Some(PathBuf::from(path));
+        } else {
+            rest.push(arg.to_string());
+        }
+    }
+    Ok((config, rest))
+}
+
+/// Remove `-v`, `-vv`, `-vvv` and `--verbose` from the arguments
+///
+/// Returns how many `v`s were given in total and the remaining arguments.
+/// Like `--config`, they may appear before or after the subcommand.
+pub fn take_verbosity_flags<I, S>(args: I) -> (u8, Vec<String>)
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut verbosity: u8 = 0;
+    let mut rest = Vec::new();
+    for arg in args {
+        let arg = arg.as_ref();
+        match arg.strip_prefix('-') {
+            Some("-verbose") => verbosity = verbosity.saturating_add(1),
+            Some(vs) if !vs.is_empty() && vs.bytes().all(|b| b == b'v') => {
+                verbosity = verbosity.saturating_add(vs.len().min(u8::MAX as usize) as u8);
+            }
+            _ => rest.push(arg.to_string()),
+        }
+    }
+    (verbosity, rest)
+}
+
+/// Parse command-line arguments (excluding the program name)
+pub fn parse_args<I, S>(args: I) -> Result<Command, CliError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut args = args.into_iter();
+    let Some(first) = args.next() else {
+        return Ok(Command::Run);
+    };
+
+    let command = match first.as_ref() {
+        "lock" => Command::Lock,
+        "unlock" => Command::Unlock,
+        "toggle" => Command::Toggle,
+        "enable" => Command::Enable,
+        "disable" => Command::Disable,
+        "sessions" => Command::Sessions,
+        "snooze" => {
+            let minutes = args.next().ok_or(CliError::MissingArgument("snooze minutes"))?;
+            let minutes = minutes.as_ref();
+            match minutes.parse() {
+                Ok(minutes) => Command::Snooze(minutes),
+                Err(_) => {
+                    return Err(CliError::InvalidArgument(
+                        minutes.to_string(),
+                        "a whole number of minutes",
+                    ))
+                }
+            }
+        }
+        "test-lock" => {
+            let secs = args.next().ok_or(CliError::MissingArgument("test-lock seconds"))?;
+            let secs = secs.as_ref();
+            match secs.parse() {
+                Ok(secs) if secs > 0 => Command::TestLock(secs),
+                _ => return Err(CliError::InvalidArgument(secs.to_string(), "a positive number of seconds")),
+            }
+        }
+        "help" | "--help" | "-h" => Command::Help,
+        "config" => match args.next() {
+            Some(sub) => match sub.as_ref() {
+                "show" => Command::ConfigShow,
+                "path" => Command::ConfigPath,
+                "schema" => Command::ConfigSchema,
+                "edit" => Command::ConfigEdit,
+                "set" => {
+                    let key = args.next().ok_or(CliError::MissingArgument("setting name"))?;
+                    let value = args.next().ok_or(CliError::MissingArgument("setting value"))?;
+                    Command::ConfigSet {
+                        key: key.as_ref().to_string(),
+                        value: value.as_ref().to_string(),
+                    }
+                }
+                other => return Err(CliError::UnknownCommand(format!("config {}", other))),
+            },
+            None => return Err(CliError::MissingArgument("config subcommand (show, path, schema, edit, set)")),
+        },
+        "autostart" => match args.next() {
+            Some(sub) => match sub.as_ref() {
+                "enable" => Command::Autostart(AutostartAction::Enable),
+                "disable" => Command::Autostart(AutostartAction::Disable),
+                "status" => Command::Autostart(AutostartAction::Status),
+                other => return Err(CliError::UnknownCommand(format!("autostart {}", other))),
+            },
+            None => return Err(CliError::MissingArgument("autostart action (enable, disable, status)")),
+        },
+        "profile" => match args.next() {
+            Some(sub) => match sub.as_ref() {
+                "list" => Command::ProfileList,
+                "use" => {
+                    let name = args.next().ok_or(CliError::MissingArgument("profile name"))?;
+                    Command::ProfileUse(name.as_ref().to_string())
+                }
+                other => return Err(CliError::UnknownCommand(format!("profile {}", other))),
+            },
+            None => return Err(CliError::MissingArgument("profile subcommand (list, use)")),
+        },
+        "test-hotkey" => {
+            let combo = args.next().ok_or(CliError::MissingArgument("hotkey to test"))?;
+            Command::TestHotkey(combo.as_ref().to_string())
+        }
+        other => return Err(CliError::UnknownCommand(other.to_string())),
+    };
+
+    if let Some(extra) = args.next() {
+        return Err(CliError::UnexpectedArgument(extra.as_ref().to_string()));
+    }
+
+    Ok(command)
+}
+
+/// Open the config file in its default editor, for `pawgate config edit`
+/// and the tray menu
+///
+/// Creates the file with default settings first if it doesn't exist yet.
+pub fn open_config() -> Result<(), String> {
+    use windows::core::{HSTRING, PCWSTR};
+    use windows::Win32::UI::Shell::ShellExecuteW;
+    use windows::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    let path = Config::config_path();
+    if !path.exists() {
+        Config::load().map_err(|e| format!("can't create {}: {}", path.display(), e))?;
+    }
+
+    let file = HSTRING::from(path.as_os_str());
+    let result = unsafe { ShellExecuteW(None, &HSTRING::from("open"), &file, PCWSTR::null(), PCWSTR::null(), SW_SHOWNORMAL) };
+    // Values above 32 mean success
+    if result.0 as isize <= 32 {
+        return Err(format!("couldn't open {}", path.display()));
+    }
+    Ok(())
+}
+
+/// Explain how `combo` parses, for `pawgate test-hotkey`
+///
+/// Uses the same parser as the config, so anything it accepts (including
+/// sequences like "ctrl+b,l") works as `hotkey`. Returns the breakdown on
+/// success, or a message naming the bad token.
+pub fn hotkey_report(combo: &str) -> Result<String, String> {
+    let steps = parse_hotkey_sequence_detailed(combo)
+        .map_err(|e| format!("\"{}\" is not a valid hotkey: {}", combo, e))?;
+
+    let mut report = if steps.len() == 1 {
+        format!("\"{}\" is a valid hotkey", combo)
+    } else {
+        format!("\"{}\" is a valid hotkey sequence of {} steps", combo, steps.len())
+    };
+    for (i, &(modifiers, vk)) in steps.iter().enumerate() {
+        let indent = if steps.len() == 1 {
+            "  "
+        } else {
+            report.push_str(&format!("\n  Step {}: {}", i + 1, describe_hotkey(modifiers, vk)));
+            "    "
+        };
+        // WHY: describe_hotkey puts the key last, so what precedes it is the modifiers
+        let key = describe_hotkey(0, vk);
+        let combined = describe_hotkey(modifiers, vk);
+        let modifier_names = combined
+            .strip_suffix(key.as_str())
+            .map(|prefix| prefix.trim_end_matches('+'))
+            .filter(|prefix| !prefix.is_empty())
+            .unwrap_or("(none)");
+        report.push_str(&format!(
+            "\n{indent}Modifiers: {}\n{indent}Key:       {} (virtual key {:#04X})",
+            modifier_names,
+            key,
+            vk,
+            indent = indent
+        ));
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_args_runs_app() {
+        /// WHY: Double-clicking pawgate.exe must still start the tray app.
+        assert_eq!(parse_args(Vec::<String>::new()), Ok(Command::Run));
+    }
+
+    #[test]
+    fn test_lock_unlock_toggle() {
+        /// WHY: These are the subcommands scripts rely on.
+        assert_eq!(parse_args(["lock"]), Ok(Command::Lock));
+        assert_eq!(parse_args(["unlock"]), Ok(Command::Unlock));
+        assert_eq!(parse_args(["toggle"]), Ok(Command::Toggle));
+    }
+
+    #[test]
+    fn test_enable_disable() {
+        /// WHY: The kill switch must be reachable without hand-editing the config.
+        assert_eq!(parse_args(["enable"]), Ok(Command::Enable));
+        assert_eq!(parse_args(["disable"]), Ok(Command::Disable));
+        assert_eq!(
+            parse_args(["disable", "now"]),
+            Err(CliError::UnexpectedArgument("now".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_sessions() {
+        /// WHY: The session history is read from the command line, not the tray.
+        assert_eq!(parse_args(["sessions"]), Ok(Command::Sessions));
+        assert_eq!(
+            parse_args(["sessions", "all"]),
+            Err(CliError::UnexpectedArgument("all".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_autostart_subcommands() {
+        /// WHY: Each autostart action must reach the registry code, and typos must not.
+        assert_eq!(
+            parse_args(["autostart", "enable"]),
+            Ok(Command::Autostart(AutostartAction::Enable))
+        );
+        assert_eq!(
+            parse_args(["autostart", "disable"]),
+            Ok(Command::Autostart(AutostartAction::Disable))
+        );
+        assert_eq!(
+            parse_args(["autostart", "status"]),
+            Ok(Command::Autostart(AutostartAction::Status))
+        );
+        assert_eq!(
+            parse_args(["autostart"]),
+            Err(CliError::MissingArgument("autostart action (enable, disable, status)"))
+        );
+        assert_eq!(
+            parse_args(["autostart", "on"]),
+            Err(CliError::UnknownCommand("autostart on".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_snooze_minutes() {
+        /// WHY: `snooze` needs a count of minutes; anything else is a usage error.
+        assert_eq!(parse_args(["snooze", "10"]), Ok(Command::Snooze(10)));
+        assert_eq!(parse_args(["snooze", "0"]), Ok(Command::Snooze(0)));
+        assert_eq!(
+            parse_args(["snooze"]),
+            Err(CliError::MissingArgument("snooze minutes"))
+        );
+        assert_eq!(
+            parse_args(["snooze", "ten"]),
+            Err(CliError::InvalidArgument("ten".to_string(), "a whole number of minutes"))
+        );
+    }
+
+    #[test]
+    fn test_help_aliases() {
+        /// WHY: Users reach for --help and -h out of habit.
+        assert_eq!(parse_args(["help"]), Ok(Command::Help));
+        assert_eq!(parse_args(["--help"]), Ok(Command::Help));
+        assert_eq!(parse_args(["-h"]), Ok(Command::Help));
+    }
+
+    #[test]
+    fn test_unknown_command_rejected() {
+        /// WHY: A typo in a script should fail loudly, not start a second GUI.
+        assert_eq!(
+            parse_args(["lcok"]),
+            Err(CliError::UnknownCommand("lcok".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_extra_argument_rejected() {
+        /// WHY: Unexpected arguments usually mean the user expected different behavior.
+        assert_eq!(
+            parse_args(["lock", "now"]),
+            Err(CliError::UnexpectedArgument("now".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_test_lock_seconds() {
+        /// WHY: A test lock needs a real duration; 0 or junk would lock with no way out but the hotkey.
+        assert_eq!(parse_args(["test-lock", "5"]), Ok(Command::TestLock(5)));
+        assert_eq!(
+            parse_args(["test-lock"]),
+            Err(CliError::MissingArgument("test-lock seconds"))
+        );
+        for bad in ["0", "-3", "soon"] {
+            assert!(
+                matches!(parse_args(["test-lock", bad]), Err(CliError::InvalidArgument(_, _))),
+                "'{}' should be rejected",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_take_config_flag() {
+        /// WHY: `--config` may come before or after the subcommand and must not reach parse_args.
+        let (path, rest) = take_config_flag(["--config", r"D:\pawgate\alt.json"]).unwrap();
+        assert_eq!(path, Some(PathBuf::from(r"D:\pawgate\alt.json")));
+        assert!(rest.is_empty());
+        assert_eq!(parse_args(rest), Ok(Command::Run));
+
+        let (path, rest) = take_config_flag(["config", "show", "--config=alt.toml"]).unwrap();
+        assert_eq!(path, Some(PathBuf::from("alt.toml")));
+        assert_eq!(rest, vec!["config", "show"]);
+
+        let (path, rest) = take_config_flag(["lock"]).unwrap();
+        assert_eq!(path, None);
+        assert_eq!(rest, vec!["lock"]);
+    }
+
+    #[test]
+    fn test_take_verbosity_flags() {
+        /// WHY: Each v adds detail, and the flags must not reach parse_args wherever they appear.
+        assert_eq!(take_verbosity_flags(["-v"]), (1, vec![]));
+        assert_eq!(take_verbosity_flags(["-vv", "lock"]), (2, vec!["lock".to_string()]));
+        assert_eq!(take_verbosity_flags(["lock", "-v", "-v", "--verbose"]), (3, vec!["lock".to_string()]));
+        assert_eq!(take_verbosity_flags(["snooze", "5"]), (0, vec!["snooze".to_string(), "5".to_string()]));
+
+        let (verbosity, rest) = take_verbosity_flags(["-vx", "-", "-h"]);
+        assert_eq!(verbosity, 0);
+        assert_eq!(rest, vec!["-vx", "-", "-h"], "Other dashed arguments are left for parse_args");
+    }
+
+    #[test]
+    fn test_config_flag_requires_file() {
+        /// WHY: A dangling `--config` is a typo; starting with the default file would hide it.
+        assert!(matches!(take_config_flag(["--config"]), Err(CliError::MissingArgument(_))));
+        assert!(matches!(take_config_flag(["--config="]), Err(CliError::MissingArgument(_))));
+    }
+
+    #[test]
+    fn test_config_subcommands() {
+        /// WHY: `config show` and `config path` are used by provisioning scripts.
+        assert_eq!(parse_args(["config", "show"]), Ok(Command::ConfigShow));
+        assert_eq!(parse_args(["config", "path"]), Ok(Command::ConfigPath));
+        assert_eq!(parse_args(["config", "schema"]), Ok(Command::ConfigSchema));
+        assert_eq!(parse_args(["config", "edit"]), Ok(Command::ConfigEdit));
+    }
+
+    #[test]
+    fn test_config_set() {
+        /// WHY: Key and value are passed through verbatim for Config::set_field to validate.
+        assert_eq!(
+            parse_args(["config", "set", "overlay_color", "#1565C0"]),
+            Ok(Command::ConfigSet {
+                key: "overlay_color".to_string(),
+                value: "#1565C0".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_args(["config", "set", "opacity"]),
+            Err(CliError::MissingArgument("setting value"))
+        );
+    }
+
+    #[test]
+    fn test_config_requires_subcommand() {
+        /// WHY: A bare `config` is ambiguous; say what's expected instead of guessing.
+        assert!(matches!(parse_args(["config"]), Err(CliError::MissingArgument(_))));
+        assert_eq!(
+            parse_args(["config", "dump"]),
+            Err(CliError::UnknownCommand("config dump".to_string()))
+        );
+        assert_eq!(
+            parse_args(["config", "show", "extra"]),
+            Err(CliError::UnexpectedArgument("extra".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_profile_subcommands() {
+        /// WHY: Scripts switch between work and home setups with `profile use`.
+        assert_eq!(parse_args(["profile", "list"]), Ok(Command::ProfileList));
+        assert_eq!(
+            parse_args(["profile", "use", "home"]),
+            Ok(Command::ProfileUse("home".to_string()))
+        );
+        assert_eq!(
+            parse_args(["profile", "use"]),
+            Err(CliError::MissingArgument("profile name"))
+        );
+        assert_eq!(
+            parse_args(["profile"]),
+            Err(CliError::MissingArgument("profile subcommand (list, use)"))
+        );
+    }
+
+    #[test]
+    fn test_test_hotkey_parsing() {
+        /// WHY: The combo is one argument; quoting is the shell's job.
+        assert_eq!(
+            parse_args(["test-hotkey", "ctrl+shift+f5"]),
+            Ok(Command::TestHotkey("ctrl+shift+f5".to_string()))
+        );
+        assert!(matches!(parse_args(["test-hotkey"]), Err(CliError::MissingArgument(_))));
+    }
+
+    #[test]
+    fn test_hotkey_report_success_breakdown() {
+        /// WHY: Users should see exactly which modifiers and key PawGate will listen for.
+        let report = hotkey_report("ctrl+shift+f5").unwrap();
+
+        assert!(report.contains("is a valid hotkey"), "{}", report);
+        assert!(report.contains("Modifiers: Ctrl+Shift"), "{}", report);
+        assert!(report.contains("Key:       F5 (virtual key 0x74)"), "{}", report);
+    }
+
+    #[test]
+    fn test_hotkey_report_names_sides() {
+        /// WHY: With sided modifiers the user must see which physical key is required.
+        let report = hotkey_report("rctrl+b").unwrap();
+        assert!(report.contains("Modifiers: RCtrl"), "{}", report);
+
+        let report = hotkey_report("altgr+e").unwrap();
+        assert!(report.contains("Modifiers: LCtrl+RAlt"), "{}", report);
+    }
+
+    #[test]
+    fn test_hotkey_report_without_modifiers() {
+        /// WHY: A bare key is valid and should say so rather than print an empty list.
+        let report = hotkey_report("play").unwrap();
+        assert!(report.contains("Modifiers: (none)"), "{}", report);
+        assert!(report.contains("Key:       Play"), "{}", report);
+    }
+
+    #[test]
+    fn test_hotkey_report_accepts_sequences() {
+        /// WHY: Sequences are valid `hotkey` values, so test-hotkey must not reject them.
+        let report = hotkey_report("ctrl+b,l").unwrap();
+        assert!(report.contains("is a valid hotkey sequence of 2 steps"), "{}", report);
+        assert!(report.contains("Step 1: Ctrl+B"), "{}", report);
+        assert!(report.contains("Step 2: L"), "{}", report);
+        assert!(report.contains("Key:       L (virtual key 0x4C)"), "{}", report);
+    }
+
+    #[test]
+    fn test_hotkey_report_names_bad_token() {
+        /// WHY: The point of the command is to say which token is wrong.
+        let err = hotkey_report("ctrl+notakey").unwrap_err();
+        assert_eq!(err, r#""ctrl+notakey" is not a valid hotkey: unknown key "notakey""#);
+    }
+
+    #[test]
+    fn test_commands_are_case_sensitive() {
+        /// WHY: Keep parsing strict and predictable, like most CLIs.
+        assert!(parse_args(["LOCK"]).is_err());
+    }
+}
@@ -1,185 +1,5250 @@
 //! Configuration management for PawGate
 //!
-//! Stores settings in JSON format at ~/.pawgate/config.json
+//! Stores settings at ~/.pawgate/config.json, or ~/.pawgate/config.toml if
+//! that file exists (TOML allows comments for hand-edited configs).
 
+use crate::gesture::Gesture;
+use crate::i18n::{self, localized};
+use crate::panic_unlock::DEFAULT_PANIC_HOTKEY;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the watcher checks the config file for changes
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Quiet period after the last write before a change is reloaded
+/// WHY: Many editors save in two steps (truncate, then write), which would
+/// otherwise trigger a reload of a half-written file.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Named overlay colors
+/// Shades match the settings dialog presets where one exists, so "green"
+/// looks the same as picking "Forest Green".
+const NAMED_COLORS: &[(&str, (u8, u8, u8))] = &[
+    ("green", (0x1B, 0x5E, 0x20)),
+    ("blue", (0x15, 0x65, 0xC0)),
+    ("purple", (0x4A, 0x14, 0x8C)),
+    ("gray", (0x37, 0x47, 0x4F)),
+    ("grey", (0x37, 0x47, 0x4F)),
+    ("orange", (0xE6, 0x51, 0x00)),
+    ("teal", (0x00, 0x69, 0x5C)),
+    ("red", (0xB7, 0x1C, 0x1C)),
+    ("black", (0x00, 0x00, 0x00)),
+    ("white", (0xFF, 0xFF, 0xFF)),
+];
+
+/// Profile name meaning "use the settings in config.json itself"
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Config file given with `--config`, used instead of the default location
+static CONFIG_PATH_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Set once the missing home directory has been warned about
+static NO_HOME_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Current config schema version, bumped whenever a migration is needed
+pub const CURRENT_CONFIG_VERSION: u32 = 1;
+
+/// Config baked into the binary from resources/default_config.json
+///
+/// Builds with the `embedded-defaults` feature write this, rather than
+/// [`Config::default`], when they create a config file.
+pub const EMBEDDED_DEFAULTS: &str = include_str!("../resources/default_config.json");
 
 /// Application configuration
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// WHY: `#[serde(default)]` fills fields missing from older config files
+/// with default values instead of failing to load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Config {
-    /// Hotkey to toggle lock (e.g., "ctrl+b", "ctrl+shift+l")
-    pub hotkey: String,
+    /// Schema version of this config (0 for files written before versioning)
+    #[serde(default)]
+    pub version: u32,
+
+    /// Kill switch: when false, PawGate starts in the tray but registers no
+    /// hotkeys, installs no hooks and never locks
+    pub enabled: bool,
+
+    /// Hotkeys that toggle lock (e.g., ["ctrl+b", "ctrl+shift+l"])
+    ///
+    /// An entry may be a comma-separated sequence such as "ctrl+b,l".
+    /// The legacy single-string `hotkey` field is still accepted on load.
+    #[serde(alias = "hotkey", deserialize_with = "deserialize_hotkeys")]
+    pub hotkeys: Vec<String>,
+
+    /// What to do when none of the hotkeys can be registered (all invalid
+    /// or reserved)
+    pub on_register_failure: OnRegisterFailure,
+
+    /// Whether to tint the screen while locked (false = rely on sound/LED only)
+    pub show_overlay: bool,
+
+    /// Overlay opacity (0.0 to 1.0)
+    ///
+    /// Also accepts a preset name on load ("light", "medium", "heavy",
+    /// "solid"); it's always saved as the number.
+    #[serde(deserialize_with = "deserialize_opacity")]
+    pub opacity: f32,
+
+    /// Whether to show notifications
+    pub notifications_enabled: bool,
+
+    /// How long PawGate's own corner toast stays up, in milliseconds; longer
+    /// than 7 seconds also asks Windows for its long toast
+    pub notification_duration_ms: u32,
+
+    /// Where notifications appear: a Windows toast, or PawGate's own toast in a corner
+    pub notification_position: NotificationPosition,
+
+    /// Language of the built-in notification text (e.g. "en", "de", "fr", "ja")
+    pub language: String,
+
+    /// Overlay color in hex (e.g., "#2D5A27" for green)
+    pub overlay_color: String,
+
+    /// Bottom color of a vertical gradient from `overlay_color` (None = flat fill)
+    pub overlay_color_end: Option<String>,
+
+    /// Monitors the overlay covers; empty means all monitors
+    pub monitors: Vec<MonitorSelector>,
+
+    /// Cover a single monitor (active or primary) instead of `monitors`
+    pub overlay_target: OverlayTarget,
+
+    /// Faint pattern tiled over the overlay tint
+    pub overlay_pattern: OverlayPattern,
+
+    /// Distance between pattern glyphs in pixels
+    pub pattern_spacing: u32,
+
+    /// Lock automatically after this many seconds without input (None = disabled)
+    pub auto_lock_idle_secs: Option<u32>,
+
+    /// Per-application rules, applied while that app is in the foreground
+    pub app_rules: Vec<AppRule>,
+
+    /// Unlock automatically this many seconds after every lock, for trying
+    /// PawGate out (None = stay locked until unlocked)
+    pub auto_unlock_secs: Option<u32>,
+
+    /// Word that must be typed to unlock; the hotkey then only locks (None = hotkey unlock)
+    pub unlock_passphrase: Option<String>,
+
+    /// Mouse gesture that also unlocks: "circle" or "zigzag" (None = off)
+    pub unlock_gesture: Option<String>,
+
+    /// Hotkey that always unlocks, whatever the unlock settings; it can be
+    /// changed but not turned off
+    pub panic_unlock_hotkey: String,
+
+    /// Grace period in milliseconds between the hotkey and the lock engaging (0 = immediate)
+    pub lock_delay_ms: u32,
+
+    /// Which input devices are blocked while locked
+    pub lock_mode: LockMode,
+
+    /// `opacity` and `overlay_color` for particular lock modes, keyed by mode
+    /// name; modes not listed (and fields left out) use the top-level values
+    pub lock_mode_overlays: BTreeMap<String, ModeOverlay>,
+
+    /// Let mouse clicks through the overlay to the windows underneath
+    /// (only while the lock mode leaves the mouse working)
+    pub overlay_click_through: bool,
+
+    /// Keys that keep working while locked (e.g., ["volup", "voldown", "mute"])
+    pub passthrough_keys: Vec<String>,
+
+    /// Append lock/unlock events to ~/.pawgate/events.log
+    pub log_events: bool,
+
+    /// Lock sessions kept in ~/.pawgate/sessions.log (0 = don't record)
+    pub session_history_limit: u32,
+
+    /// Come back locked if PawGate was restarted while locked
+    pub restore_lock_state: bool,
+
+    /// Ask for a Y keypress before quitting from the tray menu while locked
+    pub confirm_quit_while_locked: bool,
+
+    /// Maximum gap in milliseconds between the steps of a hotkey sequence
+    pub sequence_timeout_ms: u32,
+
+    /// Hotkey presses this many milliseconds after a toggle are ignored (0 = off)
+    pub toggle_debounce_ms: u32,
+
+    /// Modifier that locks when tapped twice in quick succession, e.g. "ctrl" (None = off)
+    pub double_tap_modifier: Option<String>,
+
+    /// Longest tap, and longest gap between the two taps, in milliseconds
+    pub double_tap_window_ms: u32,
+
+    /// Modifier that keeps the keyboard locked while held down, e.g. "rctrl" (None = off)
+    pub hold_to_lock: Option<String>,
+
+    /// Key that toggles the lock when tapped and unlocks when held, e.g. "pause" (None = off)
+    pub tap_hold_key: Option<String>,
+
+    /// How long `tap_hold_key` must be held to unlock rather than toggle, in milliseconds
+    pub hold_threshold_ms: u32,
+
+    /// Lock when more than this many keys are down at once, as when a paw
+    /// lands on the keyboard (None = off; at least 3)
+    pub auto_lock_on_mash: Option<u32>,
+
+    /// How close together, in milliseconds, the presses of a mash must be
+    pub mash_window_ms: u32,
+
+    /// Hotkey that lets input through for `peek_duration_ms` while locked (None = off)
+    pub peek_hotkey: Option<String>,
+
+    /// How long a peek lasts before the lock re-engages, in milliseconds
+    pub peek_duration_ms: u32,
+
+    /// Hotkey that raises the overlay opacity by one step while locked (None = off)
+    pub opacity_up_hotkey: Option<String>,
+
+    /// Hotkey that lowers the overlay opacity by one step while locked (None = off)
+    pub opacity_down_hotkey: Option<String>,
+
+    /// Save opacity changed with the opacity hotkeys back to the config file
+    pub persist_opacity: bool,
+
+    /// Drop hotkeys that are a bare printable key (e.g. "b") instead of only warning
+    pub reject_unmodified_hotkeys: bool,
+
+    /// WAV file played when the keyboard locks (None = silent)
+    pub lock_sound: Option<PathBuf>,
+
+    /// WAV file played when the keyboard unlocks (None = silent)
+    pub unlock_sound: Option<PathBuf>,
+
+    /// Lock notification text; `{hotkey}` is replaced with the hotkey (None = default text)
+    pub lock_notification_text: Option<String>,
+
+    /// Unlock notification text; `{hotkey}` is replaced with the hotkey (None = default text)
+    pub unlock_notification_text: Option<String>,
+
+    /// Text shown on the overlay; `{hotkey}` is replaced with the hotkey (None = default text)
+    pub overlay_text: Option<String>,
+
+    /// Add a small line to the overlay saying Ctrl+Alt+Del still works
+    pub show_sas_note: bool,
+
+    /// Picture drawn in the middle of the overlay (None = no picture)
+    pub overlay_image: Option<PathBuf>,
+
+    /// Named profile in ~/.pawgate/profiles whose settings replace these
+    /// ("default" = use this file's settings)
+    pub active_profile: String,
+
+    /// How the overlay's opacity animates while locked
+    pub overlay_animation: OverlayAnimation,
+
+    /// Length of one animation cycle in milliseconds
+    pub animation_period_ms: u32,
+
+    /// Milliseconds to fade the overlay in on lock and out on unlock (0 = instant)
+    pub fade_ms: u32,
+
+    /// Keyboard LED lit while locked
+    pub led_indicator: LedIndicator,
+
+    /// Flash the tray icon while locked
+    pub flash_while_locked: bool,
+
+    /// Milliseconds between tray icon frames when `flash_while_locked` is on
+    pub flash_interval_ms: u32,
+
+    /// Hide the mouse pointer over the overlay while locked
+    pub hide_cursor: bool,
+
+    /// Keep the display from sleeping while locked, so the overlay stays visible
+    pub keep_awake: bool,
+
+    /// How often, in milliseconds, the overlay is put back on top of other
+    /// topmost windows while locked (0 = off)
+    pub reassert_topmost_ms: u32,
+
+    /// Port on 127.0.0.1 serving /status and /config to local dashboards
+    /// (None = off; needs a build with the `http` feature)
+    pub http_port: Option<u16>,
+}
+
+/// Keyboard LED used as a physical lock indicator
+///
+/// Deserializes from "none", "scrolllock" or "numlock"; anything else falls
+/// back to `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LedIndicator {
+    /// Leave the LEDs alone
+    #[default]
+    None,
+    /// Light Scroll Lock while locked
+    ScrollLock,
+    /// Light Num Lock while locked
+    NumLock,
+}
+
+impl LedIndicator {
+    /// Every indicator, in the order the docs list them
+    pub const ALL: &'static [Self] = &[Self::None, Self::ScrollLock, Self::NumLock];
+
+    /// Parse a name as written in the config (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        choice_from_name(Self::ALL, Self::name, name)
+    }
+
+    /// The name used in config files
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::ScrollLock => "scrolllock",
+            Self::NumLock => "numlock",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for LedIndicator {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialize_choice(deserializer, "led_indicator", Self::from_name, Self::name)
+    }
+}
+
+/// Overlay opacity animation while locked
+///
+/// Deserializes from "none", "pulse" or "flash"; anything else falls back to
+/// `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlayAnimation {
+    /// Static tint
+    #[default]
+    None,
+    /// Opacity gently oscillates between half and full
+    Pulse,
+    /// A brief bright flash when the lock engages
+    Flash,
+}
+
+impl OverlayAnimation {
+    /// Every animation, in the order the docs list them
+    pub const ALL: &'static [Self] = &[Self::None, Self::Pulse, Self::Flash];
+
+    /// Parse a name as written in the config (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        choice_from_name(Self::ALL, Self::name, name)
+    }
+
+    /// The name used in config files
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Pulse => "pulse",
+            Self::Flash => "flash",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OverlayAnimation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialize_choice(deserializer, "overlay_animation", Self::from_name, Self::name)
+    }
+}
+
+/// Which input devices a lock blocks
+///
+/// Deserializes from "keyboardonly", "mouseonly", "both" or "targetwindow";
+/// anything else falls back to `Both` so a typo never leaves input
+/// unexpectedly unblocked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockMode {
+    KeyboardOnly,
+    MouseOnly,
+    #[default]
+    Both,
+    /// Only the window in the foreground at lock time; the rest of the
+    /// desktop stays usable
+    TargetWindow,
+}
+
+impl LockMode {
+    /// Every lock mode, in the order the docs list them
+    pub const ALL: &'static [Self] = &[Self::KeyboardOnly, Self::MouseOnly, Self::Both, Self::TargetWindow];
+
+    /// Parse a name as written in the config (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        choice_from_name(Self::ALL, Self::name, name)
+    }
+
+    /// The name used in config files
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::KeyboardOnly => "keyboardonly",
+            Self::MouseOnly => "mouseonly",
+            Self::Both => "both",
+            Self::TargetWindow => "targetwindow",
+        }
+    }
+
+    /// Whether keystrokes are swallowed while locked
+    pub fn blocks_keyboard(self) -> bool {
+        matches!(self, Self::KeyboardOnly | Self::Both)
+    }
+
+    /// Whether mouse input is swallowed while locked
+    pub fn blocks_mouse(self) -> bool {
+        matches!(self, Self::MouseOnly | Self::Both)
+    }
+}
+
+impl<'de> Deserialize<'de> for LockMode {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialize_choice(deserializer, "lock_mode", Self::from_name, Self::name)
+    }
+}
+
+/// What an [`AppRule`] does while its app is in the foreground
+///
+/// Deserializes from "normal", "disable" or "forcelock"; anything else falls
+/// back to `Normal`, so a typo just leaves the rule without effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AppRuleAction {
+    /// Hotkey and auto-lock behave as usual
+    #[default]
+    Normal,
+    /// The hotkey can't lock and auto-lock is paused (like a snooze)
+    Disable,
+    /// Lock as soon as the app comes to the foreground
+    ForceLock,
+}
+
+impl AppRuleAction {
+    /// Every action, in the order the docs list them
+    pub const ALL: &'static [Self] = &[Self::Normal, Self::Disable, Self::ForceLock];
+
+    /// Parse a name as written in the config (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        choice_from_name(Self::ALL, Self::name, name)
+    }
+
+    /// The name used in config files
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Disable => "disable",
+            Self::ForceLock => "forcelock",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for AppRuleAction {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialize_choice(deserializer, "app rule action", Self::from_name, Self::name)
+    }
+}
+
+/// Overlay settings for one lock mode in `lock_mode_overlays`
+///
+/// Each field left out falls back to the top-level setting of the same name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModeOverlay {
+    /// A number from 0.0 to 1.0, or a preset name like `opacity`
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_opacity",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub opacity: Option<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overlay_color: Option<String>,
+}
+
+/// Behavior to apply while a given application is in the foreground
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppRule {
+    /// Executable name such as "premiere.exe" (case-insensitive; ".exe" optional)
+    pub app: String,
+    #[serde(default)]
+    pub action: AppRuleAction,
+}
+
+/// Which monitor(s) the overlay covers
+///
+/// Deserializes from "all", "activemonitor" or "primarymonitor"; anything
+/// else falls back to `All`, so a typo never leaves a monitor uncovered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlayTarget {
+    /// Every monitor, or the ones listed in `monitors`
+    #[default]
+    All,
+    /// The monitor showing the foreground window when the lock starts
+    ActiveMonitor,
+    /// The primary monitor
+    PrimaryMonitor,
+}
+
+impl OverlayTarget {
+    /// Every target, in the order the docs list them
+    pub const ALL: &'static [Self] = &[Self::All, Self::ActiveMonitor, Self::PrimaryMonitor];
+
+    /// Parse a name as written in the config (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        choice_from_name(Self::ALL, Self::name, name)
+    }
+
+    /// The name used in config files
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::All => "all",
+            Self::ActiveMonitor => "activemonitor",
+            Self::PrimaryMonitor => "primarymonitor",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OverlayTarget {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialize_choice(deserializer, "overlay_target", Self::from_name, Self::name)
+    }
+}
+
+/// Where lock/unlock notifications appear
+///
+/// Deserializes from "system", "topleft", "topright", "bottomleft" or
+/// "bottomright"; anything else falls back to `System`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationPosition {
+    /// A Windows toast notification
+    #[default]
+    System,
+    /// PawGate's own toast in a corner of the primary monitor's work area
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl NotificationPosition {
+    /// Every position, in the order the docs list them
+    pub const ALL: &'static [Self] = &[Self::System, Self::TopLeft, Self::TopRight, Self::BottomLeft, Self::BottomRight];
+
+    /// Parse a name as written in the config (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        choice_from_name(Self::ALL, Self::name, name)
+    }
+
+    /// The name used in config files
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::System => "system",
+            Self::TopLeft => "topleft",
+            Self::TopRight => "topright",
+            Self::BottomLeft => "bottomleft",
+            Self::BottomRight => "bottomright",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for NotificationPosition {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialize_choice(deserializer, "notification_position", Self::from_name, Self::name)
+    }
+}
+
+/// What happens when none of the configured hotkeys can be registered
+///
+/// Deserializes from "fallbackdefault", "notify" or "exit"; anything else
+/// falls back to `FallbackDefault`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OnRegisterFailure {
+    /// Use the default hotkey ("ctrl+b") instead
+    #[default]
+    FallbackDefault,
+    /// Say so in a notification and run without a hotkey
+    Notify,
+    /// Show an error and quit
+    Exit,
+}
+
+impl OnRegisterFailure {
+    /// Every policy, in the order the docs list them
+    pub const ALL: &'static [Self] = &[Self::FallbackDefault, Self::Notify, Self::Exit];
+
+    /// Parse a name as written in the config (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        choice_from_name(Self::ALL, Self::name, name)
+    }
+
+    /// The name used in config files
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::FallbackDefault => "fallbackdefault",
+            Self::Notify => "notify",
+            Self::Exit => "exit",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OnRegisterFailure {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialize_choice(deserializer, "on_register_failure", Self::from_name, Self::name)
+    }
+}
+
+/// Pattern painted over the overlay tint
+///
+/// Deserializes from "none" or "paws"; anything else falls back to `None`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverlayPattern {
+    /// Flat tint (or gradient)
+    #[default]
+    None,
+    /// Faint paw prints tiled across the screen
+    Paws,
+}
+
+impl OverlayPattern {
+    /// Every pattern, in the order the docs list them
+    pub const ALL: &'static [Self] = &[Self::None, Self::Paws];
+
+    /// Parse a name as written in the config (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        choice_from_name(Self::ALL, Self::name, name)
+    }
+
+    /// The name used in config files
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Paws => "paws",
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OverlayPattern {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        deserialize_choice(deserializer, "overlay_pattern", Self::from_name, Self::name)
+    }
+}
+
+/// Selects a monitor for the overlay
+///
+/// Deserializes from a number (0-based enumeration index) or a string
+/// (device name such as `\\.\DISPLAY2`, or just "DISPLAY2").
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum MonitorSelector {
+    Index(usize),
+    Name(String),
+}
+
+impl MonitorSelector {
+    /// Whether this selector matches the monitor at `index` named `device_name`
+    pub fn matches(&self, index: usize, device_name: &str) -> bool {
+        // WHY: Windows reports names like "\\.\DISPLAY2"; let users omit the prefix
+        fn strip(name: &str) -> &str {
+            name.trim().trim_start_matches(r"\\.\")
+        }
+
+        match self {
+            Self::Index(i) => *i == index,
+            Self::Name(name) => strip(name).eq_ignore_ascii_case(strip(device_name)),
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CURRENT_CONFIG_VERSION,
+            enabled: true,
+            hotkeys: vec!["ctrl+b".to_string()],
+            on_register_failure: OnRegisterFailure::FallbackDefault,
+            show_overlay: true,
+            overlay_click_through: true,
+            language: crate::i18n::DEFAULT_LANGUAGE.to_string(),
+            opacity: 0.3,
+            notifications_enabled: true,
+            notification_duration_ms: 3000,
+            notification_position: NotificationPosition::System,
+            // Colorblind-friendly green that's distinguishable
+            overlay_color: "#1B5E20".to_string(),
+            overlay_color_end: None,
+            monitors: Vec::new(),
+            overlay_target: OverlayTarget::All,
+            overlay_pattern: OverlayPattern::None,
+            pattern_spacing: 160,
+            auto_lock_idle_secs: None,
+            app_rules: Vec::new(),
+            auto_unlock_secs: None,
+            unlock_passphrase: None,
+            unlock_gesture: None,
+            panic_unlock_hotkey: DEFAULT_PANIC_HOTKEY.to_string(),
+            lock_delay_ms: 0,
+            lock_mode: LockMode::Both,
+            lock_mode_overlays: BTreeMap::new(),
+            passthrough_keys: Vec::new(),
+            log_events: false,
+            session_history_limit: 500,
+            restore_lock_state: true,
+            confirm_quit_while_locked: false,
+            sequence_timeout_ms: 1000,
+            toggle_debounce_ms: 250,
+            double_tap_modifier: None,
+            double_tap_window_ms: 400,
+            hold_to_lock: None,
+            tap_hold_key: None,
+            auto_lock_on_mash: None,
+            mash_window_ms: 150,
+            hold_threshold_ms: 500,
+            peek_hotkey: None,
+            peek_duration_ms: 10_000,
+            opacity_up_hotkey: None,
+            opacity_down_hotkey: None,
+            persist_opacity: false,
+            reject_unmodified_hotkeys: false,
+            lock_sound: None,
+            unlock_sound: None,
+            lock_notification_text: None,
+            unlock_notification_text: None,
+            overlay_text: None,
+            show_sas_note: false,
+            overlay_image: None,
+            active_profile: DEFAULT_PROFILE.to_string(),
+            overlay_animation: OverlayAnimation::None,
+            animation_period_ms: 2000,
+            fade_ms: 0,
+            led_indicator: LedIndicator::None,
+            flash_while_locked: false,
+            flash_interval_ms: 500,
+            hide_cursor: false,
+            keep_awake: false,
+            reassert_topmost_ms: 1000,
+            http_port: None,
+        }
+    }
+}
+
+impl Config {
+    /// Get the config file path
+    ///
+    /// Prefers ~/.pawgate/config.toml when it exists, otherwise
+    /// ~/.pawgate/config.json. A file passed with `--config` replaces both.
+    pub fn config_path() -> PathBuf {
+        if let Some(path) = CONFIG_PATH_OVERRIDE.get() {
+            return path.clone();
+        }
+        let dir = Self::config_dir();
+        let toml_path = dir.join("config.toml");
+        if toml_path.exists() {
+            return toml_path;
+        }
+        dir.join("config.json")
+    }
+
+    /// Use `path` as the config file for the rest of the process
+    ///
+    /// WHY: Set once at startup, so loading, saving, `config set` and the
+    /// file watcher all agree on the file. A relative path is resolved
+    /// against the current directory right away.
+    pub fn set_config_path(path: &Path) {
+        let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        if let Err(path) = CONFIG_PATH_OVERRIDE.set(path) {
+            log::warn!("Config path is already set; ignoring {}", path.display());
+        }
+    }
+
+    /// The config file given with `--config`, if any
+    pub fn config_path_override() -> Option<&'static Path> {
+        CONFIG_PATH_OVERRIDE.get().map(PathBuf::as_path)
+    }
+
+    /// Get the config directory (~/.pawgate)
+    ///
+    /// PAWGATE_CONFIG_DIR overrides it, e.g. for a portable install that
+    /// keeps its settings next to the executable. XDG_CONFIG_HOME
+    /// ($XDG_CONFIG_HOME/pawgate) is honored everywhere but Windows; since
+    /// the crate only builds for Windows today, that's for a future port.
+    /// Without a home directory it's [`fallback_config_dir`], with a warning.
+    pub fn config_dir() -> PathBuf {
+        let xdg_config_home = if cfg!(windows) { None } else { std::env::var_os("XDG_CONFIG_HOME") };
+        let home = dirs::home_dir();
+        if home.is_none() && !NO_HOME_WARNED.swap(true, Ordering::SeqCst) {
+            log::warn!(
+                "Couldn't determine the home directory; settings are kept in {} (set PAWGATE_CONFIG_DIR to choose)",
+                fallback_config_dir().display()
+            );
+        }
+        config_dir_from(std::env::var_os("PAWGATE_CONFIG_DIR"), xdg_config_home, home)
+    }
+
+    /// Get the profiles directory (~/.pawgate/profiles)
+    pub fn profiles_dir() -> PathBuf {
+        Self::config_dir().join("profiles")
+    }
+
+    /// Get the file path of the named profile
+    pub fn profile_path(name: &str) -> PathBuf {
+        Self::profiles_dir().join(format!("{}.json", name))
+    }
+
+    /// Names of the profiles in the profiles directory, sorted
+    pub fn list_profiles() -> Vec<String> {
+        list_profiles_in(&Self::profiles_dir())
+    }
+
+    /// Load the named profile from ~/.pawgate/profiles/<name>.json
+    pub fn load_profile(name: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_profile_from(&Self::profile_path(name), name)
+    }
+
+    /// Load a profile file, tagging it with its name so saves go back to it
+    fn load_profile_from(path: &Path, name: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        if !is_valid_profile_name(name) {
+            return Err(format!("invalid profile name '{}'", name).into());
+        }
+        let mut config = Self::parse_file(path)?;
+        config.migrate();
+        config.validate();
+        config.active_profile = name.to_string();
+        Ok(config)
+    }
+
+    /// Whether a named profile (rather than config.json) supplies the settings
+    pub fn uses_profile(&self) -> bool {
+        !self.active_profile.is_empty() && self.active_profile != DEFAULT_PROFILE
+    }
+
+    /// Replace these settings with the active profile's, if one is selected
+    ///
+    /// A profile that can't be loaded is logged and config.json's settings
+    /// are used instead.
+    fn apply_active_profile(self) -> Self {
+        if !self.uses_profile() {
+            return self;
+        }
+        match Self::load_profile(&self.active_profile) {
+            Ok(profile) => profile,
+            Err(e) => {
+                log::warn!("Failed to load profile '{}': {}", self.active_profile, e);
+                self
+            }
+        }
+    }
+
+    /// Make `name` the active profile by updating config.json
+    ///
+    /// Pass [`DEFAULT_PROFILE`] to go back to config.json's own settings.
+    pub fn set_active_profile(name: &str) -> Result<(), Box<dyn std::error::Error>> {
+        if name != DEFAULT_PROFILE && !Self::list_profiles().iter().any(|p| p == name) {
+            return Err(format!("no profile named '{}' in {}", name, Self::profiles_dir().display()).into());
+        }
+
+        let path = Self::config_path();
+        let mut config = if path.exists() {
+            let mut config = Self::parse_file(&path)?;
+            config.migrate();
+            config
+        } else {
+            Self::initial()
+        };
+        config.active_profile = name.to_string();
+        config.save_to(&path)
+    }
+
+    /// Load configuration from disk, or return default if not found
+    ///
+    /// If config.json selects a profile, the profile's settings are returned.
+    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
+        Self::load_or_create(&Self::config_path())
+    }
+
+    /// Load the main config file at `path`, creating or repairing it as needed
+    ///
+    /// WHY: A file that can't be read or doesn't parse (a stray keystroke in
+    /// an editor is enough, or an editor saving in another encoding) is
+    /// moved aside to `<name>.bak` and replaced with defaults, so PawGate
+    /// still starts. Only errors moving it aside or writing the defaults are
+    /// returned; use [`Config::load_from`] to get read and parse errors instead.
+    pub fn load_or_create(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if !path.exists() {
+            // Create default config
+            let config = Self::initial();
+            config.save_to(path)?;
+            return Ok(config);
+        }
+
+        let mut config = match Self::parse_file(path) {
+            Ok(config) => config,
+            Err(e) => {
+                let backup = backup_path(path);
+                log::warn!(
+                    "Config file {} is invalid ({}); moved it to {} and restored defaults",
+                    path.display(),
+                    e,
+                    backup.display()
+                );
+                fs::rename(path, &backup)?;
+                let config = Self::initial();
+                config.save_to(path)?;
+                return Ok(config);
+            }
+        };
+        let migrated = config.migrate();
+        config.validate();
+
+        // Persist the upgrade so the migration only runs once
+        if migrated {
+            config.save_to(path)?;
+        }
+        Ok(config.apply_active_profile())
+    }
+
+    /// Load the config file at `path` without writing anything
+    ///
+    /// A missing file gives the settings it would be created with
+    /// ([`Config::initial`]). Unlike [`Config::load_or_create`] nothing on
+    /// disk changes, so read-only commands like `pawgate config show` leave
+    /// no trace; a file that can't be read or parsed is an error.
+    pub fn load_or_initial(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if path.exists() {
+            Self::load_from(path)
+        } else {
+            Ok(Self::initial())
+        }
+    }
+
+    /// Settings a newly created config file starts with
+    ///
+    /// [`EMBEDDED_DEFAULTS`] with the `embedded-defaults` feature, otherwise
+    /// [`Config::default`]. An embedded config that doesn't parse is logged
+    /// and the built-in defaults are used instead.
+    pub fn initial() -> Self {
+        if cfg!(feature = "embedded-defaults") {
+            match serde_json::from_str::<Self>(EMBEDDED_DEFAULTS) {
+                Ok(mut config) => {
+                    config.migrate();
+                    config.validate();
+                    return config;
+                }
+                Err(e) => log::warn!("Embedded default config is invalid ({}); using built-in defaults", e),
+            }
+        }
+        Self::default()
+    }
+
+    /// Load, migrate and validate configuration from a specific file
+    ///
+    /// The format is chosen from the file extension (.toml or JSON otherwise).
+    /// Unlike [`Config::load_or_create`] a corrupt file is an error and is
+    /// left alone, which the file watcher relies on to keep the last good
+    /// config.
+    pub fn load_from(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut config = Self::parse_file(path)?;
+        config.migrate();
+        config.validate();
+        Ok(config.apply_active_profile())
+    }
+
+    /// Parse a config file as-is, without migration or validation
+    fn parse_file(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        if is_toml_path(path) {
+            return Self::load_toml(path);
+        }
+        let contents = fs::read_to_string(path)?;
+        Self::parse_json(&contents)
+    }
+
+    /// Parse config.json contents
+    ///
+    /// With the `json5` feature, comments and trailing commas are accepted
+    /// as well. Saving always writes plain JSON.
+    fn parse_json(contents: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        #[cfg(feature = "json5")]
+        let config = json5::from_str(contents)?;
+        #[cfg(not(feature = "json5"))]
+        let config = serde_json::from_str(contents)?;
+        Ok(config)
+    }
+
+    /// Upgrade a config from an older schema version to the current one
+    ///
+    /// Returns true if anything changed and the config should be re-saved.
+    pub fn migrate(&mut self) -> bool {
+        if self.version >= CURRENT_CONFIG_VERSION {
+            return false;
+        }
+
+        // v0 -> v1: versioning introduced; missing fields were already
+        // filled with defaults during deserialization
+        if self.version == 0 {
+            self.version = 1;
+        }
+
+        log::info!("Migrated config to version {}", self.version);
+        true
+    }
+
+    /// Load configuration from a TOML file without validating it
+    pub fn load_toml(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = fs::read_to_string(path)?;
+        Ok(toml::from_str(&contents)?)
+    }
+
+    /// Save configuration to a TOML file
+    pub fn save_toml(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        write_atomic(path, toml::to_string_pretty(self)?.as_bytes())?;
+        Ok(())
+    }
+
+    /// Watch a config file and call `callback` with the new config whenever it changes
+    ///
+    /// When the file selects a profile, the profile's file is watched too, so
+    /// editing either one reloads. Writes that fail to parse are logged and
+    /// skipped, so the caller keeps the last good config. Watching stops when
+    /// the returned handle is stopped or dropped.
+    pub fn watch<F>(path: PathBuf, mut callback: F) -> ConfigWatcher
+    where
+        F: FnMut(Config) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&stop);
+
+        let thread = std::thread::spawn(move || {
+            let mut files = watched_files(&path);
+            let mut debounce = ReloadDebounce::new(file_stamps(&files));
+
+            while !thread_stop.load(Ordering::SeqCst) {
+                std::thread::sleep(WATCH_POLL_INTERVAL);
+
+                if !debounce.poll(file_stamps(&files), Instant::now()) {
+                    continue;
+                }
+                match Self::load_from(&path) {
+                    Ok(config) => {
+                        log::info!("Config file changed, reloading");
+                        callback(config);
+                    }
+                    Err(e) => log::warn!("Ignoring unparseable config change: {}", e),
+                }
+                // The edit may have switched to another profile
+                let now_watched = watched_files(&path);
+                if now_watched != files {
+                    files = now_watched;
+                    debounce = ReloadDebounce::new(file_stamps(&files));
+                }
+            }
+        });
+
+        ConfigWatcher {
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// Override fields from PAWGATE_* environment variables
+    ///
+    /// Call after [`Config::load`] so the file is the base and env vars win.
+    /// Values that don't parse are logged and ignored.
+    pub fn apply_env_overrides(&mut self) {
+        if let Ok(hotkey) = std::env::var("PAWGATE_HOTKEY") {
+            if !hotkey.trim().is_empty() {
+                self.hotkeys = vec![hotkey];
+            }
+        }
+
+        if let Ok(value) = std::env::var("PAWGATE_OPACITY") {
+            match parse_opacity(&value) {
+                Some(opacity) if opacity.is_finite() => self.opacity = opacity,
+                _ => log::warn!("Ignoring invalid PAWGATE_OPACITY: {}", value),
+            }
+        }
+
+        if let Ok(color) = std::env::var("PAWGATE_OVERLAY_COLOR") {
+            if !color.trim().is_empty() {
+                self.overlay_color = color.trim().to_string();
+            }
+        }
+
+        if let Ok(value) = std::env::var("PAWGATE_NOTIFICATIONS") {
+            match parse_env_bool(&value) {
+                Some(enabled) => self.notifications_enabled = enabled,
+                None => log::warn!("Ignoring invalid PAWGATE_NOTIFICATIONS: {}", value),
+            }
+        }
+
+        self.validate();
+    }
+
+    /// Fix up out-of-range values from a hand-edited config file
+    pub fn validate(&mut self) {
+        if self.opacity.is_nan() {
+            log::warn!("Opacity is NaN, using default");
+            self.opacity = Self::default().opacity;
+        } else if !(0.0..=1.0).contains(&self.opacity) {
+            let clamped = self.opacity.clamp(0.0, 1.0);
+            log::warn!("Opacity {} out of range, clamping to {}", self.opacity, clamped);
+            self.opacity = clamped;
+        }
+
+        if let Some(keys) = self.auto_lock_on_mash.filter(|&keys| keys < crate::mash::MIN_THRESHOLD) {
+            log::warn!(
+                "auto_lock_on_mash {} would catch everyday shortcuts, using {}",
+                keys,
+                crate::mash::MIN_THRESHOLD
+            );
+            self.auto_lock_on_mash = Some(crate::mash::MIN_THRESHOLD);
+        }
+
+        // A misspelled mode would never apply, so say so instead of ignoring it
+        self.lock_mode_overlays.retain(|mode, _| {
+            let known = LockMode::from_name(mode).is_some();
+            if !known {
+                log::warn!("Ignoring lock_mode_overlays entry for unknown lock mode '{}'", mode);
+            }
+            known
+        });
+        for (mode, overlay) in &mut self.lock_mode_overlays {
+            if let Some(opacity) = overlay.opacity {
+                if opacity.is_nan() {
+                    log::warn!("Opacity for lock mode {} is NaN, using the top-level opacity", mode);
+                    overlay.opacity = None;
+                } else if !(0.0..=1.0).contains(&opacity) {
+                    let clamped = opacity.clamp(0.0, 1.0);
+                    log::warn!("Opacity {} for lock mode {} out of range, clamping to {}", opacity, mode, clamped);
+                    overlay.opacity = Some(clamped);
+                }
+            }
+        }
+
+        // Keep the (forgiving) color, but don't let a typo go unnoticed
+        if let Err(e) = self.validate_color() {
+            log::warn!("{}; the overlay will use the default color", e);
+        }
+    }
+
+    /// Check `overlay_color` and `overlay_color_end`, naming the first bad one
+    ///
+    /// Drawing the overlay falls back to the default green for a color it
+    /// can't read; this is the strict check behind `config set` and the
+    /// startup warning.
+    pub fn validate_color(&self) -> Result<(), String> {
+        check_color("overlay_color", &self.overlay_color)?;
+        if let Some(end) = &self.overlay_color_end {
+            check_color("overlay_color_end", end)?;
+        }
+        for (mode, overlay) in &self.lock_mode_overlays {
+            if let Some(color) = &overlay.overlay_color {
+                check_color(&format!("lock_mode_overlays.{}.overlay_color", mode), color)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set one field from its string form, as used by `pawgate config set`
+    ///
+    /// The config is left unchanged if `value` isn't valid for `key`.
+    pub fn set_field(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let value = value.trim();
+        match key {
+            "enabled" => self.enabled = parse_bool_field(key, value)?,
+            "hotkey" | "hotkeys" => {
+                parse_hotkey_sequence_detailed(value)
+                    .map_err(|e| format!("invalid hotkey '{}': {}", value, e))?;
+                self.hotkeys = vec![value.to_string()];
+            }
+            "on_register_failure" => {
+                self.on_register_failure = parse_choice_field(key, value, OnRegisterFailure::ALL, OnRegisterFailure::name)?;
+            }
+            "opacity" => match parse_opacity(value) {
+                Some(opacity) if (0.0..=1.0).contains(&opacity) => self.opacity = opacity,
+                _ => {
+                    return Err(format!(
+                        "opacity must be a number from 0.0 to 1.0 or light, medium, heavy or solid, got '{}'",
+                        value
+                    ))
+                }
+            },
+            "overlay_color" => {
+                check_color(key, value)?;
+                self.overlay_color = value.to_string();
+            }
+            "overlay_color_end" => {
+                if value.is_empty() {
+                    self.overlay_color_end = None;
+                } else {
+                    check_color(key, value)?;
+                    self.overlay_color_end = Some(value.to_string());
+                }
+            }
+            "notifications_enabled" => self.notifications_enabled = parse_bool_field(key, value)?,
+            "notification_duration_ms" => {
+                self.notification_duration_ms = value.parse().map_err(|_| {
+                    format!("notification_duration_ms must be a whole number of milliseconds, got '{}'", value)
+                })?;
+            }
+            "notification_position" => {
+                self.notification_position = parse_choice_field(key, value, NotificationPosition::ALL, NotificationPosition::name)?;
+            }
+            "log_events" => self.log_events = parse_bool_field(key, value)?,
+            "session_history_limit" => {
+                self.session_history_limit = value
+                    .parse()
+                    .map_err(|_| format!("session_history_limit must be a whole number, got '{}'", value))?
+            }
+            "restore_lock_state" => self.restore_lock_state = parse_bool_field(key, value)?,
+            "confirm_quit_while_locked" => self.confirm_quit_while_locked = parse_bool_field(key, value)?,
+            "language" => {
+                if !i18n::is_supported(value) {
+                    log::warn!("No built-in strings for language '{}', using English", value);
+                }
+                self.language = value.to_string();
+            }
+            "show_overlay" => self.show_overlay = parse_bool_field(key, value)?,
+            "overlay_click_through" => self.overlay_click_through = parse_bool_field(key, value)?,
+            "hide_cursor" => self.hide_cursor = parse_bool_field(key, value)?,
+            "flash_while_locked" => self.flash_while_locked = parse_bool_field(key, value)?,
+            "show_sas_note" => self.show_sas_note = parse_bool_field(key, value)?,
+            "keep_awake" => self.keep_awake = parse_bool_field(key, value)?,
+            "reject_unmodified_hotkeys" => {
+                self.reject_unmodified_hotkeys = parse_bool_field(key, value)?
+            }
+            "auto_lock_on_mash" => {
+                self.auto_lock_on_mash = match value {
+                    "off" | "none" => None,
+                    _ => match value.parse::<u32>() {
+                        Ok(keys) if keys >= crate::mash::MIN_THRESHOLD => Some(keys),
+                        _ => {
+                            return Err(format!(
+                                "auto_lock_on_mash must be a number of keys of at least {} or 'off', got '{}'",
+                                crate::mash::MIN_THRESHOLD,
+                                value
+                            ))
+                        }
+                    },
+                }
+            }
+            "mash_window_ms" => {
+                self.mash_window_ms = value.parse().map_err(|_| {
+                    format!("mash_window_ms must be a whole number of milliseconds, got '{}'", value)
+                })?;
+            }
+            "auto_lock_idle_secs" => {
+                self.auto_lock_idle_secs = match value {
+                    "off" | "none" => None,
+                    _ => match value.parse::<u32>() {
+                        Ok(secs) if secs > 0 => Some(secs),
+                        _ => {
+                            return Err(format!(
+                                "auto_lock_idle_secs must be a positive number of seconds or 'off', got '{}'",
+                                value
+                            ))
+                        }
+                    },
+                }
+            }
+            "app_rules" => {
+                self.app_rules = split_list(value)
+                    .into_iter()
+                    .map(|item| {
+                        let (app, action) = item
+                            .split_once('=')
+                            .ok_or_else(|| format!("app rule '{}' must look like app.exe=action", item))?;
+                        let action = AppRuleAction::from_name(action).ok_or_else(|| {
+                            format!(
+                                "unknown app rule action '{}' ({})",
+                                action.trim(),
+                                describe_choices(AppRuleAction::ALL, AppRuleAction::name)
+                            )
+                        })?;
+                        Ok(AppRule {
+                            app: app.trim().to_string(),
+                            action,
+                        })
+                    })
+                    .collect::<Result<_, String>>()?;
+            }
+            "auto_unlock_secs" => {
+                self.auto_unlock_secs = match value {
+                    "off" | "none" => None,
+                    _ => match value.parse::<u32>() {
+                        Ok(secs) if secs > 0 => Some(secs),
+                        _ => {
+                            return Err(format!(
+                                "auto_unlock_secs must be a positive number of seconds or 'off', got '{}'",
+                                value
+                            ))
+                        }
+                    },
+                }
+            }
+            "sequence_timeout_ms" => {
+                self.sequence_timeout_ms = value.parse().map_err(|_| {
+                    format!("sequence_timeout_ms must be a whole number of milliseconds, got '{}'", value)
+                })?;
+            }
+            "toggle_debounce_ms" => {
+                self.toggle_debounce_ms = value.parse().map_err(|_| {
+                    format!("toggle_debounce_ms must be a whole number of milliseconds, got '{}'", value)
+                })?;
+            }
+            "double_tap_modifier" => {
+                self.double_tap_modifier = match value {
+                    "" | "off" | "none" => None,
+                    _ if double_tap_keys(value).is_some() => Some(value.to_string()),
+                    _ => {
+                        return Err(format!(
+                            "double_tap_modifier must be a modifier such as ctrl, shift, alt or lctrl, or 'off', got '{}'",
+                            value
+                        ))
+                    }
+                }
+            }
+            "double_tap_window_ms" => {
+                self.double_tap_window_ms = value.parse().map_err(|_| {
+                    format!("double_tap_window_ms must be a whole number of milliseconds, got '{}'", value)
+                })?;
+            }
+            "hold_to_lock" => {
+                self.hold_to_lock = match value {
+                    "" | "off" | "none" => None,
+                    _ if double_tap_keys(value).is_some() => Some(value.to_string()),
+                    _ => {
+                        return Err(format!(
+                            "hold_to_lock must be a modifier such as ctrl, shift, alt or rctrl, or 'off', got '{}'",
+                            value
+                        ))
+                    }
+                }
+            }
+            "tap_hold_key" => {
+                self.tap_hold_key = match value {
+                    "" | "off" | "none" => None,
+                    _ if tap_hold_keys(value).is_some() => Some(value.to_string()),
+                    _ => return Err(format!("tap_hold_key must be a single key such as pause or rctrl, or 'off', got '{}'", value)),
+                }
+            }
+            "hold_threshold_ms" => {
+                self.hold_threshold_ms = value.parse().map_err(|_| {
+                    format!("hold_threshold_ms must be a whole number of milliseconds, got '{}'", value)
+                })?;
+            }
+            "peek_hotkey" => self.peek_hotkey = parse_optional_hotkey_field(value)?,
+            "peek_duration_ms" => {
+                self.peek_duration_ms = value.parse().map_err(|_| {
+                    format!("peek_duration_ms must be a whole number of milliseconds, got '{}'", value)
+                })?;
+            }
+            "opacity_up_hotkey" => self.opacity_up_hotkey = parse_optional_hotkey_field(value)?,
+            "opacity_down_hotkey" => self.opacity_down_hotkey = parse_optional_hotkey_field(value)?,
+            "persist_opacity" => self.persist_opacity = parse_bool_field(key, value)?,
+            "overlay_animation" => {
+                self.overlay_animation = parse_choice_field(key, value, OverlayAnimation::ALL, OverlayAnimation::name)?;
+            }
+            "led_indicator" => {
+                self.led_indicator = parse_choice_field(key, value, LedIndicator::ALL, LedIndicator::name)?;
+            }
+            "animation_period_ms" => {
+                self.animation_period_ms = value.parse().map_err(|_| {
+                    format!("animation_period_ms must be a whole number of milliseconds, got '{}'", value)
+                })?;
+            }
+            "fade_ms" => {
+                self.fade_ms = value.parse().map_err(|_| {
+                    format!("fade_ms must be a whole number of milliseconds, got '{}'", value)
+                })?;
+            }
+            "http_port" => {
+                self.http_port = match value {
+                    "off" | "none" => None,
+                    _ => match value.parse::<u16>() {
+                        Ok(port) if port > 0 => Some(port),
+                        _ => {
+                            return Err(format!(
+                                "http_port must be a port number from 1 to 65535 or 'off', got '{}'",
+                                value
+                            ))
+                        }
+                    },
+                }
+            }
+            "flash_interval_ms" => {
+                self.flash_interval_ms = value.parse().map_err(|_| {
+                    format!("flash_interval_ms must be a whole number of milliseconds, got '{}'", value)
+                })?;
+            }
+            "reassert_topmost_ms" => {
+                self.reassert_topmost_ms = value.parse().map_err(|_| {
+                    format!("reassert_topmost_ms must be a whole number of milliseconds, got '{}'", value)
+                })?;
+            }
+            "lock_delay_ms" => {
+                self.lock_delay_ms = value.parse().map_err(|_| {
+                    format!("lock_delay_ms must be a whole number of milliseconds, got '{}'", value)
+                })?;
+            }
+            "unlock_passphrase" => {
+                self.unlock_passphrase = (!value.is_empty()).then(|| value.to_string());
+            }
+            "unlock_gesture" => {
+                self.unlock_gesture = match value {
+                    "" | "off" | "none" => None,
+                    _ if Gesture::from_name(value).is_some() => Some(value.to_string()),
+                    _ => {
+                        return Err(format!(
+                            "unlock_gesture must be one of {}, or 'off', got '{}'",
+                            Gesture::NAMES.join(", "),
+                            value
+                        ))
+                    }
+                }
+            }
+            "panic_unlock_hotkey" => {
+                // WHY: "off" would take away the way out this hotkey guarantees
+                parse_hotkey_sequence_detailed(value)
+                    .map_err(|e| format!("invalid panic_unlock_hotkey '{}': {}", value, e))?;
+                self.panic_unlock_hotkey = value.to_string();
+            }
+            "overlay_text" => {
+                self.overlay_text = (!value.is_empty()).then(|| value.to_string());
+            }
+            "overlay_image" => {
+                self.overlay_image = (!value.is_empty()).then(|| PathBuf::from(value));
+            }
+            "overlay_target" => {
+                self.overlay_target = parse_choice_field(key, value, OverlayTarget::ALL, OverlayTarget::name)?;
+            }
+            "overlay_pattern" => {
+                self.overlay_pattern = parse_choice_field(key, value, OverlayPattern::ALL, OverlayPattern::name)?;
+            }
+            "pattern_spacing" => {
+                self.pattern_spacing = value
+                    .parse()
+                    .map_err(|_| format!("pattern_spacing must be a whole number of pixels, got '{}'", value))?;
+            }
+            "lock_mode" => {
+                self.lock_mode = parse_choice_field(key, value, LockMode::ALL, LockMode::name)?;
+            }
+            "lock_mode_overlays" => self.lock_mode_overlays = parse_mode_overlays(value)?,
+            "passthrough_keys" => {
+                let keys = split_list(value);
+                if let Some(bad) = keys.iter().find(|key| parse_key(&key.to_lowercase()).is_none()) {
+                    return Err(format!("unknown key '{}' in passthrough_keys", bad));
+                }
+                self.passthrough_keys = keys;
+            }
+            "monitors" => {
+                self.monitors = split_list(value)
+                    .into_iter()
+                    .map(|item| match item.parse::<usize>() {
+                        Ok(index) => MonitorSelector::Index(index),
+                        Err(_) => MonitorSelector::Name(item),
+                    })
+                    .collect();
+            }
+            _ => return Err(format!("unknown setting '{}'", key)),
+        }
+        Ok(())
+    }
+
+    /// Set one field in the saved config, for `pawgate config set` and the tray
+    ///
+    /// WHY: Loads the file without env overrides so they don't get baked in,
+    /// and refuses to save over a file that couldn't be read.
+    pub fn set_saved_field(key: &str, value: &str) -> Result<(), String> {
+        Self::set_field_in_file(&Self::config_path(), key, value)
+    }
+
+    /// [`Config::set_saved_field`] for the config file at `path`
+    fn set_field_in_file(path: &Path, key: &str, value: &str) -> Result<(), String> {
+        let mut config = Self::load_saved(path)?;
+        config.set_field(key, value)?;
+        config.save_over(path)
+    }
+
+    /// Save the settings that differ between `self` and `edited`, for the
+    /// settings dialog
+    ///
+    /// WHY: `self` is the running config, with env overrides applied, so
+    /// saving `edited` whole would bake them into the file. Only the edited
+    /// fields are written over what the file holds.
+    pub fn save_changes(&self, edited: &Config) -> Result<(), String> {
+        self.save_changes_in_file(&Self::config_path(), edited)
+    }
+
+    /// [`Config::save_changes`] for the config file at `path`
+    fn save_changes_in_file(&self, path: &Path, edited: &Config) -> Result<(), String> {
+        let mut config = Self::load_saved(path)?;
+        config.apply_fields(edited, &self.diff(edited));
+        config.save_over(path)
+    }
+
+    /// The settings saved in the config file at `path` (or its active
+    /// profile), without env overrides; a missing file is created
+    fn load_saved(path: &Path) -> Result<Self, String> {
+        // load_or_create would move an unreadable file aside, so only use it
+        // to create a missing one
+        let loaded = if path.exists() { Self::load_from(path) } else { Self::load_or_create(path) };
+        loaded.map_err(|e| format!("can't read {}: {}", path.display(), e))
+    }
+
+    /// Save to the active profile's file, or else to the config file at `path`
+    fn save_over(&self, path: &Path) -> Result<(), String> {
+        let target = if self.uses_profile() {
+            Self::profile_path(&self.active_profile)
+        } else {
+            path.to_path_buf()
+        };
+        self.save_to(&target).map_err(|e| format!("failed to save config: {}", e))
+    }
+
+    /// Save configuration to disk
+    ///
+    /// Writes to the active profile's file when a profile is selected, so
+    /// settings changes stay with that profile.
+    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.uses_profile() {
+            self.save_to(&Self::profile_path(&self.active_profile))
+        } else {
+            self.save_to(&Self::config_path())
+        }
+    }
+
+    /// JSON Schema (draft-07) describing config.json, for editor validation
+    /// and autocompletion
+    ///
+    /// Enum choices and limits come from the same tables and constants the
+    /// loader uses, so the schema can't drift from what's accepted.
+    pub fn json_schema() -> String {
+        use serde_json::{json, Map, Value};
+
+        /// `{"enum": [...]}` from a name table
+        fn choice<T: Copy>(all: &[T], name: fn(T) -> &'static str) -> Value {
+            json!({ "enum": all.iter().map(|&item| name(item)).collect::<Vec<_>>() })
+        }
+
+        /// `schema` with a "description" added
+        fn described(mut schema: Value, description: &str) -> Value {
+            schema["description"] = json!(description);
+            schema
+        }
+
+        let names: Vec<&str> = NAMED_COLORS.iter().map(|(name, _)| *name).collect();
+        let color = json!({
+            "anyOf": [
+                { "type": "string", "pattern": "^#([0-9A-Fa-f]{3}|[0-9A-Fa-f]{6}|[0-9A-Fa-f]{8})$" },
+                { "type": "string", "enum": names }
+            ]
+        });
+        let millis = json!({ "type": "integer", "minimum": 0, "maximum": u32::MAX });
+        let optional_string = json!({ "type": ["string", "null"] });
+        let boolean = json!({ "type": "boolean" });
+        let opacity = json!({
+            "anyOf": [
+                { "type": "number" },
+                { "enum": OPACITY_PRESETS.iter().map(|(name, _)| *name).collect::<Vec<_>>() }
+            ],
+            "minimum": 0.0,
+            "maximum": 1.0
+        });
+        let lock_modes = choice(LockMode::ALL, LockMode::name);
+        let mut gestures: Vec<Value> = Gesture::NAMES.iter().map(|name| json!(name)).collect();
+        gestures.push(Value::Null);
+        let hotkey_entry = json!({
+            "oneOf": [
+                { "type": "string" },
+                {
+                    "type": "object",
+                    "properties": {
+                        "modifiers": { "type": "array", "items": { "type": "string" } },
+                        "key": { "type": "string" }
+                    },
+                    "required": ["key"],
+                    "additionalProperties": false
+                }
+            ]
+        });
+        let app_rule = json!({
+            "type": "object",
+            "properties": {
+                "app": { "type": "string", "examples": ["premiere.exe"] },
+                "action": choice(AppRuleAction::ALL, AppRuleAction::name)
+            },
+            "required": ["app"]
+        });
+        let mode_overlay = json!({
+            "type": "object",
+            "properties": { "opacity": opacity, "overlay_color": color },
+            "additionalProperties": false
+        });
+
+        let mut properties = Map::new();
+        let mut add = |name: &str, schema: Value| {
+            properties.insert(name.to_string(), schema);
+        };
+        add("version", json!({ "type": "integer", "minimum": 0, "description": "Config schema version" }));
+        add(
+            "enabled",
+            described(boolean.clone(), "Set to false to turn PawGate off without uninstalling it"),
+        );
+        add(
+            "hotkeys",
+            json!({
+                "type": "array",
+                "items": hotkey_entry,
+                "description": "Hotkeys that toggle the lock, e.g. \"ctrl+b\", a sequence \"ctrl+k,l\" or {\"modifiers\": [\"ctrl\"], \"key\": \"b\"}"
+            }),
+        );
+        add(
+            "on_register_failure",
+            described(
+                choice(OnRegisterFailure::ALL, OnRegisterFailure::name),
+                "What to do when no hotkey can be registered",
+            ),
+        );
+        add("show_overlay", boolean.clone());
+        add("opacity", described(opacity, "Overlay opacity, or a preset name"));
+        add("notifications_enabled", boolean.clone());
+        add("notification_duration_ms", millis.clone());
+        add(
+            "notification_position",
+            choice(NotificationPosition::ALL, NotificationPosition::name),
+        );
+        add("language", json!({ "type": "string", "examples": ["en", "de", "fr", "ja"] }));
+        add("overlay_color", color.clone());
+        add("overlay_color_end", json!({ "anyOf": [color, { "type": "null" }] }));
+        add(
+            "monitors",
+            json!({
+                "type": "array",
+                "items": { "type": ["integer", "string"], "minimum": 0 },
+                "description": "Monitor indexes or device names to cover; empty covers all"
+            }),
+        );
+        add("overlay_target", choice(OverlayTarget::ALL, OverlayTarget::name));
+        add("overlay_pattern", choice(OverlayPattern::ALL, OverlayPattern::name));
+        add(
+            "pattern_spacing",
+            json!({ "type": "integer", "minimum": 0, "description": "Pixels between pattern glyphs" }),
+        );
+        add("auto_lock_idle_secs", json!({ "type": ["integer", "null"], "minimum": 0 }));
+        add(
+            "app_rules",
+            json!({
+                "type": "array",
+                "items": app_rule,
+                "description": "What to do while an app is in the foreground"
+            }),
+        );
+        add("auto_unlock_secs", json!({ "type": ["integer", "null"], "minimum": 1 }));
+        add("unlock_passphrase", optional_string.clone());
+        add("unlock_gesture", json!({ "enum": gestures, "description": "Mouse gesture that unlocks" }));
+        add(
+            "panic_unlock_hotkey",
+            json!({ "type": "string", "description": "Hotkey that always unlocks, even with a passphrase set" }),
+        );
+        add("lock_delay_ms", millis.clone());
+        add("lock_mode", lock_modes.clone());
+        add(
+            "lock_mode_overlays",
+            json!({
+                "type": "object",
+                "propertyNames": lock_modes,
+                "additionalProperties": mode_overlay,
+                "description": "opacity and overlay_color per lock mode, overriding the top-level values"
+            }),
+        );
+        add("overlay_click_through", boolean.clone());
+        add("passthrough_keys", json!({ "type": "array", "items": { "type": "string" } }));
+        add("log_events", boolean.clone());
+        add("session_history_limit", json!({ "type": "integer", "minimum": 0 }));
+        add("restore_lock_state", boolean.clone());
+        add("confirm_quit_while_locked", boolean.clone());
+        add("sequence_timeout_ms", millis.clone());
+        add("toggle_debounce_ms", millis.clone());
+        add(
+            "double_tap_modifier",
+            json!({
+                "type": ["string", "null"],
+                "examples": ["ctrl", "shift", "alt", "lctrl", "rshift"],
+                "description": "Modifier that locks when tapped twice quickly"
+            }),
+        );
+        add("double_tap_window_ms", millis.clone());
+        add(
+            "auto_lock_on_mash",
+            json!({
+                "type": ["integer", "null"],
+                "minimum": crate::mash::MIN_THRESHOLD,
+                "description": "Lock when more than this many keys are down at once"
+            }),
+        );
+        add("mash_window_ms", millis.clone());
+        add(
+            "hold_to_lock",
+            json!({
+                "type": ["string", "null"],
+                "examples": ["rctrl", "ralt", "ctrl"],
+                "description": "Modifier that keeps the keyboard locked while held"
+            }),
+        );
+        add(
+            "tap_hold_key",
+            json!({
+                "type": ["string", "null"],
+                "examples": ["pause", "scrolllock", "rctrl"],
+                "description": "Key that toggles the lock when tapped and unlocks when held"
+            }),
+        );
+        add("hold_threshold_ms", millis.clone());
+        add(
+            "peek_hotkey",
+            described(optional_string.clone(), "Hotkey that briefly lets input through while locked"),
+        );
+        add("peek_duration_ms", millis.clone());
+        add(
+            "opacity_up_hotkey",
+            described(optional_string.clone(), "Hotkey that raises the overlay opacity while locked"),
+        );
+        add(
+            "opacity_down_hotkey",
+            described(optional_string.clone(), "Hotkey that lowers the overlay opacity while locked"),
+        );
+        add("persist_opacity", boolean.clone());
+        add("reject_unmodified_hotkeys", boolean.clone());
+        for name in [
+            "lock_sound",
+            "unlock_sound",
+            "lock_notification_text",
+            "unlock_notification_text",
+            "overlay_text",
+        ] {
+            add(name, optional_string.clone());
+        }
+        add("show_sas_note", boolean.clone());
+        add("overlay_image", optional_string);
+        add("active_profile", json!({ "type": "string" }));
+        add("overlay_animation", choice(OverlayAnimation::ALL, OverlayAnimation::name));
+        add("animation_period_ms", millis.clone());
+        add("fade_ms", millis.clone());
+        add("led_indicator", choice(LedIndicator::ALL, LedIndicator::name));
+        add("flash_while_locked", boolean.clone());
+        add("flash_interval_ms", millis.clone());
+        add("hide_cursor", boolean.clone());
+        add("keep_awake", boolean);
+        add("reassert_topmost_ms", millis);
+        add(
+            "http_port",
+            json!({ "type": ["integer", "null"], "minimum": 1, "maximum": u16::MAX }),
+        );
+
+        let schema = json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "title": "PawGate configuration",
+            "type": "object",
+            "properties": properties
+        });
+        serde_json::to_string_pretty(&schema).expect("schema is always serializable")
+    }
+
+    /// Save configuration to a specific file, choosing the format by extension
+    pub fn save_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        // Create parent directory if needed
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        // Store hotkeys in one spelling, however they were typed
+        let mut config = self.clone();
+        config.normalize_hotkeys();
+
+        if is_toml_path(path) {
+            return config.save_toml(path);
+        }
+
+        let contents = serde_json::to_string_pretty(&config)?;
+        write_atomic(path, contents.as_bytes())?;
+        Ok(())
+    }
+
+    /// Rewrite every hotkey in canonical form (see [`normalize_hotkey`]),
+    /// e.g. "B+Ctrl" and "shift + ctrl + b" become "ctrl+shift+b"
+    ///
+    /// Hotkeys that don't parse are left as written, with a warning.
+    pub fn normalize_hotkeys(&mut self) {
+        fn normalize(field: &str, hotkey: &mut String) {
+            match normalize_hotkey(hotkey) {
+                Some(canonical) => *hotkey = canonical,
+                None => log::warn!("Not normalizing unparseable {} '{}'", field, hotkey),
+            }
+        }
+
+        for hotkey in &mut self.hotkeys {
+            normalize("hotkey", hotkey);
+        }
+        for (field, hotkey) in [
+            ("peek_hotkey", &mut self.peek_hotkey),
+            ("opacity_up_hotkey", &mut self.opacity_up_hotkey),
+            ("opacity_down_hotkey", &mut self.opacity_down_hotkey),
+        ] {
+            if let Some(hotkey) = hotkey {
+                normalize(field, hotkey);
+            }
+        }
+        normalize("panic_unlock_hotkey", &mut self.panic_unlock_hotkey);
+    }
+
+    /// Parse every configured hotkey into its sequence of steps, skipping
+    /// invalid ones and duplicates
+    ///
+    /// A plain combo like "ctrl+b" yields a one-step sequence. With
+    /// `reject_unmodified_hotkeys`, hotkeys starting with a bare key are
+    /// skipped too; they stay in `hotkeys`, so saving keeps them in the file.
+    pub fn parsed_hotkeys(&self) -> Vec<Vec<(u32, u32)>> {
+        let mut parsed: Vec<Vec<(u32, u32)>> = Vec::new();
+        for hotkey in &self.hotkeys {
+            // A bare letter as a hotkey would lock on every ordinary keystroke
+            let first_step = hotkey.split(',').next().unwrap_or_default();
+            if requires_modifier_warning(first_step) {
+                if self.reject_unmodified_hotkeys {
+                    log::warn!(
+                        "Ignoring hotkey '{}': it has no modifier, so ordinary typing would trigger it",
+                        hotkey
+                    );
+                    continue;
+                }
+                log::warn!("Hotkey '{}' has no modifier; typing that key will toggle the lock", hotkey);
+            }
+            if let Some(sequence) = parse_hotkey_sequence(hotkey) {
+                if let Some(conflict) = sequence_conflict(&sequence) {
+                    log::warn!("{}", hotkey_conflict_message(hotkey, conflict));
+                    continue;
+                }
+                if !parsed.contains(&sequence) {
+                    parsed.push(sequence);
+                }
+            }
+        }
+        parsed
+    }
+
+    /// Virtual key codes of the `passthrough_keys` allowlist
+    ///
+    /// Entries use the same key names as hotkeys; invalid ones are skipped.
+    pub fn parsed_passthrough_keys(&self) -> HashSet<u32> {
+        let mut parsed = HashSet::new();
+        for token in &self.passthrough_keys {
+            match parse_key(token.trim().to_lowercase().as_str()) {
+                Some(vk) => {
+                    parsed.insert(vk);
+                }
+                None => log::warn!("Ignoring unknown passthrough key '{}'", token),
+            }
+        }
+        parsed
+    }
+
+    /// The `peek_hotkey` as a sequence of steps, if it's set and valid
+    ///
+    /// Like the lock hotkeys, a reserved combo is skipped with a warning.
+    pub fn parsed_peek_hotkey(&self) -> Option<Vec<(u32, u32)>> {
+        parse_extra_hotkey("peek_hotkey", self.peek_hotkey.as_deref()?)
+    }
+
+    /// The `panic_unlock_hotkey` as a sequence of steps
+    ///
+    /// An invalid or reserved one falls back to the default, so there is
+    /// always a panic hotkey.
+    pub fn parsed_panic_unlock_hotkey(&self) -> Vec<(u32, u32)> {
+        parse_extra_hotkey("panic_unlock_hotkey", &self.panic_unlock_hotkey)
+            .or_else(|| parse_hotkey_sequence(DEFAULT_PANIC_HOTKEY))
+            .expect("the default panic hotkey parses")
+    }
+
+    /// The `opacity_up_hotkey` as a sequence of steps, if it's set and valid
+    pub fn parsed_opacity_up_hotkey(&self) -> Option<Vec<(u32, u32)>> {
+        parse_extra_hotkey("opacity_up_hotkey", self.opacity_up_hotkey.as_deref()?)
+    }
+
+    /// The `opacity_down_hotkey` as a sequence of steps, if it's set and valid
+    pub fn parsed_opacity_down_hotkey(&self) -> Option<Vec<(u32, u32)>> {
+        parse_extra_hotkey("opacity_down_hotkey", self.opacity_down_hotkey.as_deref()?)
+    }
+
+    /// Virtual key codes of the `double_tap_modifier`, if it's set and valid
+    pub fn parsed_double_tap_keys(&self) -> Option<Vec<u32>> {
+        let token = self.double_tap_modifier.as_deref()?;
+        let keys = double_tap_keys(token);
+        if keys.is_none() {
+            log::warn!("Ignoring unknown double_tap_modifier '{}'", token);
+        }
+        keys
+    }
+
+    /// The `unlock_gesture`, if it's set and valid
+    pub fn parsed_unlock_gesture(&self) -> Option<Gesture> {
+        let name = self.unlock_gesture.as_deref()?;
+        let gesture = Gesture::from_name(name);
+        if gesture.is_none() {
+            log::warn!("Ignoring unknown unlock_gesture '{}'", name);
+        }
+        gesture
+    }
+
+    /// Virtual key codes of the `hold_to_lock` modifier, if it's set and valid
+    pub fn parsed_hold_to_lock_keys(&self) -> Option<Vec<u32>> {
+        let token = self.hold_to_lock.as_deref()?;
+        let keys = double_tap_keys(token);
+        if keys.is_none() {
+            log::warn!("Ignoring unknown hold_to_lock '{}'", token);
+        }
+        keys
+    }
+
+    /// Virtual key codes of the `tap_hold_key`, if it's set and valid
+    pub fn parsed_tap_hold_keys(&self) -> Option<Vec<u32>> {
+        let token = self.tap_hold_key.as_deref()?;
+        let keys = tap_hold_keys(token);
+        if keys.is_none() {
+            log::warn!("Ignoring unknown tap_hold_key '{}'", token);
+        }
+        keys
+    }
+
+    /// Whether a passphrase unlocks, so the hotkey only locks
+    ///
+    /// An empty `unlock_passphrase` counts as unset, as it does for the
+    /// keyboard hook, which has nothing to match against.
+    pub fn has_passphrase(&self) -> bool {
+        self.unlock_passphrase.as_deref().is_some_and(|passphrase| !passphrase.is_empty())
+    }
+
+    /// Text of the notification shown when the keyboard locks or unlocks
+    ///
+    /// Uses the custom text if configured, otherwise the built-in text in
+    /// `language`, with `{hotkey}` replaced by the configured hotkey(s).
+    pub fn notification_text(&self, locked: bool) -> String {
+        let custom = if locked {
+            &self.lock_notification_text
+        } else {
+            &self.unlock_notification_text
+        };
+        let template = match custom {
+            Some(text) => text.as_str(),
+            None if !locked => localized(i18n::NOTIFICATION_UNLOCK, &self.language),
+            None if self.has_passphrase() => {
+                localized(i18n::NOTIFICATION_LOCK_PASSPHRASE, &self.language)
+            }
+            None => localized(i18n::NOTIFICATION_LOCK, &self.language),
+        };
+        self.fill_hotkey(template)
+    }
+
+    /// Line added to the unlock notification saying how many keys were
+    /// blocked during the lock, or None if there were none
+    pub fn blocked_keys_text(&self, count: u64) -> Option<String> {
+        (count > 0).then(|| {
+            localized(i18n::NOTIFICATION_BLOCKED, &self.language).replace("{count}", &count.to_string())
+        })
+    }
+
+    /// Fields the overlay windows are built from; a reload that changes any
+    /// of them recreates the overlay
+    ///
+    /// The overlay also shows the hotkey, passphrase hint and lock mode, but
+    /// those are [`Config::HOOK_FIELDS`], which a reload leaves alone.
+    pub const OVERLAY_FIELDS: &'static [&'static str] = &[
+        "show_overlay",
+        "opacity",
+        "overlay_color",
+        "overlay_color_end",
+        "monitors",
+        "overlay_target",
+        "overlay_pattern",
+        "pattern_spacing",
+        "lock_mode_overlays",
+        "overlay_click_through",
+        "overlay_text",
+        "show_sas_note",
+        "overlay_image",
+    ];
+
+    /// Fields the keyboard hook reads once, when it starts
+    ///
+    /// Changing them needs a restart, so a reload keeps the running values
+    /// (see [`Config::keep_hook_fields`]) rather than have the overlay and
+    /// notifications advertise settings that aren't live yet.
+    pub const HOOK_FIELDS: &'static [&'static str] = &[
+        "hotkeys",
+        "on_register_failure",
+        "unlock_passphrase",
+        "unlock_gesture",
+        "panic_unlock_hotkey",
+        "lock_delay_ms",
+        "lock_mode",
+        "passthrough_keys",
+        "sequence_timeout_ms",
+        "toggle_debounce_ms",
+        "double_tap_modifier",
+        "double_tap_window_ms",
+        "hold_to_lock",
+        "tap_hold_key",
+        "hold_threshold_ms",
+        "auto_lock_on_mash",
+        "mash_window_ms",
+        "peek_hotkey",
+        "opacity_up_hotkey",
+        "opacity_down_hotkey",
+        "reject_unmodified_hotkeys",
+    ];
+
+    /// Put back `running`'s values for the [`Config::HOOK_FIELDS`], returning
+    /// the names of those that differed
+    ///
+    /// Used on a reload, since the hook keeps what it started with.
+    pub fn keep_hook_fields(&mut self, running: &Config) -> Vec<&'static str> {
+        let pending: Vec<&'static str> = running
+            .diff(self)
+            .into_iter()
+            .filter(|field| Self::HOOK_FIELDS.contains(field))
+            .collect();
+        self.apply_fields(running, &pending);
+        pending
+    }
+
+    /// Text painted in the middle of the lock overlay
+    ///
+    /// Uses `overlay_text` if configured, with `{hotkey}` replaced by the
+    /// configured hotkey(s).
+    pub fn overlay_message(&self) -> String {
+        let template = match &self.overlay_text {
+            Some(text) => text.as_str(),
+            None if self.has_passphrase() => {
+                "Keyboard Locked - Type passphrase to unlock"
+            }
+            None => "Keyboard Locked - Press hotkey to unlock",
+        };
+        self.fill_hotkey(template)
+    }
+
+    /// Small print under the overlay message, if `show_sas_note` is on
+    ///
+    /// WHY: Users are surprised that Ctrl+Alt+Del works while locked; saying
+    /// so up front turns it into a known emergency escape.
+    pub fn sas_note(&self) -> Option<String> {
+        self.show_sas_note.then(|| {
+            format!(
+                "{} still works while locked - Windows never lets apps block it",
+                HotkeyConflict::SecureAttention.combo()
+            )
+        })
+    }
+
+    /// Replace `{hotkey}` in user-facing text with the configured hotkey(s)
+    fn fill_hotkey(&self, template: &str) -> String {
+        let hotkey = if self.hotkeys.is_empty() {
+            Self::default().hotkeys.join(" or ")
+        } else {
+            self.hotkeys.join(" or ")
+        };
+        template.replace("{hotkey}", &hotkey)
+    }
+
+    /// Black or white, whichever reads better on top of the overlay color
+    pub fn overlay_text_color(&self) -> (u8, u8, u8) {
+        contrast_color(self.parse_overlay_color())
+    }
+
+    /// Whether the overlay window should let mouse input through
+    ///
+    /// WHY: When the lock mode blocks the mouse, the overlay stays a solid
+    /// target as a second line of defence behind the mouse hook.
+    pub fn overlay_passes_clicks(&self) -> bool {
+        self.overlay_click_through && !self.lock_mode.blocks_mouse()
+    }
+
+    /// Whether the overlay should cover the monitor at `index` named `device_name`
+    pub fn selects_monitor(&self, index: usize, device_name: &str) -> bool {
+        self.monitors.is_empty()
+            || self
+                .monitors
+                .iter()
+                .any(|selector| selector.matches(index, device_name))
+    }
+
+    /// Parse overlay color from hex string to RGB
+    ///
+    /// Accepts a hex string ("#1B5E20") or a color name ("green"). Any alpha
+    /// channel is dropped; see [`Config::parse_overlay_color_rgba`].
+    pub fn parse_overlay_color(&self) -> (u8, u8, u8) {
+        let (r, g, b, _) = self.parse_overlay_color_rgba();
+        (r, g, b)
+    }
+
+    /// Parse overlay color from hex string to RGBA
+    ///
+    /// Uses the color for the current lock mode (see
+    /// [`Config::effective_overlay_color`]). Accepts "#RRGGBBAA" in addition
+    /// to the RGB forms; alpha is 255 when not given.
+    pub fn parse_overlay_color_rgba(&self) -> (u8, u8, u8, u8) {
+        // Default to dark green if parsing fails
+        parse_color(self.effective_overlay_color()).unwrap_or((27, 94, 32, 255))
+    }
+
+    /// The `lock_mode_overlays` entry for the current lock mode, if any
+    pub fn mode_overlay(&self) -> Option<&ModeOverlay> {
+        self.lock_mode_overlays.get(self.lock_mode.name())
+    }
+
+    /// Overlay opacity for the current lock mode
+    ///
+    /// The mode's entry in `lock_mode_overlays` if it sets one, otherwise
+    /// the top-level `opacity`.
+    pub fn effective_opacity(&self) -> f32 {
+        self.mode_overlay()
+            .and_then(|overlay| overlay.opacity)
+            .unwrap_or(self.opacity)
+    }
+
+    /// Overlay color for the current lock mode, falling back like
+    /// [`Config::effective_opacity`]
+    pub fn effective_overlay_color(&self) -> &str {
+        self.mode_overlay()
+            .and_then(|overlay| overlay.overlay_color.as_deref())
+            .unwrap_or(&self.overlay_color)
+    }
+
+    /// Change whichever opacity [`Config::effective_opacity`] reads
+    pub fn set_effective_opacity(&mut self, opacity: f32) {
+        match self.lock_mode_overlays.get_mut(self.lock_mode.name()) {
+            Some(ModeOverlay {
+                opacity: Some(mode_opacity),
+                ..
+            }) => *mode_opacity = opacity,
+            _ => self.opacity = opacity,
+        }
+    }
+
+    /// Parse the gradient end color to RGB
+    ///
+    /// None when no gradient is configured or the color is invalid, in which
+    /// case the overlay is filled flat.
+    pub fn parse_overlay_color_end(&self) -> Option<(u8, u8, u8)> {
+        let (r, g, b, _) = parse_color(self.overlay_color_end.as_deref()?)?;
+        Some((r, g, b))
+    }
+}
+
+/// Linearly interpolate between two colors; `t` is clamped to 0..=1
+pub fn lerp_color(a: (u8, u8, u8), b: (u8, u8, u8), t: f32) -> (u8, u8, u8) {
+    let t = t.clamp(0.0, 1.0);
+    let mix = |from: u8, to: u8| (f32::from(from) + (f32::from(to) - f32::from(from)) * t).round() as u8;
+    (mix(a.0, b.0), mix(a.1, b.1), mix(a.2, b.2))
+}
+
+/// Black or white, whichever contrasts better with `rgb`
+///
+/// Uses the perceived luminance (ITU-R BT.601 weights), so bright greens and
+/// yellows get black text even though their blue channel is zero.
+pub fn contrast_color(rgb: (u8, u8, u8)) -> (u8, u8, u8) {
+    let (r, g, b) = rgb;
+    let luminance = 0.299 * f32::from(r) + 0.587 * f32::from(g) + 0.114 * f32::from(b);
+    if luminance > 128.0 {
+        (0, 0, 0)
+    } else {
+        (255, 255, 255)
+    }
+}
+
+/// Error naming `field` unless `value` is a color name or 3/6/8-digit hex code
+fn check_color(field: &str, value: &str) -> Result<(), String> {
+    if parse_color(value).is_some() {
+        return Ok(());
+    }
+    let names: Vec<&str> = NAMED_COLORS.iter().map(|(name, _)| *name).collect();
+    Err(format!(
+        "invalid {} '{}': use a 3, 6 or 8-digit hex code like #1B5E20 or one of {}",
+        field,
+        value,
+        names.join(", ")
+    ))
+}
+
+/// Parse a color name or 3/6/8-digit hex string to RGBA
+fn parse_color(color: &str) -> Option<(u8, u8, u8, u8)> {
+    let name = color.trim();
+    if let Some((_, (r, g, b))) = NAMED_COLORS
+        .iter()
+        .find(|(color_name, _)| color_name.eq_ignore_ascii_case(name))
+    {
+        return Some((*r, *g, *b, 255));
+    }
+
+    let hex = color.trim_start_matches('#');
+
+    // Expand CSS shorthand by doubling each nibble ("#1a2" -> "#11aa22")
+    let expanded: String;
+    let hex = if hex.len() == 3 {
+        expanded = hex.chars().flat_map(|c| [c, c]).collect();
+        expanded.as_str()
+    } else {
+        hex
+    };
+
+    // WHY: is_ascii() guards the byte-range slicing below against
+    // multi-byte characters that happen to make the length 6 or 8.
+    if (hex.len() == 6 || hex.len() == 8) && hex.is_ascii() {
+        let alpha = if hex.len() == 8 { &hex[6..8] } else { "FF" };
+        if let (Ok(r), Ok(g), Ok(b), Ok(a)) = (
+            u8::from_str_radix(&hex[0..2], 16),
+            u8::from_str_radix(&hex[2..4], 16),
+            u8::from_str_radix(&hex[4..6], 16),
+            u8::from_str_radix(alpha, 16),
+        ) {
+            return Some((r, g, b, a));
+        }
+    }
+    None
+}
+
+/// Implements [`Config::diff`] by comparing each listed field
+///
+/// WHY: Listing the fields once here keeps `diff` and [`Config::FIELD_NAMES`]
+/// in step; a test checks the list against the serialized fields, so a new
+/// field can't be left out.
+macro_rules! config_diff {
+    ($($field:ident),* $(,)?) => {
+        impl Config {
+            /// Every field [`Config::diff`] compares, in declaration order
+            pub const FIELD_NAMES: &'static [&'static str] = &[$(stringify!($field)),*];
+
+            /// Names of the fields whose values differ between `self` and `other`
+            ///
+            /// Lets a reload tell what actually changed, e.g. to log it or to
+            /// skip rebuilding the overlay when only notification settings moved.
+            pub fn diff(&self, other: &Config) -> Vec<&'static str> {
+                let mut changed = Vec::new();
+                $(
+                    if self.$field != other.$field {
+                        changed.push(stringify!($field));
+                    }
+                )*
+                changed
+            }
+
+            /// Copy the named fields from `other`, leaving the rest as they are
+            pub fn apply_fields(&mut self, other: &Config, fields: &[&str]) {
+                $(
+                    if fields.contains(&stringify!($field)) {
+                        self.$field = other.$field.clone();
+                    }
+                )*
+            }
+        }
+    };
+}
+
+config_diff!(
+    version, enabled, hotkeys, on_register_failure, show_overlay, opacity,
+    notifications_enabled, notification_duration_ms, notification_position, language, overlay_color, overlay_color_end,
+    monitors, overlay_target, overlay_pattern, pattern_spacing, auto_lock_idle_secs, app_rules,
+    auto_unlock_secs, unlock_passphrase, unlock_gesture, panic_unlock_hotkey, lock_delay_ms, lock_mode,
+    lock_mode_overlays, overlay_click_through, passthrough_keys, log_events, session_history_limit,
+    restore_lock_state, confirm_quit_while_locked, sequence_timeout_ms, toggle_debounce_ms, double_tap_modifier,
+    double_tap_window_ms, hold_to_lock, tap_hold_key, hold_threshold_ms, auto_lock_on_mash,
+    mash_window_ms, peek_hotkey,
+    peek_duration_ms, opacity_up_hotkey, opacity_down_hotkey,
+    persist_opacity, reject_unmodified_hotkeys, lock_sound, unlock_sound,
+    lock_notification_text, unlock_notification_text, overlay_text, show_sas_note,
+    overlay_image, active_profile, overlay_animation, animation_period_ms, fade_ms,
+    led_indicator, flash_while_locked, flash_interval_ms, hide_cursor, keep_awake,
+    reassert_topmost_ms, http_port,
+);
+
+/// Handle to a background config file watcher started by [`Config::watch`]
+pub struct ConfigWatcher {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ConfigWatcher {
+    /// Stop watching and wait for the watcher thread to exit
+    pub fn stop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl Drop for ConfigWatcher {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Where settings go when there's no home directory: a `pawgate` folder in
+/// the temp directory, rather than wherever PawGate happened to be started
+pub fn fallback_config_dir() -> PathBuf {
+    std::env::temp_dir().join("pawgate")
+}
+
+/// [`Config::config_dir`] from the PAWGATE_CONFIG_DIR and XDG_CONFIG_HOME
+/// values and the home directory, so loading and saving always agree
+fn config_dir_from(
+    override_dir: Option<std::ffi::OsString>,
+    xdg_config_home: Option<std::ffi::OsString>,
+    home: Option<PathBuf>,
+) -> PathBuf {
+    if let Some(dir) = override_dir.filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    if let Some(dir) = xdg_config_home.filter(|dir| !dir.is_empty()) {
+        return PathBuf::from(dir).join("pawgate");
+    }
+    match home {
+        Some(home) => home.join(".pawgate"),
+        None => fallback_config_dir(),
+    }
+}
+
+/// Parse a boolean environment variable value ("1"/"0", "true"/"false", "yes"/"no", "on"/"off")
+fn parse_env_bool(value: &str) -> Option<bool> {
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse a single-purpose hotkey such as `peek_hotkey`, warning and
+/// returning None if it's invalid or a reserved combo
+fn parse_extra_hotkey(field: &str, hotkey: &str) -> Option<Vec<(u32, u32)>> {
+    match parse_hotkey_sequence_detailed(hotkey) {
+        Ok(sequence) => match sequence_conflict(&sequence) {
+            Some(conflict) => {
+                log::warn!("{}", hotkey_conflict_message(hotkey, conflict));
+                None
+            }
+            None => Some(sequence),
+        },
+        Err(e) => {
+            log::warn!("Ignoring invalid {} '{}': {}", field, hotkey, e);
+            None
+        }
+    }
+}
+
+/// Parse an optional hotkey for `Config::set_field` ("off" or "none" clears it)
+fn parse_optional_hotkey_field(value: &str) -> Result<Option<String>, String> {
+    match value {
+        "" | "off" | "none" => Ok(None),
+        _ => {
+            parse_hotkey_sequence_detailed(value).map_err(|e| format!("invalid hotkey '{}': {}", value, e))?;
+            Ok(Some(value.to_string()))
+        }
+    }
+}
+
+/// Parse a boolean for `Config::set_field`
+fn parse_bool_field(key: &str, value: &str) -> Result<bool, String> {
+    parse_env_bool(value).ok_or_else(|| format!("{} must be true or false, got '{}'", key, value))
+}
+
+/// The item of a config enum's `all` whose name is `name`
+/// (case-insensitive)
+///
+/// WHY: Deserialize, `set_field` and the schema all go through each enum's
+/// `ALL`/`name` table, so they agree on what's accepted.
+fn choice_from_name<T: Copy>(all: &[T], name_of: fn(T) -> &'static str, name: &str) -> Option<T> {
+    let name = name.trim();
+    all.iter().copied().find(|&item| name_of(item).eq_ignore_ascii_case(name))
+}
+
+/// Names of a config enum's variants as "a, b or c", for error messages
+fn describe_choices<T: Copy>(all: &[T], name_of: fn(T) -> &'static str) -> String {
+    let names: Vec<&str> = all.iter().map(|&item| name_of(item)).collect();
+    match names.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+        _ => names.concat(),
+    }
+}
+
+/// Parse a CLI value for an enum setting, naming the choices on error
+fn parse_choice_field<T: Copy>(key: &str, value: &str, all: &[T], name_of: fn(T) -> &'static str) -> Result<T, String> {
+    choice_from_name(all, name_of, value)
+        .ok_or_else(|| format!("{} must be {}, got '{}'", key, describe_choices(all, name_of), value))
+}
+
+/// Deserialize an enum setting from its name; an unknown name falls back to
+/// the default with a warning rather than rejecting the whole file
+fn deserialize_choice<'de, D, T>(
+    deserializer: D,
+    field: &str,
+    from_name: fn(&str) -> Option<T>,
+    name_of: fn(T) -> &'static str,
+) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Copy + Default,
+{
+    let value = String::deserialize(deserializer)?;
+    Ok(from_name(&value).unwrap_or_else(|| {
+        let fallback = T::default();
+        log::warn!("Unknown {} '{}', using '{}'", field, value, name_of(fallback));
+        fallback
+    }))
+}
+
+/// Split a comma-separated CLI value into trimmed, non-empty items
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Whether a config path should be read and written as TOML
+fn is_toml_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+}
+
+/// Replace the file at `path` with `contents` so readers never see a partial write
+///
+/// WHY: Writing in place leaves a truncated, unparseable file if the process
+/// dies mid-write. Instead the data goes to a temp file in the same directory
+/// (so the rename stays on one volume), is flushed to disk, and then renamed
+/// over the target.
+pub(crate) fn write_atomic(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    let mut tmp_name = path.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_name);
+
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+        drop(file);
+
+        // std's rename replaces an existing file on Windows too
+        // (MoveFileExW with MOVEFILE_REPLACE_EXISTING); if that still fails,
+        // e.g. because the target is read-only, remove it and try again.
+        if let Err(e) = fs::rename(&tmp_path, path) {
+            if !path.exists() {
+                return Err(e);
+            }
+            fs::remove_file(path)?;
+            fs::rename(&tmp_path, path)?;
+        }
+        Ok(())
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Where a corrupt config file is moved: the same path with ".bak" appended
+fn backup_path(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+/// Profile names become file names, so keep them to letters, digits, - and _
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Names of the `*.json` profiles in `dir`, sorted; empty if it doesn't exist
+fn list_profiles_in(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .filter_map(|path| path.file_stem()?.to_str().map(str::to_string))
+        .filter(|name| is_valid_profile_name(name))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Files whose edits change the config loaded from `path`: the file
+/// itself, plus the profile it selects, if any
+fn watched_files(path: &Path) -> Vec<PathBuf> {
+    let mut files = vec![path.to_path_buf()];
+    if let Ok(config) = Config::parse_file(path) {
+        if config.uses_profile() {
+            files.push(Config::profile_path(&config.active_profile));
+        }
+    }
+    files
+}
+
+/// [`file_stamp`] of each of `files`
+fn file_stamps(files: &[PathBuf]) -> Vec<Option<(SystemTime, u64)>> {
+    files.iter().map(|file| file_stamp(file)).collect()
+}
+
+/// Decides when [`Config::watch`] reloads: once the watched files have
+/// stopped changing for [`WATCH_DEBOUNCE`]
+struct ReloadDebounce {
+    last_stamps: Vec<Option<(SystemTime, u64)>>,
+    pending_since: Option<Instant>,
+}
+
+impl ReloadDebounce {
+    fn new(stamps: Vec<Option<(SystemTime, u64)>>) -> Self {
+        Self {
+            last_stamps: stamps,
+            pending_since: None,
+        }
+    }
+
+    /// Feed the stamps seen at `now`; returns true when it's time to reload
+    fn poll(&mut self, stamps: Vec<Option<(SystemTime, u64)>>, now: Instant) -> bool {
+        if stamps != self.last_stamps {
+            // Restart the debounce window on every write
+            self.last_stamps = stamps;
+            self.pending_since = Some(now);
+            return false;
+        }
+        if self
+            .pending_since
+            .is_some_and(|since| now.duration_since(since) >= WATCH_DEBOUNCE)
+        {
+            self.pending_since = None;
+            return true;
+        }
+        false
+    }
+}
+
+/// Modification time and size of a file, used to detect changes
+/// WHY: Size is included because some filesystems only store mtime with
+/// one-second resolution, which can hide two quick saves.
+fn file_stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// One hotkey as written in the config: a combo string such as
+/// "ctrl+shift+b", or the same combo as an object for generated configs,
+/// `{"modifiers": ["ctrl", "shift"], "key": "b"}`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HotkeyEntry {
+    Combo(String),
+    Keys {
+        #[serde(default)]
+        modifiers: Vec<String>,
+        key: String,
+    },
+}
+
+impl HotkeyEntry {
+    /// The combo string both forms are stored as
+    fn into_combo(self) -> String {
+        match self {
+            Self::Combo(combo) => combo,
+            Self::Keys { modifiers, key } => {
+                let mut parts: Vec<&str> = modifiers.iter().map(|m| m.trim()).collect();
+                parts.push(key.trim());
+                parts.join("+")
+            }
+        }
+    }
+}
+
+/// Accept either a single hotkey (legacy `hotkey` field) or a list, each
+/// a string or a `{"modifiers": [...], "key": ...}` object
+fn deserialize_hotkeys<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(HotkeyEntry),
+        Many(Vec<HotkeyEntry>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(hotkey) => vec![hotkey.into_combo()],
+        OneOrMany::Many(hotkeys) => hotkeys.into_iter().map(HotkeyEntry::into_combo).collect(),
+    })
+}
+
+/// Named opacities for people who don't think in fractions
+pub const OPACITY_PRESETS: &[(&str, f32)] = &[
+    ("light", 0.15),
+    ("medium", 0.3),
+    ("heavy", 0.6),
+    ("solid", 1.0),
+];
+
+/// How much one press of an opacity hotkey changes the opacity
+pub const OPACITY_STEP: f32 = 0.1;
+
+/// `opacity` moved by `steps` opacity steps (negative lowers it), clamped to 0.0..=1.0
+pub fn step_opacity(opacity: f32, steps: i32) -> f32 {
+    let stepped = opacity + OPACITY_STEP * steps as f32;
+    // WHY: Round to hundredths so repeated steps land on 0.4, not 0.40000004
+    ((stepped * 100.0).round() / 100.0).clamp(0.0, 1.0)
+}
+
+/// Parse an opacity given as a number or a preset name (case-insensitive)
+pub fn parse_opacity(value: &str) -> Option<f32> {
+    let value = value.trim();
+    OPACITY_PRESETS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(value))
+        .map(|&(_, opacity)| opacity)
+        .or_else(|| value.parse().ok())
+}
+
+/// An opacity as written in a config file
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumberOrPreset {
+    Number(f32),
+    Preset(String),
+}
+
+impl NumberOrPreset {
+    /// The opacity, or None for an unknown preset name
+    fn opacity(self) -> Result<f32, String> {
+        match self {
+            Self::Number(opacity) => Ok(opacity),
+            Self::Preset(name) => parse_opacity(&name).ok_or(name),
+        }
+    }
+}
+
+/// Accept `opacity` as a number or a preset name
+///
+/// An unknown name falls back to the default with a warning, like the
+/// config enums; out-of-range numbers are clamped later by `validate`.
+fn deserialize_opacity<'de, D>(deserializer: D) -> Result<f32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(NumberOrPreset::deserialize(deserializer)?.opacity().unwrap_or_else(|name| {
+        let default = Config::default().opacity;
+        log::warn!("Unknown opacity '{}', using {}", name, default);
+        default
+    }))
+}
+
+/// Accept a [`ModeOverlay`] opacity like `opacity`, or null
+///
+/// An unknown preset name leaves the mode on the top-level opacity.
+fn deserialize_optional_opacity<'de, D>(deserializer: D) -> Result<Option<f32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Ok(Option::<NumberOrPreset>::deserialize(deserializer)?.and_then(|value| {
+        value
+            .opacity()
+            .map_err(|name| log::warn!("Unknown opacity '{}' in lock_mode_overlays, ignoring it", name))
+            .ok()
+    }))
+}
+
+/// Parse `lock_mode_overlays` from its `config set` form
+///
+/// A comma-separated list of `mode=settings`, where the settings are an
+/// opacity, a color or both, separated by a space:
+/// `"both=0.6 #000000, keyboardonly=light"`. "off" or "none" clears it.
+fn parse_mode_overlays(value: &str) -> Result<BTreeMap<String, ModeOverlay>, String> {
+    if matches!(value, "off" | "none") {
+        return Ok(BTreeMap::new());
+    }
+    let mut overlays = BTreeMap::new();
+    for item in split_list(value) {
+        let (mode, settings) = item
+            .split_once('=')
+            .ok_or_else(|| format!("lock mode overlay '{}' must look like mode=opacity or mode=color", item))?;
+        let mode = LockMode::from_name(mode).ok_or_else(|| {
+            format!(
+                "unknown lock mode '{}' ({})",
+                mode.trim(),
+                describe_choices(LockMode::ALL, LockMode::name)
+            )
+        })?;
+        let mut overlay = ModeOverlay::default();
+        for setting in settings.split_whitespace() {
+            match parse_opacity(setting) {
+                Some(opacity) if (0.0..=1.0).contains(&opacity) => overlay.opacity = Some(opacity),
+                Some(_) => return Err(format!("opacity for {} must be from 0.0 to 1.0, got '{}'", mode.name(), setting)),
+                None => {
+                    check_color(&format!("overlay_color for {}", mode.name()), setting)?;
+                    overlay.overlay_color = Some(setting.to_string());
+                }
+            }
+        }
+        if overlay == ModeOverlay::default() {
+            return Err(format!("lock mode overlay '{}' needs an opacity or a color", item));
+        }
+        overlays.insert(mode.name().to_string(), overlay);
+    }
+    Ok(overlays)
+}
+
+/// Format `lock_mode_overlays` the way [`parse_mode_overlays`] reads it
+pub fn format_mode_overlays(overlays: &BTreeMap<String, ModeOverlay>) -> String {
+    overlays
+        .iter()
+        .map(|(mode, overlay)| {
+            let settings: Vec<String> = overlay
+                .opacity
+                .map(|opacity| opacity.to_string())
+                .into_iter()
+                .chain(overlay.overlay_color.clone())
+                .collect();
+            format!("{}={}", mode, settings.join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Reason a hotkey string could not be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HotkeyParseError {
+    /// A token before the final key was not a recognized modifier (e.g. "crtl")
+    UnknownModifier(String),
+    /// The final token was not a recognized key name
+    UnknownKey(String),
+    /// Only modifiers were given (e.g. "ctrl+shift")
+    NoKeySpecified,
+    /// More than one non-modifier key was given (e.g. "ctrl+a+b"); holds
+    /// every key named, in order
+    MultipleKeys(Vec<String>),
+    /// A step of a comma-separated sequence was empty (e.g. "ctrl+b,,l")
+    EmptyStep,
+}
+
+impl std::fmt::Display for HotkeyParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownModifier(token) => write!(f, "unknown modifier \"{}\"", token),
+            Self::UnknownKey(token) => write!(f, "unknown key \"{}\"", token),
+            Self::NoKeySpecified => write!(f, "no key specified, only modifiers"),
+            Self::MultipleKeys(keys) => {
+                write!(f, "more than one non-modifier key specified ({})", keys.join(", "))
+            }
+            Self::EmptyStep => write!(f, "empty step in hotkey sequence"),
+        }
+    }
+}
+
+impl std::error::Error for HotkeyParseError {}
+
+/// Parse hotkey string into modifier flags and virtual key code
+/// Returns (modifiers, vk_code) where modifiers is a bitmask
+///
+/// Thin wrapper around [`parse_hotkey_detailed`] that discards the error, so
+/// a string naming more than one key (e.g. "ctrl+a+b") is None rather than
+/// either key.
+pub fn parse_hotkey(hotkey: &str) -> Option<(u32, u32)> {
+    parse_hotkey_detailed(hotkey).ok()
+}
+
+/// Canonical name of a parsed hotkey, e.g. "Ctrl+Shift+F5"
+///
+/// Reverses [`parse_hotkey`]: modifiers come first in a fixed order (Ctrl,
+/// Alt, Shift, Win) and special keys use their hotkey names, so the result
+/// parses back to the same values. Side requirements packed into a sequence
+/// step (see [`ParsedHotkey::to_step`]) show as "LCtrl", "RAlt" etc.
+pub fn describe_hotkey(modifiers: u32, vk: u32) -> String {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    let (modifiers, sides) = split_step_modifiers(modifiers);
+    let mut parts: Vec<&str> = Vec::new();
+    for (flag, name, left, right, left_name, right_name) in [
+        (MOD_CONTROL, "Ctrl", ModifierSides::LEFT_CTRL, ModifierSides::RIGHT_CTRL, "LCtrl", "RCtrl"),
+        (MOD_ALT, "Alt", ModifierSides::LEFT_ALT, ModifierSides::RIGHT_ALT, "LAlt", "RAlt"),
+        (MOD_SHIFT, "Shift", ModifierSides::LEFT_SHIFT, ModifierSides::RIGHT_SHIFT, "LShift", "RShift"),
+    ] {
+        if sides.contains(left) {
+            parts.push(left_name);
+        }
+        if sides.contains(right) {
+            parts.push(right_name);
+        }
+        if modifiers & flag.0 != 0 && !sides.contains(left) && !sides.contains(right) {
+            parts.push(name);
+        }
+    }
+    if modifiers & MOD_WIN.0 != 0 {
+        parts.push("Win");
+    }
+
+    let key = describe_key(vk);
+    parts.push(&key);
+    parts.join("+")
+}
+
+/// Canonical lowercase spelling of a hotkey or sequence, e.g. "B+Ctrl" ->
+/// "ctrl+b", or None if it doesn't parse
+pub fn normalize_hotkey(hotkey: &str) -> Option<String> {
+    let steps = parse_hotkey_sequence(hotkey)?;
+    let steps: Vec<String> = steps
+        .iter()
+        .map(|&(modifiers, vk)| describe_hotkey(modifiers, vk).to_lowercase())
+        .collect();
+    Some(steps.join(","))
+}
+
+/// Name of a virtual key as the hotkey parser spells it, capitalized
+///
+/// Keys the parser has no name for show as their hex code.
+fn describe_key(vk: u32) -> String {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    let code = |key: VIRTUAL_KEY| u32::from(key.0);
+    if (0x30..=0x39).contains(&vk) || (0x41..=0x5A).contains(&vk) {
+        return char::from_u32(vk).map(String::from).unwrap_or_default();
+    }
+    if (code(VK_F1)..=code(VK_F24)).contains(&vk) {
+        return format!("F{}", vk - code(VK_F1) + 1);
+    }
+    if (code(VK_NUMPAD0)..=code(VK_NUMPAD9)).contains(&vk) {
+        return format!("Num{}", vk - code(VK_NUMPAD0));
+    }
+
+    let named = [
+        (VK_ADD, "NumAdd"),
+        (VK_SUBTRACT, "NumSub"),
+        (VK_MULTIPLY, "NumMul"),
+        (VK_DIVIDE, "NumDiv"),
+        (VK_DECIMAL, "NumDec"),
+        (VK_SPACE, "Space"),
+        (VK_RETURN, "Enter"),
+        (VK_ESCAPE, "Esc"),
+        (VK_TAB, "Tab"),
+        (VK_BACK, "Backspace"),
+        (VK_DELETE, "Delete"),
+        (VK_INSERT, "Insert"),
+        (VK_HOME, "Home"),
+        (VK_END, "End"),
+        (VK_PRIOR, "PageUp"),
+        (VK_NEXT, "PageDown"),
+        (VK_UP, "Up"),
+        (VK_DOWN, "Down"),
+        (VK_LEFT, "Left"),
+        (VK_RIGHT, "Right"),
+        (VK_NUMLOCK, "NumLock"),
+        (VK_SCROLL, "ScrollLock"),
+        (VK_PAUSE, "Pause"),
+        (VK_SNAPSHOT, "PrintScreen"),
+        (VK_APPS, "Apps"),
+        (VK_OEM_1, "Semicolon"),
+        (VK_OEM_PLUS, "Equals"),
+        (VK_OEM_COMMA, "Comma"),
+        (VK_OEM_MINUS, "Minus"),
+        (VK_OEM_PERIOD, "Period"),
+        (VK_OEM_2, "Slash"),
+        (VK_OEM_3, "Backtick"),
+        (VK_OEM_4, "LBracket"),
+        (VK_OEM_5, "Backslash"),
+        (VK_OEM_6, "RBracket"),
+        (VK_OEM_7, "Quote"),
+        (VK_MEDIA_PLAY_PAUSE, "Play"),
+        (VK_MEDIA_STOP, "Stop"),
+        (VK_MEDIA_NEXT_TRACK, "Next"),
+        (VK_MEDIA_PREV_TRACK, "Prev"),
+        (VK_VOLUME_UP, "VolUp"),
+        (VK_VOLUME_DOWN, "VolDown"),
+        (VK_VOLUME_MUTE, "Mute"),
+        (VK_LBUTTON, "Mouse1"),
+        (VK_RBUTTON, "Mouse2"),
+        (VK_MBUTTON, "Mouse3"),
+        (VK_XBUTTON1, "Mouse4"),
+        (VK_XBUTTON2, "Mouse5"),
+    ];
+    named
+        .iter()
+        .find(|&&(key, _)| code(key) == vk)
+        .map(|&(_, name)| name.to_string())
+        .unwrap_or_else(|| format!("0x{:02X}", vk))
+}
+
+/// Whether `combo` is a printable key with no modifier, e.g. "b"
+///
+/// Such a hotkey fires whenever the user types that character, which is
+/// almost certainly a mistake. Keys that aren't typed in normal text (media
+/// keys, F-keys) don't count.
+pub fn requires_modifier_warning(combo: &str) -> bool {
+    parse_hotkey(combo).is_some_and(|(modifiers, vk)| modifiers == 0 && is_printable_vk(vk))
+}
+
+/// Whether a virtual key produces a character when typed
+fn is_printable_vk(vk: u32) -> bool {
+    matches!(
+        vk,
+        0x20                // Space
+        | 0x30..=0x39       // 0-9
+        | 0x41..=0x5A       // A-Z
+        | 0x60..=0x6F       // Numpad digits and operators
+        | 0xBA..=0xC0       // ;=,-./`
+        | 0xDB..=0xDF       // [\]'
+        | 0xE2 // <> on ISO keyboards
+    )
+}
+
+/// Whether `hotkey` is a complete combo: at least one modifier plus a key
+///
+/// Meant for validating input as the user types. The one exception is
+/// Pause/Break or PrintScreen on its own, which is accepted without a
+/// modifier (see [`is_standalone_key`]); other bare keys such as "play"
+/// parse, but are rejected here.
+pub fn is_valid_hotkey(hotkey: &str) -> bool {
+    parse_hotkey(hotkey).is_some_and(|(modifiers, vk)| modifiers != 0 || is_standalone_key(vk))
+}
+
+/// Whether a key makes a sensible hotkey without any modifier
+///
+/// WHY: Pause/Break and PrintScreen have no everyday use worth keeping, so
+/// binding the lock to a single press of one of them is a deliberate choice.
+pub fn is_standalone_key(vk: u32) -> bool {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{VK_PAUSE, VK_SNAPSHOT};
+
+    vk == u32::from(VK_PAUSE.0) || vk == u32::from(VK_SNAPSHOT.0)
+}
+
+/// Parse a comma-separated hotkey sequence (e.g. "ctrl+b,l") into its steps
+///
+/// A single combo parses as a one-step sequence.
+pub fn parse_hotkey_sequence(sequence: &str) -> Option<Vec<(u32, u32)>> {
+    parse_hotkey_sequence_detailed(sequence).ok()
+}
+
+/// Parse a hotkey sequence, reporting which step or token was wrong on failure
+pub fn parse_hotkey_sequence_detailed(sequence: &str) -> Result<Vec<(u32, u32)>, HotkeyParseError> {
+    sequence
+        .split(',')
+        .map(|step| {
+            if step.trim().is_empty() {
+                Err(HotkeyParseError::EmptyStep)
+            } else {
+                parse_hotkey_sided(step).map(ParsedHotkey::to_step)
+            }
+        })
+        .collect()
+}
+
+/// Side-specific modifiers of a hotkey, as a bitmask
+///
+/// WHY: RegisterHotKey's MOD_* flags can't tell left from right. PawGate
+/// detects hotkeys in its low-level keyboard hook, which sees the individual
+/// VK_LCONTROL/VK_RCONTROL (etc.) keys, so sided tokens are enforced there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct ModifierSides(pub u8);
+
+impl ModifierSides {
+    pub const LEFT_CTRL: Self = Self(1);
+    pub const RIGHT_CTRL: Self = Self(1 << 1);
+    pub const LEFT_ALT: Self = Self(1 << 2);
+    pub const RIGHT_ALT: Self = Self(1 << 3);
+    pub const LEFT_SHIFT: Self = Self(1 << 4);
+    pub const RIGHT_SHIFT: Self = Self(1 << 5);
+
+    pub fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+}
+
+impl std::ops::BitOr for ModifierSides {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+/// Bit offset of the [`ModifierSides`] packed into a sequence step's modifiers
+const SIDES_SHIFT: u32 = 16;
+
+/// A parsed hotkey combo, including which side each modifier must be on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParsedHotkey {
+    /// MOD_* flags; sided modifiers also set their generic flag
+    pub modifiers: u32,
+    /// Modifiers that must be pressed on a specific side
+    pub sides: ModifierSides,
+    /// Virtual key code of the main key
+    pub vk: u32,
+}
+
+impl ParsedHotkey {
+    /// Pack into the (modifiers, vk) step used by hotkey sequences
+    ///
+    /// The sides go in the bits above the MOD_* flags; recover them with
+    /// [`split_step_modifiers`].
+    pub fn to_step(self) -> (u32, u32) {
+        (self.modifiers | (u32::from(self.sides.0) << SIDES_SHIFT), self.vk)
+    }
+}
+
+/// Split a sequence step's modifier word into MOD_* flags and sides
+pub fn split_step_modifiers(modifiers: u32) -> (u32, ModifierSides) {
+    (
+        modifiers & ((1 << SIDES_SHIFT) - 1),
+        ModifierSides((modifiers >> SIDES_SHIFT) as u8),
+    )
+}
+
+/// Parse hotkey string, reporting which token was not understood on failure
+///
+/// Side-specific modifiers ("lctrl", "altgr", ...) are reported as their
+/// generic MOD_* flag; use [`parse_hotkey_sided`] to keep the side.
+pub fn parse_hotkey_detailed(hotkey: &str) -> Result<(u32, u32), HotkeyParseError> {
+    parse_hotkey_sided(hotkey).map(|parsed| (parsed.modifiers, parsed.vk))
+}
+
+/// Parse hotkey string, keeping which side each modifier must be pressed on
+///
+/// Doesn't allocate unless it fails: tokens are case-folded on the stack.
+pub fn parse_hotkey_sided(hotkey: &str) -> Result<ParsedHotkey, HotkeyParseError> {
+    let mut parts = hotkey.split('+').map(str::trim).filter(|s| !s.is_empty()).peekable();
+
+    let mut modifiers: u32 = 0;
+    let mut sides = ModifierSides::default();
+    let mut vk_code: Option<u32> = None;
+    // The key token that set `vk_code`, and every key once there's more than one
+    let mut key_name = "";
+    let mut extra_keys: Vec<String> = Vec::new();
+
+    while let Some(part) = parts.next() {
+        let mut buf = [0; MAX_NAME_LEN];
+        let name = ascii_lowercase_name(part, &mut buf);
+
+        if let Some(flag) = name.and_then(parse_modifier) {
+            modifiers |= flag;
+            continue;
+        }
+        if let Some((flag, side)) = name.and_then(parse_sided_modifier) {
+            modifiers |= flag;
+            sides = sides | side;
+            continue;
+        }
+
+        match name.and_then(parse_key) {
+            // WHY: Keep going so the error can name every key, not just the first two
+            Some(_) if vk_code.is_some() => {
+                if extra_keys.is_empty() {
+                    extra_keys.push(key_name.to_ascii_lowercase());
+                }
+                extra_keys.push(part.to_ascii_lowercase());
+            }
+            Some(vk) => {
+                vk_code = Some(vk);
+                key_name = part;
+            }
+            // WHY: The key conventionally comes last, so an unrecognized token
+            // anywhere before it is most likely a misspelled modifier.
+            None if parts.peek().is_none() => {
+                return Err(HotkeyParseError::UnknownKey(part.to_ascii_lowercase()))
+            }
+            None => return Err(HotkeyParseError::UnknownModifier(part.to_ascii_lowercase())),
+        }
+    }
+
+    if !extra_keys.is_empty() {
+        return Err(HotkeyParseError::MultipleKeys(extra_keys));
+    }
+    vk_code
+        .map(|vk| ParsedHotkey { modifiers, sides, vk })
+        .ok_or(HotkeyParseError::NoKeySpecified)
+}
+
+/// Room for the longest key or modifier name ("rightbracket"), with some to spare
+const MAX_NAME_LEN: usize = 16;
+
+/// `token` lowercased into `buf`, or None if it's too long or not ASCII,
+/// since then it can't be a key or modifier name
+///
+/// WHY: Names are all ASCII, so ASCII folding is enough and needs no
+/// allocation. Full Unicode lowercasing would also fold look-alikes such as
+/// the Kelvin sign (U+212A) into "k" and accept them as that key.
+fn ascii_lowercase_name<'a>(token: &str, buf: &'a mut [u8; MAX_NAME_LEN]) -> Option<&'a str> {
+    if token.len() > MAX_NAME_LEN || !token.is_ascii() {
+        return None;
+    }
+    let name = &mut buf[..token.len()];
+    name.copy_from_slice(token.as_bytes());
+    name.make_ascii_lowercase();
+    std::str::from_utf8(name).ok()
+}
+
+/// A combo that Windows handles itself, so PawGate can never own it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HotkeyConflict {
+    /// Ctrl+Alt+Del is the secure attention sequence and never reaches apps
+    SecureAttention,
+    /// Win+L locks the Windows session no matter what hooks do
+    SessionLock,
+}
+
+impl HotkeyConflict {
+    /// Every combo Windows keeps for itself; the keyboard hook never sees
+    /// them, so they keep working while locked
+    pub const ALL: [Self; 2] = [Self::SecureAttention, Self::SessionLock];
+
+    /// The combo as users write it
+    pub fn combo(self) -> &'static str {
+        match self {
+            Self::SecureAttention => "Ctrl+Alt+Del",
+            Self::SessionLock => "Win+L",
+        }
+    }
+}
+
+impl std::fmt::Display for HotkeyConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::SecureAttention => write!(f, "is reserved by Windows for the security screen"),
+            Self::SessionLock => write!(f, "always locks Windows itself"),
+        }
+    }
+}
+
+/// Check whether a parsed combo is one Windows keeps for itself
+pub fn hotkey_conflict(modifiers: u32, vk: u32) -> Option<HotkeyConflict> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    let held = |flag: HOT_KEY_MODIFIERS| modifiers & flag.0 != 0;
+    if held(MOD_CONTROL) && held(MOD_ALT) && vk == u32::from(VK_DELETE.0) {
+        Some(HotkeyConflict::SecureAttention)
+    } else if held(MOD_WIN) && vk == 'L' as u32 {
+        Some(HotkeyConflict::SessionLock)
+    } else {
+        None
+    }
+}
+
+/// The first reserved combo in a parsed sequence, if any
+pub fn sequence_conflict(sequence: &[(u32, u32)]) -> Option<HotkeyConflict> {
+    sequence
+        .iter()
+        .find_map(|&(modifiers, vk)| hotkey_conflict(modifiers, vk))
+}
+
+/// Explain a hotkey conflict to the user, naming the combo they tried
+pub fn hotkey_conflict_message(combo: &str, conflict: HotkeyConflict) -> String {
+    format!(
+        "The hotkey \"{}\" {}, so PawGate can't use it. Pick a different combo, such as ctrl+b or ctrl+alt+k.",
+        combo, conflict
+    )
+}
+
+/// Map a modifier token to its MOD_* flag
+fn parse_modifier(token: &str) -> Option<u32> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    match token {
+        "ctrl" | "control" => Some(MOD_CONTROL.0),
+        "alt" => Some(MOD_ALT.0),
+        "shift" => Some(MOD_SHIFT.0),
+        "win" | "windows" => Some(MOD_WIN.0),
+        _ => None,
+    }
+}
+
+/// Map a modifier name to the virtual keys that count as tapping it
+///
+/// A plain name accepts either side, like in hotkeys; "lctrl" etc. only one.
+fn double_tap_keys(token: &str) -> Option<Vec<u32>> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    let keys: &[VIRTUAL_KEY] = match token.trim().to_lowercase().as_str() {
+        "ctrl" | "control" => &[VK_LCONTROL, VK_RCONTROL, VK_CONTROL],
+        "shift" => &[VK_LSHIFT, VK_RSHIFT, VK_SHIFT],
+        "alt" => &[VK_LMENU, VK_RMENU, VK_MENU],
+        "lctrl" | "lcontrol" => &[VK_LCONTROL],
+        "rctrl" | "rcontrol" => &[VK_RCONTROL],
+        "lshift" => &[VK_LSHIFT],
+        "rshift" => &[VK_RSHIFT],
+        "lalt" => &[VK_LMENU],
+        "ralt" => &[VK_RMENU],
+        _ => return None,
+    };
+    Some(keys.iter().map(|vk| u32::from(vk.0)).collect())
+}
+
+/// Map a `tap_hold_key` name to its virtual keys: a modifier (either side
+/// unless one is named) or any single key that hotkeys accept
+fn tap_hold_keys(token: &str) -> Option<Vec<u32>> {
+    double_tap_keys(token).or_else(|| parse_key(&token.trim().to_lowercase()).map(|vk| vec![vk]))
+}
+
+/// Map a side-specific modifier token to its MOD_* flag(s) and side(s)
+fn parse_sided_modifier(token: &str) -> Option<(u32, ModifierSides)> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    match token {
+        "lctrl" | "lcontrol" => Some((MOD_CONTROL.0, ModifierSides::LEFT_CTRL)),
+        "rctrl" | "rcontrol" => Some((MOD_CONTROL.0, ModifierSides::RIGHT_CTRL)),
+        "lalt" => Some((MOD_ALT.0, ModifierSides::LEFT_ALT)),
+        "ralt" => Some((MOD_ALT.0, ModifierSides::RIGHT_ALT)),
+        "lshift" => Some((MOD_SHIFT.0, ModifierSides::LEFT_SHIFT)),
+        "rshift" => Some((MOD_SHIFT.0, ModifierSides::RIGHT_SHIFT)),
+        // WHY: Windows reports AltGr as Left Ctrl + Right Alt
+        "altgr" => Some((
+            MOD_CONTROL.0 | MOD_ALT.0,
+            ModifierSides::LEFT_CTRL | ModifierSides::RIGHT_ALT,
+        )),
+        _ => None,
+    }
+}
+
+/// Map a key token to its virtual key code
+fn parse_key(token: &str) -> Option<u32> {
+    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+
+    match token {
+        // Single letter keys
+        key if key.len() == 1 => {
+            let c = key.chars().next().unwrap().to_ascii_uppercase();
+            if c.is_ascii_alphanumeric() {
+                Some(c as u32)
+            } else {
+                None
+            }
+        }
+        // Function keys
+        key if key.starts_with('f') && key.len() <= 3 => match key[1..].parse::<u32>() {
+            Ok(num) if (1..=24).contains(&num) => Some(VK_F1.0 as u32 + num - 1),
+            _ => None,
+        },
+        // Numpad digits (distinct from the top-row number keys)
+        key if key.starts_with("num") && key.len() == 4 => key[3..]
+            .chars()
+            .next()
+            .and_then(|c| c.to_digit(10))
+            .map(|digit| VK_NUMPAD0.0 as u32 + digit),
+        // Numpad operators
+        "numadd" => Some(VK_ADD.0 as u32),
+        "numsub" => Some(VK_SUBTRACT.0 as u32),
+        "nummul" => Some(VK_MULTIPLY.0 as u32),
+        "numdiv" => Some(VK_DIVIDE.0 as u32),
+        "numdec" => Some(VK_DECIMAL.0 as u32),
+        // Special keys
+        "space" => Some(VK_SPACE.0 as u32),
+        "enter" | "return" => Some(VK_RETURN.0 as u32),
+        "escape" | "esc" => Some(VK_ESCAPE.0 as u32),
+        "tab" => Some(VK_TAB.0 as u32),
+        "backspace" => Some(VK_BACK.0 as u32),
+        "delete" | "del" => Some(VK_DELETE.0 as u32),
+        "insert" | "ins" => Some(VK_INSERT.0 as u32),
+        "home" => Some(VK_HOME.0 as u32),
+        "end" => Some(VK_END.0 as u32),
+        "pageup" | "pgup" => Some(VK_PRIOR.0 as u32),
+        "pagedown" | "pgdn" => Some(VK_NEXT.0 as u32),
+        "up" => Some(VK_UP.0 as u32),
+        "down" => Some(VK_DOWN.0 as u32),
+        "left" => Some(VK_LEFT.0 as u32),
+        "right" => Some(VK_RIGHT.0 as u32),
+        "numlock" => Some(VK_NUMLOCK.0 as u32),
+        "scrolllock" => Some(VK_SCROLL.0 as u32),
+        "pause" => Some(VK_PAUSE.0 as u32),
+        "printscreen" | "prtsc" => Some(VK_SNAPSHOT.0 as u32),
+        // The context-menu key (not Alt, which Windows also calls VK_MENU)
+        "apps" | "menu" => Some(VK_APPS.0 as u32),
+        // OEM punctuation keys, named after their US-layout character
+        "semicolon" => Some(VK_OEM_1.0 as u32),
+        "equals" | "equal" => Some(VK_OEM_PLUS.0 as u32),
+        "comma" => Some(VK_OEM_COMMA.0 as u32),
+        "minus" | "dash" => Some(VK_OEM_MINUS.0 as u32),
+        "period" | "dot" => Some(VK_OEM_PERIOD.0 as u32),
+        "slash" => Some(VK_OEM_2.0 as u32),
+        "backtick" | "grave" => Some(VK_OEM_3.0 as u32),
+        "lbracket" | "leftbracket" => Some(VK_OEM_4.0 as u32),
+        "backslash" => Some(VK_OEM_5.0 as u32),
+        "rbracket" | "rightbracket" => Some(VK_OEM_6.0 as u32),
+        "quote" | "apostrophe" => Some(VK_OEM_7.0 as u32),
+        // Media keys (usually registered without modifiers)
+        // NOTE: "pause" stays bound to the Pause/Break key above, so the
+        // media toggle is reached through "play" or "playpause".
+        "play" | "playpause" => Some(VK_MEDIA_PLAY_PAUSE.0 as u32),
+        "stop" | "mediastop" => Some(VK_MEDIA_STOP.0 as u32),
+        "next" | "medianext" => Some(VK_MEDIA_NEXT_TRACK.0 as u32),
+        "prev" | "mediaprev" => Some(VK_MEDIA_PREV_TRACK.0 as u32),
+        "volup" | "volumeup" => Some(VK_VOLUME_UP.0 as u32),
+        "voldown" | "volumedown" => Some(VK_VOLUME_DOWN.0 as u32),
+        "mute" | "volumemute" => Some(VK_VOLUME_MUTE.0 as u32),
+        // Mouse buttons
+        // NOTE: RegisterHotKey and the WH_KEYBOARD_LL hook never see mouse
+        // buttons, so these only parse here; matching them needs a
+        // low-level mouse hook (WH_MOUSE_LL) path.
+        "mouse1" => Some(VK_LBUTTON.0 as u32),
+        "mouse2" => Some(VK_RBUTTON.0 as u32),
+        "mouse3" => Some(VK_MBUTTON.0 as u32),
+        "mouse4" => Some(VK_XBUTTON1.0 as u32),
+        "mouse5" => Some(VK_XBUTTON2.0 as u32),
+        _ => None,
+    }
+}
+
+// =============================================================================
+// Unit Tests
+// =============================================================================
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    // -------------------------------------------------------------------------
+    // Config Default Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_default_config_values() {
+        /// WHY: Verify default values match documentation and are sensible.
+        /// The default hotkey is part of the public API contract.
+        let config = Config::default();
+
+        assert_eq!(config.hotkeys, vec!["ctrl+b"], "Default hotkey should be ctrl+b");
+        assert_eq!(config.opacity, 0.3, "Default opacity should be 0.3 (30%)");
+        assert!(config.notifications_enabled, "Notifications should be enabled by default");
+        assert_eq!(config.overlay_color, "#1B5E20", "Default color should be forest green");
+        assert_eq!(config.version, CURRENT_CONFIG_VERSION, "Default should use current schema");
+        assert!(config.show_overlay, "The overlay should be shown by default");
+    }
+
+    #[test]
+    fn test_show_overlay_serialization() {
+        /// WHY: Turning the overlay off must survive a save/load, and older files keep it on.
+        let config = Config {
+            show_overlay: false,
+            ..Config::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains(r#""show_overlay":false"#), "{}", json);
+        let loaded: Config = serde_json::from_str(&json).unwrap();
+        assert!(!loaded.show_overlay);
+
+        let legacy: Config = serde_json::from_str(r#"{"opacity": 0.5}"#).unwrap();
+        assert!(legacy.show_overlay);
+    }
+
+    #[test]
+    fn test_enabled_by_default() {
+        /// WHY: Existing configs predate the kill switch and must keep PawGate working.
+        assert!(Config::default().enabled);
+        let legacy: Config = serde_json::from_str(r#"{"opacity": 0.5}"#).unwrap();
+        assert!(legacy.enabled, "A file without the field stays enabled");
+
+        let mut config = Config::default();
+        config.set_field("enabled", "false").unwrap();
+        assert!(!config.enabled);
+        let loaded: Config = serde_json::from_str(&serde_json::to_string(&config).unwrap()).unwrap();
+        assert!(!loaded.enabled, "Disabling survives a save/load");
+    }
+
+    #[test]
+    fn test_config_path_contains_pawgate() {
+        /// WHY: Config path must include .pawgate directory for organization.
+        // Hold the env lock so a test setting PAWGATE_CONFIG_DIR can't redirect it
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let xdg = std::env::var_os("XDG_CONFIG_HOME");
+        std::env::remove_var("XDG_CONFIG_HOME");
+        let path = Config::config_path();
+        if let Some(value) = xdg {
+            std::env::set_var("XDG_CONFIG_HOME", value);
+        }
+        let path_str = path.to_string_lossy();
+
+        assert!(path_str.contains(".pawgate"), "Config path should contain .pawgate");
+        assert!(path_str.ends_with("config.json"), "Config path should end with config.json");
+    }
+
+    // -------------------------------------------------------------------------
+    // Migration Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_migrate_version_zero_config() {
+        /// WHY: Pre-versioning configs must load with defaults for newer fields
+        /// and be stamped with the current version instead of failing to parse.
+        let json = r##"{
+            "hotkey": "ctrl+shift+l",
+            "opacity": 0.5
+        }"##;
+
+        let mut config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.version, 0, "Missing version should deserialize as 0");
+
+        assert!(config.migrate(), "Version 0 config should be migrated");
+        assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+        assert_eq!(config.hotkeys, vec!["ctrl+shift+l"]);
+        assert_eq!(config.opacity, 0.5);
+        assert!(config.notifications_enabled, "Missing field should use default");
+        assert_eq!(config.overlay_color, "#1B5E20", "Missing field should use default");
+    }
+
+    #[test]
+    fn test_migrate_current_version_is_noop() {
+        /// WHY: Up-to-date configs must not be re-saved on every load.
+        let mut config = Config::default();
+
+        assert!(!config.migrate());
+        assert_eq!(config.version, CURRENT_CONFIG_VERSION);
+    }
+
+    // -------------------------------------------------------------------------
+    // Opacity Preset Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_opacity_presets_deserialize() {
+        /// WHY: "heavy" is easier to pick than 0.6 when hand-editing the config.
+        for (name, expected) in [("light", 0.15), ("medium", 0.3), ("heavy", 0.6), ("solid", 1.0)] {
+            let json = format!(r#"{{"opacity": "{}"}}"#, name);
+            let config: Config = serde_json::from_str(&json).unwrap();
+            assert_eq!(config.opacity, expected, "Preset '{}'", name);
+        }
+    }
+
+    #[test]
+    fn test_opacity_raw_float_still_deserializes() {
+        /// WHY: Every existing config stores opacity as a number.
+        let config: Config = serde_json::from_str(r#"{"opacity": 0.42}"#).unwrap();
+        assert_eq!(config.opacity, 0.42);
+
+        let config: Config = toml::from_str("opacity = 0.42").unwrap();
+        assert_eq!(config.opacity, 0.42, "TOML numbers too");
+    }
+
+    #[test]
+    fn test_opacity_preset_case_and_unknown_name() {
+        /// WHY: A typo'd preset shouldn't stop PawGate from starting.
+        let config: Config = serde_json::from_str(r#"{"opacity": "Solid"}"#).unwrap();
+        assert_eq!(config.opacity, 1.0, "Case-insensitive");
+
+        let config: Config = serde_json::from_str(r#"{"opacity": "opaque"}"#).unwrap();
+        assert_eq!(config.opacity, Config::default().opacity, "Unknown name falls back");
+    }
+
+    #[test]
+    fn test_opacity_preset_saves_as_number() {
+        /// WHY: Saved configs must stay readable by older versions that only know numbers.
+        let config: Config = serde_json::from_str(r#"{"opacity": "heavy"}"#).unwrap();
+        let json = serde_json::to_value(&config).unwrap();
+        assert!(json["opacity"].is_number(), "{}", json["opacity"]);
+    }
+
+    #[test]
+    fn test_set_field_accepts_opacity_preset() {
+        /// WHY: `pawgate config set opacity light` should work like the config file does.
+        let mut config = Config::default();
+        config.set_field("opacity", "light").unwrap();
+        assert_eq!(config.opacity, 0.15);
+        assert!(config.set_field("opacity", "opaque").is_err());
+    }
+
+    #[test]
+    fn test_step_opacity_moves_by_tenths() {
+        /// WHY: Each opacity hotkey press should land on a clean value, without float drift.
+        assert_eq!(step_opacity(0.3, 1), 0.4);
+        assert_eq!(step_opacity(0.3, -1), 0.2);
+        assert_eq!(step_opacity(0.15, 1), 0.25, "Presets between steps keep their offset");
+        let mut opacity = 0.0;
+        for _ in 0..7 {
+            opacity = step_opacity(opacity, 1);
+        }
+        assert_eq!(opacity, 0.7);
+    }
+
+    #[test]
+    fn test_step_opacity_clamps_at_boundaries() {
+        /// WHY: Mashing the hotkey must never push the overlay past solid or below invisible.
+        assert_eq!(step_opacity(1.0, 1), 1.0);
+        assert_eq!(step_opacity(0.95, 1), 1.0);
+        assert_eq!(step_opacity(0.0, -1), 0.0);
+        assert_eq!(step_opacity(0.05, -1), 0.0);
+        assert_eq!(step_opacity(0.5, 12), 1.0, "Several presses at once");
+        assert_eq!(step_opacity(0.5, -12), 0.0);
+    }
+
+    #[test]
+    fn test_opacity_hotkeys_config() {
+        /// WHY: The opacity hotkeys are optional and validated like the peek hotkey.
+        let mut config = Config::default();
+        assert_eq!(config.parsed_opacity_up_hotkey(), None);
+        assert!(!config.persist_opacity);
+
+        config.set_field("opacity_up_hotkey", "ctrl+alt+up").unwrap();
+        config.set_field("opacity_down_hotkey", "ctrl+alt+down").unwrap();
+        config.set_field("persist_opacity", "true").unwrap();
+        assert!(config.parsed_opacity_up_hotkey().is_some());
+        assert!(config.parsed_opacity_down_hotkey().is_some());
+        assert!(config.persist_opacity);
+
+        assert!(config.set_field("opacity_up_hotkey", "ctrl+nope").is_err());
+        config.set_field("opacity_down_hotkey", "off").unwrap();
+        assert_eq!(config.opacity_down_hotkey, None);
+    }
+
+    // -------------------------------------------------------------------------
+    // Validation Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_validate_clamps_high_opacity() {
+        /// WHY: Hand-edited opacity above 1.0 would make the overlay fully opaque.
+        let json = r##"{
+            "hotkey": "ctrl+b",
+            "opacity": 5.0,
+            "notifications_enabled": true,
+            "overlay_color": "#1B5E20"
+        }"##;
+
+        let mut config: Config = serde_json::from_str(json).unwrap();
+        config.validate();
+        assert_eq!(config.opacity, 1.0, "Opacity above 1.0 should clamp to 1.0");
+    }
+
+    #[test]
+    fn test_validate_clamps_negative_opacity() {
+        /// WHY: Negative opacity is meaningless and should clamp to 0.0.
+        let json = r##"{
+            "hotkey": "ctrl+b",
+            "opacity": -1.0,
+            "notifications_enabled": true,
+            "overlay_color": "#1B5E20"
+        }"##;
+
+        let mut config: Config = serde_json::from_str(json).unwrap();
+        config.validate();
+        assert_eq!(config.opacity, 0.0, "Negative opacity should clamp to 0.0");
+    }
+
+    #[test]
+    fn test_validate_nan_opacity_uses_default() {
+        /// WHY: NaN can't be clamped meaningfully, so fall back to the default.
+        let mut config = Config {
+            opacity: f32::NAN,
+            ..Default::default()
+        };
+
+        config.validate();
+        assert_eq!(config.opacity, 0.3, "NaN opacity should fall back to 0.3");
+    }
+
+    #[test]
+    fn test_validate_keeps_valid_opacity() {
+        /// WHY: In-range values must pass through untouched.
+        let mut config = Config {
+            opacity: 0.75,
+            ..Default::default()
+        };
+
+        config.validate();
+        assert_eq!(config.opacity, 0.75);
+    }
+
+    #[test]
+    fn test_requires_modifier_warning() {
+        /// WHY: A bare letter hotkey would lock on every "b" the user types.
+        assert!(requires_modifier_warning("b"));
+        assert!(requires_modifier_warning("5"));
+        assert!(!requires_modifier_warning("ctrl+b"));
+        assert!(!requires_modifier_warning("play"), "Media keys aren't typed in text");
+        assert!(!requires_modifier_warning("f9"));
+        assert!(!requires_modifier_warning("notakey"));
+    }
+
+    #[test]
+    fn test_unmodified_hotkeys_warn_or_reject() {
+        /// WHY: By default a bare key only warns; opting in to rejection keeps it from registering.
+        let mut config = Config {
+            hotkeys: vec!["b".to_string(), "ctrl+b".to_string(), "ctrl+k,l".to_string()],
+            ..Default::default()
+        };
+        config.validate();
+        assert_eq!(config.parsed_hotkeys().len(), 3, "Warn-only registers every hotkey");
+
+        config.reject_unmodified_hotkeys = true;
+        config.validate();
+        assert_eq!(config.hotkeys.len(), 3, "Validation leaves the list alone");
+        assert_eq!(
+            config.parsed_hotkeys(),
+            vec![parse_hotkey_sequence("ctrl+b").unwrap(), parse_hotkey_sequence("ctrl+k,l").unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_rejected_hotkey_survives_save() {
+        /// WHY: Saving an unrelated setting must not delete a hotkey the user wrote in the file.
+        let dir = ScratchDir::new("rejected-hotkey");
+        let path = dir.join("config.json");
+        fs::write(&path, r#"{"version": 1, "hotkeys": ["b", "ctrl+b"], "reject_unmodified_hotkeys": true}"#).unwrap();
+
+        let mut config = Config::load_or_create(&path).unwrap();
+        config.opacity = 0.5;
+        config.save_to(&path).unwrap();
+
+        let saved: serde_json::Value = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved["hotkeys"], serde_json::json!(["b", "ctrl+b"]));
+    }
+
+    #[test]
+    fn test_json_schema_covers_every_field() {
+        /// WHY: An editor only autocompletes what the schema lists, so new fields must be added to it.
+        let schema: serde_json::Value = serde_json::from_str(&Config::json_schema()).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+
+        let defaults = serde_json::to_value(Config::default()).unwrap();
+        for field in defaults.as_object().unwrap().keys() {
+            assert!(properties.contains_key(field), "Schema is missing '{}'", field);
+        }
+        for field in properties.keys() {
+            assert!(defaults.get(field).is_some(), "Schema lists unknown field '{}'", field);
+        }
+    }
+
+    #[test]
+    fn test_diff_identical_configs() {
+        /// WHY: Re-saving an unchanged file must not count as a change.
+        let config = Config::default();
+        assert_eq!(config, config.clone());
+        assert!(config.diff(&config.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_diff_names_changed_fields() {
+        /// WHY: A reload acts only on what changed, so every changed field must be named, in order.
+        let config = Config::default();
+        let mut other = config.clone();
+        other.notifications_enabled = false;
+        assert_ne!(config, other);
+        assert_eq!(config.diff(&other), vec!["notifications_enabled"]);
+
+        other.opacity = 0.8;
+        other.monitors = vec![MonitorSelector::Index(1)];
+        other.keep_awake = !config.keep_awake;
+        assert_eq!(config.diff(&other), vec!["opacity", "notifications_enabled", "monitors", "keep_awake"]);
+        assert_eq!(other.diff(&config), config.diff(&other), "Diff is symmetric");
+    }
+
+    #[test]
+    fn test_diff_covers_every_field() {
+        /// WHY: A field missing from config_diff! would change without the reload noticing.
+        let defaults = serde_json::to_value(Config::default()).unwrap();
+        let mut serialized: Vec<&str> = defaults.as_object().unwrap().keys().map(String::as_str).collect();
+        let mut listed = Config::FIELD_NAMES.to_vec();
+        listed.sort_unstable();
+        serialized.sort_unstable();
+        assert_eq!(listed, serialized);
+
+        for field in Config::OVERLAY_FIELDS {
+            assert!(Config::FIELD_NAMES.contains(field), "Unknown overlay field '{}'", field);
+        }
+        for field in Config::HOOK_FIELDS {
+            assert!(Config::FIELD_NAMES.contains(field), "Unknown hook field '{}'", field);
+            assert!(!Config::OVERLAY_FIELDS.contains(field), "'{}' is never reloaded, so it can't refresh the overlay", field);
+        }
+    }
+
+    #[test]
+    fn test_reload_keeps_hook_fields() {
+        /// WHY: The hook only reads the hotkey and passphrase at startup, so the overlay mustn't show new ones early.
+        let running = Config::default();
+        let mut reloaded = Config {
+            hotkeys: vec!["ctrl+alt+k".to_string()],
+            unlock_passphrase: Some("meow".to_string()),
+            opacity: 0.5,
+            ..Config::default()
+        };
+        assert_eq!(reloaded.keep_hook_fields(&running), vec!["hotkeys", "unlock_passphrase"]);
+        assert_eq!(reloaded.hotkeys, running.hotkeys);
+        assert_eq!(reloaded.unlock_passphrase, None);
+        assert_eq!(reloaded.overlay_message(), running.overlay_message());
+        assert_eq!(reloaded.diff(&running), vec!["opacity"], "Live settings still reload");
+    }
+
+    #[test]
+    fn test_overlay_fields_are_settable() {
+        /// WHY: OVERLAY_FIELDS is kept by hand; a misspelt or renamed entry would stop the overlay refreshing.
+        for field in Config::OVERLAY_FIELDS {
+            let result = Config::default().set_field(field, "");
+            assert_ne!(result, Err(format!("unknown setting '{}'", field)), "set_field doesn't know '{}'", field);
+        }
+    }
+
+    // =========================================================================
+    // Per-lock-mode overlay tests
+    // =========================================================================
+
+    fn with_mode_overlays(lock_mode: LockMode, json: &str) -> Config {
+        Config {
+            lock_mode,
+            opacity: 0.3,
+            overlay_color: "#1B5E20".to_string(),
+            lock_mode_overlays: serde_json::from_str(json).unwrap(),
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_mode_overlay_overrides_current_mode() {
+        /// WHY: A full lock should get its own heavier overlay without touching the other modes.
+        let json = r##"{ "both": { "opacity": 0.9, "overlay_color": "#000000" } }"##;
+        let config = with_mode_overlays(LockMode::Both, json);
+        assert_eq!(config.effective_opacity(), 0.9);
+        assert_eq!(config.effective_overlay_color(), "#000000");
+        assert_eq!(config.parse_overlay_color(), (0, 0, 0));
+
+        let config = with_mode_overlays(LockMode::KeyboardOnly, json);
+        assert_eq!(config.effective_opacity(), 0.3, "Unlisted modes use the top-level opacity");
+        assert_eq!(config.effective_overlay_color(), "#1B5E20");
+    }
+
+    #[test]
+    fn test_partial_mode_overlay_falls_back_per_field() {
+        /// WHY: Setting only a lighter opacity for keyboard-only locks must keep the usual color.
+        let json = r#"{ "keyboardonly": { "opacity": "light" }, "mouseonly": { "overlay_color": "blue" } }"#;
+        let config = with_mode_overlays(LockMode::KeyboardOnly, json);
+        assert_eq!(config.effective_opacity(), parse_opacity("light").unwrap());
+        assert_eq!(config.effective_overlay_color(), "#1B5E20");
+
+        let config = with_mode_overlays(LockMode::MouseOnly, json);
+        assert_eq!(config.effective_opacity(), 0.3);
+        assert_eq!(config.effective_overlay_color(), "blue");
+
+        assert_eq!(Config::default().effective_opacity(), Config::default().opacity, "No map, no change");
+    }
+
+    #[test]
+    fn test_set_effective_opacity_targets_override() {
+        /// WHY: The opacity hotkeys must change the value the overlay actually shows.
+        let mut config = with_mode_overlays(LockMode::Both, r#"{ "both": { "opacity": 0.9 } }"#);
+        config.set_effective_opacity(0.8);
+        assert_eq!(config.effective_opacity(), 0.8);
+        assert_eq!(config.opacity, 0.3, "Top-level opacity untouched");
+
+        let mut config = with_mode_overlays(LockMode::Both, r#"{ "both": { "overlay_color": "red" } }"#);
+        config.set_effective_opacity(0.5);
+        assert_eq!(config.opacity, 0.5, "No per-mode opacity, so the top-level one changes");
+    }
+
+    #[test]
+    fn test_validate_mode_overlays() {
+        /// WHY: A misspelled mode or an out-of-range opacity must not silently misbehave.
+        let mut config = with_mode_overlays(
+            LockMode::Both,
+            r#"{ "keyboard": { "opacity": 0.1 }, "both": { "opacity": 1.5, "overlay_color": "nope" } }"#,
+        );
+        config.validate();
+        assert_eq!(config.lock_mode_overlays.len(), 1, "Unknown mode dropped");
+        assert_eq!(config.effective_opacity(), 1.0);
+        assert!(config.validate_color().unwrap_err().contains("lock_mode_overlays.both.overlay_color"));
+    }
+
+    #[test]
+    fn test_set_field_lock_mode_overlays() {
+        /// WHY: `config set lock_mode_overlays` uses a compact form that must round-trip.
+        let mut config = Config::default();
+        config
+            .set_field("lock_mode_overlays", "Both=0.9 #000000, keyboardonly=light")
+            .unwrap();
+        assert_eq!(
+            config.lock_mode_overlays["both"],
+            ModeOverlay {
+                opacity: Some(0.9),
+                overlay_color: Some("#000000".to_string()),
+            }
+        );
+        assert_eq!(config.lock_mode_overlays["keyboardonly"].overlay_color, None);
+        let formatted = format_mode_overlays(&config.lock_mode_overlays);
+        assert_eq!(parse_mode_overlays(&formatted).unwrap(), config.lock_mode_overlays);
+
+        for bad in ["keyboard=0.5", "both", "both=", "both=1.5", "both=0.5 notacolor"] {
+            assert!(config.set_field("lock_mode_overlays", bad).is_err(), "'{}' should be rejected", bad);
+        }
+        config.set_field("lock_mode_overlays", "off").unwrap();
+        assert!(config.lock_mode_overlays.is_empty());
+    }
+
+    #[test]
+    fn test_json_schema_constraints() {
+        /// WHY: The schema should catch the mistakes validate() would otherwise fix silently.
+        let schema: serde_json::Value = serde_json::from_str(&Config::json_schema()).unwrap();
+        let properties = &schema["properties"];
+
+        assert_eq!(properties["opacity"]["minimum"], 0.0);
+        assert_eq!(properties["opacity"]["maximum"], 1.0);
+
+        let color = &properties["overlay_color"]["anyOf"];
+        assert!(color[0]["pattern"].as_str().unwrap().starts_with("^#"));
+        assert!(color[1]["enum"].as_array().unwrap().contains(&serde_json::json!("green")));
+        assert_eq!(
+            properties["lock_mode"]["enum"],
+            serde_json::json!(["keyboardonly", "mouseonly", "both", "targetwindow"])
+        );
+    }
+
+    #[test]
+    fn test_enum_names_match_serialized_form() {
+        /// WHY: The schema lists name() for each variant, so it must be what the config file holds.
+        fn check<T: Copy + Serialize>(all: &[T], name: fn(T) -> &'static str) {
+            for &item in all {
+                assert_eq!(serde_json::to_value(item).unwrap(), serde_json::json!(name(item)));
+            }
+        }
+        check(LedIndicator::ALL, LedIndicator::name);
+        check(OverlayAnimation::ALL, OverlayAnimation::name);
+        check(LockMode::ALL, LockMode::name);
+        check(AppRuleAction::ALL, AppRuleAction::name);
+        check(OverlayTarget::ALL, OverlayTarget::name);
+        check(NotificationPosition::ALL, NotificationPosition::name);
+        check(OnRegisterFailure::ALL, OnRegisterFailure::name);
+        check(OverlayPattern::ALL, OverlayPattern::name);
+    }
+
+    #[test]
+    fn test_enum_names_parse_the_same_everywhere() {
+        /// WHY: A name the file accepts must work with `config set` too, and vice versa.
+        let config: Config = serde_json::from_str(r#"{"overlay_target": "ActiveMonitor", "led_indicator": "NUMLOCK"}"#).unwrap();
+        assert_eq!(config.overlay_target, OverlayTarget::ActiveMonitor);
+        assert_eq!(config.led_indicator, LedIndicator::NumLock);
+
+        let mut config = Config::default();
+        config.set_field("overlay_target", "ActiveMonitor").unwrap();
+        config.set_field("led_indicator", "NUMLOCK").unwrap();
+        assert_eq!(config.overlay_target, OverlayTarget::ActiveMonitor);
+        assert_eq!(config.led_indicator, LedIndicator::NumLock);
+
+        assert_eq!(
+            config.set_field("overlay_animation", "wobble").unwrap_err(),
+            "overlay_animation must be none, pulse or flash, got 'wobble'"
+        );
+    }
+
+    // -------------------------------------------------------------------------
+    // Environment Override Tests
+    // -------------------------------------------------------------------------
+
+    /// Serializes tests that mutate PAWGATE_* environment variables
+    static ENV_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    const ENV_VARS: [&str; 4] = [
+        "PAWGATE_HOTKEY",
+        "PAWGATE_OPACITY",
+        "PAWGATE_OVERLAY_COLOR",
+        "PAWGATE_NOTIFICATIONS",
+    ];
+
+    #[test]
+    fn test_env_overrides_apply() {
+        /// WHY: Kiosk scripts configure PawGate through env vars; each must win over the file.
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var("PAWGATE_HOTKEY", "ctrl+alt+k");
+        std::env::set_var("PAWGATE_OPACITY", "0.8");
+        std::env::set_var("PAWGATE_OVERLAY_COLOR", "#1565C0");
+        std::env::set_var("PAWGATE_NOTIFICATIONS", "false");
+
+        let mut config = Config::default();
+        config.apply_env_overrides();
+
+        for var in ENV_VARS {
+            std::env::remove_var(var);
+        }
+
+        assert_eq!(config.hotkeys, vec!["ctrl+alt+k"]);
+        assert_eq!(config.opacity, 0.8);
+        assert_eq!(config.overlay_color, "#1565C0");
+        assert!(!config.notifications_enabled);
+    }
+
+    #[test]
+    fn test_env_overrides_ignore_invalid_values() {
+        /// WHY: A bad env var should leave the file value in place, not crash or zero it.
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var("PAWGATE_OPACITY", "very dark");
+        std::env::set_var("PAWGATE_NOTIFICATIONS", "maybe");
+
+        let mut config = Config::default();
+        config.apply_env_overrides();
+
+        for var in ENV_VARS {
+            std::env::remove_var(var);
+        }
+
+        assert_eq!(config.opacity, 0.3, "Invalid opacity should be ignored");
+        assert!(config.notifications_enabled, "Invalid boolean should be ignored");
+    }
+
+    #[test]
+    fn test_env_override_opacity_is_clamped() {
+        /// WHY: Env values go through the same validation as file values.
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        std::env::set_var("PAWGATE_OPACITY", "3");
+
+        let mut config = Config::default();
+        config.apply_env_overrides();
+
+        std::env::remove_var("PAWGATE_OPACITY");
+
+        assert_eq!(config.opacity, 1.0);
+    }
+
+    #[test]
+    fn test_config_dir_env_override() {
+        /// WHY: Portable installs must read and write config only inside PAWGATE_CONFIG_DIR.
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = ScratchDir::new("config-dir-env");
+        std::env::set_var("PAWGATE_CONFIG_DIR", &*dir);
+
+        let config_dir = Config::config_dir();
+        let config_path = Config::config_path();
+        let profiles_dir = Config::profiles_dir();
+
+        std::env::set_var("PAWGATE_CONFIG_DIR", "");
+        let empty_falls_back = Config::config_dir();
+        std::env::remove_var("PAWGATE_CONFIG_DIR");
+
+        assert_eq!(config_dir, *dir);
+        assert_eq!(config_path, dir.join("config.json"));
+        assert!(profiles_dir.starts_with(&dir));
+        assert_ne!(empty_falls_back, *dir, "An empty value should be ignored");
+    }
+
+    #[test]
+    fn test_config_dir_without_home() {
+        /// WHY: With no home directory, config must not land in whatever directory PawGate was started from.
+        let dir = config_dir_from(None, None, None);
+        assert_eq!(dir, std::env::temp_dir().join("pawgate"));
+        assert_eq!(dir, fallback_config_dir());
+        assert!(dir.is_absolute(), "Not relative to the working directory: {}", dir.display());
+        assert_eq!(config_dir_from(None, Some("".into()), None), dir, "Empty XDG_CONFIG_HOME is ignored");
+
+        let portable = std::env::temp_dir().join("pawgate-portable");
+        assert_eq!(
+            config_dir_from(Some(portable.clone().into_os_string()), None, None),
+            portable,
+            "PAWGATE_CONFIG_DIR still wins without a home directory"
+        );
+        assert_eq!(config_dir_from(None, None, Some(PathBuf::from("/home/cat"))), Path::new("/home/cat/.pawgate"));
+    }
+
+    #[test]
+    fn test_config_dir_follows_xdg_config_home() {
+        /// WHY: Where XDG_CONFIG_HOME applies, it beats the home directory but not PAWGATE_CONFIG_DIR.
+        let xdg = std::env::temp_dir().join("pawgate-xdg");
+        let home = Some(PathBuf::from("/home/cat"));
+        assert_eq!(
+            config_dir_from(None, Some(xdg.clone().into_os_string()), home.clone()),
+            xdg.join("pawgate")
+        );
+
+        let portable = std::env::temp_dir().join("pawgate-portable");
+        assert_eq!(
+            config_dir_from(Some(portable.clone().into_os_string()), Some(xdg.into_os_string()), home),
+            portable
+        );
+    }
+
+    #[test]
+    fn test_parse_env_bool() {
+        /// WHY: Scripts spell booleans many ways; accept the common ones.
+        for value in ["1", "true", "TRUE", "yes", "on"] {
+            assert_eq!(parse_env_bool(value), Some(true), "{} should be true", value);
+        }
+        for value in ["0", "false", "No", "off"] {
+            assert_eq!(parse_env_bool(value), Some(false), "{} should be false", value);
+        }
+        assert_eq!(parse_env_bool("maybe"), None);
+    }
+
+    // -------------------------------------------------------------------------
+    // File Watching Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_load_from_rejects_invalid_json() {
+        /// WHY: The watcher relies on load_from failing so it can keep the last good config.
+        let dir = ScratchDir::new("invalid-json");
+        let path = dir.join("config.json");
+        fs::write(&path, "{ not json").unwrap();
+
+        assert!(Config::load_from(&path).is_err());
+    }
+
+    #[test]
+    fn test_set_saved_field_refuses_unreadable_file() {
+        /// WHY: `config set` on a file with a typo must report it, not replace the user's settings with defaults.
+        let dir = ScratchDir::new("set-unreadable");
+        let path = dir.join("config.json");
+        fs::write(&path, "{ \"opacity\": 0.5,,, garbage").unwrap();
+
+        assert!(Config::set_field_in_file(&path, "opacity", "0.7").is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ \"opacity\": 0.5,,, garbage");
+        assert!(!backup_path(&path).exists(), "Nothing was moved aside");
+
+        fs::write(&path, r#"{"version": 1, "opacity": 0.5}"#).unwrap();
+        Config::set_field_in_file(&path, "opacity", "0.7").unwrap();
+        assert_eq!(Config::load_from(&path).unwrap().opacity, 0.7);
+    }
+
+    #[test]
+    fn test_save_changes_keeps_env_overrides_out_of_file() {
+        /// WHY: Saving the settings dialog must not write PAWGATE_* values or other unedited runtime state to disk.
+        let dir = ScratchDir::new("save-changes");
+        let path = dir.join("config.json");
+        fs::write(&path, r#"{"version": 1, "hotkeys": ["ctrl+b"], "opacity": 0.5}"#).unwrap();
+
+        let mut running = Config::load_from(&path).unwrap();
+        running.hotkeys = vec!["ctrl+alt+k".to_string()];
+        running.unlock_passphrase = Some("from-env".to_string());
+        let mut edited = running.clone();
+        edited.opacity = 0.8;
+        running.save_changes_in_file(&path, &edited).unwrap();
+
+        let saved = Config::load_from(&path).unwrap();
+        assert_eq!(saved.opacity, 0.8, "The edit is saved");
+        assert_eq!(saved.hotkeys, vec!["ctrl+b"], "The file's own hotkey is kept");
+        assert_eq!(saved.unlock_passphrase, None);
+    }
+
+    #[test]
+    fn test_load_recovers_from_corrupt_file() {
+        /// WHY: A cat on the keyboard in an editor must not stop PawGate from starting.
+        let dir = ScratchDir::new("corrupt");
+        let path = dir.join("config.json");
+        fs::write(&path, "{ \"opacity\": 0.5,,, garbage").unwrap();
+
+        let config = Config::load_or_create(&path).unwrap();
+        assert_eq!(config.opacity, Config::default().opacity);
+
+        // The bad file is kept for the user, and a fresh default replaces it
+        let backup = dir.join("config.json.bak");
+        assert_eq!(fs::read_to_string(&backup).unwrap(), "{ \"opacity\": 0.5,,, garbage");
+        let rewritten: Config = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(rewritten.opacity, Config::default().opacity);
+    }
+
+    #[test]
+    fn test_load_recovers_from_unreadable_file() {
+        /// WHY: A file that isn't valid UTF-8 can't be parsed either, and must not stop PawGate from starting.
+        let dir = ScratchDir::new("unreadable");
+        let path = dir.join("config.json");
+        let bytes = b"{ \"opacity\": \xff\xfe }";
+        fs::write(&path, bytes).unwrap();
+
+        let config = Config::load_or_create(&path).unwrap();
+        assert_eq!(config.opacity, Config::default().opacity);
+        assert_eq!(fs::read(dir.join("config.json.bak")).unwrap(), bytes);
+        assert!(Config::load_from(&path).is_ok(), "Defaults were written in its place");
+    }
+
+    #[test]
+    fn test_save_is_atomic_and_leaves_no_temp_files() {
+        /// WHY: Saving replaces the file in one step, so a reader never sees half a config.
+        let dir = ScratchDir::new("atomic-save");
+        let path = dir.join("config.json");
+        fs::write(&path, "old contents").unwrap();
+
+        let config = Config {
+            opacity: 0.55,
+            ..Config::default()
+        };
+        config.save_to(&path).unwrap();
+        config.save_to(&dir.join("config.toml")).unwrap();
+
+        let saved: Config = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.opacity, 0.55);
+        assert_eq!(Config::load_toml(&dir.join("config.toml")).unwrap().opacity, 0.55);
+
+        let mut files: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["config.json", "config.toml"]);
+    }
+
+    #[test]
+    fn test_load_or_initial_writes_nothing() {
+        /// WHY: `pawgate config show` must not create the file, or rewrite one that needs migrating.
+        let dir = ScratchDir::new("load-or-initial");
+        let path = dir.join("config.json");
+        assert_eq!(Config::load_or_initial(&path).unwrap(), Config::initial());
+        assert!(!path.exists(), "A missing file stays missing");
+
+        let old = r#"{"hotkey": "ctrl+l"}"#;
+        fs::write(&path, old).unwrap();
+        assert_eq!(Config::load_or_initial(&path).unwrap().hotkeys, vec!["ctrl+l"]);
+        assert_eq!(fs::read_to_string(&path).unwrap(), old, "An old file isn't migrated on disk");
+
+        fs::write(&path, "{ not json").unwrap();
+        assert!(Config::load_or_initial(&path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "{ not json", "A broken file is left alone");
+    }
+
+    #[test]
+    fn test_load_or_create_from_arbitrary_path() {
+        /// WHY: `--config` can point anywhere, and that file gets the same validation as the default one.
+        let dir = ScratchDir::new("arbitrary-path");
+        let path = dir.join("setups").join("cat-proof.json");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, r#"{"opacity": 7.0, "overlay_color": "teal"}"#).unwrap();
+
+        let config = Config::load_or_create(&path).unwrap();
+        assert_eq!(config.opacity, 1.0, "Out-of-range opacity is clamped as usual");
+        assert_eq!(config.overlay_color, "teal");
+
+        // A missing file at a custom path is created with defaults
+        let fresh = dir.join("setups").join("new.json");
+        assert_eq!(Config::load_or_create(&fresh).unwrap().opacity, Config::default().opacity);
+        assert!(fresh.exists());
+    }
+
+    #[test]
+    fn test_corrupt_file_at_arbitrary_path_falls_back() {
+        /// WHY: A broken `--config` file must get the same backup-and-defaults treatment as config.json.
+        let dir = ScratchDir::new("arbitrary-corrupt");
+        let path = dir.join("alt.json");
+        fs::write(&path, "{ \"opacity\": ").unwrap();
+
+        let config = Config::load_or_create(&path).unwrap();
+        assert_eq!(config.opacity, Config::default().opacity);
+        assert_eq!(fs::read_to_string(dir.join("alt.json.bak")).unwrap(), "{ \"opacity\": ");
+        assert!(Config::load_from(&path).is_ok(), "Defaults were written in its place");
+    }
+
+    #[cfg(feature = "json5")]
+    #[test]
+    fn test_json5_comments_and_trailing_commas() {
+        /// WHY: Annotating config.json by hand must not make it unreadable.
+        let contents = r#"{
+            // Lock with Ctrl+B
+            "hotkeys": ["ctrl+b", "ctrl+alt+k",],
+            /* Dim the screen a bit more */
+            "opacity": 0.5,
+            "overlay_color": "teal",
+            "lock_mode": "keyboardonly",
+        }"#;
+        let mut config = Config::parse_json(contents).unwrap();
+        config.validate();
+        assert_eq!(config.hotkeys, vec!["ctrl+b".to_string(), "ctrl+alt+k".to_string()]);
+        assert_eq!(config.opacity, 0.5);
+        assert_eq!(config.overlay_color, "teal");
+        assert_eq!(config.lock_mode, LockMode::KeyboardOnly);
+
+        let saved = serde_json::to_string_pretty(&config).unwrap();
+        assert!(serde_json::from_str::<Config>(&saved).is_ok(), "Saves stay plain JSON");
+    }
+
+    #[test]
+    fn test_embedded_defaults_are_valid() {
+        /// WHY: A broken resources/default_config.json would only show up on a managed machine's first run.
+        let raw: serde_json::Value = serde_json::from_str(EMBEDDED_DEFAULTS).unwrap();
+        let known = serde_json::to_value(Config::default()).unwrap();
+        for field in raw.as_object().unwrap().keys() {
+            assert!(known.get(field).is_some(), "Embedded defaults set unknown field '{}'", field);
+        }
+
+        let mut config: Config = serde_json::from_str(EMBEDDED_DEFAULTS).unwrap();
+        assert!(!config.migrate(), "Embedded defaults should be at the current version");
+        config.validate();
+        assert!(!config.hotkeys.is_empty(), "Embedded defaults must leave a way to unlock");
+        assert_eq!(config.parsed_hotkeys().len(), config.hotkeys.len(), "Every embedded hotkey must parse");
+    }
+
+    #[test]
+    fn test_load_creates_missing_file() {
+        /// WHY: First run must write a default config the user can then edit.
+        let dir = ScratchDir::new("missing");
+        let path = dir.join("config.json");
+
+        Config::load_or_create(&path).unwrap();
+        assert!(path.exists());
+        assert!(!dir.join("config.json.bak").exists());
+    }
+
+    #[test]
+    fn test_load_from_picks_format_by_extension() {
+        /// WHY: load_from must read .toml files as TOML and everything else as JSON.
+        let dir = ScratchDir::new("format");
+        let config = Config {
+            opacity: 0.45,
+            ..Default::default()
+        };
+
+        let toml_path = dir.join("config.toml");
+        config.save_toml(&toml_path).unwrap();
+        assert_eq!(Config::load_from(&toml_path).unwrap().opacity, 0.45);
+
+        let json_path = dir.join("config.json");
+        fs::write(&json_path, serde_json::to_string(&config).unwrap()).unwrap();
+        assert_eq!(Config::load_from(&json_path).unwrap().opacity, 0.45);
+    }
+
+    #[test]
+    fn test_reload_waits_for_writes_to_settle() {
+        /// WHY: Hot-reload must not pick up a file an editor is still halfway through saving.
+        let start = Instant::now();
+        let stamp = |len| vec![Some((SystemTime::UNIX_EPOCH, len))];
+        let mut debounce = ReloadDebounce::new(stamp(10));
+
+        assert!(!debounce.poll(stamp(10), start), "Nothing changed");
+        assert!(!debounce.poll(stamp(0), start), "Truncated");
+        assert!(!debounce.poll(stamp(20), start + WATCH_DEBOUNCE / 2), "Rewritten; the window restarts");
+        assert!(!debounce.poll(stamp(20), start + WATCH_DEBOUNCE), "Still inside the window");
+        assert!(debounce.poll(stamp(20), start + WATCH_DEBOUNCE * 2), "Settled");
+        assert!(!debounce.poll(stamp(20), start + WATCH_DEBOUNCE * 3), "Reloads only once");
+    }
+
+    #[test]
+    fn test_reload_notices_any_watched_file() {
+        /// WHY: Editing the active profile must reload just like editing config.json.
+        let start = Instant::now();
+        let main = Some((SystemTime::UNIX_EPOCH, 10));
+        let mut debounce = ReloadDebounce::new(vec![main, Some((SystemTime::UNIX_EPOCH, 5))]);
+
+        assert!(!debounce.poll(vec![main, Some((SystemTime::UNIX_EPOCH, 6))], start));
+        assert!(debounce.poll(vec![main, Some((SystemTime::UNIX_EPOCH, 6))], start + WATCH_DEBOUNCE));
+    }
+
+    #[test]
+    fn test_watched_files_include_active_profile() {
+        /// WHY: With a profile selected, its file holds the settings, so it must be watched too.
+        let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let dir = ScratchDir::new("watched-files");
+        std::env::set_var("PAWGATE_CONFIG_DIR", &*dir);
+        let path = dir.join("config.json");
+
+        fs::write(&path, r#"{"version": 1}"#).unwrap();
+        assert_eq!(watched_files(&path), vec![path.clone()]);
+
+        fs::write(&path, r#"{"version": 1, "active_profile": "night"}"#).unwrap();
+        let files = watched_files(&path);
+        std::env::remove_var("PAWGATE_CONFIG_DIR");
+        assert_eq!(files, vec![path.clone(), dir.join("profiles").join("night.json")]);
+    }
+
+    // -------------------------------------------------------------------------
+    // Profile Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_list_profiles_in_directory() {
+        /// WHY: `pawgate profile list` shows exactly the usable *.json profiles, in order.
+        let dir = ScratchDir::new("profiles-list");
+        fs::write(dir.join("work.json"), "{}").unwrap();
+        fs::write(dir.join("home.json"), "{}").unwrap();
+        fs::write(dir.join("notes.txt"), "not a profile").unwrap();
+        fs::write(dir.join("bad name.json"), "{}").unwrap();
+
+        assert_eq!(list_profiles_in(&dir), vec!["home".to_string(), "work".to_string()]);
+        assert!(list_profiles_in(&dir.join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_load_named_profile() {
+        /// WHY: A profile's settings must load, and remember which profile they came from.
+        let dir = ScratchDir::new("profiles-load");
+        let home = Config {
+            opacity: 0.8,
+            lock_sound: Some(PathBuf::from("meow.wav")),
+            ..Config::default()
+        };
+        let path = dir.join("home.json");
+        fs::write(&path, serde_json::to_string(&home).unwrap()).unwrap();
+
+        let loaded = Config::load_profile_from(&path, "home").unwrap();
+        assert_eq!(loaded.opacity, 0.8);
+        assert_eq!(loaded.lock_sound, Some(PathBuf::from("meow.wav")));
+        assert_eq!(loaded.active_profile, "home");
+        assert!(loaded.uses_profile());
+
+        assert!(Config::load_profile_from(&dir.join("gone.json"), "gone").is_err());
+    }
+
+    #[test]
+    fn test_profile_names_validated() {
+        /// WHY: Profile names become file paths, so "../x" must never be accepted.
+        assert!(is_valid_profile_name("work"));
+        assert!(is_valid_profile_name("home_2-loud"));
+        assert!(!is_valid_profile_name(""));
+        assert!(!is_valid_profile_name("../secrets"));
+        assert!(!is_valid_profile_name("a/b"));
+    }
+
+    #[test]
+    fn test_default_profile_uses_main_config() {
+        /// WHY: Existing configs have no active_profile and must keep using their own settings.
+        let config: Config = serde_json::from_str(r#"{"opacity": 0.5}"#).unwrap();
+        assert_eq!(config.active_profile, DEFAULT_PROFILE);
+        assert!(!config.uses_profile());
+    }
+
+    #[test]
+    fn test_overlay_click_through_follows_lock_mode() {
+        /// WHY: Click-through must never let clicks reach apps when the mouse is meant to be locked.
+        for (mode, click_through, expected) in [
+            (LockMode::KeyboardOnly, true, true),
+            (LockMode::KeyboardOnly, false, false),
+            (LockMode::MouseOnly, true, false),
+            (LockMode::MouseOnly, false, false),
+            (LockMode::Both, true, false),
+            (LockMode::Both, false, false),
+        ] {
+            let config = Config {
+                lock_mode: mode,
+                overlay_click_through: click_through,
+                ..Config::default()
+            };
+            assert_eq!(
+                config.overlay_passes_clicks(),
+                expected,
+                "lock_mode {:?}, overlay_click_through {}",
+                mode,
+                click_through
+            );
+        }
+        assert!(Config::default().overlay_click_through);
+    }
+
+    // -------------------------------------------------------------------------
+    // Monitor Selection Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_monitors_default_empty_selects_all() {
+        /// WHY: Existing users expect the overlay on every monitor.
+        let config = Config::default();
+
+        assert!(config.monitors.is_empty(), "Default should select all monitors");
+        for index in 0..3 {
+            let name = format!(r"\\.\DISPLAY{}", index + 1);
+            assert!(config.selects_monitor(index, &name));
+        }
+    }
+
+    #[test]
+    fn test_monitors_deserialize_index_and_name() {
+        /// WHY: Users pick monitors either by position or by device name.
+        let json = r##"{
+            "monitors": [0, "\\\\.\\DISPLAY3"]
+        }"##;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            config.monitors,
+            vec![
+                MonitorSelector::Index(0),
+                MonitorSelector::Name(r"\\.\DISPLAY3".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_notification_position_and_duration() {
+        /// WHY: Windows toasts stay the default; a typo must not lose notifications altogether.
+        let config = Config::default();
+        assert_eq!(config.notification_position, NotificationPosition::System);
+        assert_eq!(config.notification_duration_ms, 3000);
+
+        let config: Config = serde_json::from_str(r#"{"notification_position": "topright"}"#).unwrap();
+        assert_eq!(config.notification_position, NotificationPosition::TopRight);
+        let config: Config = serde_json::from_str(r#"{"notification_position": "middle"}"#).unwrap();
+        assert_eq!(config.notification_position, NotificationPosition::System);
+
+        let mut config = Config::default();
+        config.set_field("notification_position", "bottomleft").unwrap();
+        assert_eq!(config.notification_position, NotificationPosition::BottomLeft);
+        assert!(config.set_field("notification_position", "middle").is_err());
+        config.set_field("notification_duration_ms", "5000").unwrap();
+        assert_eq!(config.notification_duration_ms, 5000);
+    }
+
+    #[test]
+    fn test_overlay_pattern_deserialize() {
+        /// WHY: The pattern is opt-in, and a typo must fall back to the plain tint.
+        let config = Config::default();
+        assert_eq!(config.overlay_pattern, OverlayPattern::None);
+        assert_eq!(config.pattern_spacing, 160);
+
+        let config: Config = serde_json::from_str(r#"{"overlay_pattern": "paws", "pattern_spacing": 200}"#).unwrap();
+        assert_eq!(config.overlay_pattern, OverlayPattern::Paws);
+        assert_eq!(config.pattern_spacing, 200);
+        let config: Config = serde_json::from_str(r#"{"overlay_pattern": "fish"}"#).unwrap();
+        assert_eq!(config.overlay_pattern, OverlayPattern::None);
+
+        let mut config = Config::default();
+        config.set_field("overlay_pattern", "paws").unwrap();
+        assert_eq!(config.overlay_pattern, OverlayPattern::Paws);
+        assert!(config.set_field("overlay_pattern", "fish").is_err());
+        config.set_field("pattern_spacing", "120").unwrap();
+        assert_eq!(config.pattern_spacing, 120);
+    }
+
+    #[test]
+    fn test_overlay_target_deserialize() {
+        /// WHY: Covering one monitor is opt-in; typos must keep covering everything.
+        assert_eq!(Config::default().overlay_target, OverlayTarget::All);
+        let config: Config = serde_json::from_str(r#"{"overlay_target": "activemonitor"}"#).unwrap();
+        assert_eq!(config.overlay_target, OverlayTarget::ActiveMonitor);
+        let config: Config = serde_json::from_str(r#"{"overlay_target": "primarymonitor"}"#).unwrap();
+        assert_eq!(config.overlay_target, OverlayTarget::PrimaryMonitor);
+        let config: Config = serde_json::from_str(r#"{"overlay_target": "leftmonitor"}"#).unwrap();
+        assert_eq!(config.overlay_target, OverlayTarget::All);
+
+        let json = serde_json::to_string(&Config {
+            overlay_target: OverlayTarget::PrimaryMonitor,
+            ..Config::default()
+        })
+        .unwrap();
+        assert!(json.contains(r#""overlay_target":"primarymonitor""#), "{}", json);
+
+        let mut config = Config::default();
+        config.set_field("overlay_target", "activemonitor").unwrap();
+        assert_eq!(config.overlay_target, OverlayTarget::ActiveMonitor);
+        assert!(config.set_field("overlay_target", "leftmonitor").is_err());
+    }
+
+    #[test]
+    fn test_app_rules_deserialize() {
+        /// WHY: Rules are hand-written in the config; a typo'd action must not break loading.
+        let json = r#"{
+            "app_rules": [
+                { "app": "premiere.exe", "action": "disable" },
+                { "app": "wt.exe", "action": "forcelock" },
+                { "app": "obs64.exe", "action": "sometimes" },
+                { "app": "code.exe" }
+            ]
+        }"#;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        let actions: Vec<_> = config.app_rules.iter().map(|rule| rule.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                AppRuleAction::Disable,
+                AppRuleAction::ForceLock,
+                AppRuleAction::Normal,
+                AppRuleAction::Normal,
+            ]
+        );
+        assert!(Config::default().app_rules.is_empty(), "No rules by default");
+    }
+
+    #[test]
+    fn test_set_field_app_rules() {
+        /// WHY: `config set app_rules premiere.exe=disable` should validate each entry.
+        let mut config = Config::default();
+        config.set_field("app_rules", "premiere.exe=disable, wt.exe=ForceLock").unwrap();
+        assert_eq!(
+            config.app_rules,
+            vec![
+                AppRule {
+                    app: "premiere.exe".to_string(),
+                    action: AppRuleAction::Disable,
+                },
+                AppRule {
+                    app: "wt.exe".to_string(),
+                    action: AppRuleAction::ForceLock,
+                },
+            ]
+        );
+
+        assert!(config.set_field("app_rules", "premiere.exe").is_err(), "Missing action");
+        assert!(config.set_field("app_rules", "premiere.exe=maybe").is_err(), "Unknown action");
+        assert_eq!(config.app_rules.len(), 2, "Rejected values leave the rules alone");
+    }
+
+    #[test]
+    fn test_selects_monitor_filters() {
+        /// WHY: Unselected monitors (e.g. a pet cam) must stay uncovered.
+        let config = Config {
+            monitors: vec![
+                MonitorSelector::Index(0),
+                MonitorSelector::Name("display3".to_string()),
+            ],
+            ..Default::default()
+        };
+
+        assert!(config.selects_monitor(0, r"\\.\DISPLAY1"));
+        assert!(!config.selects_monitor(1, r"\\.\DISPLAY2"));
+        assert!(config.selects_monitor(2, r"\\.\DISPLAY3"), "Name match ignores prefix and case");
+    }
+
+    // -------------------------------------------------------------------------
+    // Auto-Lock Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_auto_lock_idle_disabled_by_default() {
+        /// WHY: Auto-lock must be opt-in so upgrading users aren't surprised.
+        let config = Config::default();
+        assert_eq!(config.auto_lock_idle_secs, None);
+
+        let config: Config = serde_json::from_str("{}").unwrap();
+        assert_eq!(config.auto_lock_idle_secs, None, "Missing field should mean disabled");
+    }
+
+    #[test]
+    fn test_auto_lock_idle_round_trip() {
+        /// WHY: The idle threshold must survive save and load.
+        let original = Config {
+            auto_lock_idle_secs: Some(300),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&original).unwrap();
+        let restored: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.auto_lock_idle_secs, Some(300));
+    }
+
+    #[test]
+    fn test_auto_unlock_secs_default_and_set() {
+        /// WHY: Safe mode is opt-in, and `config set` is how new users will switch it on and off.
+        assert_eq!(Config::default().auto_unlock_secs, None);
+
+        let mut config = Config::default();
+        config.set_field("auto_unlock_secs", "10").unwrap();
+        assert_eq!(config.auto_unlock_secs, Some(10));
+        config.set_field("auto_unlock_secs", "off").unwrap();
+        assert_eq!(config.auto_unlock_secs, None);
+        assert!(config.set_field("auto_unlock_secs", "0").is_err());
+    }
+
+    #[test]
+    fn test_unlock_passphrase_default_and_deserialize() {
+        /// WHY: Passphrase unlock is opt-in; existing configs keep hotkey unlock.
+        assert_eq!(Config::default().unlock_passphrase, None);
+
+        let config: Config = serde_json::from_str(r#"{"unlock_passphrase": "letmein"}"#).unwrap();
+        assert_eq!(config.unlock_passphrase.as_deref(), Some("letmein"));
+    }
+
+    #[test]
+    fn test_unlock_gesture_default_and_parse() {
+        /// WHY: Gesture unlock is opt-in, and `config set` must reject shapes the recognizer doesn't know.
+        assert_eq!(Config::default().parsed_unlock_gesture(), None);
+
+        let config: Config = serde_json::from_str(r#"{"unlock_gesture": "Circle"}"#).unwrap();
+        assert_eq!(config.parsed_unlock_gesture(), Some(Gesture::Circle));
+
+        let mut config = Config::default();
+        config.set_field("unlock_gesture", "zigzag").unwrap();
+        assert_eq!(config.parsed_unlock_gesture(), Some(Gesture::Zigzag));
+        assert!(config.set_field("unlock_gesture", "triangle").is_err());
+        config.set_field("unlock_gesture", "off").unwrap();
+        assert_eq!(config.unlock_gesture, None);
+    }
+
+    #[test]
+    fn test_sounds_default_to_none() {
+        /// WHY: Sounds are opt-in; a fresh install should stay silent.
+        let config = Config::default();
+        assert_eq!(config.lock_sound, None);
+        assert_eq!(config.unlock_sound, None);
+    }
+
+    #[test]
+    fn test_sound_paths_roundtrip() {
+        /// WHY: Windows paths with backslashes must survive save and load.
+        let config = Config {
+            lock_sound: Some(PathBuf::from(r"C:\Users\cat\meow.wav")),
+            ..Config::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        let loaded: Config = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.lock_sound, Some(PathBuf::from(r"C:\Users\cat\meow.wav")));
+        assert_eq!(loaded.unlock_sound, None);
+    }
+
+    #[test]
+    fn test_notification_text_defaults() {
+        /// WHY: Without custom text the lock toast reminds the user how to unlock.
+        let config = Config::default();
+        assert_eq!(config.notification_text(true), "Keyboard locked. Press ctrl+b to unlock.");
+        assert_eq!(config.notification_text(false), "Keyboard unlocked");
+    }
+
+    #[test]
+    fn test_notification_text_substitutes_hotkey() {
+        /// WHY: Users put {hotkey} in custom text to remember their combo.
+        let config = Config {
+            hotkeys: vec!["ctrl+alt+k".to_string()],
+            lock_notification_text: Some("Kitty mode ON ({hotkey} to exit)".to_string()),
+            unlock_notification_text: Some("Kitty mode OFF".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(config.notification_text(true), "Kitty mode ON (ctrl+alt+k to exit)");
+        assert_eq!(config.notification_text(false), "Kitty mode OFF");
+    }
+
+    #[test]
+    fn test_notification_text_lists_every_hotkey() {
+        /// WHY: Any configured hotkey unlocks, so the reminder should mention all of them.
+        let config = Config {
+            hotkeys: vec!["ctrl+b".to_string(), "ctrl+shift+u".to_string()],
+            lock_notification_text: Some("{hotkey} unlocks; {hotkey}!".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(
+            config.notification_text(true),
+            "ctrl+b or ctrl+shift+u unlocks; ctrl+b or ctrl+shift+u!"
+        );
+    }
+
+    #[test]
+    fn test_notification_text_follows_language() {
+        /// WHY: The built-in text is translated, but custom text is used verbatim in any language.
+        let config = Config {
+            language: "de".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(config.notification_text(true), "Tastatur gesperrt. Drücke ctrl+b zum Entsperren.");
+        assert_eq!(config.notification_text(false), "Tastatur entsperrt");
+
+        let config = Config {
+            language: "de".to_string(),
+            unlock_notification_text: Some("Bye, cat".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(config.notification_text(false), "Bye, cat");
+        assert_eq!(Config::default().language, "en");
+    }
+
+    #[test]
+    fn test_overlay_image_roundtrip() {
+        /// WHY: The image path must survive save and load, and stay off by default.
+        assert_eq!(Config::default().overlay_image, None);
+
+        let config = Config {
+            overlay_image: Some(PathBuf::from(r"C:\Users\cat\Pictures\whiskers.png")),
+            ..Config::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        let loaded: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.overlay_image, config.overlay_image);
+
+        let toml_text = toml::to_string(&config).unwrap();
+        let loaded: Config = toml::from_str(&toml_text).unwrap();
+        assert_eq!(loaded.overlay_image, config.overlay_image);
+    }
+
+    #[test]
+    fn test_overlay_message_default() {
+        /// WHY: Without overlay_text the overlay keeps explaining how to unlock.
+        let config = Config::default();
+        assert_eq!(config.overlay_message(), "Keyboard Locked - Press hotkey to unlock");
+
+        let config = Config {
+            unlock_passphrase: Some("meow".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(config.overlay_message(), "Keyboard Locked - Type passphrase to unlock");
+    }
+
+    #[test]
+    fn test_empty_passphrase_counts_as_unset() {
+        /// WHY: The overlay and the lock toast must agree with the hook, which ignores an empty passphrase.
+        let config = Config {
+            unlock_passphrase: Some(String::new()),
+            ..Config::default()
+        };
+        assert!(!config.has_passphrase());
+        assert_eq!(config.overlay_message(), "Keyboard Locked - Press hotkey to unlock");
+        assert_eq!(config.notification_text(true), Config::default().notification_text(true));
+    }
+
+    #[test]
+    fn test_overlay_message_substitutes_hotkey() {
+        /// WHY: Custom overlay text (emoji included) is shown verbatim apart from {hotkey}.
+        let config = Config {
+            overlay_text: Some("🐾 Locked — press {hotkey} to unlock".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(config.overlay_message(), "🐾 Locked — press ctrl+b to unlock");
+    }
+
+    #[test]
+    fn test_blocked_keys_text() {
+        /// WHY: The unlock toast reports the count in the user's language, and says nothing for zero.
+        let config = Config::default();
+        assert_eq!(config.blocked_keys_text(0), None);
+        assert_eq!(config.blocked_keys_text(47).as_deref(), Some("Blocked 47 paw-presses"));
+
+        let german = Config {
+            language: "de".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(german.blocked_keys_text(3).as_deref(), Some("3 Pfotentritte blockiert"));
+    }
+
+    #[test]
+    fn test_sas_note_only_when_enabled() {
+        /// WHY: The note is opt-in, and when on it must name the combo users can fall back on.
+        assert_eq!(Config::default().sas_note(), None);
+
+        let config = Config {
+            show_sas_note: true,
+            ..Config::default()
+        };
+        assert_eq!(
+            config.sas_note().as_deref(),
+            Some("Ctrl+Alt+Del still works while locked - Windows never lets apps block it")
+        );
+    }
+
+    #[test]
+    fn test_unblockable_combos_are_reserved() {
+        /// WHY: Every combo documented as unblockable must also be refused as a hotkey.
+        for conflict in HotkeyConflict::ALL {
+            let sequence = parse_hotkey_sequence(&conflict.combo().to_lowercase()).unwrap();
+            assert_eq!(sequence_conflict(&sequence), Some(conflict), "{}", conflict.combo());
+        }
+    }
+
+    #[test]
+    fn test_overlay_text_color_contrasts() {
+        /// WHY: White text vanishes on a light tint and black on a dark one.
+        let dark = Config {
+            overlay_color: "#000000".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(dark.overlay_text_color(), (255, 255, 255));
+
+        let light = Config {
+            overlay_color: "#FFFFFF".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(light.overlay_text_color(), (0, 0, 0));
+
+        // Pure yellow is bright despite having no blue
+        let yellow = Config {
+            overlay_color: "#FFFF00".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(yellow.overlay_text_color(), (0, 0, 0));
+    }
 
-    /// Overlay opacity (0.0 to 1.0)
-    pub opacity: f32,
+    #[test]
+    fn test_contrast_color() {
+        /// WHY: The default tint is dark, so text on it must be white; light tints need black.
+        let default_green = Config::default().parse_overlay_color();
+        assert_eq!(contrast_color(default_green), (255, 255, 255));
 
-    /// Whether to show notifications
-    pub notifications_enabled: bool,
+        let light = Config {
+            overlay_color: "#EEEEEE".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(contrast_color(light.parse_overlay_color()), (0, 0, 0));
 
-    /// Overlay color in hex (e.g., "#2D5A27" for green)
-    pub overlay_color: String,
-}
+        // Green weighs most: pure green is bright, pure blue is dark
+        assert_eq!(contrast_color((0, 255, 0)), (0, 0, 0));
+        assert_eq!(contrast_color((0, 0, 255)), (255, 255, 255));
+    }
 
-impl Default for Config {
-    fn default() -> Self {
-        Self {
-            hotkey: "ctrl+b".to_string(),
-            opacity: 0.3,
-            notifications_enabled: true,
-            // Colorblind-friendly green that's distinguishable
-            overlay_color: "#1B5E20".to_string(),
-        }
+    #[test]
+    fn test_lerp_color_endpoints_and_midpoint() {
+        /// WHY: The gradient must start and end exactly on the configured colors.
+        let top = (0, 100, 200);
+        let bottom = (200, 100, 0);
+        assert_eq!(lerp_color(top, bottom, 0.0), top);
+        assert_eq!(lerp_color(top, bottom, 1.0), bottom);
+        assert_eq!(lerp_color(top, bottom, 0.5), (100, 100, 100));
+        // Out-of-range t is clamped rather than overflowing
+        assert_eq!(lerp_color(top, bottom, 2.0), bottom);
     }
-}
 
-impl Config {
-    /// Get the config file path (~/.pawgate/config.json)
-    pub fn config_path() -> PathBuf {
-        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
-        home.join(".pawgate").join("config.json")
+    #[test]
+    fn test_overlay_color_end() {
+        /// WHY: The gradient end is optional and parsed like overlay_color; bad values mean a flat fill.
+        assert_eq!(Config::default().parse_overlay_color_end(), None);
+
+        let config = Config {
+            overlay_color_end: Some("black".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(config.parse_overlay_color_end(), Some((0, 0, 0)));
+
+        let config = Config {
+            overlay_color_end: Some("not-a-color".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(config.parse_overlay_color_end(), None);
     }
 
-    /// Load configuration from disk, or return default if not found
-    pub fn load() -> Result<Self, Box<dyn std::error::Error>> {
-        let path = Self::config_path();
+    #[test]
+    fn test_overlay_animation_deserialize() {
+        /// WHY: Each documented animation name must load as its variant.
+        for (text, expected) in [
+            ("none", OverlayAnimation::None),
+            ("pulse", OverlayAnimation::Pulse),
+            ("flash", OverlayAnimation::Flash),
+        ] {
+            let json = format!(r#"{{"overlay_animation": "{}"}}"#, text);
+            let config: Config = serde_json::from_str(&json).unwrap();
+            assert_eq!(config.overlay_animation, expected, "overlay_animation '{}'", text);
+        }
+    }
 
-        if !path.exists() {
-            // Create default config
-            let config = Self::default();
-            config.save()?;
-            return Ok(config);
+    #[test]
+    fn test_overlay_animation_defaults_and_fallback() {
+        /// WHY: Animation is opt-in, and a typo should fall back rather than fail the load.
+        let config = Config::default();
+        assert_eq!(config.overlay_animation, OverlayAnimation::None);
+        assert_eq!(config.animation_period_ms, 2000);
+        assert_eq!(config.fade_ms, 0, "The overlay appears instantly unless asked to fade");
+
+        let config: Config = serde_json::from_str(r#"{"overlay_animation": "strobe"}"#).unwrap();
+        assert_eq!(config.overlay_animation, OverlayAnimation::None);
+    }
+
+    #[test]
+    fn test_led_indicator_deserialize() {
+        /// WHY: Each documented LED name must load, and anything else falls back to no LED.
+        for (text, expected) in [
+            ("none", LedIndicator::None),
+            ("scrolllock", LedIndicator::ScrollLock),
+            ("numlock", LedIndicator::NumLock),
+            ("capslock", LedIndicator::None),
+        ] {
+            let json = format!(r#"{{"led_indicator": "{}"}}"#, text);
+            let config: Config = serde_json::from_str(&json).unwrap();
+            assert_eq!(config.led_indicator, expected, "led_indicator '{}'", text);
         }
+        assert_eq!(Config::default().led_indicator, LedIndicator::None);
 
-        let contents = fs::read_to_string(&path)?;
-        let config: Config = serde_json::from_str(&contents)?;
-        Ok(config)
+        let json = serde_json::to_string(&Config {
+            led_indicator: LedIndicator::ScrollLock,
+            ..Config::default()
+        })
+        .unwrap();
+        assert!(json.contains(r#""led_indicator":"scrolllock""#), "{}", json);
     }
 
-    /// Save configuration to disk
-    pub fn save(&self) -> Result<(), Box<dyn std::error::Error>> {
-        let path = Self::config_path();
+    #[test]
+    fn test_hide_cursor_default_and_deserialize() {
+        /// WHY: Hiding the pointer is opt-in, and older configs without the field must still load.
+        assert!(!Config::default().hide_cursor);
 
-        // Create parent directory if needed
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
+        let config: Config = serde_json::from_str(r#"{"hide_cursor": true}"#).unwrap();
+        assert!(config.hide_cursor);
+    }
 
-        let contents = serde_json::to_string_pretty(self)?;
-        fs::write(&path, contents)?;
-        Ok(())
+    #[test]
+    fn test_flash_while_locked_default_and_set() {
+        /// WHY: A blinking icon is opt-in, and the interval must be settable from the CLI.
+        let config = Config::default();
+        assert!(!config.flash_while_locked);
+        assert_eq!(config.flash_interval_ms, 500);
+
+        let mut config = Config::default();
+        config.set_field("flash_while_locked", "true").unwrap();
+        config.set_field("flash_interval_ms", "250").unwrap();
+        assert!(config.flash_while_locked);
+        assert_eq!(config.flash_interval_ms, 250);
+        assert!(config.set_field("flash_interval_ms", "fast").is_err());
     }
 
-    /// Parse overlay color from hex string to RGB
-    pub fn parse_overlay_color(&self) -> (u8, u8, u8) {
-        let hex = self.overlay_color.trim_start_matches('#');
-        if hex.len() == 6 {
-            if let (Ok(r), Ok(g), Ok(b)) = (
-                u8::from_str_radix(&hex[0..2], 16),
-                u8::from_str_radix(&hex[2..4], 16),
-                u8::from_str_radix(&hex[4..6], 16),
-            ) {
-                return (r, g, b);
+    #[test]
+    fn test_keep_awake_default_and_deserialize() {
+        /// WHY: Overriding the user's power settings is opt-in.
+        assert!(!Config::default().keep_awake);
+
+        let config: Config = serde_json::from_str(r#"{"keep_awake": true}"#).unwrap();
+        assert!(config.keep_awake);
+    }
+
+    #[test]
+    fn test_sequence_timeout_default() {
+        /// WHY: One second is enough to type a two-step sequence deliberately.
+        assert_eq!(Config::default().sequence_timeout_ms, 1000);
+    }
+
+    #[test]
+    fn test_toggle_debounce_default_and_set() {
+        /// WHY: A quarter second swallows key bounce and mashing without getting in the way of a deliberate second press.
+        assert_eq!(Config::default().toggle_debounce_ms, 250);
+
+        let mut config = Config::default();
+        config.set_field("toggle_debounce_ms", "0").unwrap();
+        assert_eq!(config.toggle_debounce_ms, 0);
+        assert!(config.set_field("toggle_debounce_ms", "-5").is_err());
+    }
+
+    #[test]
+    fn test_reassert_topmost_default_and_set() {
+        /// WHY: Re-raising once a second keeps games and other topmost windows off the overlay at no real cost.
+        assert_eq!(Config::default().reassert_topmost_ms, 1000);
+
+        let mut config = Config::default();
+        config.set_field("reassert_topmost_ms", "0").unwrap();
+        assert_eq!(config.reassert_topmost_ms, 0);
+        assert!(config.set_field("reassert_topmost_ms", "soon").is_err());
+    }
+
+    #[test]
+    fn test_http_port_default_off_and_set() {
+        /// WHY: Nothing should listen on a port unless asked to.
+        assert_eq!(Config::default().http_port, None);
+
+        let mut config = Config::default();
+        config.set_field("http_port", "8765").unwrap();
+        assert_eq!(config.http_port, Some(8765));
+        config.set_field("http_port", "off").unwrap();
+        assert_eq!(config.http_port, None);
+        assert!(config.set_field("http_port", "0").is_err());
+        assert!(config.set_field("http_port", "70000").is_err());
+    }
+
+    #[test]
+    fn test_auto_lock_on_mash_settings() {
+        /// WHY: Mash detection is opt-in, and a threshold low enough to catch ctrl+c must never apply.
+        let config = Config::default();
+        assert_eq!(config.auto_lock_on_mash, None);
+        assert_eq!(config.mash_window_ms, 150);
+
+        let mut config = Config::default();
+        config.set_field("auto_lock_on_mash", "4").unwrap();
+        assert_eq!(config.auto_lock_on_mash, Some(4));
+        assert!(config.set_field("auto_lock_on_mash", "2").is_err());
+        config.set_field("auto_lock_on_mash", "off").unwrap();
+        assert_eq!(config.auto_lock_on_mash, None);
+
+        let mut config: Config = serde_json::from_str(r#"{"auto_lock_on_mash": 1}"#).unwrap();
+        config.validate();
+        assert_eq!(config.auto_lock_on_mash, Some(crate::mash::MIN_THRESHOLD));
+    }
+
+    #[test]
+    fn test_double_tap_default_off() {
+        /// WHY: Tapping Ctrl twice is common while typing, so it only locks when opted in.
+        let config = Config::default();
+        assert_eq!(config.double_tap_modifier, None);
+        assert_eq!(config.double_tap_window_ms, 400);
+        assert_eq!(config.parsed_double_tap_keys(), None);
+    }
+
+    #[test]
+    fn test_double_tap_keys_by_side() {
+        /// WHY: "ctrl" accepts either key like hotkeys do, while "lctrl" leaves Right Ctrl alone.
+        let keys = |name: &str| {
+            Config {
+                double_tap_modifier: Some(name.to_string()),
+                ..Config::default()
             }
-        }
-        // Default to dark green if parsing fails
-        (27, 94, 32)
+            .parsed_double_tap_keys()
+        };
+        let ctrl = keys("Ctrl").unwrap();
+        assert!(ctrl.contains(&0xA2) && ctrl.contains(&0xA3), "{:x?}", ctrl);
+        assert_eq!(keys("lctrl"), Some(vec![0xA2]));
+        assert_eq!(keys("rshift"), Some(vec![0xA1]));
+        assert_eq!(keys("ctrl+ctrl"), None, "Only a single modifier name");
+        assert_eq!(keys("b"), None, "Ordinary keys can't be double-tapped to lock");
     }
-}
 
-/// Parse hotkey string into modifier flags and virtual key code
-/// Returns (modifiers, vk_code) where modifiers is a bitmask
-pub fn parse_hotkey(hotkey: &str) -> Option<(u32, u32)> {
-    use windows::Win32::UI::Input::KeyboardAndMouse::*;
+    #[test]
+    fn test_peek_hotkey_default_and_parse() {
+        /// WHY: Peeking weakens the lock, so it's off unless a hotkey is chosen.
+        let config = Config::default();
+        assert_eq!(config.peek_hotkey, None);
+        assert_eq!(config.peek_duration_ms, 10_000);
+        assert_eq!(config.parsed_peek_hotkey(), None);
 
-    let parts: Vec<&str> = hotkey.to_lowercase().split('+').map(|s| s.trim()).collect();
+        let config = Config {
+            peek_hotkey: Some("ctrl+shift+p".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(
+            config.parsed_peek_hotkey(),
+            parse_hotkey_sequence("ctrl+shift+p"),
+            "Same syntax as the lock hotkeys"
+        );
 
-    let mut modifiers: u32 = 0;
-    let mut vk_code: Option<u32> = None;
+        let config = Config {
+            peek_hotkey: Some("ctrl+alt+delete".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(config.parsed_peek_hotkey(), None, "Reserved combos are skipped");
+    }
 
-    for part in parts {
-        match part {
-            "ctrl" | "control" => modifiers |= MOD_CONTROL.0,
-            "alt" => modifiers |= MOD_ALT.0,
-            "shift" => modifiers |= MOD_SHIFT.0,
-            "win" | "windows" => modifiers |= MOD_WIN.0,
-            // Single letter keys
-            key if key.len() == 1 => {
-                let c = key.chars().next().unwrap().to_ascii_uppercase();
-                if c.is_ascii_alphabetic() {
-                    vk_code = Some(c as u32);
-                } else if c.is_ascii_digit() {
-                    vk_code = Some(c as u32);
-                }
+    #[test]
+    fn test_panic_unlock_hotkey() {
+        /// WHY: The way out must exist by default and survive a bad config.
+        let config = Config::default();
+        assert_eq!(config.panic_unlock_hotkey, "ctrl+alt+shift+u");
+        assert_eq!(config.parsed_panic_unlock_hotkey(), parse_hotkey_sequence("ctrl+alt+shift+u").unwrap());
+
+        let mut config = Config::default();
+        config.set_field("panic_unlock_hotkey", "ctrl+alt+shift+f12").unwrap();
+        assert_eq!(config.parsed_panic_unlock_hotkey(), parse_hotkey_sequence("ctrl+alt+shift+f12").unwrap());
+        assert!(config.set_field("panic_unlock_hotkey", "off").is_err(), "It can't be turned off");
+        assert!(config.set_field("panic_unlock_hotkey", "ctrl+nope").is_err());
+
+        let config = Config {
+            panic_unlock_hotkey: "ctrl+nope".to_string(),
+            ..Config::default()
+        };
+        assert_eq!(
+            config.parsed_panic_unlock_hotkey(),
+            parse_hotkey_sequence("ctrl+alt+shift+u").unwrap(),
+            "An invalid hotkey falls back to the default"
+        );
+    }
+
+    #[test]
+    fn test_set_field_peek() {
+        /// WHY: `config set` must reject a hotkey the hook couldn't parse, and allow turning peek off.
+        let mut config = Config::default();
+        config.set_field("peek_hotkey", "ctrl+shift+p").unwrap();
+        assert_eq!(config.peek_hotkey.as_deref(), Some("ctrl+shift+p"));
+        assert!(config.set_field("peek_hotkey", "ctrl+nope").is_err());
+        config.set_field("peek_hotkey", "off").unwrap();
+        assert_eq!(config.peek_hotkey, None);
+
+        config.set_field("peek_duration_ms", "3000").unwrap();
+        assert_eq!(config.peek_duration_ms, 3000);
+        assert!(config.set_field("peek_duration_ms", "soon").is_err());
+    }
+
+    #[test]
+    fn test_set_field_double_tap() {
+        /// WHY: `config set` must reject names the hook would ignore, and allow turning it off.
+        let mut config = Config::default();
+        config.set_field("double_tap_modifier", "ctrl").unwrap();
+        assert_eq!(config.double_tap_modifier.as_deref(), Some("ctrl"));
+        assert!(config.set_field("double_tap_modifier", "capslock").is_err());
+        assert_eq!(config.double_tap_modifier.as_deref(), Some("ctrl"), "Unchanged on error");
+        config.set_field("double_tap_modifier", "off").unwrap();
+        assert_eq!(config.double_tap_modifier, None);
+
+        config.set_field("double_tap_window_ms", "300").unwrap();
+        assert_eq!(config.double_tap_window_ms, 300);
+    }
+
+    #[test]
+    fn test_hold_to_lock_default_and_parse() {
+        /// WHY: Holding a modifier is everyday typing, so it only locks when opted in, and only modifiers qualify.
+        assert_eq!(Config::default().parsed_hold_to_lock_keys(), None);
+
+        let keys = |name: &str| {
+            Config {
+                hold_to_lock: Some(name.to_string()),
+                ..Config::default()
             }
-            // Function keys
-            key if key.starts_with('f') && key.len() <= 3 => {
-                if let Ok(num) = key[1..].parse::<u32>() {
-                    if num >= 1 && num <= 24 {
-                        vk_code = Some(VK_F1.0 as u32 + num - 1);
-                    }
-                }
+            .parsed_hold_to_lock_keys()
+        };
+        assert_eq!(keys("rctrl"), Some(vec![0xA3]));
+        assert!(keys("alt").unwrap().contains(&0xA5));
+        assert_eq!(keys("space"), None);
+
+        let mut config = Config::default();
+        config.set_field("hold_to_lock", "RCtrl").unwrap();
+        assert_eq!(config.hold_to_lock.as_deref(), Some("RCtrl"));
+        assert!(config.set_field("hold_to_lock", "capslock").is_err());
+        config.set_field("hold_to_lock", "off").unwrap();
+        assert_eq!(config.hold_to_lock, None);
+    }
+
+    #[test]
+    fn test_tap_hold_key_default_and_parse() {
+        /// WHY: The tap/hold key is swallowed, so it must be opted into, and it's one key, not a combo.
+        let config = Config::default();
+        assert_eq!(config.parsed_tap_hold_keys(), None);
+        assert_eq!(config.hold_threshold_ms, 500);
+
+        let keys = |name: &str| {
+            Config {
+                tap_hold_key: Some(name.to_string()),
+                ..Config::default()
             }
-            // Special keys
-            "space" => vk_code = Some(VK_SPACE.0 as u32),
-            "enter" | "return" => vk_code = Some(VK_RETURN.0 as u32),
-            "escape" | "esc" => vk_code = Some(VK_ESCAPE.0 as u32),
-            "tab" => vk_code = Some(VK_TAB.0 as u32),
-            "backspace" => vk_code = Some(VK_BACK.0 as u32),
-            "delete" | "del" => vk_code = Some(VK_DELETE.0 as u32),
-            "insert" | "ins" => vk_code = Some(VK_INSERT.0 as u32),
-            "home" => vk_code = Some(VK_HOME.0 as u32),
-            "end" => vk_code = Some(VK_END.0 as u32),
-            "pageup" | "pgup" => vk_code = Some(VK_PRIOR.0 as u32),
-            "pagedown" | "pgdn" => vk_code = Some(VK_NEXT.0 as u32),
-            "up" => vk_code = Some(VK_UP.0 as u32),
-            "down" => vk_code = Some(VK_DOWN.0 as u32),
-            "left" => vk_code = Some(VK_LEFT.0 as u32),
-            "right" => vk_code = Some(VK_RIGHT.0 as u32),
-            "numlock" => vk_code = Some(VK_NUMLOCK.0 as u32),
-            "scrolllock" => vk_code = Some(VK_SCROLL.0 as u32),
-            "pause" => vk_code = Some(VK_PAUSE.0 as u32),
-            "printscreen" | "prtsc" => vk_code = Some(VK_SNAPSHOT.0 as u32),
-            _ => {}
-        }
-    }
-
-    vk_code.map(|vk| (modifiers, vk))
-}
+            .parsed_tap_hold_keys()
+        };
+        assert_eq!(keys("pause"), Some(vec![0x13]));
+        assert_eq!(keys("F12"), Some(vec![0x7B]));
+        assert_eq!(keys("rctrl"), Some(vec![0xA3]));
+        assert!(keys("ctrl").unwrap().contains(&0xA2));
+        assert_eq!(keys("ctrl+b"), None);
 
-// =============================================================================
-// Unit Tests
-// =============================================================================
+        let mut config = Config::default();
+        config.set_field("tap_hold_key", "ScrollLock").unwrap();
+        assert_eq!(config.tap_hold_key.as_deref(), Some("ScrollLock"));
+        assert!(config.set_field("tap_hold_key", "ctrl+b").is_err());
+        assert_eq!(config.tap_hold_key.as_deref(), Some("ScrollLock"), "Unchanged on error");
+        config.set_field("tap_hold_key", "off").unwrap();
+        assert_eq!(config.tap_hold_key, None);
+        config.set_field("hold_threshold_ms", "800").unwrap();
+        assert_eq!(config.hold_threshold_ms, 800);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_lock_delay_default_and_deserialize() {
+        /// WHY: Existing users expect the hotkey to lock immediately.
+        assert_eq!(Config::default().lock_delay_ms, 0);
 
-    // -------------------------------------------------------------------------
-    // Config Default Tests
-    // -------------------------------------------------------------------------
+        let config: Config = serde_json::from_str(r#"{"lock_delay_ms": 3000}"#).unwrap();
+        assert_eq!(config.lock_delay_ms, 3000);
+    }
 
     #[test]
-    fn test_default_config_values() {
-        /// WHY: Verify default values match documentation and are sensible.
-        /// The default hotkey is part of the public API contract.
-        let config = Config::default();
+    fn test_lock_mode_deserialize_variants() {
+        /// WHY: Users write these lowercase strings by hand in config.json.
+        for (text, expected) in [
+            ("keyboardonly", LockMode::KeyboardOnly),
+            ("mouseonly", LockMode::MouseOnly),
+            ("both", LockMode::Both),
+            ("targetwindow", LockMode::TargetWindow),
+        ] {
+            let json = format!(r#"{{"lock_mode": "{}"}}"#, text);
+            let config: Config = serde_json::from_str(&json).unwrap();
+            assert_eq!(config.lock_mode, expected, "lock_mode '{}'", text);
+        }
+    }
 
-        assert_eq!(config.hotkey, "ctrl+b", "Default hotkey should be ctrl+b");
-        assert_eq!(config.opacity, 0.3, "Default opacity should be 0.3 (30%)");
-        assert!(config.notifications_enabled, "Notifications should be enabled by default");
-        assert_eq!(config.overlay_color, "#1B5E20", "Default color should be forest green");
+    #[test]
+    fn test_target_window_mode_blocks_nothing_globally() {
+        /// WHY: Locking one window must leave the keyboard and mouse working everywhere else.
+        let mut config = Config::default();
+        config.set_field("lock_mode", "targetwindow").unwrap();
+        assert_eq!(config.lock_mode, LockMode::TargetWindow);
+        assert!(!config.lock_mode.blocks_keyboard());
+        assert!(!config.lock_mode.blocks_mouse());
+        assert!(config.set_field("lock_mode", "window").is_err());
     }
 
     #[test]
-    fn test_config_path_contains_pawgate() {
-        /// WHY: Config path must include .pawgate directory for organization.
-        let path = Config::config_path();
-        let path_str = path.to_string_lossy();
+    fn test_lock_mode_unknown_falls_back_to_both() {
+        /// WHY: A typo must never silently leave the keyboard or mouse unblocked.
+        let config: Config = serde_json::from_str(r#"{"lock_mode": "keyboard"}"#).unwrap();
+        assert_eq!(config.lock_mode, LockMode::Both);
+        assert_eq!(Config::default().lock_mode, LockMode::Both);
+    }
 
-        assert!(path_str.contains(".pawgate"), "Config path should contain .pawgate");
-        assert!(path_str.ends_with("config.json"), "Config path should end with config.json");
+    #[test]
+    fn test_lock_mode_roundtrip() {
+        /// WHY: The value written by save() must load back as the same mode.
+        let config = Config {
+            lock_mode: LockMode::MouseOnly,
+            ..Config::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains(r#""lock_mode":"mouseonly""#));
+
+        let loaded: Config = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.lock_mode, LockMode::MouseOnly);
+    }
+
+    #[test]
+    fn test_log_events_defaults_off() {
+        /// WHY: The event log is opt-in for privacy.
+        assert!(!Config::default().log_events);
+
+        let config: Config = serde_json::from_str(r#"{"log_events": true}"#).unwrap();
+        assert!(config.log_events);
+    }
+
+    #[test]
+    fn test_session_history_limit() {
+        /// WHY: Session history is on by default but must be capped, and 0 must turn it off.
+        assert_eq!(Config::default().session_history_limit, 500);
+
+        let mut config = Config::default();
+        config.set_field("session_history_limit", "0").unwrap();
+        assert_eq!(config.session_history_limit, 0);
+        assert!(config.set_field("session_history_limit", "lots").is_err());
+    }
+
+    #[test]
+    fn test_restore_lock_state_defaults_on() {
+        /// WHY: Restarting for an update while the cat is around shouldn't hand it an unlocked keyboard.
+        assert!(Config::default().restore_lock_state);
+
+        let mut config = Config::default();
+        config.set_field("restore_lock_state", "false").unwrap();
+        assert!(!config.restore_lock_state);
+    }
+
+    #[test]
+    fn test_confirm_quit_while_locked_defaults_off() {
+        /// WHY: Quitting from the menu has always been immediate; the extra keypress is opt-in.
+        assert!(!Config::default().confirm_quit_while_locked);
+
+        let mut config = Config::default();
+        config.set_field("confirm_quit_while_locked", "true").unwrap();
+        assert!(config.confirm_quit_while_locked);
+        assert!(config.set_field("confirm_quit_while_locked", "maybe").is_err());
+    }
+
+    #[test]
+    fn test_on_register_failure_names() {
+        /// WHY: Falling back to ctrl+b is what PawGate always did, so it stays the default.
+        assert_eq!(Config::default().on_register_failure, OnRegisterFailure::FallbackDefault);
+
+        let config: Config = serde_json::from_str(r#"{"on_register_failure": "notify"}"#).unwrap();
+        assert_eq!(config.on_register_failure, OnRegisterFailure::Notify);
+        let config: Config = serde_json::from_str(r#"{"on_register_failure": "panic"}"#).unwrap();
+        assert_eq!(config.on_register_failure, OnRegisterFailure::FallbackDefault);
+
+        let mut config = Config::default();
+        config.set_field("on_register_failure", "exit").unwrap();
+        assert_eq!(config.on_register_failure, OnRegisterFailure::Exit);
+        assert!(config.set_field("on_register_failure", "panic").is_err());
     }
 
     // -------------------------------------------------------------------------
@@ -213,28 +5278,221 @@ mod tests {
     }
 
     #[test]
-    fn test_parse_overlay_color_invalid_returns_default() {
-        /// WHY: Invalid color strings should fall back to default, not crash.
+    fn test_parse_overlay_color_invalid_returns_default() {
+        /// WHY: Invalid color strings should fall back to default, not crash.
+        let config = Config {
+            overlay_color: "not-a-color".to_string(),
+            ..Default::default()
+        };
+
+        let (r, g, b) = config.parse_overlay_color();
+        // Should return default dark green
+        assert_eq!((r, g, b), (27, 94, 32));
+    }
+
+    #[test]
+    fn test_parse_overlay_color_named() {
+        /// WHY: Non-technical users should be able to write "green" instead of hex.
+        let test_cases = [
+            ("green", (0x1B, 0x5E, 0x20)),
+            ("Blue", (0x15, 0x65, 0xC0)),
+            ("RED", (0xB7, 0x1C, 0x1C)),
+            ("black", (0, 0, 0)),
+            (" white ", (255, 255, 255)),
+        ];
+
+        for (name, expected) in test_cases {
+            let config = Config {
+                overlay_color: name.to_string(),
+                ..Default::default()
+            };
+            assert_eq!(config.parse_overlay_color(), expected, "{} parsed incorrectly", name);
+        }
+    }
+
+    #[test]
+    fn test_parse_overlay_color_unknown_name_returns_default() {
+        /// WHY: Unknown names must fall back to the default like invalid hex does.
+        let config = Config {
+            overlay_color: "chartreuse".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(config.parse_overlay_color(), (27, 94, 32));
+    }
+
+    #[test]
+    fn test_parse_overlay_color_short_hex() {
+        /// WHY: 3-digit CSS shorthand doubles each nibble, so "#FFF" is white.
+        let config = Config {
+            overlay_color: "#FFF".to_string(),
+            ..Default::default()
+        };
+
+        let (r, g, b) = config.parse_overlay_color();
+        assert_eq!((r, g, b), (0xFF, 0xFF, 0xFF), "#FFF should expand to #FFFFFF");
+    }
+
+    #[test]
+    fn test_parse_overlay_color_short_hex_values() {
+        /// WHY: Each shorthand nibble must expand independently.
+        let test_cases = [
+            ("#1a2", (0x11, 0xAA, 0x22)),
+            ("#000", (0x00, 0x00, 0x00)),
+            ("0F8", (0x00, 0xFF, 0x88)),
+        ];
+
+        for (hex, expected) in test_cases {
+            let config = Config {
+                overlay_color: hex.to_string(),
+                ..Default::default()
+            };
+            assert_eq!(config.parse_overlay_color(), expected, "{} parsed incorrectly", hex);
+        }
+    }
+
+    #[test]
+    fn test_parse_overlay_color_rgba_eight_digits() {
+        /// WHY: An 8-digit hex carries its own alpha for a tint independent of opacity.
+        let config = Config {
+            overlay_color: "#1B5E20CC".to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(config.parse_overlay_color_rgba(), (0x1B, 0x5E, 0x20, 0xCC));
+        assert_eq!(
+            config.parse_overlay_color(),
+            (0x1B, 0x5E, 0x20),
+            "RGB parsing should drop the alpha channel"
+        );
+    }
+
+    #[test]
+    fn test_parse_overlay_color_rgba_defaults_alpha() {
+        /// WHY: Existing 6-digit, shorthand and named colors must stay fully opaque.
+        for color in ["#FF6600", "#FFF", "green", "not-a-color"] {
+            let config = Config {
+                overlay_color: color.to_string(),
+                ..Default::default()
+            };
+            let (_, _, _, a) = config.parse_overlay_color_rgba();
+            assert_eq!(a, 255, "{} should have alpha 255", color);
+        }
+    }
+
+    #[test]
+    fn test_parse_overlay_color_other_lengths_return_default() {
+        /// WHY: Only 3 or 6 hex digits are valid; anything else falls back to default.
+        for hex in ["#FF", "#FFFF", "#FFFFF", "#FFFFFFF", "#ééé"] {
+            let config = Config {
+                overlay_color: hex.to_string(),
+                ..Default::default()
+            };
+            assert_eq!(config.parse_overlay_color(), (27, 94, 32), "{} should return default", hex);
+        }
+    }
+
+    #[test]
+    fn test_validate_color_accepts_valid_colors() {
+        /// WHY: Every form the renderer understands must pass the strict check too.
+        for color in ["#1B5E20", "1b5e20", "#FFF", "#1B5E20CC", "teal", "WHITE"] {
+            let config = Config {
+                overlay_color: color.to_string(),
+                ..Default::default()
+            };
+            assert_eq!(config.validate_color(), Ok(()), "{}", color);
+        }
+    }
+
+    #[test]
+    fn test_validate_color_rejects_invalid_colors() {
+        /// WHY: Typos must be reported instead of silently turning the overlay green.
+        for color in ["#12345", "#GGGGGG", "chartreuse", "", "#FFFFFFF", "#ééé"] {
+            let config = Config {
+                overlay_color: color.to_string(),
+                ..Default::default()
+            };
+            let err = config.validate_color().unwrap_err();
+            assert!(err.contains("overlay_color"), "Error should name the field: {}", err);
+        }
+    }
+
+    #[test]
+    fn test_validate_color_checks_gradient_end() {
+        /// WHY: A bad gradient end silently drops the gradient, which is just as confusing.
         let config = Config {
-            overlay_color: "not-a-color".to_string(),
+            overlay_color_end: Some("bleu".to_string()),
             ..Default::default()
         };
+        let err = config.validate_color().unwrap_err();
+        assert!(err.contains("overlay_color_end"), "{}", err);
+        assert!(err.contains("blue"), "Error should list the known names: {}", err);
+    }
 
-        let (r, g, b) = config.parse_overlay_color();
-        // Should return default dark green
-        assert_eq!((r, g, b), (27, 94, 32));
+    // -------------------------------------------------------------------------
+    // CLI Set Tests
+    // -------------------------------------------------------------------------
+
+    #[test]
+    fn test_set_field_opacity() {
+        /// WHY: `pawgate config set opacity 0.5` must update the value that gets saved.
+        let mut config = Config::default();
+        config.set_field("opacity", "0.5").unwrap();
+        assert_eq!(config.opacity, 0.5);
     }
 
     #[test]
-    fn test_parse_overlay_color_short_hex() {
-        /// WHY: Short hex strings (< 6 chars) should fall back to default.
-        let config = Config {
-            overlay_color: "#FFF".to_string(),
-            ..Default::default()
-        };
+    fn test_set_field_rejects_out_of_range_opacity() {
+        /// WHY: Unlike a hand-edited file, the CLI can refuse bad input instead of clamping.
+        let mut config = Config::default();
+        for value in ["1.5", "-0.1", "NaN", "half"] {
+            let err = config.set_field("opacity", value).unwrap_err();
+            assert!(err.contains("opacity"), "Error should name the field: {}", err);
+        }
+        assert_eq!(config.opacity, Config::default().opacity, "Rejected values must not be applied");
+    }
 
-        let (r, g, b) = config.parse_overlay_color();
-        assert_eq!((r, g, b), (27, 94, 32), "Short hex should return default");
+    #[test]
+    fn test_set_field_rejects_invalid_color() {
+        /// WHY: The renderer silently falls back to green, so catch typos when setting.
+        let mut config = Config::default();
+        assert!(config.set_field("overlay_color", "#12345").is_err());
+        assert!(config.set_field("overlay_color", "chartreuse").is_err());
+        assert_eq!(config.overlay_color, "#1B5E20");
+
+        config.set_field("overlay_color", "teal").unwrap();
+        assert_eq!(config.overlay_color, "teal");
+    }
+
+    #[test]
+    fn test_set_field_other_types() {
+        /// WHY: Each setting type should accept its natural string form.
+        let mut config = Config::default();
+        config.set_field("notifications_enabled", "false").unwrap();
+        config.set_field("auto_lock_idle_secs", "300").unwrap();
+        config.set_field("lock_mode", "keyboardonly").unwrap();
+        config.set_field("monitors", "0, DISPLAY3").unwrap();
+        config.set_field("show_overlay", "false").unwrap();
+
+        assert!(!config.notifications_enabled);
+        assert!(!config.show_overlay);
+        assert_eq!(config.auto_lock_idle_secs, Some(300));
+        assert_eq!(config.lock_mode, LockMode::KeyboardOnly);
+        assert_eq!(
+            config.monitors,
+            vec![MonitorSelector::Index(0), MonitorSelector::Name("DISPLAY3".to_string())]
+        );
+
+        config.set_field("auto_lock_idle_secs", "off").unwrap();
+        assert_eq!(config.auto_lock_idle_secs, None);
+        assert!(config.set_field("lock_mode", "all").is_err());
+    }
+
+    #[test]
+    fn test_set_field_unknown_key() {
+        /// WHY: A misspelled key must not be silently ignored.
+        let err = Config::default().set_field("opactiy", "0.5").unwrap_err();
+        assert!(err.contains("opactiy"));
     }
 
     // -------------------------------------------------------------------------
@@ -258,18 +5516,18 @@ mod tests {
     #[test]
     fn test_config_deserializes_from_json() {
         /// WHY: Config must deserialize correctly for load() to work.
-        let json = r#"{
+        let json = r##"{
             "hotkey": "ctrl+shift+l",
             "opacity": 0.5,
             "notifications_enabled": false,
             "overlay_color": "#FF0000"
-        }"#;
+        }"##;
 
         let config: Result<Config, _> = serde_json::from_str(json);
         assert!(config.is_ok(), "Config should deserialize from JSON");
 
         let config = config.unwrap();
-        assert_eq!(config.hotkey, "ctrl+shift+l");
+        assert_eq!(config.hotkeys, vec!["ctrl+shift+l"]);
         assert_eq!(config.opacity, 0.5);
         assert!(!config.notifications_enabled);
         assert_eq!(config.overlay_color, "#FF0000");
@@ -279,21 +5537,105 @@ mod tests {
     fn test_config_round_trip() {
         /// WHY: Serialize then deserialize should preserve all values.
         let original = Config {
-            hotkey: "alt+f12".to_string(),
+            hotkeys: vec!["alt+f12".to_string(), "ctrl+shift+l".to_string()],
             opacity: 0.75,
             notifications_enabled: false,
             overlay_color: "#123456".to_string(),
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&original).unwrap();
         let restored: Config = serde_json::from_str(&json).unwrap();
 
-        assert_eq!(original.hotkey, restored.hotkey);
+        assert_eq!(original.hotkeys, restored.hotkeys);
+        assert_eq!(original.opacity, restored.opacity);
+        assert_eq!(original.notifications_enabled, restored.notifications_enabled);
+        assert_eq!(original.overlay_color, restored.overlay_color);
+    }
+
+    #[test]
+    fn test_legacy_hotkey_field_populates_hotkeys() {
+        /// WHY: Existing config files only have the single `hotkey` field.
+        let json = r##"{
+            "hotkey": "ctrl+shift+l",
+            "opacity": 0.3,
+            "notifications_enabled": true,
+            "overlay_color": "#1B5E20"
+        }"##;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.hotkeys, vec!["ctrl+shift+l"]);
+    }
+
+    #[test]
+    fn test_hotkeys_list_deserializes() {
+        /// WHY: New config files store every toggle hotkey in a list.
+        let json = r##"{
+            "hotkeys": ["ctrl+b", "ctrl+alt+shift+u"],
+            "opacity": 0.3,
+            "notifications_enabled": true,
+            "overlay_color": "#1B5E20"
+        }"##;
+
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.hotkeys, vec!["ctrl+b", "ctrl+alt+shift+u"]);
+    }
+
+    #[test]
+    fn test_hotkey_object_form_deserializes() {
+        /// WHY: Generated configs can write the modifiers and key separately instead of building a string.
+        let json = r#"{"hotkeys": ["ctrl+b", {"modifiers": ["ctrl", "shift"], "key": "b"}, {"key": "pause"}]}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+        assert_eq!(config.hotkeys, vec!["ctrl+b", "ctrl+shift+b", "pause"]);
+
+        let config: Config = serde_json::from_str(r#"{"hotkey": {"modifiers": ["alt"], "key": "f12"}}"#).unwrap();
+        assert_eq!(config.hotkeys, vec!["alt+f12"], "The legacy single field takes the object too");
+    }
+
+    #[test]
+    fn test_config_serializes_hotkeys_list() {
+        /// WHY: Saving must write the new field so the legacy alias can be retired.
+        let json = serde_json::to_string(&Config::default()).unwrap();
+        assert!(json.contains("\"hotkeys\":[\"ctrl+b\"]"), "JSON should contain hotkeys list");
+    }
+
+    #[test]
+    fn test_config_toml_round_trip() {
+        /// WHY: TOML configs must preserve all values just like JSON.
+        let original = Config {
+            hotkeys: vec!["alt+f12".to_string(), "ctrl+shift+l".to_string()],
+            opacity: 0.75,
+            notifications_enabled: false,
+            overlay_color: "#123456".to_string(),
+            ..Default::default()
+        };
+
+        let text = toml::to_string_pretty(&original).unwrap();
+        let restored: Config = toml::from_str(&text).unwrap();
+
+        assert_eq!(original.hotkeys, restored.hotkeys);
         assert_eq!(original.opacity, restored.opacity);
         assert_eq!(original.notifications_enabled, restored.notifications_enabled);
         assert_eq!(original.overlay_color, restored.overlay_color);
     }
 
+    #[test]
+    fn test_toml_config_allows_comments() {
+        /// WHY: Inline comments are the reason to use TOML over JSON.
+        let text = r##"
+            # Lock with Ctrl+B
+            hotkey = "ctrl+b"
+            opacity = 0.4 # a bit darker than default
+            notifications_enabled = true
+            overlay_color = "#1565C0"
+        "##;
+
+        let config: Config = toml::from_str(text).unwrap();
+        assert_eq!(config.hotkeys, vec!["ctrl+b"]);
+        assert_eq!(config.opacity, 0.4);
+        assert_eq!(config.overlay_color, "#1565C0");
+    }
+
     // -------------------------------------------------------------------------
     // Hotkey Parsing Tests (platform-independent logic)
     // -------------------------------------------------------------------------
@@ -306,6 +5648,121 @@ mod tests {
         use super::*;
         use windows::Win32::UI::Input::KeyboardAndMouse::*;
 
+        #[test]
+        fn test_is_valid_hotkey() {
+            /// WHY: A settings UI validates as the user types, so partial input must be rejected.
+            assert!(is_valid_hotkey("ctrl+b"));
+            assert!(is_valid_hotkey("ctrl+shift+f5"));
+            assert!(is_valid_hotkey("rctrl+b"));
+
+            assert!(!is_valid_hotkey("ctrl"), "Modifier without a key");
+            assert!(!is_valid_hotkey("b"), "Key without a modifier");
+            assert!(!is_valid_hotkey(""), "Empty input");
+            assert!(!is_valid_hotkey("ctrl+notakey"));
+        }
+
+        #[test]
+        fn test_left_and_right_ctrl_distinguishable() {
+            /// WHY: Power users bind different actions to each side; the parse must tell them apart.
+            let left = parse_hotkey_sided("lctrl+b").unwrap();
+            let right = parse_hotkey_sided("rctrl+b").unwrap();
+
+            assert_ne!(left, right);
+            assert_eq!(left.modifiers, MOD_CONTROL.0);
+            assert_eq!(left.sides, ModifierSides::LEFT_CTRL);
+            assert_eq!(right.sides, ModifierSides::RIGHT_CTRL);
+            assert_eq!(left.vk, 'B' as u32);
+
+            // The sequence steps the hook matches on keep the difference too
+            assert_ne!(parse_hotkey_sequence("lctrl+b"), parse_hotkey_sequence("rctrl+b"));
+        }
+
+        #[test]
+        fn test_sided_modifier_tokens() {
+            /// WHY: Every documented sided token must map to its generic flag plus a side.
+            let cases = [
+                ("lalt+x", MOD_ALT.0, ModifierSides::LEFT_ALT),
+                ("ralt+x", MOD_ALT.0, ModifierSides::RIGHT_ALT),
+                ("lshift+x", MOD_SHIFT.0, ModifierSides::LEFT_SHIFT),
+                ("RShift+x", MOD_SHIFT.0, ModifierSides::RIGHT_SHIFT),
+                (
+                    "altgr+x",
+                    MOD_CONTROL.0 | MOD_ALT.0,
+                    ModifierSides::LEFT_CTRL | ModifierSides::RIGHT_ALT,
+                ),
+            ];
+            for (combo, modifiers, sides) in cases {
+                let parsed = parse_hotkey_sided(combo).unwrap();
+                assert_eq!((parsed.modifiers, parsed.sides), (modifiers, sides), "{}", combo);
+            }
+        }
+
+        #[test]
+        fn test_generic_modifiers_have_no_side() {
+            /// WHY: Plain "ctrl" must keep accepting either Ctrl key, and old callers see no change.
+            let parsed = parse_hotkey_sided("ctrl+b").unwrap();
+            assert!(parsed.sides.is_empty());
+            assert_eq!(parse_hotkey("lctrl+b"), parse_hotkey("ctrl+b"));
+
+            let step = parsed.to_step();
+            assert_eq!(step, (MOD_CONTROL.0, 'B' as u32));
+
+            let (modifiers, sides) = split_step_modifiers(parse_hotkey_sided("rshift+b").unwrap().to_step().0);
+            assert_eq!((modifiers, sides), (MOD_SHIFT.0, ModifierSides::RIGHT_SHIFT));
+        }
+
+        #[test]
+        fn test_reserved_combos_detected() {
+            /// WHY: Windows handles these combos itself, so configuring one would silently never work.
+            let (modifiers, vk) = parse_hotkey("ctrl+alt+delete").unwrap();
+            assert_eq!(hotkey_conflict(modifiers, vk), Some(HotkeyConflict::SecureAttention));
+
+            let (modifiers, vk) = parse_hotkey("win+l").unwrap();
+            assert_eq!(hotkey_conflict(modifiers, vk), Some(HotkeyConflict::SessionLock));
+
+            let (modifiers, vk) = parse_hotkey("ctrl+l").unwrap();
+            assert_eq!(hotkey_conflict(modifiers, vk), None);
+            let (modifiers, vk) = parse_hotkey("ctrl+delete").unwrap();
+            assert_eq!(hotkey_conflict(modifiers, vk), None);
+        }
+
+        #[test]
+        fn test_conflict_message_names_combo() {
+            /// WHY: The message must say which hotkey failed and what to do instead.
+            let message = hotkey_conflict_message("win+l", HotkeyConflict::SessionLock);
+            assert!(message.contains("\"win+l\""), "{}", message);
+            assert!(message.contains("always locks Windows itself"), "{}", message);
+            assert!(message.contains("Pick a different combo"), "{}", message);
+
+            let message = hotkey_conflict_message("ctrl+alt+del", HotkeyConflict::SecureAttention);
+            assert!(message.contains("security screen"), "{}", message);
+        }
+
+        #[test]
+        fn test_conflicting_hotkey_skipped_others_kept() {
+            /// WHY: One unusable hotkey must not stop the rest from working.
+            let config = Config {
+                hotkeys: vec!["win+l".to_string(), "ctrl+b".to_string()],
+                ..Config::default()
+            };
+            assert_eq!(config.parsed_hotkeys(), vec![vec![(MOD_CONTROL.0, 'B' as u32)]]);
+
+            let sequence = parse_hotkey_sequence("ctrl+b,win+l").unwrap();
+            assert_eq!(sequence_conflict(&sequence), Some(HotkeyConflict::SessionLock));
+        }
+
+        #[test]
+        fn test_hotkey_string_and_object_parse_alike() {
+            /// WHY: Both config forms must end up as the same hotkey, or switching forms would change what locks.
+            let string: Config = serde_json::from_str(r#"{"hotkeys": ["ctrl+shift+b"]}"#).unwrap();
+            let object: Config =
+                serde_json::from_str(r#"{"hotkeys": [{"modifiers": ["ctrl", "shift"], "key": "b"}]}"#).unwrap();
+
+            assert_eq!(parse_hotkey(&string.hotkeys[0]), Some((MOD_CONTROL.0 | MOD_SHIFT.0, 'B' as u32)));
+            assert_eq!(parse_hotkey(&object.hotkeys[0]), parse_hotkey(&string.hotkeys[0]));
+            assert_eq!(object.parsed_hotkeys(), string.parsed_hotkeys());
+        }
+
         #[test]
         fn test_parse_simple_hotkey_ctrl_b() {
             /// WHY: The default hotkey must parse correctly.
@@ -424,6 +5881,388 @@ mod tests {
             }
         }
 
+        #[test]
+        fn test_parse_numpad_keys() {
+            /// WHY: Numpad digits must map to VK_NUMPAD0-9, not the top-row keys.
+            for i in 0..=9 {
+                let hotkey = format!("ctrl+num{}", i);
+                let result = parse_hotkey(&hotkey);
+
+                assert!(result.is_some(), "ctrl+num{} should parse", i);
+                let (_, vk) = result.unwrap();
+                assert_eq!(vk, VK_NUMPAD0.0 as u32 + i, "Numpad {} vk code incorrect", i);
+                assert_ne!(vk, ('0' as u32) + i, "Numpad {} should differ from top row", i);
+            }
+        }
+
+        #[test]
+        fn test_parse_numpad_operators() {
+            /// WHY: Numpad arithmetic keys are common single-hand hotkey targets.
+            let test_cases = [
+                ("numadd", VK_ADD.0 as u32),
+                ("numsub", VK_SUBTRACT.0 as u32),
+                ("nummul", VK_MULTIPLY.0 as u32),
+                ("numdiv", VK_DIVIDE.0 as u32),
+                ("numdec", VK_DECIMAL.0 as u32),
+            ];
+
+            for (key_name, expected_vk) in test_cases {
+                let result = parse_hotkey(&format!("ctrl+{}", key_name));
+                assert!(result.is_some(), "{} should parse", key_name);
+
+                let (_, vk) = result.unwrap();
+                assert_eq!(vk, expected_vk, "{} vk code incorrect", key_name);
+            }
+        }
+
+        #[test]
+        fn test_parse_media_keys() {
+            /// WHY: HTPC users bind the lock to media keys, typically with no modifiers.
+            let test_cases = [
+                ("play", VK_MEDIA_PLAY_PAUSE.0 as u32),
+                ("playpause", VK_MEDIA_PLAY_PAUSE.0 as u32),
+                ("stop", VK_MEDIA_STOP.0 as u32),
+                ("next", VK_MEDIA_NEXT_TRACK.0 as u32),
+                ("medianext", VK_MEDIA_NEXT_TRACK.0 as u32),
+                ("prev", VK_MEDIA_PREV_TRACK.0 as u32),
+                ("volup", VK_VOLUME_UP.0 as u32),
+                ("volumeup", VK_VOLUME_UP.0 as u32),
+                ("voldown", VK_VOLUME_DOWN.0 as u32),
+                ("mute", VK_VOLUME_MUTE.0 as u32),
+            ];
+
+            for (key_name, expected_vk) in test_cases {
+                let result = parse_hotkey(key_name);
+                assert!(result.is_some(), "{} should parse", key_name);
+
+                let (modifiers, vk) = result.unwrap();
+                assert_eq!(modifiers, 0, "{} should have no modifiers", key_name);
+                assert_eq!(vk, expected_vk, "{} vk code incorrect", key_name);
+            }
+        }
+
+        #[test]
+        fn test_parse_apps_and_oem_keys() {
+            /// WHY: The context-menu key and punctuation keys make handy combos on full keyboards.
+            let test_cases = [
+                ("apps", VK_APPS.0 as u32),
+                ("menu", VK_APPS.0 as u32),
+                ("semicolon", VK_OEM_1.0 as u32),
+                ("equals", VK_OEM_PLUS.0 as u32),
+                ("equal", VK_OEM_PLUS.0 as u32),
+                ("comma", VK_OEM_COMMA.0 as u32),
+                ("minus", VK_OEM_MINUS.0 as u32),
+                ("dash", VK_OEM_MINUS.0 as u32),
+                ("period", VK_OEM_PERIOD.0 as u32),
+                ("dot", VK_OEM_PERIOD.0 as u32),
+                ("slash", VK_OEM_2.0 as u32),
+                ("backtick", VK_OEM_3.0 as u32),
+                ("grave", VK_OEM_3.0 as u32),
+                ("lbracket", VK_OEM_4.0 as u32),
+                ("leftbracket", VK_OEM_4.0 as u32),
+                ("backslash", VK_OEM_5.0 as u32),
+                ("rbracket", VK_OEM_6.0 as u32),
+                ("rightbracket", VK_OEM_6.0 as u32),
+                ("quote", VK_OEM_7.0 as u32),
+                ("apostrophe", VK_OEM_7.0 as u32),
+            ];
+
+            for (key_name, expected_vk) in test_cases {
+                let result = parse_hotkey(&format!("ctrl+{}", key_name));
+                assert!(result.is_some(), "{} should parse", key_name);
+
+                let (modifiers, vk) = result.unwrap();
+                assert_eq!(modifiers, MOD_CONTROL.0, "{} modifiers incorrect", key_name);
+                assert_eq!(vk, expected_vk, "{} vk code incorrect", key_name);
+            }
+        }
+
+        #[test]
+        fn test_menu_key_is_not_alt() {
+            /// WHY: "menu" names the context-menu key; treating it as Alt (VK_MENU) would change the combo.
+            assert_eq!(parse_hotkey("menu"), Some((0, VK_APPS.0 as u32)));
+            assert_ne!(VK_APPS, VK_MENU);
+        }
+
+        #[test]
+        fn test_parse_pause_is_not_media_key() {
+            /// WHY: "pause" predates media key support and must keep meaning Pause/Break.
+            let result = parse_hotkey("win+pause");
+
+            assert!(result.is_some());
+            let (_, vk) = result.unwrap();
+            assert_eq!(vk, VK_PAUSE.0 as u32, "pause should map to VK_PAUSE");
+        }
+
+        #[test]
+        fn test_pause_and_printscreen_alone() {
+            /// WHY: A single press of Pause or PrintScreen is a deliberate bind and must not need a modifier.
+            assert_eq!(parse_hotkey("pause"), Some((0, VK_PAUSE.0 as u32)));
+            assert_eq!(parse_hotkey("printscreen"), Some((0, VK_SNAPSHOT.0 as u32)));
+
+            for hotkey in ["pause", "printscreen", "prtsc"] {
+                assert!(!requires_modifier_warning(hotkey), "{} shouldn't warn", hotkey);
+                assert!(is_valid_hotkey(hotkey), "{} is a complete hotkey", hotkey);
+            }
+            assert!(!is_valid_hotkey("play"), "Other bare keys still need a modifier here");
+        }
+
+        #[test]
+        fn test_standalone_hotkey_survives_validation() {
+            /// WHY: Even with bare keys rejected, a lone Pause must stay registered.
+            let mut config = Config {
+                hotkeys: vec!["pause".to_string(), "printscreen".to_string()],
+                reject_unmodified_hotkeys: true,
+                ..Default::default()
+            };
+            config.validate();
+            assert_eq!(
+                config.parsed_hotkeys(),
+                vec![vec![(0, VK_PAUSE.0 as u32)], vec![(0, VK_SNAPSHOT.0 as u32)]]
+            );
+        }
+
+        #[test]
+        fn test_parse_mouse_buttons() {
+            /// WHY: Mouse side buttons are requested lock triggers, alone or with modifiers.
+            let test_cases = [
+                ("mouse1", VK_LBUTTON.0 as u32),
+                ("mouse2", VK_RBUTTON.0 as u32),
+                ("mouse3", VK_MBUTTON.0 as u32),
+                ("mouse4", VK_XBUTTON1.0 as u32),
+                ("mouse5", VK_XBUTTON2.0 as u32),
+            ];
+
+            for (key_name, expected_vk) in test_cases {
+                let result = parse_hotkey(key_name);
+                assert_eq!(result, Some((0, expected_vk)), "{} should parse", key_name);
+
+                let result = parse_hotkey(&format!("ctrl+{}", key_name));
+                assert_eq!(
+                    result,
+                    Some((MOD_CONTROL.0, expected_vk)),
+                    "ctrl+{} should parse",
+                    key_name
+                );
+            }
+        }
+
+        #[test]
+        fn test_parse_detailed_unknown_modifier() {
+            /// WHY: A misspelled modifier should be reported, not silently ignored.
+            let result = parse_hotkey_detailed("crtl+b");
+
+            assert_eq!(
+                result,
+                Err(HotkeyParseError::UnknownModifier("crtl".to_string()))
+            );
+        }
+
+        #[test]
+        fn test_parse_detailed_unknown_key() {
+            /// WHY: An unrecognized final token is a bad key name, not a modifier.
+            let result = parse_hotkey_detailed("ctrl+f99");
+
+            assert_eq!(result, Err(HotkeyParseError::UnknownKey("f99".to_string())));
+        }
+
+        #[test]
+        fn test_parse_rejects_unicode_look_alikes() {
+            /// WHY: Unicode lowercasing turns the Kelvin sign into "k"; it must not register as the K key.
+            assert_eq!(parse_hotkey("ctrl+\u{212A}"), None);
+            assert_eq!(parse_hotkey("ctrl+K"), Some((MOD_CONTROL.0, 'K' as u32)));
+            assert_eq!(
+                parse_hotkey_detailed("ctrl+é"),
+                Err(HotkeyParseError::UnknownKey("é".to_string()))
+            );
+            assert_eq!(
+                parse_hotkey_detailed("ctrl+çtrl+b"),
+                Err(HotkeyParseError::UnknownModifier("çtrl".to_string()))
+            );
+            assert_eq!(parse_hotkey("ctrl+🐾"), None);
+            assert_eq!(parse_hotkey("ctrl+aVeryLongKeyNameIndeed"), None, "Longer than any name");
+        }
+
+        #[test]
+        fn test_parse_detailed_no_key() {
+            /// WHY: Modifier-only strings can't be registered as a hotkey.
+            assert_eq!(
+                parse_hotkey_detailed("ctrl+shift"),
+                Err(HotkeyParseError::NoKeySpecified)
+            );
+            assert_eq!(parse_hotkey_detailed(""), Err(HotkeyParseError::NoKeySpecified));
+        }
+
+        #[test]
+        fn test_parse_detailed_multiple_keys() {
+            /// WHY: Only one non-modifier key can be part of a hotkey.
+            assert_eq!(
+                parse_hotkey_detailed("ctrl+a+b"),
+                Err(HotkeyParseError::MultipleKeys(vec!["a".to_string(), "b".to_string()]))
+            );
+            assert_eq!(parse_hotkey("ctrl+a+b"), None, "Neither key wins");
+        }
+
+        #[test]
+        fn test_parse_detailed_multiple_keys_names_all() {
+            /// WHY: The message should show every stray key so the user can see what to remove.
+            let result = parse_hotkey_detailed("A+ctrl+F5+b");
+            assert_eq!(
+                result,
+                Err(HotkeyParseError::MultipleKeys(vec!["a".to_string(), "f5".to_string(), "b".to_string()]))
+            );
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                "more than one non-modifier key specified (a, f5, b)"
+            );
+        }
+
+        #[test]
+        fn test_parse_hotkey_wrapper_matches_detailed() {
+            /// WHY: parse_hotkey is kept for backward compatibility and must agree.
+            for hotkey in ["ctrl+b", "alt+f12", "crtl+b", "ctrl+shift"] {
+                assert_eq!(
+                    parse_hotkey(hotkey),
+                    parse_hotkey_detailed(hotkey).ok(),
+                    "{} should match",
+                    hotkey
+                );
+            }
+        }
+
+        #[test]
+        fn test_parsed_hotkeys_deduplicates() {
+            /// WHY: Equivalent spellings of one combo should only be matched once.
+            let config = Config {
+                hotkeys: vec![
+                    "ctrl+b".to_string(),
+                    "B+Control".to_string(),
+                    "ctrl+shift+l".to_string(),
+                ],
+                ..Default::default()
+            };
+
+            let parsed = config.parsed_hotkeys();
+            assert_eq!(
+                parsed,
+                vec![
+                    vec![(MOD_CONTROL.0, 'B' as u32)],
+                    vec![(MOD_CONTROL.0 | MOD_SHIFT.0, 'L' as u32)],
+                ]
+            );
+        }
+
+        #[test]
+        fn test_parsed_hotkeys_skips_invalid() {
+            /// WHY: One typo shouldn't disable the other configured hotkeys.
+            let config = Config {
+                hotkeys: vec!["crtl+b".to_string(), "alt+f12".to_string()],
+                ..Default::default()
+            };
+
+            assert_eq!(config.parsed_hotkeys(), vec![vec![(MOD_ALT.0, VK_F12.0 as u32)]]);
+        }
+
+        #[test]
+        fn test_parse_hotkey_sequence_two_steps() {
+            /// WHY: A leader-style sequence must keep its steps in order.
+            assert_eq!(
+                parse_hotkey_sequence("ctrl+b, l"),
+                Some(vec![(MOD_CONTROL.0, 'B' as u32), (0, 'L' as u32)])
+            );
+        }
+
+        #[test]
+        fn test_parse_hotkey_sequence_single_combo() {
+            /// WHY: Existing single-combo configs are one-step sequences.
+            assert_eq!(
+                parse_hotkey_sequence("ctrl+b"),
+                Some(vec![(MOD_CONTROL.0, 'B' as u32)])
+            );
+        }
+
+        #[test]
+        fn test_parse_hotkey_sequence_rejects_empty_step() {
+            /// WHY: "ctrl+b,,l" or a trailing comma is a typo, not a shorter sequence.
+            for sequence in ["ctrl+b,,l", "ctrl+b,", ",l", ""] {
+                assert_eq!(
+                    parse_hotkey_sequence_detailed(sequence),
+                    Err(HotkeyParseError::EmptyStep),
+                    "{:?} should be rejected",
+                    sequence
+                );
+            }
+        }
+
+        #[test]
+        fn test_parse_hotkey_sequence_reports_bad_step() {
+            /// WHY: The error should point at the token that's wrong, whichever step it's in.
+            assert_eq!(
+                parse_hotkey_sequence_detailed("ctrl+b,notakey"),
+                Err(HotkeyParseError::UnknownKey("notakey".to_string()))
+            );
+        }
+
+        #[test]
+        fn test_parsed_passthrough_keys() {
+            /// WHY: The allowlist uses hotkey key names and must resolve to the VKs the hook sees.
+            let config = Config {
+                passthrough_keys: vec![
+                    "volup".to_string(),
+                    "VolumeDown".to_string(),
+                    " mute ".to_string(),
+                    "mute".to_string(),
+                    "notakey".to_string(),
+                ],
+                ..Default::default()
+            };
+
+            let parsed = config.parsed_passthrough_keys();
+            let expected: HashSet<u32> = [
+                VK_VOLUME_UP.0 as u32,
+                VK_VOLUME_DOWN.0 as u32,
+                VK_VOLUME_MUTE.0 as u32,
+            ]
+            .into_iter()
+            .collect();
+            assert_eq!(parsed, expected, "Duplicates and unknown keys should be dropped");
+        }
+
+        #[test]
+        fn test_passthrough_lookup() {
+            /// WHY: Only allowlisted keys may reach apps while locked; the default blocks everything.
+            let config = Config {
+                passthrough_keys: vec!["mute".to_string()],
+                ..Default::default()
+            };
+            let allowed = config.parsed_passthrough_keys();
+
+            assert!(allowed.contains(&(VK_VOLUME_MUTE.0 as u32)));
+            assert!(!allowed.contains(&('A' as u32)));
+            assert!(Config::default().parsed_passthrough_keys().is_empty());
+        }
+
+        #[test]
+        fn test_set_field_hotkey() {
+            /// WHY: The CLI replaces the hotkey list with a single validated combo.
+            let mut config = Config {
+                hotkeys: vec!["ctrl+b".to_string(), "alt+f12".to_string()],
+                ..Default::default()
+            };
+            config.set_field("hotkey", "ctrl+shift+l").unwrap();
+            assert_eq!(config.hotkeys, vec!["ctrl+shift+l".to_string()]);
+        }
+
+        #[test]
+        fn test_set_field_rejects_unparseable_hotkey() {
+            /// WHY: Saving a broken hotkey would leave the user unable to unlock.
+            let mut config = Config::default();
+            let err = config.set_field("hotkey", "ctrl+notakey").unwrap_err();
+
+            assert!(err.contains("notakey"), "Error should name the bad token: {}", err);
+            assert_eq!(config.hotkeys, vec!["ctrl+b".to_string()], "Config must be untouched");
+        }
+
         #[test]
         fn test_parse_modifier_aliases() {
             /// WHY: Both "ctrl" and "control" should work.
@@ -432,5 +6271,95 @@ mod tests {
 
             assert_eq!(ctrl, control, "ctrl and control should be equivalent");
         }
+
+        #[test]
+        fn test_describe_hotkey_round_trips() {
+            /// WHY: A settings UI shows the parsed hotkey back; saving what it shows must not change it.
+            for combo in [
+                "Ctrl+B",
+                "Ctrl+Shift+F5",
+                "Ctrl+Alt+Shift+Win+Z",
+                "Alt+Space",
+                "Win+PageDown",
+                "Ctrl+Num7",
+                "Shift+NumAdd",
+                "Play",
+                "Ctrl+Alt+F24",
+                "Ctrl+0",
+                "Win+Apps",
+                "Ctrl+Alt+Semicolon",
+                "Ctrl+Backtick",
+                "Shift+LBracket",
+            ] {
+                let (modifiers, vk) = parse_hotkey(combo).unwrap();
+                assert_eq!(describe_hotkey(modifiers, vk), combo);
+            }
+        }
+
+        #[test]
+        fn test_describe_hotkey_canonical_form() {
+            /// WHY: However the user typed it, the display uses one spelling and one modifier order.
+            for (typed, canonical) in [
+                ("shift+ctrl+f5", "Ctrl+Shift+F5"),
+                ("win+control+alt+del", "Ctrl+Alt+Win+Delete"),
+                ("alt+return", "Alt+Enter"),
+                ("ctrl+escape", "Ctrl+Esc"),
+                ("volumeup", "VolUp"),
+            ] {
+                let (modifiers, vk) = parse_hotkey(typed).unwrap();
+                assert_eq!(describe_hotkey(modifiers, vk), canonical, "{}", typed);
+            }
+        }
+
+        #[test]
+        fn test_describe_hotkey_keeps_sides() {
+            /// WHY: A sided sequence step must display as the side the hook will require.
+            let (modifiers, vk) = parse_hotkey_sided("rctrl+shift+b").unwrap().to_step();
+            assert_eq!(describe_hotkey(modifiers, vk), "RCtrl+Shift+B");
+            assert_eq!(parse_hotkey_sided("RCtrl+Shift+B").unwrap().to_step(), (modifiers, vk));
+
+            let (modifiers, vk) = parse_hotkey_sided("altgr+q").unwrap().to_step();
+            assert_eq!(describe_hotkey(modifiers, vk), "LCtrl+RAlt+Q");
+            assert_eq!(parse_hotkey_sided("LCtrl+RAlt+Q").unwrap().to_step(), (modifiers, vk));
+        }
+
+        #[test]
+        fn test_normalize_hotkey_equivalent_inputs() {
+            /// WHY: However a combo is typed, the saved config must hold one spelling so comparisons work.
+            for typed in ["ctrl+shift+b", "B+Ctrl+Shift", "shift + ctrl + b", "CONTROL+SHIFT+B", "shift+b+ctrl"] {
+                assert_eq!(normalize_hotkey(typed).as_deref(), Some("ctrl+shift+b"), "{}", typed);
+            }
+            assert_eq!(normalize_hotkey("Ctrl+B , L").as_deref(), Some("ctrl+b,l"), "Each sequence step");
+            assert_eq!(normalize_hotkey("rctrl+Return").as_deref(), Some("rctrl+enter"));
+            assert_eq!(normalize_hotkey("ctrl+notakey"), None);
+        }
+
+        #[test]
+        fn test_normalize_hotkeys_rewrites_config() {
+            /// WHY: Every hotkey field is normalized, and a typo is kept so the user can still fix it.
+            let mut config = Config {
+                hotkeys: vec!["B+Ctrl".to_string(), "ctrl+notakey".to_string()],
+                peek_hotkey: Some("P + Shift + Ctrl".to_string()),
+                ..Config::default()
+            };
+            config.normalize_hotkeys();
+            assert_eq!(config.hotkeys, vec!["ctrl+b".to_string(), "ctrl+notakey".to_string()]);
+            assert_eq!(config.peek_hotkey.as_deref(), Some("ctrl+shift+p"));
+            assert_eq!(config.opacity_up_hotkey, None);
+        }
+
+        #[test]
+        fn test_save_normalizes_hotkeys() {
+            /// WHY: Normalizing happens on save, so the file on disk is what's consistent.
+            let dir = ScratchDir::new("normalize");
+            let path = dir.join("config.json");
+            let config = Config {
+                hotkeys: vec!["shift + ctrl + F5".to_string()],
+                ..Config::default()
+            };
+            config.save_to(&path).unwrap();
+            assert_eq!(Config::load_from(&path).unwrap().hotkeys, vec!["ctrl+shift+f5".to_string()]);
+            assert_eq!(config.hotkeys, vec!["shift + ctrl + F5".to_string()], "The caller's copy is untouched");
+        }
     }
 }
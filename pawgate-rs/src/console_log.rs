@@ -0,0 +1,164 @@
+//! Colored log output on the console, for `pawgate -v` / `-vv` / `-vvv`
+//!
+//! A minimal `log::Log` that writes to stderr with the level in color.
+//! `-v` shows lock and unlock transitions, hotkey registration and config
+//! loading; `-vv` adds the details (each hotkey press, hook events); `-vvv`
+//! everything. Messages from dependencies are only shown from warnings up,
+//! so their chatter doesn't bury PawGate's own.
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::time::Instant;
+
+/// Log target prefix of PawGate's own messages (the crate name)
+const OWN_TARGET: &str = "pawgate";
+
+/// Most detailed level shown for `verbosity` `-v` flags
+pub fn level_filter(verbosity: u8) -> LevelFilter {
+    match verbosity {
+        0 => LevelFilter::Warn,
+        1 => LevelFilter::Info,
+        2 => LevelFilter::Debug,
+        _ => LevelFilter::Trace,
+    }
+}
+
+/// Whether a message at `level` from `target` is printed under `max`
+pub fn should_log(max: LevelFilter, level: Level, target: &str) -> bool {
+    let own = target == OWN_TARGET || target.starts_with("pawgate::");
+    level <= max && (own || level <= Level::Warn)
+}
+
+/// ANSI color escape for a level
+fn level_color(level: Level) -> &'static str {
+    match level {
+        Level::Error => "\x1b[31m",
+        Level::Warn => "\x1b[33m",
+        Level::Info => "\x1b[32m",
+        Level::Debug => "\x1b[36m",
+        Level::Trace => "\x1b[90m",
+    }
+}
+
+/// One line of output: seconds since start, level, module and message
+pub fn format_line(secs: f64, level: Level, target: &str, message: &str, color: bool) -> String {
+    let module = target.strip_prefix("pawgate::").unwrap_or(target);
+    if color {
+        format!("{:8.3} {}{:<5}\x1b[0m {}: {}", secs, level_color(level), level, module, message)
+    } else {
+        format!("{:8.3} {:<5} {}: {}", secs, level, module, message)
+    }
+}
+
+struct ConsoleLogger {
+    max: LevelFilter,
+    color: bool,
+    started: Instant,
+}
+
+impl Log for ConsoleLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        should_log(self.max, metadata.level(), metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let line = format_line(
+            self.started.elapsed().as_secs_f64(),
+            record.level(),
+            record.target(),
+            &record.args().to_string(),
+            self.color,
+        );
+        eprintln!("{}", line);
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the console logger for `verbosity` `-v` flags
+///
+/// Call once, after attaching to the parent console. Colors are used when
+/// the console understands them and `NO_COLOR` isn't set.
+pub fn init(verbosity: u8) -> Result<(), log::SetLoggerError> {
+    let max = level_filter(verbosity);
+    let color = std::env::var_os("NO_COLOR").is_none() && enable_ansi_colors();
+    log::set_logger(Box::leak(Box::new(ConsoleLogger {
+        max,
+        color,
+        started: Instant::now(),
+    })))?;
+    log::set_max_level(max);
+    Ok(())
+}
+
+/// Turn on escape sequence handling for stderr; false if it's not a console
+fn enable_ansi_colors() -> bool {
+    use windows::Win32::System::Console::{
+        GetConsoleMode, GetStdHandle, SetConsoleMode, CONSOLE_MODE, ENABLE_VIRTUAL_TERMINAL_PROCESSING,
+        STD_ERROR_HANDLE,
+    };
+
+    unsafe {
+        let Ok(handle) = GetStdHandle(STD_ERROR_HANDLE) else {
+            return false;
+        };
+        let mut mode = CONSOLE_MODE::default();
+        GetConsoleMode(handle, &mut mode).is_ok()
+            && SetConsoleMode(handle, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING).is_ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_filter_per_verbosity() {
+        /// WHY: Each extra -v must show one more level of detail.
+        assert_eq!(level_filter(0), LevelFilter::Warn);
+        assert_eq!(level_filter(1), LevelFilter::Info);
+        assert_eq!(level_filter(2), LevelFilter::Debug);
+        assert_eq!(level_filter(3), LevelFilter::Trace);
+        assert_eq!(level_filter(9), LevelFilter::Trace, "Extra flags don't wrap around");
+    }
+
+    #[test]
+    fn test_should_log_by_level() {
+        /// WHY: -v shows lock transitions (info) but not every hotkey press (debug).
+        let max = level_filter(1);
+        assert!(should_log(max, Level::Error, "pawgate::tray"));
+        assert!(should_log(max, Level::Warn, "pawgate::config"));
+        assert!(should_log(max, Level::Info, "pawgate"));
+        assert!(!should_log(max, Level::Debug, "pawgate::keyboard"));
+        assert!(!should_log(max, Level::Trace, "pawgate::keyboard"));
+
+        let max = level_filter(2);
+        assert!(should_log(max, Level::Debug, "pawgate::keyboard"));
+        assert!(!should_log(max, Level::Trace, "pawgate::keyboard"));
+    }
+
+    #[test]
+    fn test_dependencies_only_log_warnings() {
+        /// WHY: Dependencies' debug output would drown out PawGate's at -vv.
+        let max = level_filter(3);
+        assert!(should_log(max, Level::Warn, "notify::windows"));
+        assert!(!should_log(max, Level::Info, "notify::windows"));
+        assert!(!should_log(max, Level::Debug, "pawgatex"), "Only the crate itself counts as own");
+        assert!(should_log(max, Level::Trace, "pawgate::tray"));
+    }
+
+    #[test]
+    fn test_format_line() {
+        /// WHY: Redirected output (or NO_COLOR) must not contain escape codes.
+        assert_eq!(
+            format_line(1.5, Level::Info, "pawgate::tray", "Keyboard locked (Hotkey)", false),
+            "   1.500 INFO  tray: Keyboard locked (Hotkey)"
+        );
+        assert_eq!(
+            format_line(0.0, Level::Warn, "pawgate", "x", true),
+            "   0.000 \x1b[33mWARN \x1b[0m pawgate: x"
+        );
+    }
+}
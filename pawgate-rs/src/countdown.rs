@@ -0,0 +1,110 @@
+//! Grace countdown between pressing the hotkey and the lock engaging
+//!
+//! The keyboard hook only flips `AppState::lock_pending`; the tray loop owns a
+//! [`LockCountdown`] that turns a pending request into a real lock once the
+//! configured delay has elapsed.
+
+use std::time::{Duration, Instant};
+
+/// State machine for a cancellable delayed lock
+pub struct LockCountdown {
+    delay: Duration,
+    started: Option<Instant>,
+}
+
+impl LockCountdown {
+    pub fn new(delay_ms: u32) -> Self {
+        Self {
+            delay: Duration::from_millis(u64::from(delay_ms)),
+            started: None,
+        }
+    }
+
+    /// Begin counting down; does nothing if a countdown is already running
+    pub fn start(&mut self, now: Instant) {
+        if self.started.is_none() {
+            self.started = Some(now);
+        }
+    }
+
+    /// Abandon a running countdown
+    pub fn cancel(&mut self) {
+        self.started = None;
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Time left before the lock engages, or None if no countdown is running
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.started
+            .map(|started| self.delay.saturating_sub(now.saturating_duration_since(started)))
+    }
+
+    /// Advance the countdown; returns true exactly once, when it completes
+    pub fn tick(&mut self, now: Instant) -> bool {
+        if self.remaining(now) == Some(Duration::ZERO) {
+            self.started = None;
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_countdown_locks_after_delay() {
+        /// WHY: The lock must engage only once the full grace period has passed.
+        let start = Instant::now();
+        let mut countdown = LockCountdown::new(3000);
+        countdown.start(start);
+
+        assert!(!countdown.tick(start + Duration::from_millis(2999)));
+        assert_eq!(countdown.remaining(start + Duration::from_millis(1000)), Some(Duration::from_secs(2)));
+        assert!(countdown.tick(start + Duration::from_millis(3000)));
+        assert!(!countdown.is_pending(), "Completed countdown should clear itself");
+        assert!(!countdown.tick(start + Duration::from_millis(4000)), "Should fire only once");
+    }
+
+    #[test]
+    fn test_cancel_prevents_lock() {
+        /// WHY: Pressing the hotkey again during the countdown must abort the lock.
+        let start = Instant::now();
+        let mut countdown = LockCountdown::new(3000);
+        countdown.start(start);
+        countdown.cancel();
+
+        assert!(!countdown.is_pending());
+        assert_eq!(countdown.remaining(start), None);
+        assert!(!countdown.tick(start + Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_restart_after_cancel_uses_new_start_time() {
+        /// WHY: A cancelled countdown must not shorten the next one.
+        let start = Instant::now();
+        let mut countdown = LockCountdown::new(1000);
+        countdown.start(start);
+        countdown.cancel();
+
+        let restart = start + Duration::from_millis(900);
+        countdown.start(restart);
+        assert!(!countdown.tick(start + Duration::from_millis(1000)));
+        assert!(countdown.tick(restart + Duration::from_millis(1000)));
+    }
+
+    #[test]
+    fn test_start_while_pending_keeps_original_deadline() {
+        /// WHY: The tray loop calls start() every frame while pending; it must not reset the timer.
+        let start = Instant::now();
+        let mut countdown = LockCountdown::new(1000);
+        countdown.start(start);
+        countdown.start(start + Duration::from_millis(500));
+
+        assert!(countdown.tick(start + Duration::from_millis(1000)));
+    }
+}
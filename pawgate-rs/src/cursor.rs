@@ -0,0 +1,165 @@
+//! Hiding the mouse pointer while locked
+//!
+//! ShowCursor keeps a display counter rather than a flag: the pointer is
+//! hidden while the counter is negative. Every hiding call is counted so
+//! unlock undoes exactly that many, leaving the counter where it started.
+//! The counter belongs to this thread, so the pointer is hidden while it's
+//! over the overlay.
+
+use windows::Win32::UI::WindowsAndMessaging::ShowCursor;
+
+/// Most `ShowCursor(false)` calls made for one hide, in case something has
+/// pushed the counter far above zero
+const MAX_HIDE_CALLS: u32 = 16;
+
+/// Bookkeeping for the ShowCursor display counter
+#[derive(Debug, Default)]
+pub struct CursorCounter {
+    /// `ShowCursor(false)` calls not yet undone
+    hides: u32,
+}
+
+impl CursorCounter {
+    /// Hide the pointer, calling `show_cursor(false)` until the counter is
+    /// negative; does nothing if already hidden
+    ///
+    /// `show_cursor` wraps ShowCursor and returns the new counter value.
+    pub fn hide(&mut self, mut show_cursor: impl FnMut(bool) -> i32) {
+        if self.hides > 0 {
+            return;
+        }
+        while self.hides < MAX_HIDE_CALLS {
+            self.hides += 1;
+            if show_cursor(false) < 0 {
+                break;
+            }
+        }
+    }
+
+    /// Undo every call made by `hide`
+    pub fn restore(&mut self, mut show_cursor: impl FnMut(bool) -> i32) {
+        for _ in 0..std::mem::take(&mut self.hides) {
+            show_cursor(true);
+        }
+    }
+}
+
+/// Hides the pointer across lock transitions when `hide_cursor` is set
+pub struct CursorHider {
+    enabled: bool,
+    counter: CursorCounter,
+}
+
+impl CursorHider {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            counter: CursorCounter::default(),
+        }
+    }
+
+    /// Update the pointer for a lock state change
+    pub fn set_locked(&mut self, locked: bool) {
+        if locked && self.enabled {
+            self.counter.hide(show_cursor);
+        } else {
+            self.counter.restore(show_cursor);
+        }
+    }
+}
+
+impl Drop for CursorHider {
+    fn drop(&mut self) {
+        // WHY: Never leave the pointer hidden, e.g. when exiting while locked
+        self.counter.restore(show_cursor);
+    }
+}
+
+fn show_cursor(show: bool) -> i32 {
+    unsafe { ShowCursor(show) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Simulates the ShowCursor counter, recording every call
+    struct FakeCursor {
+        count: i32,
+        calls: u32,
+    }
+
+    impl FakeCursor {
+        fn new(count: i32) -> Self {
+            Self { count, calls: 0 }
+        }
+
+        fn show_cursor(&mut self) -> impl FnMut(bool) -> i32 + '_ {
+            move |show| {
+                self.calls += 1;
+                self.count += if show { 1 } else { -1 };
+                self.count
+            }
+        }
+    }
+
+    #[test]
+    fn test_hide_and_restore_balance_counter() {
+        /// WHY: The usual case: one hide call, and unlock brings the counter back to 0.
+        let mut cursor = FakeCursor::new(0);
+        let mut counter = CursorCounter::default();
+
+        counter.hide(cursor.show_cursor());
+        assert_eq!(cursor.count, -1, "Hidden");
+        counter.restore(cursor.show_cursor());
+        assert_eq!(cursor.count, 0, "Visible again, as before");
+    }
+
+    #[test]
+    fn test_hide_pushes_positive_counter_below_zero() {
+        /// WHY: A counter already above zero needs several calls before the pointer disappears.
+        let mut cursor = FakeCursor::new(2);
+        let mut counter = CursorCounter::default();
+
+        counter.hide(cursor.show_cursor());
+        assert_eq!(cursor.count, -1);
+        counter.restore(cursor.show_cursor());
+        assert_eq!(cursor.count, 2, "Restored to where it started");
+    }
+
+    #[test]
+    fn test_repeated_hide_and_restore_are_no_ops() {
+        /// WHY: Extra calls must not drift the counter and leave the pointer hidden for good.
+        let mut cursor = FakeCursor::new(0);
+        let mut counter = CursorCounter::default();
+
+        counter.restore(cursor.show_cursor());
+        assert_eq!(cursor.calls, 0, "Nothing to restore before hiding");
+
+        counter.hide(cursor.show_cursor());
+        counter.hide(cursor.show_cursor());
+        assert_eq!(cursor.count, -1);
+        counter.restore(cursor.show_cursor());
+        counter.restore(cursor.show_cursor());
+        assert_eq!(cursor.count, 0);
+    }
+
+    #[test]
+    fn test_hide_gives_up_after_max_calls() {
+        /// WHY: A broken ShowCursor must not spin the tray loop forever.
+        let mut calls = 0;
+        let mut counter = CursorCounter::default();
+        counter.hide(|_| {
+            calls += 1;
+            0
+        });
+        assert_eq!(calls, MAX_HIDE_CALLS);
+
+        let mut restores = 0;
+        counter.restore(|_| {
+            restores += 1;
+            0
+        });
+        assert_eq!(restores, MAX_HIDE_CALLS);
+    }
+}
@@ -0,0 +1,71 @@
+//! Ignoring hotkey presses that come too quickly after a toggle
+//!
+//! Mashing the hotkey would otherwise flip the lock on every press and
+//! leave it in whichever state the last press happened to pick.
+
+use std::time::{Duration, Instant};
+
+/// Lets a toggle through only if the previous one was at least `window` ago
+pub struct ToggleDebounce {
+    window: Duration,
+    /// When the last accepted toggle happened
+    last: Option<Instant>,
+}
+
+impl ToggleDebounce {
+    /// A zero `window` accepts every toggle
+    pub fn new(window: Duration) -> Self {
+        Self { window, last: None }
+    }
+
+    /// Whether a toggle at `now` should go ahead; accepted toggles restart the window
+    pub fn accept(&mut self, now: Instant) -> bool {
+        if self
+            .last
+            .is_some_and(|last| now.saturating_duration_since(last) < self.window)
+        {
+            return false;
+        }
+        self.last = Some(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Which of the presses at these millisecond offsets are accepted
+    fn accepted(window_ms: u64, presses: &[u64]) -> Vec<bool> {
+        let mut debounce = ToggleDebounce::new(Duration::from_millis(window_ms));
+        let base = Instant::now();
+        presses
+            .iter()
+            .map(|&ms| debounce.accept(base + Duration::from_millis(ms)))
+            .collect()
+    }
+
+    #[test]
+    fn test_first_press_always_accepted() {
+        /// WHY: The very first hotkey press after startup must lock.
+        assert_eq!(accepted(250, &[0]), [true]);
+    }
+
+    #[test]
+    fn test_mashing_is_ignored() {
+        /// WHY: Presses inside the window after a toggle are the bounce, not a new intent.
+        assert_eq!(accepted(250, &[0, 50, 120, 249]), [true, false, false, false]);
+    }
+
+    #[test]
+    fn test_window_counts_from_accepted_toggle() {
+        /// WHY: Ignored presses don't extend the window, so holding a steady rhythm still toggles.
+        assert_eq!(accepted(250, &[0, 100, 200, 250, 300, 600]), [true, false, false, true, false, true]);
+    }
+
+    #[test]
+    fn test_zero_window_accepts_everything() {
+        /// WHY: `toggle_debounce_ms: 0` turns the debounce off.
+        assert_eq!(accepted(0, &[0, 0, 1, 2]), [true, true, true, true]);
+    }
+}
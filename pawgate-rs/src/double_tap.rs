@@ -0,0 +1,197 @@
+//! Detection of a modifier tapped twice in quick succession, e.g. Ctrl Ctrl
+//!
+//! A tap is a press and release of the modifier with no other key in
+//! between, released within the window. Two taps make a double tap when the
+//! second press follows the first release within the window. The detector
+//! only watches; the taps themselves still reach applications.
+
+use std::time::{Duration, Instant};
+
+/// Watches key events for a double tap of one modifier
+pub struct DoubleTapDetector {
+    /// Virtual keys that count as the modifier (e.g. left and right Ctrl)
+    keys: Vec<u32>,
+    window: Duration,
+    /// Whether the modifier is down, to ignore auto-repeat
+    held: bool,
+    /// When the current press started, while no other key has interrupted it
+    pressed_at: Option<Instant>,
+    /// When the previous tap was released, if it could start a double tap
+    last_tap: Option<Instant>,
+}
+
+impl DoubleTapDetector {
+    pub fn new(keys: Vec<u32>, window: Duration) -> Self {
+        Self {
+            keys,
+            window,
+            held: false,
+            pressed_at: None,
+            last_tap: None,
+        }
+    }
+
+    /// Feed a key press
+    pub fn key_down(&mut self, vk: u32, now: Instant) {
+        if !self.keys.contains(&vk) {
+            // WHY: Another key means the modifier is part of a combo like ctrl+c
+            self.pressed_at = None;
+            self.last_tap = None;
+            return;
+        }
+        if self.held {
+            return;
+        }
+        self.held = true;
+
+        // A second press that comes too late starts over
+        if self
+            .last_tap
+            .is_some_and(|last| now.saturating_duration_since(last) > self.window)
+        {
+            self.last_tap = None;
+        }
+        self.pressed_at = Some(now);
+    }
+
+    /// Feed a key release; returns true when it completes a double tap
+    pub fn key_up(&mut self, vk: u32, now: Instant) -> bool {
+        if !self.keys.contains(&vk) {
+            return false;
+        }
+        self.held = false;
+
+        let Some(pressed_at) = self.pressed_at.take() else {
+            return false;
+        };
+        // Holding the modifier isn't a tap
+        if now.saturating_duration_since(pressed_at) > self.window {
+            self.last_tap = None;
+            return false;
+        }
+
+        if self.last_tap.take().is_some() {
+            return true;
+        }
+        self.last_tap = Some(now);
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LCTRL: u32 = 0xA2;
+    const RCTRL: u32 = 0xA3;
+    const LSHIFT: u32 = 0xA0;
+    const C: u32 = 0x43;
+
+    fn detector() -> DoubleTapDetector {
+        DoubleTapDetector::new(vec![LCTRL, RCTRL], Duration::from_millis(400))
+    }
+
+    /// Tap `vk` at `start` ms for `hold` ms, returning whether it completed a double tap
+    fn tap(detector: &mut DoubleTapDetector, vk: u32, base: Instant, start: u64, hold: u64) -> bool {
+        detector.key_down(vk, base + Duration::from_millis(start));
+        detector.key_up(vk, base + Duration::from_millis(start + hold))
+    }
+
+    #[test]
+    fn test_two_quick_taps_fire() {
+        /// WHY: The basic gesture: Ctrl, Ctrl within the window locks.
+        let mut detector = detector();
+        let base = Instant::now();
+
+        assert!(!tap(&mut detector, LCTRL, base, 0, 50), "One tap is not enough");
+        assert!(tap(&mut detector, LCTRL, base, 200, 50));
+    }
+
+    #[test]
+    fn test_single_tap_does_not_fire() {
+        /// WHY: A lone Ctrl press is everyday typing and must never lock.
+        let mut detector = detector();
+        let base = Instant::now();
+
+        assert!(!tap(&mut detector, LCTRL, base, 0, 50));
+        assert!(!tap(&mut detector, C, base, 1000, 50));
+    }
+
+    #[test]
+    fn test_slow_second_tap_starts_over() {
+        /// WHY: Taps further apart than the window are two separate presses.
+        let mut detector = detector();
+        let base = Instant::now();
+
+        assert!(!tap(&mut detector, LCTRL, base, 0, 50));
+        assert!(!tap(&mut detector, LCTRL, base, 500, 50), "450ms gap exceeds 400ms");
+        assert!(tap(&mut detector, LCTRL, base, 700, 50), "The late tap counts as a new first tap");
+    }
+
+    #[test]
+    fn test_combo_in_between_cancels() {
+        /// WHY: Ctrl, then Ctrl+C is copying, not a double tap.
+        let mut detector = detector();
+        let base = Instant::now();
+
+        assert!(!tap(&mut detector, LCTRL, base, 0, 50));
+        detector.key_down(LCTRL, base + Duration::from_millis(100));
+        detector.key_down(C, base + Duration::from_millis(120));
+        detector.key_up(C, base + Duration::from_millis(140));
+        assert!(!detector.key_up(LCTRL, base + Duration::from_millis(160)));
+    }
+
+    #[test]
+    fn test_other_modifier_cancels() {
+        /// WHY: Ctrl, Shift, Ctrl is a different gesture.
+        let mut detector = detector();
+        let base = Instant::now();
+
+        assert!(!tap(&mut detector, LCTRL, base, 0, 50));
+        assert!(!tap(&mut detector, LSHIFT, base, 80, 20));
+        assert!(!tap(&mut detector, LCTRL, base, 120, 50));
+    }
+
+    #[test]
+    fn test_hold_is_not_a_tap() {
+        /// WHY: Holding Ctrl while thinking, then tapping it, shouldn't lock.
+        let mut detector = detector();
+        let base = Instant::now();
+
+        assert!(!tap(&mut detector, LCTRL, base, 0, 600));
+        assert!(!tap(&mut detector, LCTRL, base, 700, 50));
+    }
+
+    #[test]
+    fn test_auto_repeat_ignored() {
+        /// WHY: A held key sends repeated key-downs, which mustn't count as extra presses.
+        let mut detector = detector();
+        let base = Instant::now();
+
+        detector.key_down(LCTRL, base);
+        detector.key_down(LCTRL, base + Duration::from_millis(30));
+        assert!(!detector.key_up(LCTRL, base + Duration::from_millis(60)));
+        assert!(tap(&mut detector, LCTRL, base, 150, 50));
+    }
+
+    #[test]
+    fn test_either_side_counts() {
+        /// WHY: "ctrl" means either Ctrl key, as it does in hotkeys.
+        let mut detector = detector();
+        let base = Instant::now();
+
+        assert!(!tap(&mut detector, LCTRL, base, 0, 50));
+        assert!(tap(&mut detector, RCTRL, base, 150, 50));
+    }
+
+    #[test]
+    fn test_third_tap_starts_over() {
+        /// WHY: A triple tap fires once, not twice.
+        let mut detector = detector();
+        let base = Instant::now();
+
+        assert!(!tap(&mut detector, LCTRL, base, 0, 50));
+        assert!(tap(&mut detector, LCTRL, base, 100, 50));
+        assert!(!tap(&mut detector, LCTRL, base, 200, 50));
+    }
+}
@@ -0,0 +1,255 @@
+//! Structured log of lock and unlock events
+//!
+//! When `log_events` is enabled, every lock state change is appended as one
+//! JSON object per line to ~/.pawgate/events.log, for piecing together what
+//! happened during a pet incident.
+
+use crate::config::Config;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Size at which events.log is rotated to events.log.1
+pub const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// What happened to the lock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    Lock,
+    Unlock,
+}
+
+/// What caused a lock state change
+///
+/// Stored in `AppState::lock_trigger` as its `u8` discriminant so it can be
+/// shared between threads alongside the `locked` flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum Trigger {
+    Hotkey = 0,
+    Passphrase = 1,
+    AutoIdle = 2,
+    Menu = 3,
+    Cli = 4,
+    /// A test lock or `auto_unlock_secs` ran out
+    AutoUnlock = 5,
+    /// The watchdog released a hung instance
+    Failsafe = 6,
+    /// Locked or unlocked by a program through the library API
+    Api = 7,
+    /// A `forcelock` app rule matched the foreground app
+    AppRule = 8,
+    /// The `unlock_gesture` was drawn with the mouse
+    Gesture = 9,
+    /// Locked again at startup because PawGate was restarted while locked
+    Restored = 10,
+    /// Unlocked because PawGate quit while locked
+    Quit = 11,
+    /// Many keys went down at once (`auto_lock_on_mash`)
+    Mash = 12,
+    /// The `panic_unlock_hotkey` was pressed
+    Panic = 13,
+}
+
+impl Trigger {
+    /// Inverse of `trigger as u8`; unknown values map to `Hotkey`
+    pub fn from_u8(value: u8) -> Self {
+        match value {
+            1 => Self::Passphrase,
+            2 => Self::AutoIdle,
+            3 => Self::Menu,
+            4 => Self::Cli,
+            5 => Self::AutoUnlock,
+            6 => Self::Failsafe,
+            7 => Self::Api,
+            8 => Self::AppRule,
+            9 => Self::Gesture,
+            10 => Self::Restored,
+            11 => Self::Quit,
+            12 => Self::Mash,
+            13 => Self::Panic,
+            _ => Self::Hotkey,
+        }
+    }
+}
+
+/// One line of the event log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EventRecord {
+    /// UTC time in RFC 3339 format, e.g. "2024-05-01T12:30:00Z"
+    pub timestamp: String,
+    pub action: Action,
+    pub trigger: Trigger,
+}
+
+impl EventRecord {
+    /// Create a record stamped with the current time
+    pub fn now(action: Action, trigger: Trigger) -> Self {
+        Self {
+            timestamp: format_timestamp(unix_now()),
+            action,
+            trigger,
+        }
+    }
+
+    /// Serialize as a single JSON line (without the trailing newline)
+    pub fn to_json_line(&self) -> String {
+        serde_json::to_string(self).expect("EventRecord is always serializable")
+    }
+}
+
+/// Path of the event log (~/.pawgate/events.log)
+pub fn events_path() -> PathBuf {
+    Config::config_dir().join("events.log")
+}
+
+/// Append `record` to the event log, rotating it once it exceeds `max_bytes`
+///
+/// WHY: Only one old file (events.log.1) is kept, so the log never takes
+/// more than about twice `max_bytes` on disk.
+pub fn append(path: &Path, record: &EventRecord, max_bytes: u64) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    if fs::metadata(path).is_ok_and(|meta| meta.len() >= max_bytes) {
+        let mut rotated = path.as_os_str().to_owned();
+        rotated.push(".1");
+        fs::rename(path, rotated)?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", record.to_json_line())
+}
+
+/// The current time in Unix seconds (0 if the clock is before 1970)
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Format Unix seconds as an RFC 3339 UTC timestamp
+///
+/// WHY: Avoids pulling in a date/time crate for a single format.
+pub fn format_timestamp(unix_secs: u64) -> String {
+    let days = (unix_secs / 86_400) as i64;
+    let secs_of_day = unix_secs % 86_400;
+
+    // Civil-from-days (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    #[test]
+    fn test_record_serializes_as_json_line() {
+        /// WHY: The field names and values are the format people grep and parse.
+        let record = EventRecord {
+            timestamp: "2024-05-01T12:30:00Z".to_string(),
+            action: Action::Lock,
+            trigger: Trigger::AutoIdle,
+        };
+
+        assert_eq!(
+            record.to_json_line(),
+            r#"{"timestamp":"2024-05-01T12:30:00Z","action":"lock","trigger":"auto_idle"}"#
+        );
+    }
+
+    #[test]
+    fn test_action_and_trigger_names() {
+        /// WHY: Each variant must have a stable, readable name in the log.
+        assert_eq!(serde_json::to_string(&Action::Unlock).unwrap(), r#""unlock""#);
+        assert_eq!(serde_json::to_string(&Trigger::Hotkey).unwrap(), r#""hotkey""#);
+        assert_eq!(serde_json::to_string(&Trigger::Passphrase).unwrap(), r#""passphrase""#);
+        assert_eq!(serde_json::to_string(&Trigger::Menu).unwrap(), r#""menu""#);
+    }
+
+    #[test]
+    fn test_trigger_u8_roundtrip() {
+        /// WHY: Triggers travel between threads as u8 in AppState.
+        for trigger in [
+            Trigger::Hotkey,
+            Trigger::Passphrase,
+            Trigger::AutoIdle,
+            Trigger::Menu,
+            Trigger::Cli,
+            Trigger::AutoUnlock,
+            Trigger::Failsafe,
+            Trigger::Api,
+            Trigger::AppRule,
+            Trigger::Gesture,
+            Trigger::Restored,
+            Trigger::Quit,
+            Trigger::Mash,
+            Trigger::Panic,
+        ] {
+            assert_eq!(Trigger::from_u8(trigger as u8), trigger);
+        }
+    }
+
+    #[test]
+    fn test_format_timestamp() {
+        /// WHY: Timestamps must be correct UTC dates, including leap days.
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1_714_566_600), "2024-05-01T12:30:00Z");
+    }
+
+    #[test]
+    fn test_append_writes_one_line_per_event() {
+        /// WHY: JSON-lines readers expect exactly one record per line.
+        let dir = ScratchDir::new("events-append");
+        let path = dir.join("events.log");
+        let record = EventRecord::now(Action::Lock, Trigger::Hotkey);
+
+        append(&path, &record, MAX_LOG_BYTES).unwrap();
+        append(&path, &record, MAX_LOG_BYTES).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 2);
+        assert!(contents.lines().all(|line| line == record.to_json_line()));
+    }
+
+    #[test]
+    fn test_append_rotates_at_cap() {
+        /// WHY: The log must not grow without bound.
+        let dir = ScratchDir::new("events-rotate");
+        let path = dir.join("events.log");
+        let record = EventRecord::now(Action::Unlock, Trigger::Menu);
+        let line_len = record.to_json_line().len() as u64 + 1;
+
+        append(&path, &record, line_len).unwrap();
+        append(&path, &record, line_len).unwrap();
+
+        assert_eq!(fs::metadata(&path).unwrap().len(), line_len);
+        assert_eq!(fs::metadata(dir.join("events.log.1")).unwrap().len(), line_len);
+    }
+}
@@ -0,0 +1,279 @@
+//! Fail-safes that release the keyboard if PawGate crashes or hangs
+//!
+//! Every installed hook is wrapped in a [`HookGuard`] that unhooks on drop
+//! and is recorded in a process-wide list. A panic hook and a watchdog thread
+//! use that list to remove the hooks from any thread: the watchdog fires when
+//! the tray loop stops beating its [`Heartbeat`] while the keyboard is locked.
+
+use crate::events::Trigger;
+use crate::lock_state;
+use crate::target_window;
+use crate::AppState;
+use log::{error, info};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use windows::Win32::UI::WindowsAndMessaging::{UnhookWindowsHookEx, HHOOK};
+
+/// How long the tray loop may go without a heartbeat before the hooks are removed
+pub const WATCHDOG_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often the watchdog checks the heartbeat
+const WATCHDOG_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Heartbeat value meaning "not being watched right now"
+const SUSPENDED: u64 = u64::MAX;
+
+/// Raw handles of the hooks currently installed
+static INSTALLED_HOOKS: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+/// Something that can be unhooked; implemented by [`SystemHook`] and by mocks in tests
+pub trait Unhook {
+    fn unhook(&mut self);
+}
+
+/// Unhooks `H` when dropped, including while unwinding from a panic
+pub struct HookGuard<H: Unhook> {
+    hook: Option<H>,
+}
+
+impl<H: Unhook> HookGuard<H> {
+    pub fn new(hook: H) -> Self {
+        Self { hook: Some(hook) }
+    }
+
+    /// Unhook now instead of at the end of the scope
+    pub fn release(&mut self) {
+        if let Some(mut hook) = self.hook.take() {
+            hook.unhook();
+        }
+    }
+}
+
+impl<H: Unhook> Drop for HookGuard<H> {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// A hook installed with SetWindowsHookExW, registered for [`release_all_hooks`]
+pub struct SystemHook {
+    raw: usize,
+}
+
+impl SystemHook {
+    pub fn new(hook: HHOOK) -> Self {
+        let raw = hook.0 as usize;
+        if let Ok(mut hooks) = INSTALLED_HOOKS.lock() {
+            hooks.push(raw);
+        }
+        Self { raw }
+    }
+}
+
+impl Unhook for SystemHook {
+    fn unhook(&mut self) {
+        // WHY: Skip hooks the fail-safe already removed; unhooking twice is harmless
+        // but a stale handle could in theory have been reused.
+        let registered = INSTALLED_HOOKS.lock().map_or(true, |mut hooks| {
+            let before = hooks.len();
+            hooks.retain(|&raw| raw != self.raw);
+            hooks.len() != before
+        });
+        if registered {
+            unhook_raw(self.raw);
+        }
+    }
+}
+
+/// Remove every installed hook, from any thread
+///
+/// Returns how many hooks were removed.
+pub fn release_all_hooks() -> usize {
+    // WHY: try_lock, since this also runs from the panic hook, which may fire
+    // while this thread already holds the lock.
+    let hooks = match INSTALLED_HOOKS.try_lock() {
+        Ok(mut hooks) => std::mem::take(&mut *hooks),
+        Err(_) => return 0,
+    };
+    for &raw in &hooks {
+        unhook_raw(raw);
+    }
+    hooks.len()
+}
+
+fn unhook_raw(raw: usize) {
+    unsafe {
+        let _ = UnhookWindowsHookEx(HHOOK(raw as *mut std::ffi::c_void));
+    }
+}
+
+/// Release the keyboard before the default panic handler runs
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let released = release_all_hooks();
+        if released > 0 {
+            error!("Panic: removed {} input hook(s) so the keyboard isn't stuck", released);
+        }
+        if target_window::release_disabled_window() {
+            error!("Panic: enabled the locked window again");
+        }
+        // Don't come back locked from a crash
+        lock_state::forget(&lock_state::lock_state_path());
+        default_hook(info);
+    }));
+}
+
+/// Liveness signal from the tray loop, checked by the watchdog
+pub struct Heartbeat {
+    epoch: Instant,
+    /// Milliseconds after `epoch` of the last beat, or `SUSPENDED`
+    last_ms: AtomicU64,
+}
+
+impl Default for Heartbeat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Heartbeat {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            last_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Record that the loop is alive
+    pub fn beat(&self) {
+        self.beat_at(Instant::now());
+    }
+
+    fn beat_at(&self, now: Instant) {
+        let ms = now.saturating_duration_since(self.epoch).as_millis() as u64;
+        self.last_ms.store(ms, Ordering::SeqCst);
+    }
+
+    /// Stop watching until the next beat
+    ///
+    /// WHY: Modal dialogs (Settings) block the tray loop legitimately.
+    pub fn suspend(&self) {
+        self.last_ms.store(SUSPENDED, Ordering::SeqCst);
+    }
+
+    /// Whether more than `timeout` has passed since the last beat
+    pub fn is_stalled(&self, now: Instant, timeout: Duration) -> bool {
+        let last = self.last_ms.load(Ordering::SeqCst);
+        if last == SUSPENDED {
+            return false;
+        }
+        let now_ms = now.saturating_duration_since(self.epoch).as_millis() as u64;
+        now_ms.saturating_sub(last) > timeout.as_millis() as u64
+    }
+}
+
+/// Watch the tray loop's heartbeat and release the keyboard if it hangs
+///
+/// Runs until `should_quit` is set. Fires at most once: after that the hooks
+/// are gone and PawGate needs a restart to lock again.
+pub fn run_watchdog(state: Arc<AppState>) {
+    info!("Watchdog started");
+    while !state.should_quit.load(Ordering::SeqCst) {
+        std::thread::sleep(WATCHDOG_POLL_INTERVAL);
+
+        let locked = state.locked.load(Ordering::SeqCst);
+        if locked && state.heartbeat.is_stalled(Instant::now(), WATCHDOG_TIMEOUT) {
+            let released = release_all_hooks();
+            target_window::release_disabled_window();
+            state.set_locked(false, Trigger::Failsafe);
+            // The hung tray loop can't record the unlock, so don't restore
+            // this lock at the next start either
+            lock_state::forget(&lock_state::lock_state_path());
+            error!(
+                "Tray loop unresponsive for over {:?}; removed {} input hook(s) and unlocked",
+                WATCHDOG_TIMEOUT, released
+            );
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    struct MockHook {
+        unhooked: Rc<Cell<u32>>,
+    }
+
+    impl Unhook for MockHook {
+        fn unhook(&mut self) {
+            self.unhooked.set(self.unhooked.get() + 1);
+        }
+    }
+
+    #[test]
+    fn test_guard_unhooks_on_drop() {
+        /// WHY: Leaving the hook thread by any path, panics included, must release the keyboard.
+        let unhooked = Rc::new(Cell::new(0));
+        {
+            let _guard = HookGuard::new(MockHook { unhooked: Rc::clone(&unhooked) });
+            assert_eq!(unhooked.get(), 0);
+        }
+        assert_eq!(unhooked.get(), 1);
+    }
+
+    #[test]
+    fn test_guard_unhooks_while_unwinding() {
+        /// WHY: A panic in the hook thread must not leave the hook installed.
+        let unhooked = Rc::new(Cell::new(0));
+        let hook = MockHook { unhooked: Rc::clone(&unhooked) };
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(move || {
+            let _guard = HookGuard::new(hook);
+            panic!("simulated crash");
+        }));
+        assert!(result.is_err());
+        assert_eq!(unhooked.get(), 1);
+    }
+
+    #[test]
+    fn test_guard_release_only_unhooks_once() {
+        /// WHY: An explicit release followed by drop must not unhook twice.
+        let unhooked = Rc::new(Cell::new(0));
+        let mut guard = HookGuard::new(MockHook { unhooked: Rc::clone(&unhooked) });
+        guard.release();
+        drop(guard);
+        assert_eq!(unhooked.get(), 1);
+    }
+
+    #[test]
+    fn test_heartbeat_stall_detection() {
+        /// WHY: The watchdog must fire only after the full timeout without a beat.
+        let heartbeat = Heartbeat::new();
+        let start = heartbeat.epoch;
+        heartbeat.beat_at(start + Duration::from_secs(1));
+
+        assert!(!heartbeat.is_stalled(start + Duration::from_secs(3), WATCHDOG_TIMEOUT));
+        assert!(!heartbeat.is_stalled(start + Duration::from_secs(6), WATCHDOG_TIMEOUT));
+        assert!(heartbeat.is_stalled(start + Duration::from_millis(6001), WATCHDOG_TIMEOUT));
+
+        heartbeat.beat_at(start + Duration::from_secs(10));
+        assert!(!heartbeat.is_stalled(start + Duration::from_secs(12), WATCHDOG_TIMEOUT), "A beat resets it");
+    }
+
+    #[test]
+    fn test_suspended_heartbeat_never_stalls() {
+        /// WHY: Sitting in the Settings dialog must not be mistaken for a hang.
+        let heartbeat = Heartbeat::new();
+        let start = heartbeat.epoch;
+        heartbeat.suspend();
+        assert!(!heartbeat.is_stalled(start + Duration::from_secs(600), WATCHDOG_TIMEOUT));
+
+        heartbeat.beat_at(start + Duration::from_secs(600));
+        assert!(heartbeat.is_stalled(start + Duration::from_secs(610), WATCHDOG_TIMEOUT));
+    }
+}
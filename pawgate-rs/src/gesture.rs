@@ -0,0 +1,274 @@
+//! Mouse gestures that unlock, e.g. drawing a circle
+//!
+//! The mouse hook feeds pointer positions while locked into a
+//! [`GestureRecognizer`]. A stroke is the movement since the last pause;
+//! each stroke is checked against the configured [`Gesture`] as it grows,
+//! so the lock releases as soon as the shape is complete.
+
+use std::time::{Duration, Instant};
+
+/// Built-in unlock gestures
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gesture {
+    /// One full loop in either direction
+    Circle,
+    /// Back and forth sideways at least four times, like a Z with an extra stroke
+    Zigzag,
+}
+
+impl Gesture {
+    /// Names accepted in the config
+    pub const NAMES: &'static [&'static str] = &["circle", "zigzag"];
+
+    /// Parse a gesture name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "circle" => Some(Self::Circle),
+            "zigzag" => Some(Self::Zigzag),
+            _ => None,
+        }
+    }
+}
+
+/// Minimum distance between recorded points, in pixels
+///
+/// WHY: Keeps strokes short however fast the mouse reports, so checking
+/// one on every move stays cheap inside the hook.
+const MIN_STEP: f64 = 10.0;
+/// Minimum width and height of a circle, and length of a zigzag leg
+const MIN_SIZE: f64 = 120.0;
+/// A pause this long ends the stroke; the next move starts a new one
+const STROKE_PAUSE: Duration = Duration::from_millis(400);
+/// Oldest points are dropped beyond this
+const MAX_POINTS: usize = 256;
+/// Turning needed for a circle; a little short of 360° since hands overshoot or stop early
+const CIRCLE_TURN: f64 = 330.0;
+/// Sideways strokes needed for a zigzag
+const ZIGZAG_LEGS: usize = 4;
+
+/// Watches pointer movement for one gesture
+pub struct GestureRecognizer {
+    gesture: Gesture,
+    points: Vec<(f64, f64)>,
+    last_move: Option<Instant>,
+}
+
+impl GestureRecognizer {
+    pub fn new(gesture: Gesture) -> Self {
+        Self {
+            gesture,
+            points: Vec::new(),
+            last_move: None,
+        }
+    }
+
+    /// Forget the current stroke
+    pub fn reset(&mut self) {
+        self.points.clear();
+        self.last_move = None;
+    }
+
+    /// Feed a pointer position; returns true when it completes the gesture
+    pub fn feed(&mut self, x: f64, y: f64, now: Instant) -> bool {
+        if self
+            .last_move
+            .is_some_and(|last| now.saturating_duration_since(last) > STROKE_PAUSE)
+        {
+            self.points.clear();
+        }
+        self.last_move = Some(now);
+
+        if let Some(&(last_x, last_y)) = self.points.last() {
+            if (x - last_x).hypot(y - last_y) < MIN_STEP {
+                return false;
+            }
+        }
+        if self.points.len() == MAX_POINTS {
+            self.points.remove(0);
+        }
+        self.points.push((x, y));
+
+        let matched = match self.gesture {
+            Gesture::Circle => is_circle(&self.points),
+            Gesture::Zigzag => is_zigzag(&self.points),
+        };
+        if matched {
+            self.reset();
+        }
+        matched
+    }
+}
+
+/// Whether the stroke turns through a full loop and is roughly round
+fn is_circle(points: &[(f64, f64)]) -> bool {
+    if points.len() < 8 {
+        return false;
+    }
+    let (width, height) = extent(points);
+    if width < MIN_SIZE || height < MIN_SIZE || width > height * 2.0 || height > width * 2.0 {
+        return false;
+    }
+
+    // WHY: Signed, so wobbles cancel out and only a real loop adds up
+    let turned: f64 = points
+        .windows(3)
+        .map(|w| {
+            let (ax, ay) = (w[1].0 - w[0].0, w[1].1 - w[0].1);
+            let (bx, by) = (w[2].0 - w[1].0, w[2].1 - w[1].1);
+            (ax * by - ay * bx).atan2(ax * bx + ay * by)
+        })
+        .sum();
+    turned.abs().to_degrees() >= CIRCLE_TURN
+}
+
+/// Whether the stroke goes sideways and back at least `ZIGZAG_LEGS` times
+fn is_zigzag(points: &[(f64, f64)]) -> bool {
+    let Some(&(start, _)) = points.first() else {
+        return false;
+    };
+    // Direction of the current leg (0 until the first leg is long enough)
+    // and the furthest x it has reached
+    let mut direction = 0.0;
+    let mut extreme = start;
+    let mut legs = 0;
+
+    for &(x, _) in points {
+        if direction == 0.0 {
+            if (x - start).abs() >= MIN_SIZE {
+                direction = (x - start).signum();
+                extreme = x;
+                legs = 1;
+            }
+        } else if (x - extreme) * direction > 0.0 {
+            extreme = x;
+        } else if (extreme - x) * direction >= MIN_SIZE {
+            direction = -direction;
+            extreme = x;
+            legs += 1;
+        }
+    }
+    legs >= ZIGZAG_LEGS
+}
+
+/// Width and height of the stroke's bounding box
+fn extent(points: &[(f64, f64)]) -> (f64, f64) {
+    let (mut min_x, mut min_y) = (f64::MAX, f64::MAX);
+    let (mut max_x, mut max_y) = (f64::MIN, f64::MIN);
+    for &(x, y) in points {
+        min_x = min_x.min(x);
+        max_x = max_x.max(x);
+        min_y = min_y.min(y);
+        max_y = max_y.max(y);
+    }
+    (max_x - min_x, max_y - min_y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Feed `points` 10ms apart from `base`, returning whether any completed the gesture
+    fn draw(recognizer: &mut GestureRecognizer, base: Instant, points: &[(f64, f64)]) -> bool {
+        let mut matched = false;
+        for (i, &(x, y)) in points.iter().enumerate() {
+            matched |= recognizer.feed(x, y, base + Duration::from_millis(10 * i as u64));
+        }
+        matched
+    }
+
+    /// A loop of `radius` around (500, 500), `turns` times round
+    fn circle(radius: f64, turns: f64) -> Vec<(f64, f64)> {
+        let steps = (72.0 * turns) as usize;
+        (0..=steps)
+            .map(|i| {
+                let angle = i as f64 * 5f64.to_radians();
+                (500.0 + radius * angle.cos(), 500.0 + radius * angle.sin())
+            })
+            .collect()
+    }
+
+    /// `legs` sideways strokes of `width`, drifting down like a Z
+    fn zigzag(width: f64, legs: usize) -> Vec<(f64, f64)> {
+        let mut points = Vec::new();
+        for leg in 0..legs {
+            for step in 0..=20 {
+                let t = step as f64 / 20.0;
+                let x = if leg % 2 == 0 { t * width } else { (1.0 - t) * width };
+                points.push((100.0 + x, 100.0 + (leg as f64 + t) * 40.0));
+            }
+        }
+        points
+    }
+
+    #[test]
+    fn test_gesture_names() {
+        /// WHY: Config names are matched case-insensitively, like other config enums.
+        assert_eq!(Gesture::from_name("Circle"), Some(Gesture::Circle));
+        assert_eq!(Gesture::from_name("zigzag"), Some(Gesture::Zigzag));
+        assert_eq!(Gesture::from_name("triangle"), None);
+    }
+
+    #[test]
+    fn test_circle_matches_either_direction() {
+        /// WHY: Clockwise and anticlockwise loops are both natural to draw.
+        let base = Instant::now();
+        assert!(draw(&mut GestureRecognizer::new(Gesture::Circle), base, &circle(150.0, 1.0)));
+
+        let mut reversed = circle(150.0, 1.0);
+        reversed.reverse();
+        assert!(draw(&mut GestureRecognizer::new(Gesture::Circle), base, &reversed));
+    }
+
+    #[test]
+    fn test_partial_or_tiny_circle_does_not_match() {
+        /// WHY: An arc or a small jiggle, like a paw nudging the mouse, isn't a deliberate loop.
+        let base = Instant::now();
+        assert!(!draw(&mut GestureRecognizer::new(Gesture::Circle), base, &circle(150.0, 0.5)));
+        assert!(!draw(&mut GestureRecognizer::new(Gesture::Circle), base, &circle(30.0, 2.0)));
+    }
+
+    #[test]
+    fn test_zigzag_matches() {
+        /// WHY: Four wide sideways strokes unlock.
+        let base = Instant::now();
+        let mut recognizer = GestureRecognizer::new(Gesture::Zigzag);
+        assert!(!draw(&mut recognizer, base, &zigzag(200.0, 3)), "Three strokes are not enough");
+        recognizer.reset();
+        assert!(draw(&mut recognizer, base, &zigzag(200.0, 4)));
+    }
+
+    #[test]
+    fn test_narrow_zigzag_does_not_match() {
+        /// WHY: Small back-and-forth twitches are what a cat batting the mouse produces.
+        let base = Instant::now();
+        assert!(!draw(&mut GestureRecognizer::new(Gesture::Zigzag), base, &zigzag(60.0, 10)));
+    }
+
+    #[test]
+    fn test_straight_line_matches_nothing() {
+        /// WHY: Sweeping the mouse across the screen must not unlock.
+        let base = Instant::now();
+        let line: Vec<(f64, f64)> = (0..100).map(|i| (i as f64 * 15.0, i as f64 * 5.0)).collect();
+        assert!(!draw(&mut GestureRecognizer::new(Gesture::Circle), base, &line));
+        assert!(!draw(&mut GestureRecognizer::new(Gesture::Zigzag), base, &line));
+    }
+
+    #[test]
+    fn test_other_gesture_does_not_match() {
+        /// WHY: Only the configured gesture unlocks.
+        let base = Instant::now();
+        assert!(!draw(&mut GestureRecognizer::new(Gesture::Zigzag), base, &circle(150.0, 1.0)));
+        assert!(!draw(&mut GestureRecognizer::new(Gesture::Circle), base, &zigzag(200.0, 4)));
+    }
+
+    #[test]
+    fn test_pause_splits_strokes() {
+        /// WHY: Half a gesture, a pause, then the other half is two unrelated movements.
+        let mut recognizer = GestureRecognizer::new(Gesture::Zigzag);
+        let base = Instant::now();
+        let points = zigzag(200.0, 4);
+        let (first, second) = points.split_at(points.len() / 2);
+        assert!(!draw(&mut recognizer, base, first));
+        assert!(!draw(&mut recognizer, base + Duration::from_secs(2), second));
+    }
+}
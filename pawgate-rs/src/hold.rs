@@ -0,0 +1,107 @@
+//! Locking for as long as a modifier is held down
+//!
+//! Pressing the `hold_to_lock` key engages the lock and releasing it lets
+//! go again. The keyboard hook swallows the key itself, so holding it
+//! doesn't also act as a modifier for the app underneath.
+
+/// Tracks the hold key and whether holding it engaged the lock
+pub struct HoldToLock {
+    /// Virtual keys that count as the hold key (e.g. left and right Ctrl)
+    keys: Vec<u32>,
+    /// Whether the key is down, to ignore auto-repeat
+    held: bool,
+    /// Whether the current hold engaged the lock, so only it is released
+    engaged: bool,
+}
+
+impl HoldToLock {
+    pub fn new(keys: Vec<u32>) -> Self {
+        Self {
+            keys,
+            held: false,
+            engaged: false,
+        }
+    }
+
+    /// Whether `vk` is the hold key
+    pub fn is_hold_key(&self, vk: u32) -> bool {
+        self.keys.contains(&vk)
+    }
+
+    /// Feed a key press; returns Some(true) when it should lock
+    ///
+    /// `locked` is the current lock state. Holding while already locked by
+    /// other means does nothing, so the release can't unlock that lock.
+    pub fn key_down(&mut self, vk: u32, locked: bool) -> Option<bool> {
+        if !self.is_hold_key(vk) || self.held {
+            return None;
+        }
+        self.held = true;
+        if locked {
+            return None;
+        }
+        self.engaged = true;
+        Some(true)
+    }
+
+    /// Feed a key release; returns Some(false) when it should unlock
+    pub fn key_up(&mut self, vk: u32) -> Option<bool> {
+        if !self.is_hold_key(vk) {
+            return None;
+        }
+        self.held = false;
+        std::mem::take(&mut self.engaged).then_some(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LCTRL: u32 = 0xA2;
+    const RCTRL: u32 = 0xA3;
+    const C: u32 = 0x43;
+
+    fn hold() -> HoldToLock {
+        HoldToLock::new(vec![LCTRL, RCTRL])
+    }
+
+    #[test]
+    fn test_press_locks_release_unlocks() {
+        /// WHY: The basic gesture: locked exactly while the key is down.
+        let mut hold = hold();
+        assert_eq!(hold.key_down(LCTRL, false), Some(true));
+        assert_eq!(hold.key_up(LCTRL), Some(false));
+        assert_eq!(hold.key_up(LCTRL), None, "A stray release changes nothing");
+    }
+
+    #[test]
+    fn test_auto_repeat_ignored() {
+        /// WHY: A held key sends repeated key-downs, which mustn't re-lock after an unlock elsewhere.
+        let mut hold = hold();
+        assert_eq!(hold.key_down(LCTRL, false), Some(true));
+        assert_eq!(hold.key_down(LCTRL, true), None);
+        assert_eq!(hold.key_down(LCTRL, false), None, "Still the same hold");
+        assert_eq!(hold.key_up(LCTRL), Some(false));
+        assert_eq!(hold.key_down(LCTRL, false), Some(true), "A new press engages again");
+    }
+
+    #[test]
+    fn test_existing_lock_is_left_alone() {
+        /// WHY: Releasing the key must not undo a lock from the hotkey or auto-lock.
+        let mut hold = hold();
+        assert_eq!(hold.key_down(LCTRL, true), None);
+        assert_eq!(hold.key_up(LCTRL), None);
+    }
+
+    #[test]
+    fn test_other_keys_ignored() {
+        /// WHY: Only the configured modifier drives the lock.
+        let mut hold = hold();
+        assert!(!hold.is_hold_key(C));
+        assert_eq!(hold.key_down(C, false), None);
+        assert_eq!(hold.key_down(RCTRL, false), Some(true), "Either side counts");
+        assert_eq!(hold.key_up(C), None);
+        assert_eq!(hold.key_up(RCTRL), Some(false));
+    }
+}
@@ -0,0 +1,203 @@
+//! Read-only status for local dashboards (`http_port`, `http` feature)
+//!
+//! A tiny HTTP/1.1 server on std's TcpListener, bound to 127.0.0.1 only.
+//! `GET /status` answers `{"locked", "blocked_keys", "uptime_secs"}` and
+//! `GET /config` the config as currently saved, with the unlock passphrase
+//! left out. One request per connection; nothing can be changed through it.
+
+use crate::config::Config;
+use crate::AppState;
+use log::{debug, info, warn};
+use serde::Serialize;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the listener checks for connections and for quitting
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long a client may take to send its request
+const READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Largest request head read; the rest is ignored
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Body of `GET /status`
+#[derive(Debug, Serialize)]
+pub struct StatusReport {
+    pub locked: bool,
+    /// Keys blocked during the current lock (0 while unlocked)
+    pub blocked_keys: u32,
+    pub uptime_secs: u64,
+}
+
+/// Serialize a status report
+pub fn status_json(report: &StatusReport) -> String {
+    serde_json::to_string(report).expect("StatusReport is always serializable")
+}
+
+/// Serialize a config, leaving out the unlock passphrase
+///
+/// WHY: Any local program can read the endpoint, and the passphrase is
+/// what unlocks the keyboard.
+pub fn config_json(config: &Config) -> String {
+    let mut value = serde_json::to_value(config).expect("Config is always serializable");
+    if config.unlock_passphrase.is_some() {
+        value["unlock_passphrase"] = serde_json::Value::String("(hidden)".to_string());
+    }
+    value.to_string()
+}
+
+/// A full HTTP response with a JSON body
+pub fn response(status: u16, body: &str) -> String {
+    let reason = match status {
+        200 => "OK",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    )
+}
+
+/// Method and path (without any query string) of a request's first line
+pub fn parse_request_line(line: &str) -> Option<(&str, &str)> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let target = parts.next()?;
+    parts.next().filter(|version| version.starts_with("HTTP/"))?;
+    let path = target.split('?').next().unwrap_or(target);
+    Some((method, path))
+}
+
+/// Serve requests on 127.0.0.1:`port` until the app quits
+pub fn run_http_server(state: Arc<AppState>, port: u16) {
+    let listener = match TcpListener::bind((Ipv4Addr::LOCALHOST, port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            warn!("Failed to start the status server on port {}: {}", port, e);
+            return;
+        }
+    };
+    // WHY: Non-blocking so the loop notices `should_quit` between connections
+    if let Err(e) = listener.set_nonblocking(true) {
+        warn!("Failed to start the status server on port {}: {}", port, e);
+        return;
+    }
+    info!("Status server listening on http://127.0.0.1:{}", port);
+
+    let started = Instant::now();
+    while !state.should_quit.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = handle_connection(stream, &state, started) {
+                    debug!("Status request failed: {}", e);
+                }
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::sleep(POLL_INTERVAL),
+            Err(e) => {
+                debug!("Status server accept failed: {}", e);
+                std::thread::sleep(POLL_INTERVAL);
+            }
+        }
+    }
+}
+
+fn handle_connection(mut stream: TcpStream, state: &AppState, started: Instant) -> std::io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(READ_TIMEOUT))?;
+
+    let mut request = Vec::new();
+    let mut buf = [0; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        let read = stream.read(&mut buf)?;
+        if read == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let first_line = request.lines().next().unwrap_or("");
+    let (status, body) = match parse_request_line(first_line) {
+        Some(("GET", "/status")) => {
+            let report = StatusReport {
+                locked: state.locked.load(Ordering::SeqCst),
+                blocked_keys: state.blocked_keys.load(Ordering::SeqCst),
+                uptime_secs: started.elapsed().as_secs(),
+            };
+            (200, status_json(&report))
+        }
+        Some(("GET", "/config")) => match Config::load_from(&Config::config_path()) {
+            Ok(mut config) => {
+                config.apply_env_overrides();
+                (200, config_json(&config))
+            }
+            Err(e) => (500, serde_json::json!({ "error": e.to_string() }).to_string()),
+        },
+        Some((_, "/status" | "/config")) => (405, r#"{"error":"only GET is supported"}"#.to_string()),
+        _ => (404, r#"{"error":"not found"}"#.to_string()),
+    };
+    stream.write_all(response(status, &body).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_json_fields() {
+        /// WHY: Dashboards read these exact keys.
+        let report = StatusReport {
+            locked: true,
+            blocked_keys: 42,
+            uptime_secs: 3600,
+        };
+        assert_eq!(status_json(&report), r#"{"locked":true,"blocked_keys":42,"uptime_secs":3600}"#);
+    }
+
+    #[test]
+    fn test_config_json_hides_passphrase() {
+        /// WHY: Reading the config over HTTP must not reveal how to unlock.
+        let config = Config {
+            unlock_passphrase: Some("tuna".to_string()),
+            ..Config::default()
+        };
+        let json = config_json(&config);
+        assert!(!json.contains("tuna"), "{}", json);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["unlock_passphrase"], "(hidden)");
+        assert_eq!(value["hotkeys"], serde_json::json!(["ctrl+b"]));
+
+        let value: serde_json::Value = serde_json::from_str(&config_json(&Config::default())).unwrap();
+        assert!(value["unlock_passphrase"].is_null(), "No passphrase stays null");
+    }
+
+    #[test]
+    fn test_response_framing() {
+        /// WHY: Clients rely on Content-Length and the blank line to find the body.
+        let text = response(200, r#"{"locked":false}"#);
+        assert!(text.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(text.contains("Content-Type: application/json\r\n"));
+        assert!(text.contains("Content-Length: 16\r\n"));
+        assert!(text.ends_with("\r\n\r\n{\"locked\":false}"));
+        assert!(response(404, "{}").starts_with("HTTP/1.1 404 Not Found\r\n"));
+    }
+
+    #[test]
+    fn test_parse_request_line() {
+        /// WHY: Query strings (e.g. cache busters) must not turn /status into a 404.
+        assert_eq!(parse_request_line("GET /status HTTP/1.1"), Some(("GET", "/status")));
+        assert_eq!(parse_request_line("GET /config?t=123 HTTP/1.1"), Some(("GET", "/config")));
+        assert_eq!(parse_request_line("POST /status HTTP/1.0"), Some(("POST", "/status")));
+        assert_eq!(parse_request_line("GET /status"), None);
+        assert_eq!(parse_request_line(""), None);
+    }
+}
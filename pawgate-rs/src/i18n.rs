@@ -0,0 +1,112 @@
+//! Built-in translations of user-facing strings
+//!
+//! Strings are looked up by key and `language` config code. Anything missing
+//! falls back to English, so a partial translation never shows a blank.
+
+/// Language used when the configured one has no translation
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Notification shown on lock; `{hotkey}` is filled in by the caller
+pub const NOTIFICATION_LOCK: &str = "notification.lock";
+/// Notification shown on lock when a passphrase unlocks
+pub const NOTIFICATION_LOCK_PASSPHRASE: &str = "notification.lock_passphrase";
+/// Notification shown on unlock
+pub const NOTIFICATION_UNLOCK: &str = "notification.unlock";
+/// Added to the unlock notification; `{count}` is the number of blocked keys
+pub const NOTIFICATION_BLOCKED: &str = "notification.blocked";
+
+type Table = &'static [(&'static str, &'static str)];
+
+const EN: Table = &[
+    (NOTIFICATION_LOCK, "Keyboard locked. Press {hotkey} to unlock."),
+    (NOTIFICATION_LOCK_PASSPHRASE, "Keyboard locked. Type your passphrase to unlock."),
+    (NOTIFICATION_UNLOCK, "Keyboard unlocked"),
+    (NOTIFICATION_BLOCKED, "Blocked {count} paw-presses"),
+];
+
+const DE: Table = &[
+    (NOTIFICATION_LOCK, "Tastatur gesperrt. Drücke {hotkey} zum Entsperren."),
+    (NOTIFICATION_LOCK_PASSPHRASE, "Tastatur gesperrt. Gib deine Passphrase ein, um sie zu entsperren."),
+    (NOTIFICATION_UNLOCK, "Tastatur entsperrt"),
+    (NOTIFICATION_BLOCKED, "{count} Pfotentritte blockiert"),
+];
+
+const FR: Table = &[
+    (NOTIFICATION_LOCK, "Clavier verrouillé. Appuyez sur {hotkey} pour le déverrouiller."),
+    (NOTIFICATION_LOCK_PASSPHRASE, "Clavier verrouillé. Tapez votre phrase secrète pour le déverrouiller."),
+    (NOTIFICATION_UNLOCK, "Clavier déverrouillé"),
+    (NOTIFICATION_BLOCKED, "{count} coups de patte bloqués"),
+];
+
+const JA: Table = &[
+    (NOTIFICATION_LOCK, "キーボードをロックしました。{hotkey} で解除します。"),
+    (NOTIFICATION_LOCK_PASSPHRASE, "キーボードをロックしました。パスフレーズを入力すると解除します。"),
+    (NOTIFICATION_UNLOCK, "キーボードのロックを解除しました"),
+    (NOTIFICATION_BLOCKED, "肉球による入力を {count} 回ブロックしました"),
+];
+
+const LANGUAGES: &[(&str, Table)] = &[("en", EN), ("de", DE), ("fr", FR), ("ja", JA)];
+
+/// Translation of `key` for `lang`, falling back to English
+///
+/// `lang` is matched case-insensitively and a region suffix is ignored, so
+/// "de-AT" and "DE" both get German. An unknown key is returned as-is.
+pub fn localized<'a>(key: &'a str, lang: &str) -> &'a str {
+    let base = lang.split(['-', '_']).next().unwrap_or_default().trim();
+    LANGUAGES
+        .iter()
+        .find(|(code, _)| code.eq_ignore_ascii_case(base))
+        .and_then(|(_, table)| lookup(table, key))
+        .or_else(|| lookup(EN, key))
+        .unwrap_or(key)
+}
+
+/// Whether there are built-in strings for `lang`
+pub fn is_supported(lang: &str) -> bool {
+    let base = lang.split(['-', '_']).next().unwrap_or_default().trim();
+    LANGUAGES.iter().any(|(code, _)| code.eq_ignore_ascii_case(base))
+}
+
+fn lookup(table: Table, key: &str) -> Option<&'static str> {
+    table.iter().find(|(k, _)| *k == key).map(|(_, text)| *text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_localized_german_and_french() {
+        /// WHY: Users running Windows in another language should get notifications in it.
+        assert_eq!(localized(NOTIFICATION_UNLOCK, "de"), "Tastatur entsperrt");
+        assert_eq!(localized(NOTIFICATION_UNLOCK, "fr"), "Clavier déverrouillé");
+        assert!(localized(NOTIFICATION_LOCK, "ja").contains("{hotkey}"), "Placeholder kept");
+    }
+
+    #[test]
+    fn test_localized_falls_back_to_english() {
+        /// WHY: An unsupported language must still produce readable text, never a blank.
+        assert_eq!(localized(NOTIFICATION_UNLOCK, "xx"), "Keyboard unlocked");
+        assert_eq!(localized(NOTIFICATION_UNLOCK, ""), "Keyboard unlocked");
+        assert_eq!(localized("no.such.key", "de"), "no.such.key");
+    }
+
+    #[test]
+    fn test_language_code_normalization() {
+        /// WHY: Locale-style codes like "de-AT" or "FR" are common and should just work.
+        assert_eq!(localized(NOTIFICATION_UNLOCK, "de-AT"), "Tastatur entsperrt");
+        assert_eq!(localized(NOTIFICATION_UNLOCK, "FR_ca"), "Clavier déverrouillé");
+        assert!(is_supported("ja"));
+        assert!(!is_supported("xx"));
+    }
+
+    #[test]
+    fn test_every_language_has_every_key() {
+        /// WHY: A new string added to English only would silently show English in other languages.
+        for (code, table) in LANGUAGES {
+            for (key, _) in EN {
+                assert!(lookup(table, key).is_some(), "'{}' is missing '{}'", code, key);
+            }
+        }
+    }
+}
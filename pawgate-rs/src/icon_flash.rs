@@ -0,0 +1,126 @@
+//! Flashing tray icon while locked (`flash_while_locked`)
+//!
+//! While locked, the tray icon swaps between its normal frame and an
+//! inverted one every `flash_interval_ms`, starting on the inverted frame so
+//! the lock shows at once. Unlocking puts the normal icon back. The tray
+//! loop owns the icons; this only decides which frame should be showing.
+
+use std::time::{Duration, Instant};
+
+/// Shortest interval between frames, so a typo can't strobe the icon
+pub const MIN_INTERVAL_MS: u32 = 100;
+
+/// Which tray icon to show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frame {
+    Normal,
+    Inverted,
+}
+
+/// The frame to show `elapsed` after the lock began, swapping every `interval`
+pub fn frame_at(elapsed: Duration, interval: Duration) -> Frame {
+    let interval_ms = interval.as_millis().max(1);
+    if (elapsed.as_millis() / interval_ms) % 2 == 0 {
+        Frame::Inverted
+    } else {
+        Frame::Normal
+    }
+}
+
+/// Decides when the tray icon should change frame
+pub struct IconFlasher {
+    /// None when `flash_while_locked` is off
+    interval: Option<Duration>,
+    /// When the current lock began
+    started: Option<Instant>,
+    shown: Frame,
+}
+
+impl IconFlasher {
+    pub fn new(enabled: bool, interval_ms: u32) -> Self {
+        Self {
+            interval: enabled.then(|| Duration::from_millis(u64::from(interval_ms.max(MIN_INTERVAL_MS)))),
+            started: None,
+            shown: Frame::Normal,
+        }
+    }
+
+    /// The frame to switch to this frame, if it differs from the one showing
+    pub fn update(&mut self, locked: bool, now: Instant) -> Option<Frame> {
+        let wanted = match (self.interval, locked) {
+            (Some(interval), true) => {
+                let started = *self.started.get_or_insert(now);
+                frame_at(now.saturating_duration_since(started), interval)
+            }
+            _ => {
+                self.started = None;
+                Frame::Normal
+            }
+        };
+        (wanted != self.shown).then(|| {
+            self.shown = wanted;
+            wanted
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_millis(500);
+
+    #[test]
+    fn test_frames_alternate_each_interval() {
+        /// WHY: The icon must swap once per interval, and not in between.
+        let at = |ms| frame_at(Duration::from_millis(ms), INTERVAL);
+        assert_eq!(at(0), Frame::Inverted, "Flashing starts on the changed frame");
+        assert_eq!(at(499), Frame::Inverted);
+        assert_eq!(at(500), Frame::Normal);
+        assert_eq!(at(999), Frame::Normal);
+        assert_eq!(at(1000), Frame::Inverted);
+        assert_eq!(at(10_250), Frame::Inverted);
+    }
+
+    #[test]
+    fn test_zero_interval_does_not_panic() {
+        /// WHY: frame_at divides by the interval; a zero must not crash the tray loop.
+        assert_eq!(frame_at(Duration::from_millis(3), Duration::ZERO), Frame::Normal);
+    }
+
+    #[test]
+    fn test_flasher_only_reports_changes() {
+        /// WHY: Setting the tray icon every frame (60 times a second) would be wasted work.
+        let mut flasher = IconFlasher::new(true, 500);
+        let base = Instant::now();
+        let at = |ms| base + Duration::from_millis(ms);
+
+        assert_eq!(flasher.update(false, at(0)), None, "Unlocked already shows the normal icon");
+        assert_eq!(flasher.update(true, at(100)), Some(Frame::Inverted));
+        assert_eq!(flasher.update(true, at(300)), None);
+        assert_eq!(flasher.update(true, at(600)), Some(Frame::Normal), "Timed from the lock, not startup");
+        assert_eq!(flasher.update(true, at(1100)), Some(Frame::Inverted));
+        assert_eq!(flasher.update(false, at(1200)), Some(Frame::Normal), "Unlocking restores the icon");
+        assert_eq!(flasher.update(true, at(5000)), Some(Frame::Inverted), "The next lock starts over");
+    }
+
+    #[test]
+    fn test_flasher_off() {
+        /// WHY: With flash_while_locked off the icon must never change.
+        let mut flasher = IconFlasher::new(false, 500);
+        let base = Instant::now();
+        for ms in (0..3000).step_by(100) {
+            assert_eq!(flasher.update(true, base + Duration::from_millis(ms)), None);
+        }
+    }
+
+    #[test]
+    fn test_interval_has_a_floor() {
+        /// WHY: A 1 ms interval would strobe the icon.
+        let mut flasher = IconFlasher::new(true, 1);
+        let base = Instant::now();
+        assert_eq!(flasher.update(true, base), Some(Frame::Inverted));
+        assert_eq!(flasher.update(true, base + Duration::from_millis(50)), None);
+        assert_eq!(flasher.update(true, base + Duration::from_millis(100)), Some(Frame::Normal));
+    }
+}
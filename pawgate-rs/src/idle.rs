@@ -0,0 +1,128 @@
+//! Auto-lock after a period of keyboard and mouse inactivity
+//!
+//! Polls GetLastInputInfo on a background thread and engages the lock once
+//! the configured idle threshold is exceeded.
+
+use crate::events::Trigger;
+use crate::AppState;
+use log::{debug, info};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use windows::Win32::System::SystemInformation::GetTickCount;
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO};
+
+/// How often the idle time is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Decides when idle time should trigger a lock
+///
+/// Fires once when the threshold is crossed, then stays disarmed until input
+/// resumes (idle time drops back below the threshold). This keeps it from
+/// re-locking immediately after the user unlocks.
+pub struct IdleTracker {
+    threshold: Duration,
+    armed: bool,
+}
+
+impl IdleTracker {
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+            armed: true,
+        }
+    }
+
+    /// Feed the current idle time; returns true if the lock should engage now
+    pub fn update(&mut self, idle: Duration, locked: bool) -> bool {
+        if idle < self.threshold {
+            // Input resumed, so the next idle period may lock again
+            self.armed = true;
+            return false;
+        }
+
+        if locked || !self.armed {
+            return false;
+        }
+
+        self.armed = false;
+        true
+    }
+}
+
+/// Run the idle watcher until the app quits
+pub fn run_idle_monitor(state: Arc<AppState>, idle_secs: u32) {
+    let mut tracker = IdleTracker::new(Duration::from_secs(idle_secs as u64));
+    info!("Idle auto-lock enabled after {}s", idle_secs);
+
+    while !state.should_quit.load(Ordering::SeqCst) {
+        std::thread::sleep(POLL_INTERVAL);
+
+        // Auto-lock pauses while snoozed or disabled by an app rule
+        if state.snoozed.load(Ordering::SeqCst) || state.app_disabled.load(Ordering::SeqCst) {
+            continue;
+        }
+
+        let Some(idle) = system_idle_time() else {
+            continue;
+        };
+
+        let locked = state.locked.load(Ordering::SeqCst);
+        if tracker.update(idle, locked) {
+            debug!("Idle for {:?}, locking", idle);
+            state.set_locked(true, Trigger::AutoIdle);
+        }
+    }
+}
+
+/// Time since the last keyboard or mouse input in this session
+fn system_idle_time() -> Option<Duration> {
+    let mut info = LASTINPUTINFO {
+        cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+        dwTime: 0,
+    };
+
+    unsafe {
+        if !GetLastInputInfo(&mut info).as_bool() {
+            return None;
+        }
+        // WHY: Both values are 32-bit tick counts that wrap every ~49.7 days;
+        // wrapping_sub gives the right difference across a wrap.
+        let elapsed_ms = GetTickCount().wrapping_sub(info.dwTime);
+        Some(Duration::from_millis(elapsed_ms as u64))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_tracker_fires_once_past_threshold() {
+        /// WHY: Crossing the threshold should lock exactly once, not every poll.
+        let mut tracker = IdleTracker::new(Duration::from_secs(60));
+
+        assert!(!tracker.update(Duration::from_secs(30), false));
+        assert!(tracker.update(Duration::from_secs(60), false), "Should lock at threshold");
+        assert!(!tracker.update(Duration::from_secs(61), true), "Already locked");
+        assert!(!tracker.update(Duration::from_secs(62), false), "Unlocked but no input yet");
+    }
+
+    #[test]
+    fn test_idle_tracker_rearms_after_input() {
+        /// WHY: After unlocking and typing again, the next idle period must lock.
+        let mut tracker = IdleTracker::new(Duration::from_secs(60));
+
+        assert!(tracker.update(Duration::from_secs(90), false));
+        assert!(!tracker.update(Duration::from_secs(1), false), "Input resumed");
+        assert!(tracker.update(Duration::from_secs(60), false), "Should lock again");
+    }
+
+    #[test]
+    fn test_idle_tracker_skips_when_already_locked() {
+        /// WHY: A manual lock shouldn't be reported as an idle lock.
+        let mut tracker = IdleTracker::new(Duration::from_secs(60));
+
+        assert!(!tracker.update(Duration::from_secs(120), true));
+    }
+}
@@ -0,0 +1,195 @@
+//! Control channel between `pawgate <command>` and the running instance
+//!
+//! The running instance owns a hidden message-only window; the CLI finds it
+//! by class name and sends it a private window message.
+
+use crate::events::Trigger;
+use crate::AppState;
+use log::{info, warn};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::*;
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+const CONTROL_CLASS_NAME: PCWSTR = w!("PawGateControl");
+
+/// Private message carrying a [`ControlCommand`] in WPARAM
+const WM_PAWGATE_COMMAND: u32 = WM_APP + 1;
+
+/// How long the CLI waits for the running instance to respond
+const SEND_TIMEOUT_MS: u32 = 2000;
+
+/// Thread-local state for the control window procedure
+thread_local! {
+    static CONTROL_STATE: std::cell::RefCell<Option<Arc<AppState>>> = const { std::cell::RefCell::new(None) };
+}
+
+/// A request sent to the running instance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlCommand {
+    Lock,
+    Unlock,
+    Toggle,
+    /// Ignore the hotkey and auto-lock for this many minutes (0 = resume now)
+    Snooze(u32),
+    /// Lock, then unlock again after this many seconds
+    TestLock(u32),
+}
+
+impl ControlCommand {
+    /// Encode as the WPARAM (command) and LPARAM (argument) of the message
+    fn to_params(self) -> (WPARAM, LPARAM) {
+        match self {
+            Self::Lock => (WPARAM(1), LPARAM(0)),
+            Self::Unlock => (WPARAM(2), LPARAM(0)),
+            Self::Toggle => (WPARAM(3), LPARAM(0)),
+            Self::Snooze(minutes) => (WPARAM(4), LPARAM(minutes as isize)),
+            Self::TestLock(secs) => (WPARAM(5), LPARAM(secs as isize)),
+        }
+    }
+
+    fn from_params(wparam: WPARAM, lparam: LPARAM) -> Option<Self> {
+        match wparam.0 {
+            1 => Some(Self::Lock),
+            2 => Some(Self::Unlock),
+            3 => Some(Self::Toggle),
+            4 => u32::try_from(lparam.0).ok().map(Self::Snooze),
+            5 => u32::try_from(lparam.0).ok().filter(|&secs| secs > 0).map(Self::TestLock),
+            _ => None,
+        }
+    }
+}
+
+/// Create the hidden window that receives commands from the CLI
+///
+/// Must be called on a thread that pumps messages (the tray loop).
+pub fn create_control_window(state: Arc<AppState>) -> Option<HWND> {
+    CONTROL_STATE.with(|cs| *cs.borrow_mut() = Some(state));
+
+    unsafe {
+        let hinstance = GetModuleHandleW(None).ok()?;
+
+        let wc = WNDCLASSEXW {
+            cbSize: std::mem::size_of::<WNDCLASSEXW>() as u32,
+            lpfnWndProc: Some(control_wnd_proc),
+            hInstance: hinstance.into(),
+            lpszClassName: CONTROL_CLASS_NAME,
+            ..Default::default()
+        };
+        RegisterClassExW(&wc);
+
+        // WHY: HWND_MESSAGE makes a message-only window: never shown, not
+        // enumerated as a top-level window, but findable by class name.
+        let hwnd = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            CONTROL_CLASS_NAME,
+            w!("PawGate Control"),
+            WINDOW_STYLE::default(),
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            None,
+            hinstance,
+            None,
+        );
+
+        match hwnd {
+            Ok(hwnd) => {
+                info!("Control window created");
+                crate::session::register_notifications(hwnd);
+                Some(hwnd)
+            }
+            Err(e) => {
+                warn!("Failed to create control window: {}", e);
+                None
+            }
+        }
+    }
+}
+
+/// Send `command` to the running instance
+///
+/// Returns an error message suitable for printing if no instance answered.
+pub fn send_command(command: ControlCommand) -> Result<(), String> {
+    unsafe {
+        let hwnd = FindWindowExW(HWND_MESSAGE, None, CONTROL_CLASS_NAME, None)
+            .map_err(|_| "PawGate is not running. Start pawgate.exe first.".to_string())?;
+
+        let (wparam, lparam) = command.to_params();
+        let mut result = 0usize;
+        let sent = SendMessageTimeoutW(
+            hwnd,
+            WM_PAWGATE_COMMAND,
+            wparam,
+            lparam,
+            SMTO_ABORTIFHUNG,
+            SEND_TIMEOUT_MS,
+            Some(&mut result),
+        );
+
+        if sent.0 == 0 {
+            return Err("PawGate did not respond.".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Window procedure for the control window
+unsafe extern "system" fn control_wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if msg == WM_PAWGATE_COMMAND {
+        let Some(command) = ControlCommand::from_params(wparam, lparam) else {
+            return LRESULT(0);
+        };
+
+        CONTROL_STATE.with(|cs| {
+            if let Some(state) = cs.borrow().as_ref() {
+                let locked = match command {
+                    ControlCommand::Lock => true,
+                    ControlCommand::Unlock => false,
+                    ControlCommand::Toggle => !state.locked.load(Ordering::SeqCst),
+                    ControlCommand::Snooze(minutes) => {
+                        // The tray loop owns the snooze timer and picks this up
+                        state.snooze_request.store(minutes, Ordering::SeqCst);
+                        info!("CLI command {:?}", command);
+                        return;
+                    }
+                    ControlCommand::TestLock(secs) => {
+                        // The tray loop locks and owns the unlock timer
+                        state.test_lock_request.store(secs, Ordering::SeqCst);
+                        info!("CLI command {:?}", command);
+                        return;
+                    }
+                };
+                // Cancel any grace countdown so the command takes effect now
+                state.lock_pending.store(false, Ordering::SeqCst);
+                state.set_locked(locked, Trigger::Cli);
+                info!("CLI command {:?}, locked={}", command, locked);
+            }
+        });
+        return LRESULT(1);
+    }
+
+    // The tray loop notices the change and restores the lock if needed
+    if msg == WM_WTSSESSION_CHANGE {
+        if let Some(active) = crate::session::active_after(wparam.0 as u32) {
+            CONTROL_STATE.with(|cs| {
+                if let Some(state) = cs.borrow().as_ref() {
+                    state.session_active.store(active, Ordering::SeqCst);
+                    info!("Session {}", if active { "active" } else { "inactive" });
+                }
+            });
+        }
+        return LRESULT(0);
+    }
+
+    DefWindowProcW(hwnd, msg, wparam, lparam)
+}
@@ -2,12 +2,33 @@
 //!
 //! Uses Windows SetWindowsHookEx with WH_KEYBOARD_LL to intercept all keyboard events.
 //! When locked, blocks all keys except the configured unlock hotkey.
+//! If an unlock passphrase is configured, the hotkey only locks: typing the
+//! passphrase unlocks, and so do the unlock gesture (if configured) and the
+//! panic unlock hotkey, which always works.
+//! Mouse input is blocked with a WH_MOUSE_LL hook when the lock mode asks for it,
+//! which also watches for the unlock gesture if one is configured.
 
-use crate::config::{parse_hotkey, Config};
+use crate::config::{split_step_modifiers, Config, ModifierSides};
+use crate::debounce::ToggleDebounce;
+use crate::double_tap::DoubleTapDetector;
+use crate::events::Trigger;
+use crate::failsafe::{HookGuard, SystemHook};
+use crate::gesture::GestureRecognizer;
+use crate::hold::HoldToLock;
+use crate::mash::MashDetector;
+use crate::panic_unlock::PanicUnlock;
+use crate::passphrase::{vk_to_passphrase_char, PassphraseMatcher};
+use crate::register_failure::{self, Outcome};
+use crate::sequence::{SequenceMatch, SequenceMatcher};
+use crate::shutdown;
+use crate::tap_hold::{Press, TapHoldDiscriminator};
 use crate::AppState;
-use log::{debug, info};
-use std::sync::atomic::Ordering;
+use log::{debug, error, info, warn};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
 use std::sync::Arc;
+use std::time::Instant;
 use windows::Win32::Foundation::{LPARAM, LRESULT, WPARAM};
 use windows::Win32::UI::Input::KeyboardAndMouse::*;
 use windows::Win32::UI::WindowsAndMessaging::*;
@@ -16,12 +37,61 @@ use windows::Win32::UI::WindowsAndMessaging::*;
 /// Required because the hook callback can't capture closures
 thread_local! {
     static HOOK_STATE: std::cell::RefCell<Option<HookState>> = const { std::cell::RefCell::new(None) };
+    static MOUSE_HOOK_STATE: std::cell::RefCell<Option<MouseHookState>> = const { std::cell::RefCell::new(None) };
+}
+
+struct MouseHookState {
+    state: Arc<AppState>,
+    /// Whether mouse input is swallowed while locked
+    block_mouse: bool,
+    /// Unlocks when the configured gesture is drawn, if any
+    gesture: Option<GestureRecognizer>,
+    /// Where the pointer would be if it weren't held still by blocking
+    pointer: (f64, f64),
 }
 
 struct HookState {
     state: Arc<AppState>,
-    hotkey_modifiers: u32,
-    hotkey_vk: u32,
+    /// Matches the configured hotkey sequences, any of which toggles the lock
+    hotkeys: SequenceMatcher,
+    /// Ignores hotkey presses right after a toggle
+    debounce: ToggleDebounce,
+    /// Matches the peek hotkey, if configured
+    peek: Option<SequenceMatcher>,
+    /// Match the opacity hotkeys, if configured
+    opacity_up: Option<SequenceMatcher>,
+    opacity_down: Option<SequenceMatcher>,
+    /// Locks on a double tap of the configured modifier, if any
+    double_tap: Option<DoubleTapDetector>,
+    /// Locks while the configured modifier is held, if any
+    hold: Option<HoldToLock>,
+    /// Toggles on a tap and unlocks on a hold of the configured key, if any
+    tap_hold: Option<TapHoldDiscriminator>,
+    /// Locks when many keys go down at once, if `auto_lock_on_mash` is set
+    mash: Option<MashDetector>,
+    /// Unlocks whatever the unlock settings
+    panic_unlock: PanicUnlock,
+    /// When set, typing the passphrase unlocks instead of the hotkey
+    passphrase: Option<PassphraseMatcher>,
+    /// Lock state seen on the previous event, to reset the passphrase on lock
+    was_locked: bool,
+    /// Whether locking goes through the tray's grace countdown
+    lock_delayed: bool,
+    /// Whether keys other than the hotkey are swallowed while locked
+    block_keyboard: bool,
+    /// Virtual keys that keep working while locked
+    passthrough_keys: HashSet<u32>,
+}
+
+/// What the keyboard hook does with a key event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum KeyAction {
+    /// Let it through to the rest of the system
+    Pass,
+    /// Swallow it
+    Swallow,
+    /// Swallow it and unlock (also cancelling a pending lock)
+    Unlock(Trigger),
 }
 
 /// Virtual key codes for modifier keys
@@ -34,37 +104,65 @@ const VK_RMENU_U32: u32 = VK_RMENU.0 as u32;  // Right Alt
 const VK_LWIN_U32: u32 = VK_LWIN.0 as u32;
 const VK_RWIN_U32: u32 = VK_RWIN.0 as u32;
 
-/// Run the keyboard hook message loop
-pub fn run_keyboard_hook(state: Arc<AppState>, config: Config) {
-    // Parse the hotkey configuration
-    let (modifiers, vk) = parse_hotkey(&config.hotkey).unwrap_or((MOD_CONTROL.0, 'B' as u32));
-
-    info!(
-        "Keyboard hook starting with hotkey: {} (modifiers={:#x}, vk={:#x})",
-        config.hotkey, modifiers, vk
-    );
+/// Run the keyboard hook message loop until the app quits or `stop` is set
+///
+/// `installed` receives the outcome of installing the keyboard hook; on
+/// failure this returns straight away.
+pub fn run_keyboard_hook_until(
+    state: Arc<AppState>,
+    config: Config,
+    stop: Arc<AtomicBool>,
+    installed: Sender<Result<(), String>>,
+) {
+    // Parse the hotkey configuration, following `on_register_failure` if none work
+    let hotkeys = match register_failure::resolve(config.parsed_hotkeys(), config.on_register_failure) {
+        Outcome::Configured(hotkeys) => hotkeys,
+        Outcome::Fallback(sequence) => {
+            warn!("No valid hotkeys configured, falling back to {}", register_failure::FALLBACK_HOTKEY);
+            vec![sequence]
+        }
+        Outcome::Hotkeyless => {
+            warn!("No valid hotkeys configured; running without a hotkey");
+            state.hotkey_unavailable.store(true, Ordering::SeqCst);
+            Vec::new()
+        }
+        Outcome::Exit => {
+            error!("No valid hotkeys configured; quitting (on_register_failure is exit)");
+            state.should_quit.store(true, Ordering::SeqCst);
+            let _ = installed.send(Err("no hotkey could be registered".to_string()));
+            return;
+        }
+    };
 
     // Store state in thread-local storage for the hook callback
     HOOK_STATE.with(|hs| {
-        *hs.borrow_mut() = Some(HookState {
-            state: Arc::clone(&state),
-            hotkey_modifiers: modifiers,
-            hotkey_vk: vk,
-        });
+        *hs.borrow_mut() = Some(HookState::new(Arc::clone(&state), &config, hotkeys));
     });
 
-    // Install low-level keyboard hook
-    let hook = unsafe {
-        SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook_proc), None, 0)
-            .expect("Failed to install keyboard hook")
+    // WHY: Guards unhook when this function returns or unwinds from a panic.
+    let mut hooks = match install_hooks(&state, &config) {
+        Ok(hooks) => hooks,
+        Err(e) => {
+            let _ = installed.send(Err(e));
+            return;
+        }
     };
-
-    info!("Keyboard hook installed");
+    let _ = installed.send(Ok(()));
 
     // Message loop - required for low-level hooks to work
     unsafe {
         let mut msg = MSG::default();
-        while !state.should_quit.load(Ordering::SeqCst) {
+        while !state.should_quit.load(Ordering::SeqCst) && !stop.load(Ordering::SeqCst) {
+            // Windows can drop low-level hooks across a session switch, so
+            // the tray loop asks for fresh ones when the session comes back
+            if state.reinstall_hooks.swap(false, Ordering::SeqCst) {
+                match install_hooks(&state, &config) {
+                    // The old hooks are removed as they're replaced
+                    Ok(new_hooks) => hooks = new_hooks,
+                    Err(e) => warn!("Failed to reinstall hooks: {}", e),
+                }
+            }
+
             // Use PeekMessage with a short timeout to allow checking should_quit
             if PeekMessageW(&mut msg, None, 0, 0, PM_REMOVE).as_bool() {
                 if msg.message == WM_QUIT {
@@ -77,14 +175,60 @@ pub fn run_keyboard_hook(state: Arc<AppState>, config: Config) {
                 std::thread::sleep(std::time::Duration::from_millis(10));
             }
         }
-
-        // Unhook before exiting
-        let _ = UnhookWindowsHookEx(hook);
     }
 
+    drop(hooks);
     info!("Keyboard hook removed");
 }
 
+/// Install the keyboard hook, plus the mouse hook if the lock mode needs it
+///
+/// Only failing to hook the keyboard is an error; without the mouse hook
+/// the keyboard lock still works.
+fn install_hooks(
+    state: &Arc<AppState>,
+    config: &Config,
+) -> Result<(HookGuard<SystemHook>, Option<HookGuard<SystemHook>>), String> {
+    // WHY: Needed in every lock mode, since the hotkey is detected here even
+    // when only the mouse is blocked.
+    let hook = unsafe { SetWindowsHookExW(WH_KEYBOARD_LL, Some(keyboard_hook_proc), None, 0) }
+        .map(|hook| HookGuard::new(SystemHook::new(hook)))
+        .map_err(|e| format!("Failed to install keyboard hook: {}", e))?;
+
+    info!("Keyboard hook installed (lock mode {:?})", config.lock_mode);
+
+    // The mouse hook is only installed when the lock mode blocks the mouse
+    // or an unlock gesture needs watching
+    let gesture = config.parsed_unlock_gesture().map(|gesture| {
+        info!("Unlock gesture enabled ({:?})", gesture);
+        GestureRecognizer::new(gesture)
+    });
+    let mouse_hook = if config.lock_mode.blocks_mouse() || gesture.is_some() {
+        MOUSE_HOOK_STATE.with(|ms| {
+            *ms.borrow_mut() = Some(MouseHookState {
+                state: Arc::clone(state),
+                block_mouse: config.lock_mode.blocks_mouse(),
+                gesture,
+                pointer: (0.0, 0.0),
+            })
+        });
+        match unsafe { SetWindowsHookExW(WH_MOUSE_LL, Some(mouse_hook_proc), None, 0) } {
+            Ok(hook) => {
+                info!("Mouse hook installed");
+                Some(HookGuard::new(SystemHook::new(hook)))
+            }
+            Err(e) => {
+                warn!("Failed to install mouse hook: {}", e);
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    Ok((hook, mouse_hook))
+}
+
 /// Check if a modifier key is currently pressed
 fn is_modifier_pressed(modifier: u32) -> bool {
     unsafe {
@@ -111,7 +255,23 @@ fn is_modifier_pressed(modifier: u32) -> bool {
 }
 
 /// Check if all required modifiers are pressed (and no extra ones)
+///
+/// `required` may carry side requirements (see `config::ParsedHotkey::to_step`);
+/// a sided modifier must be held on that side only.
 fn check_modifiers(required: u32) -> bool {
+    let (required, sides) = split_step_modifiers(required);
+    let sided = [
+        (ModifierSides::LEFT_CTRL, VK_LCONTROL, VK_RCONTROL),
+        (ModifierSides::RIGHT_CTRL, VK_RCONTROL, VK_LCONTROL),
+        (ModifierSides::LEFT_ALT, VK_LMENU, VK_RMENU),
+        (ModifierSides::RIGHT_ALT, VK_RMENU, VK_LMENU),
+        (ModifierSides::LEFT_SHIFT, VK_LSHIFT, VK_RSHIFT),
+        (ModifierSides::RIGHT_SHIFT, VK_RSHIFT, VK_LSHIFT),
+    ];
+    let sides_ok = sided.iter().all(|&(side, wanted, other)| {
+        !sides.contains(side) || (is_key_down(wanted) && !is_key_down(other))
+    });
+
     let ctrl_required = (required & MOD_CONTROL.0) != 0;
     let shift_required = (required & MOD_SHIFT.0) != 0;
     let alt_required = (required & MOD_ALT.0) != 0;
@@ -122,12 +282,18 @@ fn check_modifiers(required: u32) -> bool {
     let alt_pressed = is_modifier_pressed(MOD_ALT.0);
     let win_pressed = is_modifier_pressed(MOD_WIN.0);
 
-    ctrl_required == ctrl_pressed
+    sides_ok
+        && ctrl_required == ctrl_pressed
         && shift_required == shift_pressed
         && alt_required == alt_pressed
         && win_required == win_pressed
 }
 
+/// Whether a specific key is currently held down
+fn is_key_down(vk: VIRTUAL_KEY) -> bool {
+    unsafe { (GetAsyncKeyState(vk.0 as i32) as u16 & 0x8000) != 0 }
+}
+
 /// Check if a virtual key code is a modifier key
 fn is_modifier_vk(vk: u32) -> bool {
     matches!(
@@ -146,6 +312,375 @@ fn is_modifier_vk(vk: u32) -> bool {
     )
 }
 
+impl HookState {
+    /// Build the hook's matchers from `config`, with `hotkeys` already
+    /// resolved against `on_register_failure`
+    fn new(state: Arc<AppState>, config: &Config, hotkeys: Vec<Vec<(u32, u32)>>) -> Self {
+        for sequence in &hotkeys {
+            info!("Keyboard hook registering hotkey (steps={:x?})", sequence);
+        }
+        let hotkeys = SequenceMatcher::new(
+            hotkeys,
+            std::time::Duration::from_millis(u64::from(config.sequence_timeout_ms)),
+        );
+
+        let peek = config.parsed_peek_hotkey().map(|sequence| {
+            info!("Peek hotkey registered (steps={:x?})", sequence);
+            SequenceMatcher::new(
+                vec![sequence],
+                std::time::Duration::from_millis(u64::from(config.sequence_timeout_ms)),
+            )
+        });
+
+        let opacity_up = config.parsed_opacity_up_hotkey().map(|sequence| {
+            info!("Opacity up hotkey registered (steps={:x?})", sequence);
+            SequenceMatcher::new(
+                vec![sequence],
+                std::time::Duration::from_millis(u64::from(config.sequence_timeout_ms)),
+            )
+        });
+        let opacity_down = config.parsed_opacity_down_hotkey().map(|sequence| {
+            info!("Opacity down hotkey registered (steps={:x?})", sequence);
+            SequenceMatcher::new(
+                vec![sequence],
+                std::time::Duration::from_millis(u64::from(config.sequence_timeout_ms)),
+            )
+        });
+
+        let double_tap = config.parsed_double_tap_keys().map(|keys| {
+            info!("Double-tap lock enabled (keys={:x?})", keys);
+            DoubleTapDetector::new(
+                keys,
+                std::time::Duration::from_millis(u64::from(config.double_tap_window_ms)),
+            )
+        });
+
+        let hold = config.parsed_hold_to_lock_keys().map(|keys| {
+            info!("Hold-to-lock enabled (keys={:x?})", keys);
+            HoldToLock::new(keys)
+        });
+
+        let tap_hold = config.parsed_tap_hold_keys().map(|keys| {
+            info!("Tap/hold key enabled (keys={:x?}, hold={}ms)", keys, config.hold_threshold_ms);
+            TapHoldDiscriminator::new(
+                keys,
+                std::time::Duration::from_millis(u64::from(config.hold_threshold_ms)),
+            )
+        });
+
+        let mash = config.auto_lock_on_mash.map(|keys| {
+            info!("Mash lock enabled (more than {} keys within {}ms)", keys, config.mash_window_ms);
+            MashDetector::new(keys, std::time::Duration::from_millis(u64::from(config.mash_window_ms)))
+        });
+
+        let panic_unlock = PanicUnlock::new(config);
+        info!("Panic unlock hotkey registered (steps={:x?})", config.parsed_panic_unlock_hotkey());
+
+        let passphrase = config
+            .unlock_passphrase
+            .as_deref()
+            .and_then(PassphraseMatcher::new);
+        if passphrase.is_some() {
+            info!("Passphrase unlock enabled");
+        }
+
+        Self {
+            state,
+            hotkeys,
+            debounce: ToggleDebounce::new(std::time::Duration::from_millis(u64::from(
+                config.toggle_debounce_ms,
+            ))),
+            peek,
+            opacity_up,
+            opacity_down,
+            double_tap,
+            hold,
+            tap_hold,
+            mash,
+            panic_unlock,
+            passphrase,
+            was_locked: false,
+            lock_delayed: config.lock_delay_ms > 0,
+            block_keyboard: config.lock_mode.blocks_keyboard(),
+            passthrough_keys: config.parsed_passthrough_keys(),
+        }
+    }
+
+    /// Decide what happens to a key event
+    ///
+    /// `modifiers_held` is the modifier check, as for [`SequenceMatcher::feed`];
+    /// the hook passes `check_modifiers`. Unlocks come back as
+    /// [`KeyAction::Unlock`] for the caller to apply; anything else the key
+    /// does is applied here.
+    fn key_event(
+        &mut self,
+        vk_code: u32,
+        is_keydown: bool,
+        is_keyup: bool,
+        now: Instant,
+        modifiers_held: &dyn Fn(u32) -> bool,
+    ) -> KeyAction {
+        let is_locked = self.state.locked.load(Ordering::SeqCst);
+
+        // Start every lock with an empty passphrase buffer
+        if is_locked && !self.was_locked {
+            if let Some(matcher) = self.passphrase.as_mut() {
+                matcher.reset();
+            }
+        }
+        self.was_locked = is_locked;
+
+        // The panic hotkey unlocks (or cancels a pending lock) before
+        // anything else gets a look at the key, the passphrase included
+        let lock_pending = self.state.lock_pending.load(Ordering::SeqCst);
+        if is_keydown
+            && !is_modifier_vk(vk_code)
+            && (is_locked || lock_pending)
+            && self.panic_unlock.key_down(vk_code, now, modifiers_held)
+        {
+            self.was_locked = false;
+            info!("Panic unlock hotkey pressed, unlocking");
+            return KeyAction::Unlock(Trigger::Panic);
+        }
+
+        // A quit waiting for confirmation takes the next key as its answer
+        if is_keydown && !is_modifier_vk(vk_code) && self.state.quit_pending.load(Ordering::SeqCst) {
+            // WHY: Answer before clearing the question, so the tray
+            // never sees neither and takes it as a cancel
+            let confirmed = shutdown::confirms_quit(vk_code);
+            self.state.quit_confirmed.store(confirmed, Ordering::SeqCst);
+            self.state.quit_pending.store(false, Ordering::SeqCst);
+            debug!("Quit {}", if confirmed { "confirmed" } else { "cancelled" });
+            return KeyAction::Swallow;
+        }
+
+        // While snoozed (or disabled by an app rule) the hotkey
+        // can't lock, but it can still unlock
+        let snoozed = !is_locked
+            && (self.state.snoozed.load(Ordering::SeqCst)
+                || self.state.app_disabled.load(Ordering::SeqCst));
+
+        // Many keys down at once is a paw: lock straight away,
+        // skipping the grace countdown that would let the rest of the
+        // mash through. Watched ahead of the lock handling below,
+        // which can return early, so every release is seen.
+        if let Some(detector) = self.mash.as_mut() {
+            if is_keyup {
+                detector.key_up(vk_code);
+            } else if is_keydown
+                && detector.key_down(vk_code, now)
+                && !is_locked
+                && !snoozed
+            {
+                self.state.lock_pending.store(false, Ordering::SeqCst);
+                self.state.set_locked(true, Trigger::Mash);
+                debug!("Keys mashed, locking");
+                if self.block_keyboard {
+                    return KeyAction::Swallow;
+                }
+            }
+        }
+
+        // The hold key locks on press and unlocks on release,
+        // skipping the grace countdown. It's swallowed either way,
+        // unless snoozed, when it's an ordinary modifier again.
+        if let Some(hold) = self.hold.as_mut() {
+            if hold.is_hold_key(vk_code) && !snoozed {
+                let change = if is_keydown {
+                    hold.key_down(vk_code, is_locked)
+                } else if is_keyup {
+                    hold.key_up(vk_code)
+                } else {
+                    None
+                };
+                if let Some(locked) = change {
+                    debug!("Hold key {}, locked={}", if locked { "pressed" } else { "released" }, locked);
+                    if !locked {
+                        return KeyAction::Unlock(Trigger::Hotkey);
+                    }
+                    self.state.set_locked(true, Trigger::Hotkey);
+                }
+                return KeyAction::Swallow;
+            }
+        }
+
+        // A tap of the tap/hold key toggles like the hotkey; holding
+        // it unlocks whatever the state, and cancels a pending lock.
+        // It's swallowed either way, unless snoozed.
+        if let Some(tap_hold) = self.tap_hold.as_mut() {
+            if tap_hold.is_key(vk_code) && !snoozed {
+                let press = if is_keydown {
+                    tap_hold.key_down(vk_code, now);
+                    None
+                } else if is_keyup {
+                    tap_hold.key_up(vk_code, now)
+                } else {
+                    None
+                };
+                // In passphrase mode neither a tap nor a hold unlocks
+                let can_unlock = self.passphrase.is_none();
+                match press {
+                    Some(Press::Tap) if self.debounce.accept(now) => {
+                        if !is_locked && self.lock_delayed {
+                            let pending = !self.state.lock_pending.load(Ordering::SeqCst);
+                            self.state.lock_pending.store(pending, Ordering::SeqCst);
+                            debug!("Tap/hold key tapped, lock_pending={}", pending);
+                        } else if !is_locked || can_unlock {
+                            debug!("Tap/hold key tapped, locked={}", !is_locked);
+                            if is_locked {
+                                return KeyAction::Unlock(Trigger::Hotkey);
+                            }
+                            self.state.set_locked(true, Trigger::Hotkey);
+                        }
+                    }
+                    Some(Press::Hold) if can_unlock => {
+                        debug!("Tap/hold key held, unlocked");
+                        return KeyAction::Unlock(Trigger::Hotkey);
+                    }
+                    _ => {}
+                }
+                return KeyAction::Swallow;
+            }
+        }
+
+        // A double-tapped modifier locks. The taps themselves pass
+        // through, and it can't unlock, since modifiers reach the
+        // hook while locked and a paw could tap them too.
+        if let Some(detector) = self.double_tap.as_mut() {
+            let double_tapped = if is_keydown {
+                detector.key_down(vk_code, now);
+                false
+            } else {
+                is_keyup && detector.key_up(vk_code, now)
+            };
+            if double_tapped && !is_locked && !snoozed {
+                if self.lock_delayed {
+                    self.state.lock_pending.store(true, Ordering::SeqCst);
+                } else {
+                    self.state.set_locked(true, Trigger::Hotkey);
+                }
+                debug!("Modifier double-tapped, locking");
+            }
+        }
+
+        // The peek hotkey starts a peek while locked, or ends it early
+        if is_locked && is_keydown && !is_modifier_vk(vk_code) {
+            if let Some(matcher) = self.peek.as_mut() {
+                match matcher.feed(vk_code, now, modifiers_held) {
+                    SequenceMatch::Complete => {
+                        let peeking = !self.state.peeking.fetch_xor(true, Ordering::SeqCst);
+                        debug!("Peek hotkey pressed, peeking={}", peeking);
+                        return KeyAction::Swallow;
+                    }
+                    SequenceMatch::InProgress => return KeyAction::Swallow,
+                    SequenceMatch::NoMatch => {}
+                }
+            }
+        }
+        // The opacity hotkeys adjust the overlay while locked; the
+        // tray applies the steps
+        if is_locked && is_keydown && !is_modifier_vk(vk_code) {
+            for (matcher, step) in [(self.opacity_up.as_mut(), 1), (self.opacity_down.as_mut(), -1)] {
+                let Some(matcher) = matcher else { continue };
+                match matcher.feed(vk_code, now, modifiers_held) {
+                    SequenceMatch::Complete => {
+                        self.state.opacity_steps.fetch_add(step, Ordering::SeqCst);
+                        debug!("Opacity hotkey pressed, step={}", step);
+                        return KeyAction::Swallow;
+                    }
+                    SequenceMatch::InProgress => return KeyAction::Swallow,
+                    SequenceMatch::NoMatch => {}
+                }
+            }
+        }
+        let peeking = is_locked && self.state.peeking.load(Ordering::SeqCst);
+
+        // Check for hotkey press (only on keydown, not modifiers themselves)
+        if is_keydown && !is_modifier_vk(vk_code) && !snoozed {
+            let hotkey_match = self.hotkeys.feed(vk_code, now, modifiers_held);
+
+            // Swallow the leading steps of a sequence, like the final one
+            if hotkey_match == SequenceMatch::InProgress {
+                return KeyAction::Swallow;
+            }
+            let hotkey_pressed = hotkey_match == SequenceMatch::Complete;
+            // Mashing the hotkey: swallow presses right after a toggle
+            if hotkey_pressed && !self.debounce.accept(now) {
+                debug!("Hotkey pressed again within toggle_debounce_ms, ignoring");
+                return KeyAction::Swallow;
+            }
+            if hotkey_pressed && !is_locked && self.lock_delayed {
+                // Start or cancel the grace countdown; the tray loop
+                // engages the lock when it runs out
+                let pending = !self.state.lock_pending.load(Ordering::SeqCst);
+                self.state.lock_pending.store(pending, Ordering::SeqCst);
+                debug!("Hotkey pressed, lock_pending={}", pending);
+
+                return KeyAction::Swallow;
+            }
+
+            // In passphrase mode the hotkey only locks; it can't unlock
+            if hotkey_pressed && !(is_locked && self.passphrase.is_some()) {
+                // Toggle lock state
+                debug!("Hotkey pressed, locked={}", !is_locked);
+                if is_locked {
+                    return KeyAction::Unlock(Trigger::Hotkey);
+                }
+                self.state.set_locked(true, Trigger::Hotkey);
+
+                // Block this keypress so it doesn't pass through
+                return KeyAction::Swallow;
+            }
+        }
+
+        // If locked, block all keys except:
+        // - The unlock hotkey modifiers (so user can press the combo)
+        // - Ctrl+Alt+Del (can't be blocked anyway, OS-level)
+        // Nothing is blocked during a peek.
+        if is_locked && !peeking {
+            // Allow modifier keys through so user can build up the hotkey combo
+            if is_modifier_vk(vk_code) {
+                // Pass through modifier keys
+                return KeyAction::Pass;
+            }
+
+            // Allowlisted keys (e.g. volume) keep working
+            if self.passthrough_keys.contains(&vk_code) {
+                return KeyAction::Pass;
+            }
+
+            // Feed typed characters to the passphrase matcher.
+            // The keys are still swallowed, the last one included.
+            if is_keydown {
+                if let Some(matcher) = self.passphrase.as_mut() {
+                    let matched = match vk_to_passphrase_char(vk_code) {
+                        Some(c) => matcher.feed(c),
+                        None => {
+                            matcher.reset();
+                            false
+                        }
+                    };
+                    if matched {
+                        self.was_locked = false;
+                        debug!("Passphrase entered, unlocking");
+                        return KeyAction::Unlock(Trigger::Passphrase);
+                    }
+                }
+            }
+
+            // Block everything else, unless only the mouse is locked
+            if self.block_keyboard {
+                debug!("Blocking key: vk={:#x}", vk_code);
+                if is_keydown {
+                    self.state.blocked_keys.fetch_add(1, Ordering::Relaxed);
+                }
+                return KeyAction::Swallow;
+            }
+        }
+        KeyAction::Pass
+    }
+}
+
 /// Low-level keyboard hook procedure
 unsafe extern "system" fn keyboard_hook_proc(
     code: i32,
@@ -155,52 +690,83 @@ unsafe extern "system" fn keyboard_hook_proc(
     if code >= 0 {
         let kb_struct = &*(lparam.0 as *const KBDLLHOOKSTRUCT);
         let vk_code = kb_struct.vkCode;
+
+        // Our own LED indicator presses must reach the system even while locked
+        if kb_struct.dwExtraInfo == crate::led::INJECTED_MARKER {
+            return CallNextHookEx(None, code, wparam, lparam);
+        }
         let is_keydown = wparam.0 == WM_KEYDOWN as usize || wparam.0 == WM_SYSKEYDOWN as usize;
+        let is_keyup = wparam.0 == WM_KEYUP as usize || wparam.0 == WM_SYSKEYUP as usize;
 
-        // Use Option to propagate the return value from the closure
         // WHY: Returns inside HOOK_STATE.with() only return from the closure,
-        // not from keyboard_hook_proc. We must capture the result and return it
-        // from the outer function.
-        let result: Option<LRESULT> = HOOK_STATE.with(|hs| {
-            if let Some(hook_state) = hs.borrow().as_ref() {
-                let is_locked = hook_state.state.locked.load(Ordering::SeqCst);
-
-                // Check for hotkey press (only on keydown, not modifiers themselves)
-                if is_keydown && !is_modifier_vk(vk_code) {
-                    if vk_code == hook_state.hotkey_vk
-                        && check_modifiers(hook_state.hotkey_modifiers)
-                    {
-                        // Toggle lock state
-                        let new_state = !is_locked;
-                        hook_state.state.locked.store(new_state, Ordering::SeqCst);
-                        debug!("Hotkey pressed, locked={}", new_state);
-
-                        // Block this keypress so it doesn't pass through
-                        return Some(LRESULT(1));
-                    }
+        // so the action is carried out and acted on here
+        let action = HOOK_STATE.with(|hs| {
+            let mut hs = hs.borrow_mut();
+            let hook_state = hs.as_mut()?;
+            let action = hook_state.key_event(vk_code, is_keydown, is_keyup, Instant::now(), &check_modifiers);
+            if let KeyAction::Unlock(trigger) = action {
+                hook_state.state.lock_pending.store(false, Ordering::SeqCst);
+                hook_state.state.set_locked(false, trigger);
+            }
+            Some(action)
+        });
+
+        if matches!(action, Some(KeyAction::Swallow | KeyAction::Unlock(_))) {
+            return LRESULT(1);
+        }
+    }
+
+    CallNextHookEx(None, code, wparam, lparam)
+}
+
+/// Low-level mouse hook procedure: swallows all mouse input while locked
+/// (if the lock mode blocks the mouse) and feeds moves to the unlock gesture
+unsafe extern "system" fn mouse_hook_proc(
+    code: i32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if code >= 0 {
+        let block = MOUSE_HOOK_STATE.with(|ms| {
+            let mut ms = ms.borrow_mut();
+            let Some(mouse) = ms.as_mut() else {
+                return false;
+            };
+            let is_locked =
+                mouse.state.locked.load(Ordering::SeqCst) && !mouse.state.peeking.load(Ordering::SeqCst);
+            if !is_locked {
+                if let Some(recognizer) = mouse.gesture.as_mut() {
+                    recognizer.reset();
                 }
+                return false;
+            }
 
-                // If locked, block all keys except:
-                // - The unlock hotkey modifiers (so user can press the combo)
-                // - Ctrl+Alt+Del (can't be blocked anyway, OS-level)
-                if is_locked {
-                    // Allow modifier keys through so user can build up the hotkey combo
-                    if is_modifier_vk(vk_code) {
-                        // Pass through modifier keys
-                        return None; // Let it fall through to CallNextHookEx
+            if wparam.0 == WM_MOUSEMOVE as usize {
+                if let Some(recognizer) = mouse.gesture.as_mut() {
+                    let info = &*(lparam.0 as *const MSLLHOOKSTRUCT);
+                    let (x, y) = (f64::from(info.pt.x), f64::from(info.pt.y));
+                    // WHY: A swallowed move leaves the cursor where it was,
+                    // so each event only carries the offset from there
+                    mouse.pointer = if mouse.block_mouse {
+                        let mut cursor = windows::Win32::Foundation::POINT::default();
+                        let _ = GetCursorPos(&mut cursor);
+                        (
+                            mouse.pointer.0 + x - f64::from(cursor.x),
+                            mouse.pointer.1 + y - f64::from(cursor.y),
+                        )
+                    } else {
+                        (x, y)
+                    };
+                    if recognizer.feed(mouse.pointer.0, mouse.pointer.1, std::time::Instant::now()) {
+                        mouse.state.set_locked(false, Trigger::Gesture);
+                        debug!("Unlock gesture drawn, unlocking");
                     }
-
-                    // Block everything else
-                    debug!("Blocking key: vk={:#x}", vk_code);
-                    return Some(LRESULT(1));
                 }
             }
-            None
+            mouse.block_mouse
         });
-
-        // If the closure determined we should block, return that result
-        if let Some(lresult) = result {
-            return lresult;
+        if block {
+            return LRESULT(1);
         }
     }
 
@@ -283,3 +849,47 @@ pub const BLOCKED_KEYS: &[u16] = &[
     0x2C, // VK_SNAPSHOT (Print Screen)
     0x03, // VK_CANCEL (Ctrl+Break)
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_hotkey_sequence;
+    use crate::panic_unlock::DEFAULT_PANIC_HOTKEY;
+
+    /// Hook state for `config`, with the lock engaged
+    fn locked_hook(config: &Config) -> HookState {
+        let state = Arc::new(AppState::default());
+        state.set_locked(true, Trigger::Hotkey);
+        HookState::new(state, config, config.parsed_hotkeys())
+    }
+
+    /// Press a one-step `hotkey`, holding exactly its modifiers
+    fn press(hook: &mut HookState, hotkey: &str) -> KeyAction {
+        let [(modifiers, vk)] = parse_hotkey_sequence(hotkey).unwrap()[..] else {
+            panic!("{} is more than one step", hotkey);
+        };
+        hook.key_event(vk, true, false, Instant::now(), &|held| held == modifiers)
+    }
+
+    #[test]
+    fn test_panic_unlock_beats_half_typed_passphrase() {
+        /// WHY: A passphrase makes the hotkey lock-only; the panic hotkey must still get you out,
+        /// even with the passphrase half typed.
+        let config = Config {
+            unlock_passphrase: Some("meow".to_string()),
+            ..Config::default()
+        };
+        let mut hook = locked_hook(&config);
+        assert_eq!(press(&mut hook, "m"), KeyAction::Swallow);
+        assert_eq!(press(&mut hook, "e"), KeyAction::Swallow);
+        assert_eq!(press(&mut hook, "ctrl+b"), KeyAction::Swallow);
+        assert_eq!(press(&mut hook, DEFAULT_PANIC_HOTKEY), KeyAction::Unlock(Trigger::Panic));
+    }
+
+    #[test]
+    fn test_hotkey_unlocks_without_passphrase() {
+        /// WHY: The lock-only hotkey above is down to the passphrase, not the hook ignoring it.
+        let mut hook = locked_hook(&Config::default());
+        assert_eq!(press(&mut hook, "ctrl+b"), KeyAction::Unlock(Trigger::Hotkey));
+    }
+}
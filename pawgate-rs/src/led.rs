@@ -0,0 +1,141 @@
+//! Keyboard LED lock indicator
+//!
+//! Turns the Scroll Lock or Num Lock LED on while the keyboard is locked and
+//! puts it back the way it was on unlock. The LED follows the key's toggle
+//! state, so it's switched by injecting a key press.
+
+use crate::config::LedIndicator;
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetKeyState, SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYBD_EVENT_FLAGS,
+    KEYEVENTF_KEYUP, VIRTUAL_KEY, VK_NUMLOCK, VK_SCROLL,
+};
+
+/// `dwExtraInfo` of the key presses PawGate injects, so its own keyboard
+/// hook lets them through while locked
+pub const INJECTED_MARKER: usize = 0x5057_4754; // "PWGT"
+
+/// Remembers an LED's state from before the lock so unlock can restore it
+#[derive(Debug, Default)]
+pub struct LedRestore {
+    saved: Option<bool>,
+}
+
+impl LedRestore {
+    /// Record the LED state at lock time; returns true if the key must be
+    /// pressed to turn the LED on
+    pub fn lock(&mut self, currently_on: bool) -> bool {
+        if self.saved.is_none() {
+            self.saved = Some(currently_on);
+        }
+        !currently_on
+    }
+
+    /// Returns true if the key must be pressed to restore the pre-lock state
+    pub fn unlock(&mut self, currently_on: bool) -> bool {
+        match self.saved.take() {
+            Some(was_on) => currently_on != was_on,
+            None => false,
+        }
+    }
+}
+
+/// Drives the configured LED across lock transitions
+pub struct KeyboardLed {
+    vk: Option<VIRTUAL_KEY>,
+    restore: LedRestore,
+}
+
+impl KeyboardLed {
+    pub fn new(indicator: LedIndicator) -> Self {
+        let vk = match indicator {
+            LedIndicator::None => None,
+            LedIndicator::ScrollLock => Some(VK_SCROLL),
+            LedIndicator::NumLock => Some(VK_NUMLOCK),
+        };
+        Self {
+            vk,
+            restore: LedRestore::default(),
+        }
+    }
+
+    /// Update the LED for a lock state change
+    pub fn set_locked(&mut self, locked: bool) {
+        let Some(vk) = self.vk else {
+            return;
+        };
+        let on = is_toggled(vk);
+        let press = if locked {
+            self.restore.lock(on)
+        } else {
+            self.restore.unlock(on)
+        };
+        if press {
+            tap_key(vk);
+        }
+    }
+}
+
+/// Whether a toggle key (and its LED) is currently on
+fn is_toggled(vk: VIRTUAL_KEY) -> bool {
+    unsafe { GetKeyState(i32::from(vk.0)) & 1 != 0 }
+}
+
+/// Inject a press and release of `vk`
+fn tap_key(vk: VIRTUAL_KEY) {
+    let input = |flags: KEYBD_EVENT_FLAGS| INPUT {
+        r#type: INPUT_KEYBOARD,
+        Anonymous: INPUT_0 {
+            ki: KEYBDINPUT {
+                wVk: vk,
+                wScan: 0,
+                dwFlags: flags,
+                time: 0,
+                dwExtraInfo: INJECTED_MARKER,
+            },
+        },
+    };
+    let inputs = [input(KEYBD_EVENT_FLAGS(0)), input(KEYEVENTF_KEYUP)];
+
+    let sent = unsafe { SendInput(&inputs, std::mem::size_of::<INPUT>() as i32) };
+    if sent as usize != inputs.len() {
+        log::warn!("Failed to toggle keyboard LED (key {:#04X})", vk.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_led_turned_on_and_restored_off() {
+        /// WHY: An LED that was off must light up while locked and go dark again after.
+        let mut restore = LedRestore::default();
+        assert!(restore.lock(false), "Press to turn it on");
+        assert!(restore.unlock(true), "Press again to turn it back off");
+    }
+
+    #[test]
+    fn test_led_already_on_left_alone() {
+        /// WHY: If the user already had Num Lock on, locking and unlocking must not turn it off.
+        let mut restore = LedRestore::default();
+        assert!(!restore.lock(true));
+        assert!(!restore.unlock(true));
+    }
+
+    #[test]
+    fn test_led_restore_respects_changes_while_locked() {
+        /// WHY: Unlock restores the pre-lock state, whatever happened in between.
+        let mut restore = LedRestore::default();
+        assert!(restore.lock(false));
+        // Something turned it off again while locked; it was off before, so leave it
+        assert!(!restore.unlock(false));
+    }
+
+    #[test]
+    fn test_unlock_without_lock_does_nothing() {
+        /// WHY: Starting PawGate unlocked must not touch the user's LEDs.
+        let mut restore = LedRestore::default();
+        assert!(!restore.unlock(true));
+        assert!(!restore.unlock(false));
+    }
+}
@@ -0,0 +1,356 @@
+//! PawGate - Windows keyboard locking utility
+//!
+//! Prevents accidental keyboard input (e.g., from pets walking on keyboard).
+//! Uses a configurable hotkey to toggle lock state.
+//!
+//! The `pawgate` binary is a thin wrapper around [`PawGate`]; other Rust
+//! programs can embed the same lock:
+//!
+//! ```no_run
+//! use pawgate::{config::Config, PawGate};
+//!
+//! let mut gate = PawGate::with_config(Config::default());
+//! gate.register_hotkeys().expect("failed to install the keyboard hook");
+//! gate.run().expect("tray loop failed");
+//! ```
+
+mod animation;
+mod app_rules;
+mod auto_unlock;
+pub mod autostart;
+pub mod cli;
+pub mod config;
+pub mod console_log;
+mod countdown;
+mod cursor;
+mod debounce;
+mod double_tap;
+pub mod events;
+mod failsafe;
+mod gesture;
+mod hold;
+#[cfg(feature = "http")]
+mod http;
+mod i18n;
+mod icon_flash;
+mod idle;
+pub mod ipc;
+mod keyboard;
+mod led;
+pub mod lock_guard;
+pub mod lock_history;
+mod lock_state;
+mod mash;
+mod notify;
+mod overlay;
+mod overlay_image;
+mod overlay_pattern;
+mod overlay_target;
+mod panic_unlock;
+mod passphrase;
+mod peek;
+pub mod platform;
+mod power;
+pub mod register_failure;
+mod sequence;
+mod session;
+mod settings_dialog;
+mod shutdown;
+mod snooze;
+mod sound;
+mod stats;
+mod tap_hold;
+mod target_window;
+#[cfg(test)]
+mod test_util;
+mod toast;
+mod topmost;
+mod tray;
+mod tray_menu;
+
+use config::Config;
+use events::Trigger;
+use log::info;
+use platform::{Platform, WindowsPlatform};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU32, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Called with the new lock state after every lock/unlock transition
+pub type StateCallback = Box<dyn Fn(bool) + Send + Sync>;
+
+/// Global state shared across threads
+pub struct AppState {
+    /// Whether keyboard is currently locked
+    pub locked: AtomicBool,
+    /// Whether a lock has been requested and is waiting out `lock_delay_ms`
+    pub lock_pending: AtomicBool,
+    /// What caused the latest lock state change (an `events::Trigger` as u8)
+    pub lock_trigger: AtomicU8,
+    /// Keystrokes swallowed since the last unlock
+    pub blocked_keys: AtomicU32,
+    /// Whether a locked keyboard is briefly letting input through (see `peek_hotkey`)
+    pub peeking: AtomicBool,
+    /// Opacity steps requested with the opacity hotkeys, not yet applied by the tray
+    pub opacity_steps: AtomicI32,
+    /// Whether the hotkey and auto-lock are snoozed
+    pub snoozed: AtomicBool,
+    /// Whether the foreground app's rule disables the hotkey and auto-lock
+    pub app_disabled: AtomicBool,
+    /// Snooze minutes requested from the CLI (`snooze::NO_REQUEST` if none)
+    pub snooze_request: AtomicU32,
+    /// Seconds of a test lock requested from the CLI (`auto_unlock::NO_REQUEST` if none)
+    pub test_lock_request: AtomicU32,
+    /// Whether this Windows session is on screen (see `session.rs`)
+    pub session_active: AtomicBool,
+    /// Set by the keyboard thread when no hotkey could be registered and
+    /// `on_register_failure` is notify; cleared once the tray has said so
+    pub hotkey_unavailable: AtomicBool,
+    /// Signal the keyboard thread to replace its hooks
+    pub reinstall_hooks: AtomicBool,
+    /// Liveness of the tray loop, checked by the fail-safe watchdog
+    pub heartbeat: failsafe::Heartbeat,
+    /// Signal to quit the application
+    pub should_quit: AtomicBool,
+    /// Whether a quit while locked waits for its confirmation keypress
+    pub quit_pending: AtomicBool,
+    /// Set by the keyboard hook when that keypress confirmed the quit
+    pub quit_confirmed: AtomicBool,
+    /// Signal to show settings dialog
+    pub show_settings: AtomicBool,
+    /// Callbacks registered with `on_state_change`
+    state_callbacks: Mutex<Vec<StateCallback>>,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Change the lock state, recording what caused it for the event log
+    pub fn set_locked(&self, locked: bool, trigger: events::Trigger) {
+        // WHY: Store the trigger first so a reader that sees the new lock
+        // state also sees its cause.
+        self.lock_trigger.store(trigger as u8, Ordering::SeqCst);
+        let was_locked = self.locked.swap(locked, Ordering::SeqCst);
+        if was_locked != locked {
+            info!("Keyboard {} ({:?})", if locked { "locked" } else { "unlocked" }, trigger);
+            for callback in self.state_callbacks.lock().unwrap().iter() {
+                callback(locked);
+            }
+        }
+    }
+
+    /// Register a callback for every lock/unlock transition
+    ///
+    /// Callbacks run on whichever thread changed the state, including the
+    /// keyboard hook, so they should return quickly and must not call
+    /// `set_locked` or `on_state_change` themselves.
+    pub fn on_state_change(&self, callback: StateCallback) {
+        self.state_callbacks.lock().unwrap().push(callback);
+    }
+}
+
+// WHY: Implementing Default allows using AppState::default() and satisfies
+// the clippy::new_without_default lint for types that have a new() method.
+impl Default for AppState {
+    fn default() -> Self {
+        Self {
+            locked: AtomicBool::new(false),
+            lock_pending: AtomicBool::new(false),
+            lock_trigger: AtomicU8::new(events::Trigger::Hotkey as u8),
+            blocked_keys: AtomicU32::new(0),
+            peeking: AtomicBool::new(false),
+            opacity_steps: AtomicI32::new(0),
+            snoozed: AtomicBool::new(false),
+            app_disabled: AtomicBool::new(false),
+            snooze_request: AtomicU32::new(snooze::NO_REQUEST),
+            test_lock_request: AtomicU32::new(auto_unlock::NO_REQUEST),
+            session_active: AtomicBool::new(true),
+            hotkey_unavailable: AtomicBool::new(false),
+            reinstall_hooks: AtomicBool::new(false),
+            heartbeat: failsafe::Heartbeat::new(),
+            should_quit: AtomicBool::new(false),
+            quit_pending: AtomicBool::new(false),
+            quit_confirmed: AtomicBool::new(false),
+            show_settings: AtomicBool::new(false),
+            state_callbacks: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+/// A PawGate instance: shared lock state plus the [`Platform`] that enforces it
+///
+/// `lock()`, `unlock()` and `is_locked()` only touch the shared state, so
+/// they work (and can be tested) without hooks or a message loop. Input is
+/// actually blocked once `register_hotkeys()` has started the platform, and
+/// the tray icon and overlay appear while `run()` is running.
+pub struct PawGate {
+    state: Arc<AppState>,
+    config: Config,
+    platform: Box<dyn Platform>,
+}
+
+impl Default for PawGate {
+    fn default() -> Self {
+        Self::with_config(Config::default())
+    }
+}
+
+impl PawGate {
+    /// Create an instance with the default settings
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an instance with the given settings
+    pub fn with_config(config: Config) -> Self {
+        Self::with_platform(config, WindowsPlatform::default())
+    }
+
+    /// Create an instance that enforces the lock through `platform`, e.g. a
+    /// [`platform::SimulatedPlatform`] to test without touching the real keyboard
+    pub fn with_platform(config: Config, platform: impl Platform + 'static) -> Self {
+        Self {
+            state: Arc::new(AppState::new()),
+            config,
+            platform: Box::new(platform),
+        }
+    }
+
+    /// The settings this instance was created with
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// The shared state, e.g. for a [`lock_guard::LockGuard`]
+    pub fn state(&self) -> Arc<AppState> {
+        Arc::clone(&self.state)
+    }
+
+    /// Start the platform: on Windows, install the keyboard (and, per
+    /// `lock_mode`, mouse) hooks on a background thread, which also watches
+    /// for the configured hotkeys
+    ///
+    /// The Windows platform also installs a panic hook that removes the
+    /// hooks, so a crash can't leave the keyboard blocked. Calling this again
+    /// does nothing, and neither does calling it while the config has
+    /// `enabled` set to false. Fails if the platform can't start, e.g. the
+    /// keyboard hook couldn't be installed.
+    pub fn register_hotkeys(&mut self) -> Result<(), String> {
+        if self.platform.is_running() {
+            return Ok(());
+        }
+        if !self.config.enabled {
+            info!("PawGate is disabled in the config; not registering hotkeys");
+            return Ok(());
+        }
+        self.platform.start(Arc::clone(&self.state), &self.config)
+    }
+
+    /// Lock now, skipping any `lock_delay_ms` countdown
+    pub fn lock(&self) {
+        self.state.lock_pending.store(false, Ordering::SeqCst);
+        self.state.set_locked(true, Trigger::Api);
+    }
+
+    /// Unlock now
+    pub fn unlock(&self) {
+        self.state.lock_pending.store(false, Ordering::SeqCst);
+        self.state.set_locked(false, Trigger::Api);
+    }
+
+    /// Whether `register_hotkeys()` has started the platform
+    pub fn hotkeys_registered(&self) -> bool {
+        self.platform.is_running()
+    }
+
+    /// Whether input is currently locked
+    pub fn is_locked(&self) -> bool {
+        self.state.locked.load(Ordering::SeqCst)
+    }
+
+    /// Call `callback` with the new state whenever the lock turns on or off,
+    /// whatever caused it (hotkey, idle, CLI, this API, ...)
+    ///
+    /// Handy for keeping a status bar or external indicator in sync.
+    /// Setting the state it already has doesn't count as a transition.
+    pub fn on_state_change(&self, callback: StateCallback) {
+        self.state.on_state_change(callback);
+    }
+
+    /// Ask `run()` and the background threads to stop
+    pub fn quit(&self) {
+        self.state.should_quit.store(true, Ordering::SeqCst);
+    }
+
+    /// Run the tray icon and overlay on this thread until Exit is chosen or
+    /// `quit()` is called, then stop the background threads
+    ///
+    /// Starts the fail-safe watchdog and, if configured, the idle auto-lock
+    /// and the app rule watcher.
+    /// Must be called on a thread that can own windows (normally main).
+    pub fn run(mut self) -> Result<(), Box<dyn std::error::Error>> {
+        // Release the keyboard if the tray loop hangs while locked
+        let watchdog_state = Arc::clone(&self.state);
+        let watchdog_thread = std::thread::spawn(move || {
+            failsafe::run_watchdog(watchdog_state);
+        });
+
+        // Start idle auto-lock watcher if enabled
+        let idle_thread = self.config.auto_lock_idle_secs.filter(|_| self.config.enabled).map(|idle_secs| {
+            let idle_state = Arc::clone(&self.state);
+            std::thread::spawn(move || {
+                idle::run_idle_monitor(idle_state, idle_secs);
+            })
+        });
+
+        // Start the foreground app watcher if any rules are set
+        let app_rules_thread = (self.config.enabled && !self.config.app_rules.is_empty()).then(|| {
+            let rules_state = Arc::clone(&self.state);
+            let rules = self.config.app_rules.clone();
+            std::thread::spawn(move || {
+                app_rules::run_app_rule_monitor(rules_state, rules);
+            })
+        });
+
+        // Start the local status server if a port is set and it's built in
+        #[cfg(feature = "http")]
+        let http_thread = self.config.http_port.map(|port| {
+            let http_state = Arc::clone(&self.state);
+            std::thread::spawn(move || {
+                http::run_http_server(http_state, port);
+            })
+        });
+        #[cfg(not(feature = "http"))]
+        let http_thread: Option<std::thread::JoinHandle<()>> = {
+            if self.config.http_port.is_some() {
+                log::warn!("http_port is set, but this build has no HTTP support (build with --features http)");
+            }
+            None
+        };
+
+        // Run the main UI loop (tray icon + overlay management)
+        // This runs on the main thread to handle Windows messages properly.
+        // On quit it unlocks and stops the platform itself.
+        let result = tray::run_tray_loop(Arc::clone(&self.state), self.config.clone(), self.platform.as_mut());
+
+        // Signal background threads to stop
+        self.quit();
+
+        // Wait for background threads to finish
+        self.platform.stop();
+        let _ = watchdog_thread.join();
+        if let Some(thread) = idle_thread {
+            let _ = thread.join();
+        }
+        if let Some(thread) = app_rules_thread {
+            let _ = thread.join();
+        }
+        if let Some(thread) = http_thread {
+            let _ = thread.join();
+        }
+
+        info!("PawGate stopped");
+        result
+    }
+}
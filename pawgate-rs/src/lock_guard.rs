@@ -0,0 +1,206 @@
+//! Scoped lock for driving PawGate from other code
+//!
+//! A [`LockGuard`] installs the input hooks and locks when created, and
+//! unlocks and removes the hooks when dropped, so an early return can't leave
+//! the keyboard locked. The overlay follows the lock state as usual while the
+//! tray loop runs. Hooks are installed through [`InputHooks`], which tests
+//! replace with a mock.
+
+use crate::config::Config;
+use crate::events::Trigger;
+use crate::keyboard;
+use crate::AppState;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::JoinHandle;
+
+/// Installs and removes the input hooks that enforce a lock
+pub trait InputHooks {
+    /// Install the hooks; called once when the guard is created
+    fn install(&mut self) -> Result<(), String>;
+    /// Remove the hooks; called once when the guard is dropped
+    fn uninstall(&mut self);
+}
+
+/// Keeps the keyboard locked for as long as it lives
+pub struct LockGuard<H: InputHooks> {
+    state: Arc<AppState>,
+    hooks: H,
+}
+
+impl<H: InputHooks> LockGuard<H> {
+    /// Install `hooks` and lock; nothing is locked if installing fails
+    pub fn new(state: Arc<AppState>, mut hooks: H) -> Result<Self, String> {
+        hooks.install()?;
+        // Skip any grace countdown, like the other programmatic locks
+        state.lock_pending.store(false, Ordering::SeqCst);
+        state.set_locked(true, Trigger::Api);
+        Ok(Self { state, hooks })
+    }
+}
+
+impl<H: InputHooks> Drop for LockGuard<H> {
+    fn drop(&mut self) {
+        self.state.set_locked(false, Trigger::Api);
+        self.hooks.uninstall();
+    }
+}
+
+/// The real keyboard and mouse hooks, run on their own thread
+pub struct SystemHooks {
+    state: Arc<AppState>,
+    config: Config,
+    running: Option<(JoinHandle<()>, Arc<AtomicBool>)>,
+}
+
+impl SystemHooks {
+    pub fn new(state: Arc<AppState>, config: Config) -> Self {
+        Self {
+            state,
+            config,
+            running: None,
+        }
+    }
+}
+
+impl InputHooks for SystemHooks {
+    fn install(&mut self) -> Result<(), String> {
+        if self.running.is_some() {
+            return Ok(());
+        }
+
+        // WHY: Low-level hooks need a message loop on the installing thread,
+        // so they get a thread of their own that reports back once hooked.
+        let stop = Arc::new(AtomicBool::new(false));
+        let (installed_tx, installed_rx) = mpsc::channel();
+        let state = Arc::clone(&self.state);
+        let config = self.config.clone();
+        let thread_stop = Arc::clone(&stop);
+        let handle = std::thread::spawn(move || {
+            keyboard::run_keyboard_hook_until(state, config, thread_stop, installed_tx);
+        });
+
+        match installed_rx.recv() {
+            Ok(Ok(())) => {
+                self.running = Some((handle, stop));
+                Ok(())
+            }
+            Ok(Err(e)) => {
+                let _ = handle.join();
+                Err(e)
+            }
+            Err(_) => Err("Keyboard hook thread exited before installing the hook".to_string()),
+        }
+    }
+
+    fn uninstall(&mut self) {
+        if let Some((handle, stop)) = self.running.take() {
+            stop.store(true, Ordering::SeqCst);
+            let _ = handle.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    /// Records install/uninstall calls instead of touching Windows
+    struct MockHooks {
+        calls: Rc<RefCell<Vec<&'static str>>>,
+        fail: bool,
+    }
+
+    impl InputHooks for MockHooks {
+        fn install(&mut self) -> Result<(), String> {
+            self.calls.borrow_mut().push("install");
+            if self.fail {
+                Err("no hooks for you".to_string())
+            } else {
+                Ok(())
+            }
+        }
+
+        fn uninstall(&mut self) {
+            self.calls.borrow_mut().push("uninstall");
+        }
+    }
+
+    fn mock(fail: bool) -> (MockHooks, Rc<RefCell<Vec<&'static str>>>) {
+        let calls = Rc::new(RefCell::new(Vec::new()));
+        (MockHooks { calls: Rc::clone(&calls), fail }, calls)
+    }
+
+    #[test]
+    fn test_guard_locks_until_dropped() {
+        /// WHY: The whole point of the guard is that the keyboard unlocks when it goes out of scope.
+        let state = Arc::new(AppState::default());
+        let (hooks, calls) = mock(false);
+        {
+            let _guard = LockGuard::new(Arc::clone(&state), hooks).unwrap();
+            assert!(state.locked.load(Ordering::SeqCst));
+            assert_eq!(*calls.borrow(), vec!["install"]);
+        }
+        assert!(!state.locked.load(Ordering::SeqCst));
+        assert_eq!(*calls.borrow(), vec!["install", "uninstall"]);
+        assert_eq!(Trigger::from_u8(state.lock_trigger.load(Ordering::SeqCst)), Trigger::Api);
+    }
+
+    #[test]
+    fn test_guard_unlocks_on_early_return() {
+        /// WHY: `?` and panics must not leave the keyboard locked.
+        let state = Arc::new(AppState::default());
+        let (hooks, calls) = mock(false);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _guard = LockGuard::new(Arc::clone(&state), hooks).unwrap();
+            panic!("caller bailed out");
+        }));
+        assert!(result.is_err());
+        assert!(!state.locked.load(Ordering::SeqCst));
+        assert_eq!(calls.borrow().last(), Some(&"uninstall"));
+    }
+
+    #[test]
+    fn test_state_callback_sees_lock_then_unlock() {
+        /// WHY: Status bars listening for changes must see the guard's lock and its release, in order.
+        let state = Arc::new(AppState::default());
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorder = Arc::clone(&seen);
+        state.on_state_change(Box::new(move |locked| recorder.lock().unwrap().push(locked)));
+
+        let (hooks, _calls) = mock(false);
+        drop(LockGuard::new(Arc::clone(&state), hooks).unwrap());
+        assert_eq!(*seen.lock().unwrap(), vec![true, false]);
+    }
+
+    #[test]
+    fn test_state_callback_skips_non_transitions() {
+        /// WHY: Re-setting the current state isn't a change, so listeners shouldn't hear about it.
+        let state = Arc::new(AppState::default());
+        let count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = Arc::clone(&count);
+        state.on_state_change(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        state.set_locked(false, Trigger::Hotkey);
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        let (hooks, _calls) = mock(false);
+        let _guard = LockGuard::new(Arc::clone(&state), hooks).unwrap();
+        state.set_locked(true, Trigger::Hotkey);
+        assert_eq!(count.load(Ordering::SeqCst), 1, "Already locked by the guard");
+    }
+
+    #[test]
+    fn test_failed_install_does_not_lock() {
+        /// WHY: Locking without working hooks would show an overlay that blocks nothing.
+        let state = Arc::new(AppState::default());
+        let (hooks, calls) = mock(true);
+        assert!(LockGuard::new(Arc::clone(&state), hooks).is_err());
+        assert!(!state.locked.load(Ordering::SeqCst));
+        assert_eq!(*calls.borrow(), vec!["install"], "Nothing to uninstall");
+    }
+}
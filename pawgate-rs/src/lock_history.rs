@@ -0,0 +1,270 @@
+//! History of past lock sessions, for `pawgate sessions`
+//!
+//! When a lock ends, the tray loop appends one JSON object per line to
+//! ~/.pawgate/sessions.log: when the lock started and ended, how long it
+//! lasted, what started it and how many keystrokes it swallowed. Only the
+//! newest `session_history_limit` sessions are kept.
+
+use crate::config::Config;
+use crate::events::{format_timestamp, Trigger};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One finished lock session, as a line of sessions.log
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionRecord {
+    /// UTC start time in RFC 3339 format, e.g. "2024-05-01T12:30:00Z"
+    pub start: String,
+    /// UTC end time in the same format
+    pub end: String,
+    /// Seconds the lock lasted
+    pub duration_secs: u64,
+    /// What started the lock
+    pub trigger: Trigger,
+    /// Keystrokes swallowed during the lock
+    pub blocked_keys: u64,
+}
+
+impl SessionRecord {
+    /// A session that started and ended at the given Unix seconds
+    pub fn new(start_secs: u64, end_secs: u64, trigger: Trigger, blocked_keys: u64) -> Self {
+        Self {
+            start: format_timestamp(start_secs),
+            end: format_timestamp(end_secs),
+            duration_secs: end_secs.saturating_sub(start_secs),
+            trigger,
+            blocked_keys,
+        }
+    }
+
+    /// Serialize as a single JSON line (without the trailing newline)
+    pub fn to_json_line(&self) -> String {
+        serde_json::to_string(self).expect("SessionRecord is always serializable")
+    }
+}
+
+/// Totals over a list of sessions
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Summary {
+    pub sessions: usize,
+    /// Seconds locked across all sessions
+    pub total_secs: u64,
+    /// The longest single session, in seconds
+    pub longest_secs: u64,
+    pub blocked_keys: u64,
+}
+
+/// Add up `records`
+pub fn summarize(records: &[SessionRecord]) -> Summary {
+    records.iter().fold(Summary::default(), |summary, record| Summary {
+        sessions: summary.sessions + 1,
+        total_secs: summary.total_secs.saturating_add(record.duration_secs),
+        longest_secs: summary.longest_secs.max(record.duration_secs),
+        blocked_keys: summary.blocked_keys.saturating_add(record.blocked_keys),
+    })
+}
+
+/// Path of the session history (~/.pawgate/sessions.log)
+pub fn sessions_path() -> PathBuf {
+    Config::config_dir().join("sessions.log")
+}
+
+/// Path of the rotated-out history next to `path` (sessions.log.1)
+fn rotated_path(path: &Path) -> PathBuf {
+    let mut rotated = OsString::from(path.as_os_str());
+    rotated.push(".1");
+    PathBuf::from(rotated)
+}
+
+/// Read the newest `limit` sessions, oldest first; missing files are empty
+///
+/// Lines that don't parse (e.g. from a crash mid-write) are skipped.
+pub fn load(path: &Path, limit: usize) -> Vec<SessionRecord> {
+    let mut records = load_file(&rotated_path(path));
+    records.extend(load_file(path));
+    let excess = records.len().saturating_sub(limit);
+    records.split_off(excess)
+}
+
+/// The records in one history file
+fn load_file(path: &Path) -> Vec<SessionRecord> {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(record) => Some(record),
+            Err(e) => {
+                log::warn!("Skipping unreadable line in {}: {}", path.display(), e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Append `record`, rotating the file to sessions.log.1 once it holds
+/// `limit` sessions
+///
+/// WHY: Appending never rewrites past sessions, so a crash mid-write costs at
+/// most the new line. The current file and sessions.log.1 together always
+/// hold at least the newest `limit` sessions, which is all [`load`] returns.
+pub fn append(path: &Path, record: &SessionRecord, limit: usize) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let lines = fs::read(path).map_or(0, |contents| contents.iter().filter(|&&byte| byte == b'\n').count());
+    if lines >= limit {
+        fs::rename(path, rotated_path(path))?;
+    }
+
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", record.to_json_line())
+}
+
+/// Seconds as "1h 02m 03s", "4m 05s" or "6s"
+pub fn format_duration(secs: u64) -> String {
+    let (hours, minutes, seconds) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if hours > 0 {
+        format!("{}h {:02}m {:02}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// The table `pawgate sessions` prints: one row per session, then totals
+pub fn format_table(records: &[SessionRecord]) -> String {
+    if records.is_empty() {
+        return "No lock sessions recorded yet.".to_string();
+    }
+    let mut table = format!("{:<22}{:>12}  {:<12}{:>8}\n", "Start", "Duration", "Trigger", "Blocked");
+    for record in records {
+        let trigger = serde_json::to_value(record.trigger)
+            .ok()
+            .and_then(|value| value.as_str().map(str::to_string))
+            .unwrap_or_default();
+        table.push_str(&format!(
+            "{:<22}{:>12}  {:<12}{:>8}\n",
+            record.start,
+            format_duration(record.duration_secs),
+            trigger,
+            record.blocked_keys
+        ));
+    }
+    let summary = summarize(records);
+    table.push_str(&format!(
+        "\n{} sessions, {} locked in total (longest {}), {} keys blocked",
+        summary.sessions,
+        format_duration(summary.total_secs),
+        format_duration(summary.longest_secs),
+        summary.blocked_keys
+    ));
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    #[test]
+    fn test_record_serializes_as_json_line() {
+        /// WHY: The field names and values are the format people grep and parse.
+        let record = SessionRecord::new(1_714_566_600, 1_714_566_725, Trigger::AutoIdle, 37);
+        let line = record.to_json_line();
+        assert_eq!(
+            line,
+            r#"{"start":"2024-05-01T12:30:00Z","end":"2024-05-01T12:32:05Z","duration_secs":125,"trigger":"auto_idle","blocked_keys":37}"#
+        );
+        assert_eq!(serde_json::from_str::<SessionRecord>(&line).unwrap(), record, "Lines read back unchanged");
+    }
+
+    #[test]
+    fn test_clock_going_backwards() {
+        /// WHY: A clock adjusted mid-lock must not underflow the duration.
+        assert_eq!(SessionRecord::new(1000, 900, Trigger::Hotkey, 0).duration_secs, 0);
+    }
+
+    #[test]
+    fn test_summary_totals() {
+        /// WHY: The total locked time is the headline of the audit view.
+        let records = [
+            SessionRecord::new(0, 90, Trigger::Hotkey, 12),
+            SessionRecord::new(1000, 4600, Trigger::AutoIdle, 300),
+            SessionRecord::new(5000, 5005, Trigger::Menu, 0),
+        ];
+        assert_eq!(
+            summarize(&records),
+            Summary {
+                sessions: 3,
+                total_secs: 3695,
+                longest_secs: 3600,
+                blocked_keys: 312,
+            }
+        );
+        assert_eq!(summarize(&[]), Summary::default());
+    }
+
+    #[test]
+    fn test_format_duration() {
+        /// WHY: Durations must read naturally from seconds to hours.
+        assert_eq!(format_duration(6), "6s");
+        assert_eq!(format_duration(245), "4m 05s");
+        assert_eq!(format_duration(3723), "1h 02m 03s");
+        assert_eq!(format_duration(90_000), "25h 00m 00s");
+    }
+
+    #[test]
+    fn test_table_ends_with_totals() {
+        /// WHY: `pawgate sessions` must list each session and sum them up.
+        let records = [
+            SessionRecord::new(0, 90, Trigger::Hotkey, 12),
+            SessionRecord::new(1000, 1060, Trigger::AutoIdle, 3),
+        ];
+        let table = format_table(&records);
+        assert_eq!(table.lines().count(), 5, "{}", table);
+        assert!(table.contains("1970-01-01T00:00:00Z"), "{}", table);
+        assert!(table.contains("auto_idle"), "{}", table);
+        assert!(table.ends_with("2 sessions, 2m 30s locked in total (longest 1m 30s), 15 keys blocked"), "{}", table);
+        assert_eq!(format_table(&[]), "No lock sessions recorded yet.");
+    }
+
+    #[test]
+    fn test_history_capped_at_limit() {
+        /// WHY: The log must not grow without bound; the newest sessions are the ones worth keeping.
+        let dir = ScratchDir::new("sessions-cap");
+        let path = dir.join("sessions.log");
+        assert!(load(&path, 3).is_empty(), "Missing file is empty");
+        let blocked = |path: &Path| load(path, 3).iter().map(|r| r.blocked_keys).collect::<Vec<_>>();
+
+        for i in 0..5 {
+            append(&path, &SessionRecord::new(i * 100, i * 100 + 10, Trigger::Hotkey, i), 3).unwrap();
+            let kept = blocked(&path);
+            assert_eq!(kept.len(), (i as usize + 1).min(3));
+            assert_eq!(kept.last(), Some(&i), "The newest session is always kept");
+        }
+        assert_eq!(blocked(&path), vec![2, 3, 4]);
+
+        for i in 5..7 {
+            append(&path, &SessionRecord::new(i * 100, i * 100 + 10, Trigger::Hotkey, i), 3).unwrap();
+        }
+        assert_eq!(blocked(&path), vec![4, 5, 6], "Exactly `limit` sessions across rotations");
+    }
+
+    #[test]
+    fn test_load_skips_damaged_lines() {
+        /// WHY: One torn line must not hide the rest of the history.
+        let dir = ScratchDir::new("sessions-damaged");
+        let path = dir.join("sessions.log");
+        let good = SessionRecord::new(0, 10, Trigger::Cli, 1).to_json_line();
+        fs::write(&path, format!("{}\n{{\"start\":\n\n{}\n", good, good)).unwrap();
+        assert_eq!(load(&path, 10).len(), 2);
+    }
+}
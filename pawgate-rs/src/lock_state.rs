@@ -0,0 +1,122 @@
+//! Coming back locked after a restart
+//!
+//! While `restore_lock_state` is on, the tray loop records every lock and
+//! unlock in ~/.pawgate/lock_state.json. At startup the file is read and
+//! removed, and the lock re-engages once the tray loop is running, so a
+//! start that fails partway never leaves a lock behind for the next one. The
+//! fail-safes remove the file too, so a crash or hang doesn't come back
+//! locked.
+
+use crate::config::{self, Config};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Contents of the lock state file
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct SavedLockState {
+    locked: bool,
+}
+
+/// Path of the lock state file (~/.pawgate/lock_state.json)
+pub fn lock_state_path() -> PathBuf {
+    Config::config_dir().join("lock_state.json")
+}
+
+/// Record whether PawGate is locked
+pub fn save(path: &Path, locked: bool) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let contents = serde_json::to_string(&SavedLockState { locked }).expect("SavedLockState is always serializable");
+    config::write_atomic(path, contents.as_bytes())
+}
+
+/// Whether the file says PawGate was locked; a missing or unreadable file means unlocked
+///
+/// WHY: When in doubt, starting unlocked is the safe side.
+pub fn load(path: &Path) -> bool {
+    let Ok(contents) = fs::read_to_string(path) else {
+        return false;
+    };
+    serde_json::from_str::<SavedLockState>(&contents)
+        .map(|saved| saved.locked)
+        .unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable lock state file {}: {}", path.display(), e);
+            false
+        })
+}
+
+/// Remove the file, so the next start comes up unlocked
+pub fn forget(path: &Path) {
+    if let Err(e) = fs::remove_file(path) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            log::warn!("Failed to remove lock state file {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Read the saved state and remove the file, so it's only acted on once
+pub fn take(path: &Path) -> bool {
+    let locked = load(path);
+    forget(path);
+    locked
+}
+
+/// Whether to lock again at startup, given `restore_lock_state`, the
+/// `enabled` kill switch and whether the previous run was locked
+pub fn should_restore(restore_enabled: bool, app_enabled: bool, was_locked: bool) -> bool {
+    restore_enabled && app_enabled && was_locked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        /// WHY: A restart while locked must see the lock it left behind.
+        let dir = ScratchDir::new("lock-state-round-trip");
+        let path = dir.join("lock_state.json");
+        assert!(!load(&path), "Missing file means unlocked");
+
+        save(&path, true).unwrap();
+        assert!(load(&path));
+        save(&path, false).unwrap();
+        assert!(!load(&path));
+    }
+
+    #[test]
+    fn test_take_only_restores_once() {
+        /// WHY: If the start that restored the lock fails, the next start must come up unlocked.
+        let dir = ScratchDir::new("lock-state-take");
+        let path = dir.join("lock_state.json");
+        save(&path, true).unwrap();
+        assert!(take(&path));
+        assert!(!path.exists());
+        assert!(!take(&path));
+    }
+
+    #[test]
+    fn test_damaged_file_means_unlocked() {
+        /// WHY: A corrupt file must never lock the user out.
+        let dir = ScratchDir::new("lock-state-damaged");
+        let path = dir.join("lock_state.json");
+        fs::write(&path, "{not json").unwrap();
+        assert!(!load(&path));
+        forget(&path);
+        forget(&path);
+        assert!(!path.exists(), "Forgetting twice is fine");
+    }
+
+    #[test]
+    fn test_should_restore_decision() {
+        /// WHY: Only a locked previous run restores, and the config can opt out or be switched off.
+        assert!(should_restore(true, true, true));
+        assert!(!should_restore(true, true, false));
+        assert!(!should_restore(false, true, true), "restore_lock_state: false");
+        assert!(!should_restore(true, false, true), "PawGate is disabled");
+    }
+}
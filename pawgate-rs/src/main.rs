@@ -1,53 +1,49 @@
-//! PawGate - Windows keyboard locking utility
+//! PawGate executable
 //!
-//! Prevents accidental keyboard input (e.g., from pets walking on keyboard).
-//! Uses a configurable hotkey to toggle lock state.
+//! Runs a CLI subcommand against the running instance, or starts PawGate in
+//! the system tray via [`pawgate::PawGate`].
 
 #![windows_subsystem = "windows"]
 
-mod config;
-mod keyboard;
-mod overlay;
-mod tray;
-mod settings_dialog;
-
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
-use log::{info, error};
+use log::{debug, info, error};
+use pawgate::{autostart, cli, config, console_log, ipc, lock_history, register_failure, PawGate};
 use single_instance::SingleInstance;
 
-/// Global state shared across threads
-pub struct AppState {
-    /// Whether keyboard is currently locked
-    pub locked: AtomicBool,
-    /// Signal to quit the application
-    pub should_quit: AtomicBool,
-    /// Signal to show settings dialog
-    pub show_settings: AtomicBool,
-}
-
-impl AppState {
-    pub fn new() -> Self {
-        Self::default()
+fn main() {
+    // -v and friends log to the console that started us; otherwise debug
+    // builds log through env_logger (RUST_LOG)
+    let (verbosity, args) = cli::take_verbosity_flags(std::env::args().skip(1));
+    if verbosity > 0 {
+        attach_parent_console();
+        let _ = console_log::init(verbosity);
+    } else {
+        #[cfg(debug_assertions)]
+        env_logger::init();
     }
-}
 
-// WHY: Implementing Default allows using AppState::default() and satisfies
-// the clippy::new_without_default lint for types that have a new() method.
-impl Default for AppState {
-    fn default() -> Self {
-        Self {
-            locked: AtomicBool::new(false),
-            should_quit: AtomicBool::new(false),
-            show_settings: AtomicBool::new(false),
+    // Subcommands talk to the running instance and exit
+    let args = match cli::take_config_flag(args) {
+        Ok((config_path, args)) => {
+            if let Some(path) = config_path {
+                config::Config::set_config_path(&path);
+            }
+            args
+        }
+        Err(e) => {
+            attach_parent_console();
+            eprintln!("pawgate: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    match cli::parse_args(args) {
+        Ok(cli::Command::Run) => {}
+        Ok(command) => std::process::exit(run_cli_command(command)),
+        Err(e) => {
+            attach_parent_console();
+            eprintln!("pawgate: {}\n\n{}", e, cli::USAGE);
+            std::process::exit(2);
         }
     }
-}
-
-fn main() {
-    // Initialize logging (only in debug builds)
-    #[cfg(debug_assertions)]
-    env_logger::init();
 
     info!("PawGate starting...");
 
@@ -59,40 +55,274 @@ fn main() {
         return;
     }
 
-    // Load configuration
-    let config = match config::Config::load() {
+    let config = load_config();
+
+    info!("Loaded config: hotkeys={:?}, opacity={}", config.hotkeys, config.opacity);
+    debug!(
+        "Config details: lock_mode={:?}, lock_delay_ms={}, enabled={}, profile={}",
+        config.lock_mode, config.lock_delay_ms, config.enabled, config.active_profile
+    );
+
+    // Tell the user which part of a hotkey was wrong instead of silently
+    // skipping it in the keyboard thread
+    for hotkey in &config.hotkeys {
+        match config::parse_hotkey_sequence_detailed(hotkey) {
+            Err(e) => {
+                error!("Invalid hotkey '{}': {}", hotkey, e);
+                show_error_message(&format!(
+                    "The hotkey \"{}\" in your config is invalid: {}.\n\nIt will be ignored.",
+                    hotkey, e
+                ));
+            }
+            Ok(sequence) => {
+                // Reserved combos are skipped; the other hotkeys still work
+                if let Some(conflict) = config::sequence_conflict(&sequence) {
+                    let message = config::hotkey_conflict_message(hotkey, conflict);
+                    error!("{}", message);
+                    show_error_message(&format!("{}\n\nIt will be ignored.", message));
+                }
+            }
+        }
+    }
+
+    // With `on_register_failure` exit, quit now rather than run without a hotkey
+    if config.enabled
+        && register_failure::resolve(config.parsed_hotkeys(), config.on_register_failure)
+            == register_failure::Outcome::Exit
+    {
+        let message = register_failure::failure_message(&config.hotkeys);
+        error!("{}", message);
+        show_error_message(&format!("{}\n\nPawGate will exit (on_register_failure is \"exit\").", message));
+        std::process::exit(1);
+    }
+
+    // Same for a color the overlay can't draw
+    if let Err(e) = config.validate_color() {
+        error!("{}", e);
+        show_error_message(&format!("{}.\n\nThe overlay will use the default green.", e));
+    }
+
+    let mut app = PawGate::with_config(config);
+
+    // Start the keyboard hook in a background thread
+    if let Err(e) = app.register_hotkeys() {
+        error!("{}", e);
+        show_error_message(&format!("{}.\n\nPawGate can't lock the keyboard without it and will exit.", e));
+        std::process::exit(1);
+    }
+
+    // Run the main UI loop (tray icon + overlay management) until exit
+    if let Err(e) = app.run() {
+        error!("Tray loop error: {}", e);
+    }
+
+    info!("PawGate exiting...");
+}
+
+/// Load the config file and apply environment overrides
+///
+/// Falls back to defaults if the file can't be loaded.
+fn load_config() -> config::Config {
+    let path = config::Config::config_path();
+    debug!("Loading config from {}", path.display());
+    // WHY: load_or_create, so a corrupt --config file is backed up and
+    // replaced with defaults just like ~/.pawgate/config.json
+    let mut config = match config::Config::load_or_create(&path) {
         Ok(c) => c,
         Err(e) => {
             error!("Failed to load config: {}", e);
             config::Config::default()
         }
     };
+    config.apply_env_overrides();
+    config
+}
 
-    info!("Loaded config: hotkey={}, opacity={}", config.hotkey, config.opacity);
+/// Load the config for a read-only command, with environment overrides
+///
+/// WHY: Unlike [`load_config`] this never creates, migrates or replaces the
+/// file; a missing one reads as the defaults.
+fn read_config() -> Result<config::Config, String> {
+    let path = config::Config::config_path();
+    let mut config =
+        config::Config::load_or_initial(&path).map_err(|e| format!("can't read {}: {}", path.display(), e))?;
+    config.apply_env_overrides();
+    Ok(config)
+}
 
-    // Create shared state
-    let state = Arc::new(AppState::new());
+/// Run a CLI subcommand, returning the process exit code
+fn run_cli_command(command: cli::Command) -> i32 {
+    attach_parent_console();
 
-    // Start keyboard hook in background thread
-    let kb_state = Arc::clone(&state);
-    let kb_config = config.clone();
-    let keyboard_thread = std::thread::spawn(move || {
-        keyboard::run_keyboard_hook(kb_state, kb_config);
-    });
+    let control = match command {
+        cli::Command::Lock => ipc::ControlCommand::Lock,
+        cli::Command::Unlock => ipc::ControlCommand::Unlock,
+        cli::Command::Toggle => ipc::ControlCommand::Toggle,
+        cli::Command::Snooze(minutes) => ipc::ControlCommand::Snooze(minutes),
+        cli::Command::TestLock(secs) => ipc::ControlCommand::TestLock(secs),
+        // Config commands only read files, so they work without a running instance
+        cli::Command::ConfigShow => {
+            let config = match read_config() {
+                Ok(config) => config,
+                Err(e) => {
+                    eprintln!("pawgate: {}", e);
+                    return 1;
+                }
+            };
+            return match serde_json::to_string_pretty(&config) {
+                Ok(json) => {
+                    println!("{}", json);
+                    0
+                }
+                Err(e) => {
+                    eprintln!("pawgate: failed to serialize config: {}", e);
+                    1
+                }
+            };
+        }
+        cli::Command::ConfigPath => {
+            println!("{}", config::Config::config_path().display());
+            return 0;
+        }
+        cli::Command::ConfigSchema => {
+            println!("{}", config::Config::json_schema());
+            return 0;
+        }
+        cli::Command::ConfigSet { key, value } => return set_config_value(&key, &value),
+        cli::Command::ConfigEdit => {
+            return match cli::open_config() {
+                Ok(()) => 0,
+                Err(e) => {
+                    eprintln!("pawgate: {}", e);
+                    1
+                }
+            };
+        }
+        cli::Command::Autostart(action) => return run_autostart(action),
+        cli::Command::Enable => return set_enabled(true),
+        cli::Command::Disable => return set_enabled(false),
+        cli::Command::Sessions => {
+            let limit = read_config()
+                .unwrap_or_else(|e| {
+                    eprintln!("pawgate: {}; using the default session_history_limit", e);
+                    config::Config::default()
+                })
+                .session_history_limit;
+            let records = lock_history::load(&lock_history::sessions_path(), limit as usize);
+            println!("{}", lock_history::format_table(&records));
+            return 0;
+        }
+        cli::Command::TestHotkey(combo) => {
+            return match cli::hotkey_report(&combo) {
+                Ok(report) => {
+                    println!("{}", report);
+                    0
+                }
+                Err(e) => {
+                    eprintln!("pawgate: {}", e);
+                    1
+                }
+            };
+        }
+        cli::Command::ProfileList => {
+            let active = match read_config() {
+                Ok(config) => config.active_profile,
+                Err(e) => {
+                    eprintln!("pawgate: {}", e);
+                    return 1;
+                }
+            };
+            let names = std::iter::once(config::DEFAULT_PROFILE.to_string())
+                .chain(config::Config::list_profiles());
+            for name in names {
+                let marker = if name == active { "*" } else { " " };
+                println!("{} {}", marker, name);
+            }
+            return 0;
+        }
+        cli::Command::ProfileUse(name) => {
+            return match config::Config::set_active_profile(&name) {
+                Ok(()) => {
+                    println!("Using profile {}", name);
+                    0
+                }
+                Err(e) => {
+                    eprintln!("pawgate: {}", e);
+                    1
+                }
+            };
+        }
+        cli::Command::Help | cli::Command::Run => {
+            println!("{}", cli::USAGE);
+            return 0;
+        }
+    };
 
-    // Run the main UI loop (tray icon + overlay management)
-    // This runs on the main thread to handle Windows messages properly
-    if let Err(e) = tray::run_tray_loop(Arc::clone(&state), config) {
-        error!("Tray loop error: {}", e);
+    match ipc::send_command(control) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("pawgate: {}", e);
+            1
+        }
     }
+}
 
-    // Signal keyboard thread to stop
-    state.should_quit.store(true, Ordering::SeqCst);
+/// Handle `pawgate config set`, returning the process exit code
+fn set_config_value(key: &str, value: &str) -> i32 {
+    match config::Config::set_saved_field(key, value) {
+        Ok(()) => {
+            println!("{} = {}", key, value.trim());
+            0
+        }
+        Err(e) => {
+            eprintln!("pawgate: {}", e);
+            1
+        }
+    }
+}
 
-    // Wait for keyboard thread to finish
-    let _ = keyboard_thread.join();
+/// Handle `pawgate enable` and `pawgate disable`, returning the process exit code
+fn set_enabled(enabled: bool) -> i32 {
+    let code = set_config_value("enabled", if enabled { "true" } else { "false" });
+    if code == 0 {
+        // Hooks are installed (or not) at startup
+        println!("Restart PawGate for this to take effect.");
+    }
+    code
+}
 
-    info!("PawGate exiting...");
+/// Handle `pawgate autostart`, returning the process exit code
+fn run_autostart(action: autostart::AutostartAction) -> i32 {
+    let exe = match std::env::current_exe() {
+        Ok(exe) => exe,
+        Err(e) => {
+            eprintln!("pawgate: can't find pawgate.exe: {}", e);
+            return 1;
+        }
+    };
+    let command_line = autostart::command_line(&exe, config::Config::config_path_override());
+    match autostart::run(action, &mut autostart::RegistryRunEntry, &command_line) {
+        Ok(message) => {
+            println!("{}", message);
+            0
+        }
+        Err(e) => {
+            eprintln!("pawgate: {}", e);
+            1
+        }
+    }
+}
+
+/// Route println!/eprintln! to the console that launched us, if any
+///
+/// WHY: The windows subsystem means we start without a console, so CLI
+/// output would otherwise be lost.
+fn attach_parent_console() {
+    use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
+
+    unsafe {
+        let _ = AttachConsole(ATTACH_PARENT_PROCESS);
+    }
 }
 
 /// Show a Windows error message box
@@ -0,0 +1,158 @@
+//! Detection of a paw landing on the keyboard (`auto_lock_on_mash`)
+//!
+//! A person presses a few keys at a time, and a combo's keys go down one
+//! after another. A paw presses a handful at once. The detector tracks which
+//! keys are down and when each went down; when more than the threshold are
+//! down and were all pressed within the window, it's a mash. It only
+//! watches: the hook decides to lock.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Lowest `auto_lock_on_mash` allowed, so everyday combos like
+/// ctrl+shift+t can't trigger it
+pub const MIN_THRESHOLD: u32 = 3;
+
+/// Watches key events for many keys going down at once
+pub struct MashDetector {
+    /// A mash is more than this many keys down at once
+    threshold: usize,
+    window: Duration,
+    /// Keys currently down, with when each went down
+    down: HashMap<u32, Instant>,
+}
+
+impl MashDetector {
+    pub fn new(threshold: u32, window: Duration) -> Self {
+        Self {
+            threshold: threshold.max(MIN_THRESHOLD) as usize,
+            window,
+            down: HashMap::new(),
+        }
+    }
+
+    /// Feed a key press; returns true when this press makes a mash
+    ///
+    /// Only the press that crosses the threshold reports it, so a mash
+    /// locks once rather than for every key the paw adds.
+    pub fn key_down(&mut self, vk: u32, now: Instant) -> bool {
+        // WHY: A held key repeats its key-down; only the first one is a press
+        if self.down.contains_key(&vk) {
+            return false;
+        }
+        self.down.insert(vk, now);
+        let recent = self
+            .down
+            .values()
+            .filter(|&&pressed| now.saturating_duration_since(pressed) <= self.window)
+            .count();
+        recent == self.threshold + 1
+    }
+
+    /// Feed a key release
+    pub fn key_up(&mut self, vk: u32) {
+        self.down.remove(&vk);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Key events as (ms, vk, down)
+    fn feed(detector: &mut MashDetector, events: &[(u64, u32, bool)]) -> Vec<u64> {
+        let base = Instant::now();
+        let mut mashes = Vec::new();
+        for &(ms, vk, down) in events {
+            if down {
+                if detector.key_down(vk, base + Duration::from_millis(ms)) {
+                    mashes.push(ms);
+                }
+            } else {
+                detector.key_up(vk);
+            }
+        }
+        mashes
+    }
+
+    fn detector() -> MashDetector {
+        MashDetector::new(4, Duration::from_millis(150))
+    }
+
+    #[test]
+    fn test_paw_mash_detected() {
+        /// WHY: Five keys landing together is the paw this feature is for.
+        let events = [(0, 0x41, true), (10, 0x53, true), (15, 0x44, true), (30, 0x46, true), (40, 0x47, true)];
+        assert_eq!(feed(&mut detector(), &events), vec![40]);
+    }
+
+    #[test]
+    fn test_threshold_itself_is_not_a_mash() {
+        /// WHY: "More than N keys": exactly N down at once must not lock.
+        let events = [(0, 0x41, true), (10, 0x53, true), (15, 0x44, true), (30, 0x46, true)];
+        assert!(feed(&mut detector(), &events).is_empty());
+    }
+
+    #[test]
+    fn test_fast_typing_is_not_a_mash() {
+        /// WHY: Rolling over keys while typing quickly releases each one soon after the next goes down.
+        let mut events = Vec::new();
+        for (i, vk) in (0x41..0x51).enumerate() {
+            let ms = i as u64 * 40;
+            events.push((ms, vk, true));
+            events.push((ms + 60, vk, false));
+        }
+        events.sort_by_key(|&(ms, _, down)| (ms, down));
+        assert!(feed(&mut detector(), &events).is_empty());
+    }
+
+    #[test]
+    fn test_held_keys_outside_window_do_not_count() {
+        /// WHY: Holding shift (or a game's WASD) for seconds must not make the next few presses a mash.
+        let events = [
+            (0, 0xA0, true),
+            (10, 0x57, true),
+            (2000, 0x41, true),
+            (2010, 0x53, true),
+            (2020, 0x44, true),
+            (2030, 0x46, true),
+        ];
+        assert!(feed(&mut detector(), &events).is_empty());
+    }
+
+    #[test]
+    fn test_auto_repeat_and_releases() {
+        /// WHY: A held key's repeats aren't new keys, and released keys no longer count.
+        let events = [
+            (0, 0x41, true),
+            (5, 0x41, true),
+            (10, 0x41, true),
+            (20, 0x53, true),
+            (25, 0x53, false),
+            (30, 0x44, true),
+            (40, 0x46, true),
+            (50, 0x47, true),
+        ];
+        assert!(feed(&mut detector(), &events).is_empty(), "Only four keys are ever down together");
+    }
+
+    #[test]
+    fn test_mash_reported_once_then_again_after_release() {
+        /// WHY: More keys from the same paw must not report again, but a fresh mash later must.
+        let mut mash = vec![(0, 0x41, true), (5, 0x53, true), (10, 0x44, true), (15, 0x46, true), (20, 0x47, true), (25, 0x48, true)];
+        for vk in [0x41, 0x53, 0x44, 0x46, 0x47, 0x48] {
+            mash.push((100, vk, false));
+        }
+        for (i, vk) in [0x41, 0x53, 0x44, 0x46, 0x47].into_iter().enumerate() {
+            mash.push((500 + i as u64 * 5, vk, true));
+        }
+        assert_eq!(feed(&mut detector(), &mash), vec![20, 520]);
+    }
+
+    #[test]
+    fn test_threshold_has_a_floor() {
+        /// WHY: A threshold of 1 would lock on ctrl+c.
+        let events = [(0, 0xA2, true), (20, 0x43, true), (30, 0xA0, true)];
+        assert!(feed(&mut MashDetector::new(1, Duration::from_millis(150)), &events).is_empty());
+    }
+}
@@ -0,0 +1,80 @@
+//! Toast notifications for lock and unlock
+//!
+//! Uses the WinRT ToastNotificationManager from the `windows` crate, so no
+//! extra dependency is needed.
+
+use windows::core::HSTRING;
+use windows::Data::Xml::Dom::XmlDocument;
+use windows::UI::Notifications::{ToastNotification, ToastNotificationManager};
+
+/// App ID the toasts are shown under
+///
+/// WHY: Unpackaged apps have no registered AppUserModelID of their own, and
+/// toasts from an unregistered ID are silently dropped. PowerShell's ID is
+/// always present, which is the usual workaround.
+const TOAST_APP_ID: &str =
+    r"{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe";
+
+/// Durations above this ask Windows for its long toast (about 25 seconds)
+/// instead of the short one (about 7 seconds)
+///
+/// WHY: Windows only offers those two; it doesn't take an exact duration.
+const LONG_TOAST_MS: u32 = 7000;
+
+/// Show a toast with a title and one line of body text
+pub fn show_toast(title: &str, body: &str, duration_ms: u32) -> windows::core::Result<()> {
+    let document = XmlDocument::new()?;
+    document.LoadXml(&HSTRING::from(toast_xml(title, body, duration_ms)))?;
+    let toast = ToastNotification::CreateToastNotification(&document)?;
+    ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(TOAST_APP_ID))?.Show(&toast)
+}
+
+/// The toast's XML payload
+fn toast_xml(title: &str, body: &str, duration_ms: u32) -> String {
+    let duration = if duration_ms > LONG_TOAST_MS { " duration=\"long\"" } else { "" };
+    format!(
+        "<toast{}><visual><binding template=\"ToastGeneric\"><text>{}</text><text>{}</text></binding></visual></toast>",
+        duration,
+        xml_escape(title),
+        xml_escape(body)
+    )
+}
+
+/// Escape text for inclusion in the toast XML
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_escape() {
+        /// WHY: User-configured text must not break (or inject into) the toast XML.
+        assert_eq!(xml_escape("Kitty mode ON"), "Kitty mode ON");
+        assert_eq!(
+            xml_escape(r#"<b>Tom & "Jerry's"</b>"#),
+            "&lt;b&gt;Tom &amp; &quot;Jerry&apos;s&quot;&lt;/b&gt;"
+        );
+    }
+
+    #[test]
+    fn test_toast_duration() {
+        /// WHY: Windows only has short and long toasts, so longer durations pick the long one.
+        assert!(toast_xml("PawGate", "Locked", 3000).starts_with("<toast><visual>"));
+        assert!(toast_xml("PawGate", "Locked", 7000).starts_with("<toast><visual>"));
+        assert!(toast_xml("PawGate", "Locked", 10000).starts_with("<toast duration=\"long\"><visual>"));
+    }
+}
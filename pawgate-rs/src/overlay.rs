@@ -11,7 +11,10 @@ use windows::Win32::Graphics::Gdi::*;
 use windows::Win32::System::LibraryLoader::GetModuleHandleW;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
-use crate::config::Config;
+use crate::config::{lerp_color, Config, OverlayPattern, OverlayTarget};
+use crate::overlay_image::{self, OverlayImage};
+use crate::overlay_pattern;
+use crate::overlay_target::{target_monitor, MonitorArea};
 use crate::AppState;
 
 const OVERLAY_CLASS_NAME: PCWSTR = w!("PawGateOverlay");
@@ -24,26 +27,71 @@ thread_local! {
 struct OverlayState {
     state: Arc<AppState>,
     color: (u8, u8, u8),
+    /// Bottom color of a vertical gradient starting at `color`
+    color_end: Option<(u8, u8, u8)>,
+    /// Text color chosen to contrast with `color`
+    text_color: (u8, u8, u8),
+    /// Pattern drawn over the tint, and the distance between its glyphs
+    pattern: OverlayPattern,
+    pattern_spacing: u32,
     opacity: u8,
+    /// Message painted in the middle of the overlay
+    message: String,
+    /// Temporary message shown instead of `message` (e.g. the lock countdown)
+    status: Option<String>,
+    /// Small print near the bottom edge (e.g. the Ctrl+Alt+Del note)
+    note: Option<String>,
+    /// Picture drawn above the message while locked
+    image: Option<OverlayImage>,
 }
 
-/// Create and show the overlay window
-/// Returns the window handle
-pub fn create_overlay(state: Arc<AppState>, config: &Config) -> Option<HWND> {
-    let (r, g, b) = config.parse_overlay_color();
-    let opacity = (config.opacity * 255.0) as u8;
+/// Create the overlay windows (initially hidden)
+///
+/// A single window spans every monitor unless `config.monitors` selects
+/// specific ones, in which case each selected monitor gets its own window.
+/// A single-monitor `overlay_target` overrides both; for the active monitor,
+/// call this again at each lock so it follows the foreground window.
+/// Returns no windows when `config.show_overlay` is off; the input hooks
+/// still lock as usual.
+pub fn create_overlay(state: Arc<AppState>, config: &Config) -> Vec<HWND> {
+    create_overlay_in(state, config, None)
+}
+
+/// Create a single overlay window covering just `area`, e.g. the window
+/// locked by `lock_mode: "targetwindow"`
+pub fn create_overlay_over(state: Arc<AppState>, config: &Config, area: RECT) -> Vec<HWND> {
+    create_overlay_in(state, config, Some(area))
+}
+
+fn create_overlay_in(state: Arc<AppState>, config: &Config, area: Option<RECT>) -> Vec<HWND> {
+    if !config.show_overlay {
+        return Vec::new();
+    }
+
+    let (r, g, b, _) = config.parse_overlay_color_rgba();
+    let opacity = overlay_alpha(config);
 
     // Store state for window procedure
     OVERLAY_STATE.with(|os| {
         *os.borrow_mut() = Some(OverlayState {
             state: Arc::clone(&state),
             color: (r, g, b),
+            color_end: config.parse_overlay_color_end(),
+            text_color: config.overlay_text_color(),
+            pattern: config.overlay_pattern,
+            pattern_spacing: config.pattern_spacing,
             opacity,
+            message: config.overlay_message(),
+            status: None,
+            note: config.sas_note(),
+            image: None,
         });
     });
 
     unsafe {
-        let hinstance = GetModuleHandleW(None).ok()?;
+        let Ok(hinstance) = GetModuleHandleW(None) else {
+            return Vec::new();
+        };
 
         // Register window class
         let wc = WNDCLASSEXW {
@@ -59,46 +107,175 @@ pub fn create_overlay(state: Arc<AppState>, config: &Config) -> Option<HWND> {
 
         RegisterClassExW(&wc);
 
-        // Get virtual screen dimensions (all monitors)
-        let x = GetSystemMetrics(SM_XVIRTUALSCREEN);
-        let y = GetSystemMetrics(SM_YVIRTUALSCREEN);
-        let width = GetSystemMetrics(SM_CXVIRTUALSCREEN);
-        let height = GetSystemMetrics(SM_CYVIRTUALSCREEN);
-
-        // Create layered window
-        // WHY: Window is created without WS_VISIBLE flag so it starts hidden.
-        // The overlay should only appear when the keyboard is locked, not on startup.
-        let hwnd = CreateWindowExW(
-            WS_EX_LAYERED | WS_EX_TOPMOST | WS_EX_TOOLWINDOW | WS_EX_TRANSPARENT,
-            OVERLAY_CLASS_NAME,
-            w!("PawGate Overlay"),
-            WS_POPUP, // No WS_VISIBLE - starts hidden
-            x,
-            y,
-            width,
-            height,
-            None,
-            None,
-            Some(hinstance.into()),
-            None,
-        )?;
+        let areas: Vec<RECT> = if let Some(area) = area.or_else(|| target_area(config.overlay_target)) {
+            vec![area]
+        } else if config.monitors.is_empty() {
+            // Get virtual screen dimensions (all monitors)
+            let x = GetSystemMetrics(SM_XVIRTUALSCREEN);
+            let y = GetSystemMetrics(SM_YVIRTUALSCREEN);
+            let width = GetSystemMetrics(SM_CXVIRTUALSCREEN);
+            let height = GetSystemMetrics(SM_CYVIRTUALSCREEN);
+            vec![RECT {
+                left: x,
+                top: y,
+                right: x + width,
+                bottom: y + height,
+            }]
+        } else {
+            enumerate_monitors()
+                .into_iter()
+                .enumerate()
+                .filter(|(index, monitor)| config.selects_monitor(*index, &monitor.device_name))
+                .map(|(_, monitor)| monitor.rect)
+                .collect()
+        };
 
-        // Set layered window attributes for transparency
-        SetLayeredWindowAttributes(
-            hwnd,
-            COLORREF(0),
-            opacity,
-            LWA_ALPHA,
-        ).ok()?;
+        let ex_style = overlay_ex_style(config.overlay_passes_clicks());
+        areas
+            .iter()
+            .filter_map(|area| create_overlay_window(hinstance, area, opacity, ex_style))
+            .collect()
+    }
+}
 
-        // Force a repaint
-        InvalidateRect(hwnd, None, true);
-        UpdateWindow(hwnd);
+/// Window alpha for the opacity and color of the current lock mode
+pub fn overlay_alpha(config: &Config) -> u8 {
+    // The color's own alpha scales the opacity
+    let (_, _, _, a) = config.parse_overlay_color_rgba();
+    (config.effective_opacity() * a as f32) as u8
+}
 
-        Some(hwnd)
+/// Change the window alpha of the overlays (used for animation)
+pub fn set_overlay_alpha(hwnds: &[HWND], alpha: u8) {
+    for &hwnd in hwnds {
+        unsafe {
+            let _ = SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA);
+        }
     }
 }
 
+/// Extended window style of the overlay
+///
+/// WS_EX_TRANSPARENT makes a layered window ignore the mouse, so clicks go to
+/// whatever is underneath.
+fn overlay_ex_style(click_through: bool) -> WINDOW_EX_STYLE {
+    let style = WS_EX_LAYERED | WS_EX_TOPMOST | WS_EX_TOOLWINDOW;
+    if click_through {
+        style | WS_EX_TRANSPARENT
+    } else {
+        style
+    }
+}
+
+/// Create one hidden overlay window covering `area`
+unsafe fn create_overlay_window(
+    hinstance: HMODULE,
+    area: &RECT,
+    opacity: u8,
+    ex_style: WINDOW_EX_STYLE,
+) -> Option<HWND> {
+    // Create layered window
+    // WHY: Window is created without WS_VISIBLE flag so it starts hidden.
+    // The overlay should only appear when the keyboard is locked, not on startup.
+    let hwnd = CreateWindowExW(
+        ex_style,
+        OVERLAY_CLASS_NAME,
+        w!("PawGate Overlay"),
+        WS_POPUP, // No WS_VISIBLE - starts hidden
+        area.left,
+        area.top,
+        area.right - area.left,
+        area.bottom - area.top,
+        None,
+        None,
+        Some(hinstance.into()),
+        None,
+    )?;
+
+    // Set layered window attributes for transparency
+    SetLayeredWindowAttributes(
+        hwnd,
+        COLORREF(0),
+        opacity,
+        LWA_ALPHA,
+    ).ok()?;
+
+    // Force a repaint
+    InvalidateRect(hwnd, None, true);
+    UpdateWindow(hwnd);
+
+    Some(hwnd)
+}
+
+/// Bounds of the one monitor `target` selects, if it selects one
+unsafe fn target_area(target: OverlayTarget) -> Option<RECT> {
+    if target == OverlayTarget::All {
+        return None;
+    }
+    let monitors = enumerate_monitors();
+    let active = if target == OverlayTarget::ActiveMonitor {
+        active_monitor_rect()
+    } else {
+        None
+    };
+    target_monitor(target, &monitors, active).map(|index| monitors[index].rect)
+}
+
+/// Bounds of the monitor showing the foreground window, if there is one
+unsafe fn active_monitor_rect() -> Option<RECT> {
+    let foreground = GetForegroundWindow();
+    if foreground.0.is_null() {
+        return None;
+    }
+    let hmonitor = MonitorFromWindow(foreground, MONITOR_DEFAULTTONULL);
+    if hmonitor.is_invalid() {
+        return None;
+    }
+    let mut info = MONITORINFO {
+        cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+        ..Default::default()
+    };
+    GetMonitorInfoW(hmonitor, &mut info).as_bool().then_some(info.rcMonitor)
+}
+
+/// List the bounds and device names of all connected monitors
+fn enumerate_monitors() -> Vec<MonitorArea> {
+    let mut monitors: Vec<MonitorArea> = Vec::new();
+    unsafe {
+        let _ = EnumDisplayMonitors(
+            HDC::default(),
+            None,
+            Some(monitor_enum_proc),
+            LPARAM(&mut monitors as *mut Vec<MonitorArea> as isize),
+        );
+    }
+    monitors
+}
+
+/// EnumDisplayMonitors callback; `data` points at the Vec<MonitorArea> being filled
+unsafe extern "system" fn monitor_enum_proc(
+    hmonitor: HMONITOR,
+    _hdc: HDC,
+    _rect: *mut RECT,
+    data: LPARAM,
+) -> BOOL {
+    let monitors = &mut *(data.0 as *mut Vec<MonitorArea>);
+
+    let mut info = MONITORINFOEXW::default();
+    info.monitorInfo.cbSize = std::mem::size_of::<MONITORINFOEXW>() as u32;
+    if GetMonitorInfoW(hmonitor, &mut info as *mut MONITORINFOEXW as *mut MONITORINFO).as_bool() {
+        let len = info.szDevice.iter().position(|&c| c == 0).unwrap_or(info.szDevice.len());
+        monitors.push(MonitorArea {
+            rect: info.monitorInfo.rcMonitor,
+            device_name: String::from_utf16_lossy(&info.szDevice[..len]),
+            primary: info.monitorInfo.dwFlags & MONITORINFOF_PRIMARY != 0,
+        });
+    }
+
+    // Keep enumerating
+    TRUE
+}
+
 /// Hide and destroy the overlay window
 pub fn destroy_overlay(hwnd: HWND) {
     unsafe {
@@ -123,6 +300,130 @@ pub fn set_overlay_visible(hwnd: HWND, visible: bool) {
     }
 }
 
+/// Put the overlay windows back above other topmost windows, without
+/// taking focus
+pub fn raise_overlay(hwnds: &[HWND]) {
+    for &hwnd in hwnds {
+        unsafe {
+            let _ = SetWindowPos(
+                hwnd,
+                HWND_TOPMOST,
+                0, 0, 0, 0,
+                SWP_NOMOVE | SWP_NOSIZE | SWP_NOACTIVATE,
+            );
+        }
+    }
+}
+
+/// Replace the overlay message with `status`, or restore it with None
+pub fn set_overlay_status(hwnds: &[HWND], status: Option<String>) {
+    OVERLAY_STATE.with(|os| {
+        if let Some(state) = os.borrow_mut().as_mut() {
+            state.status = status;
+        }
+    });
+    for &hwnd in hwnds {
+        unsafe {
+            InvalidateRect(hwnd, None, true);
+        }
+    }
+}
+
+/// Set the picture drawn above the overlay message, or remove it with None
+pub fn set_overlay_image(hwnds: &[HWND], image: Option<OverlayImage>) {
+    OVERLAY_STATE.with(|os| {
+        if let Some(state) = os.borrow_mut().as_mut() {
+            state.image = image;
+        }
+    });
+    for &hwnd in hwnds {
+        unsafe {
+            InvalidateRect(hwnd, None, true);
+        }
+    }
+}
+
+/// Fill `rect` with a solid color
+unsafe fn fill_rect(hdc: HDC, rect: &RECT, (r, g, b): (u8, u8, u8)) {
+    let brush = CreateSolidBrush(COLORREF(
+        (r as u32) | ((g as u32) << 8) | ((b as u32) << 16),
+    ));
+    FillRect(hdc, rect, brush);
+    let _ = DeleteObject(brush);
+}
+
+/// Tile faint paw prints over `rect`, in `color` nudged toward `text_color`
+unsafe fn draw_paws(hdc: HDC, rect: &RECT, spacing: u32, color: (u8, u8, u8), text_color: (u8, u8, u8)) {
+    let (r, g, b) = lerp_color(color, text_color, overlay_pattern::PATTERN_STRENGTH);
+    let brush = CreateSolidBrush(COLORREF(
+        (r as u32) | ((g as u32) << 8) | ((b as u32) << 16),
+    ));
+    let old_brush = SelectObject(hdc, brush);
+    let old_pen = SelectObject(hdc, GetStockObject(NULL_PEN));
+
+    let size = overlay_pattern::paw_size(spacing);
+    let width = rect.right - rect.left;
+    let height = rect.bottom - rect.top;
+    for (x, y) in overlay_pattern::glyph_positions(width, height, spacing) {
+        for shape in overlay_pattern::paw_shapes((rect.left + x, rect.top + y), size) {
+            let _ = Ellipse(hdc, shape.left, shape.top, shape.right, shape.bottom);
+        }
+    }
+
+    SelectObject(hdc, old_pen);
+    SelectObject(hdc, old_brush);
+    let _ = DeleteObject(brush);
+}
+
+/// Alpha-blend `image` onto `hdc`, scaled into `dest`
+unsafe fn draw_image(hdc: HDC, image: &OverlayImage, dest: &RECT) {
+    let info = BITMAPINFO {
+        bmiHeader: BITMAPINFOHEADER {
+            biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+            biWidth: image.width as i32,
+            // Negative height means top-down rows, matching the decoded pixels
+            biHeight: -(image.height as i32),
+            biPlanes: 1,
+            biBitCount: 32,
+            biCompression: BI_RGB.0,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut bits: *mut std::ffi::c_void = std::ptr::null_mut();
+    let Ok(bitmap) = CreateDIBSection(hdc, &info, DIB_RGB_COLORS, &mut bits, HANDLE::default(), 0) else {
+        return;
+    };
+    std::ptr::copy_nonoverlapping(image.bgra.as_ptr(), bits as *mut u8, image.bgra.len());
+
+    let mem_dc = CreateCompatibleDC(hdc);
+    let old_bitmap = SelectObject(mem_dc, bitmap);
+    let blend = BLENDFUNCTION {
+        BlendOp: AC_SRC_OVER as u8,
+        BlendFlags: 0,
+        SourceConstantAlpha: 255,
+        AlphaFormat: AC_SRC_ALPHA as u8,
+    };
+    let _ = AlphaBlend(
+        hdc,
+        dest.left,
+        dest.top,
+        dest.right - dest.left,
+        dest.bottom - dest.top,
+        mem_dc,
+        0,
+        0,
+        image.width as i32,
+        image.height as i32,
+        blend,
+    );
+
+    SelectObject(mem_dc, old_bitmap);
+    let _ = DeleteDC(mem_dc);
+    let _ = DeleteObject(bitmap);
+}
+
 /// Window procedure for the overlay
 unsafe extern "system" fn overlay_wnd_proc(
     hwnd: HWND,
@@ -141,19 +442,33 @@ unsafe extern "system" fn overlay_wnd_proc(
                     let mut rect = RECT::default();
                     let _ = GetClientRect(hwnd, &mut rect);
 
-                    // Create brush with overlay color
-                    let (r, g, b) = state.color;
-                    let brush = CreateSolidBrush(COLORREF(
-                        (r as u32) | ((g as u32) << 8) | ((b as u32) << 16),
-                    ));
+                    // Fill the window, flat or as a top-to-bottom gradient
+                    match state.color_end {
+                        None => fill_rect(hdc, &rect, state.color),
+                        Some(end) => {
+                            // Bands of at most 256 steps are indistinguishable
+                            // from a per-pixel gradient on 8-bit channels
+                            let height = rect.bottom - rect.top;
+                            let bands = height.clamp(1, 256);
+                            for band in 0..bands {
+                                let band_rect = RECT {
+                                    top: rect.top + height * band / bands,
+                                    bottom: rect.top + height * (band + 1) / bands,
+                                    ..rect
+                                };
+                                let t = band as f32 / (bands - 1).max(1) as f32;
+                                fill_rect(hdc, &band_rect, lerp_color(state.color, end, t));
+                            }
+                        }
+                    }
 
-                    // Fill the window
-                    FillRect(hdc, &rect, brush);
-                    let _ = DeleteObject(brush);
+                    if state.pattern == OverlayPattern::Paws {
+                        draw_paws(hdc, &rect, state.pattern_spacing, state.color, state.text_color);
+                    }
 
                     // Draw centered text
-                    let text = "Keyboard Locked - Press hotkey to unlock";
-                    let wide_text: Vec<u16> = text.encode_utf16().chain(std::iter::once(0)).collect();
+                    let text = state.status.as_deref().unwrap_or(&state.message);
+                    let mut wide_text: Vec<u16> = text.encode_utf16().collect();
 
                     // Create a larger font
                     let font = CreateFontW(
@@ -169,20 +484,93 @@ unsafe extern "system" fn overlay_wnd_proc(
                     );
 
                     let old_font = SelectObject(hdc, font);
-                    SetTextColor(hdc, COLORREF(0xFFFFFF)); // White text
+                    let (tr, tg, tb) = state.text_color;
+                    SetTextColor(hdc, COLORREF(
+                        (tr as u32) | ((tg as u32) << 8) | ((tb as u32) << 16),
+                    ));
                     SetBkMode(hdc, TRANSPARENT);
 
-                    // Draw text centered
-                    let _ = DrawTextW(
-                        hdc,
-                        &mut wide_text.as_slice()[..wide_text.len()-1].to_vec(),
-                        &mut rect,
-                        DT_CENTER | DT_VCENTER | DT_SINGLELINE,
-                    );
+                    // WHY: DT_VCENTER only works with DT_SINGLELINE, so measure
+                    // the wrapped text first and center its box ourselves.
+                    // Keep a margin so long text doesn't run to the screen edge.
+                    let margin = (rect.right - rect.left) / 10;
+                    let mut bounds = RECT {
+                        left: rect.left + margin,
+                        right: rect.right - margin,
+                        ..rect
+                    };
+                    let format = DT_CENTER | DT_WORDBREAK | DT_NOPREFIX;
+                    let height = DrawTextW(hdc, &mut wide_text, &mut bounds, format | DT_CALCRECT);
+
+                    // Stack the image (if any) above the text and center both
+                    let width = rect.right - rect.left;
+                    let (image_width, image_height) = state.image.as_ref().map_or((0, 0), |image| {
+                        let fraction = overlay_image::MAX_SCREEN_FRACTION;
+                        let (w, h) = overlay_image::fit_within(
+                            (image.width, image.height),
+                            (
+                                (width as f32 * fraction) as u32,
+                                ((rect.bottom - rect.top) as f32 * fraction) as u32,
+                            ),
+                        );
+                        (w as i32, h as i32)
+                    });
+                    let gap = if image_height > 0 { 24 } else { 0 };
+                    let block_height = image_height + gap + height;
+                    let top = rect.top + ((rect.bottom - rect.top) - block_height).max(0) / 2;
+
+                    if let Some(image) = &state.image {
+                        let left = rect.left + (width - image_width) / 2;
+                        let dest = RECT {
+                            left,
+                            top,
+                            right: left + image_width,
+                            bottom: top + image_height,
+                        };
+                        draw_image(hdc, image, &dest);
+                    }
+
+                    bounds.left = rect.left + margin;
+                    bounds.right = rect.right - margin;
+                    bounds.top = top + image_height + gap;
+                    bounds.bottom = bounds.top + height;
+
+                    let _ = DrawTextW(hdc, &mut wide_text, &mut bounds, format);
 
                     SelectObject(hdc, old_font);
                     let _ = DeleteObject(font);
 
+                    // Small print along the bottom, clear of the message
+                    if let Some(note) = &state.note {
+                        let mut wide_note: Vec<u16> = note.encode_utf16().collect();
+                        let small_font = CreateFontW(
+                            20, 0, 0, 0,
+                            FW_NORMAL.0 as i32,
+                            0, 0, 0,
+                            DEFAULT_CHARSET.0 as u32,
+                            OUT_DEFAULT_PRECIS.0 as u32,
+                            CLIP_DEFAULT_PRECIS.0 as u32,
+                            CLEARTYPE_QUALITY.0 as u32,
+                            DEFAULT_PITCH.0 as u32 | (FF_DONTCARE.0 as u32),
+                            w!("Segoe UI"),
+                        );
+                        let old_font = SelectObject(hdc, small_font);
+                        let mut note_bounds = RECT {
+                            left: rect.left + margin,
+                            right: rect.right - margin,
+                            top: rect.bottom - 64,
+                            bottom: rect.bottom - 24,
+                        };
+                        let _ = DrawTextW(
+                            hdc,
+                            &mut wide_note,
+                            &mut note_bounds,
+                            DT_CENTER | DT_BOTTOM | DT_SINGLELINE | DT_NOPREFIX,
+                        );
+                        SelectObject(hdc, old_font);
+                        let _ = DeleteObject(small_font);
+                    }
+
                     let _ = EndPaint(hwnd, &ps);
                 }
             });
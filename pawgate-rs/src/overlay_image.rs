@@ -0,0 +1,114 @@
+//! Picture shown in the middle of the lock overlay
+//!
+//! Decodes `overlay_image` into the premultiplied BGRA layout that GDI's
+//! AlphaBlend expects, and works out how large to draw it.
+
+use std::path::Path;
+
+/// Largest share of the overlay's width and height the image may cover
+pub const MAX_SCREEN_FRACTION: f32 = 0.3;
+
+/// A decoded image ready for drawing
+pub struct OverlayImage {
+    pub width: u32,
+    pub height: u32,
+    /// Premultiplied BGRA pixels, top row first
+    pub bgra: Vec<u8>,
+}
+
+/// Load and decode an image file (PNG, JPEG, BMP, ...)
+///
+/// Returns None, with a warning logged, if the file can't be read or decoded.
+pub fn load(path: &Path) -> Option<OverlayImage> {
+    let image = match image::open(path) {
+        Ok(image) => image.to_rgba8(),
+        Err(e) => {
+            log::warn!("Failed to load overlay image {}: {}", path.display(), e);
+            return None;
+        }
+    };
+    if image.width() == 0 || image.height() == 0 {
+        log::warn!("Overlay image {} is empty", path.display());
+        return None;
+    }
+
+    Some(OverlayImage {
+        width: image.width(),
+        height: image.height(),
+        bgra: premultiply_bgra(image.as_raw()),
+    })
+}
+
+/// Convert straight-alpha RGBA to premultiplied BGRA
+fn premultiply_bgra(rgba: &[u8]) -> Vec<u8> {
+    rgba.chunks_exact(4)
+        .flat_map(|px| {
+            let a = u16::from(px[3]);
+            let scale = |c: u8| ((u16::from(c) * a + 127) / 255) as u8;
+            [scale(px[2]), scale(px[1]), scale(px[0]), px[3]]
+        })
+        .collect()
+}
+
+/// Size to draw an image of `size` so it fits inside `bounds`
+///
+/// Keeps the aspect ratio and never scales up, so small icons stay crisp.
+pub fn fit_within(size: (u32, u32), bounds: (u32, u32)) -> (u32, u32) {
+    let (width, height) = size;
+    let (max_width, max_height) = bounds;
+    if width == 0 || height == 0 {
+        return (0, 0);
+    }
+
+    let scale = (max_width as f64 / width as f64)
+        .min(max_height as f64 / height as f64)
+        .min(1.0);
+    (
+        (width as f64 * scale).round() as u32,
+        (height as f64 * scale).round() as u32,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_within_scales_down_preserving_aspect() {
+        /// WHY: A big photo must shrink into the box without being stretched.
+        // 4000x3000 into 576x324 (30% of 1920x1080): height is the limit
+        assert_eq!(fit_within((4000, 3000), (576, 324)), (432, 324));
+        // A tall image is limited by height, a wide one by width
+        assert_eq!(fit_within((1000, 2000), (500, 500)), (250, 500));
+        assert_eq!(fit_within((2000, 1000), (500, 500)), (500, 250));
+    }
+
+    #[test]
+    fn test_fit_within_never_upscales() {
+        /// WHY: Blowing up a 64px paw icon would just make it blurry.
+        assert_eq!(fit_within((64, 64), (576, 324)), (64, 64));
+    }
+
+    #[test]
+    fn test_fit_within_degenerate_sizes() {
+        /// WHY: Zero-sized inputs must not divide by zero or panic.
+        assert_eq!(fit_within((0, 100), (500, 500)), (0, 0));
+        assert_eq!(fit_within((100, 100), (0, 0)), (0, 0));
+    }
+
+    #[test]
+    fn test_premultiply_bgra() {
+        /// WHY: AlphaBlend with AC_SRC_ALPHA expects premultiplied BGRA; straight RGBA shows fringes.
+        let rgba = [255, 0, 0, 255, 0, 255, 0, 128, 10, 20, 30, 0];
+        assert_eq!(
+            premultiply_bgra(&rgba),
+            vec![0, 0, 255, 255, 0, 128, 0, 128, 0, 0, 0, 0]
+        );
+    }
+
+    #[test]
+    fn test_load_missing_file() {
+        /// WHY: A bad path falls back to no image instead of failing the lock.
+        assert!(load(Path::new("/definitely/not/here/cat.png")).is_none());
+    }
+}
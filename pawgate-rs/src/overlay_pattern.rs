@@ -0,0 +1,133 @@
+//! Paw prints tiled across the overlay (`overlay_pattern: "paws"`)
+//!
+//! The overlay paints each paw as five filled ellipses, a pad and four toes,
+//! in a color just off the tint. Where the paws go is plain geometry, kept
+//! here so it can be tested without a window.
+
+use windows::Win32::Foundation::RECT;
+
+/// Closest allowed distance between paws, in pixels
+///
+/// WHY: Keeps a tiny `pattern_spacing` from painting hundreds of thousands
+/// of ellipses on every repaint.
+pub const MIN_SPACING: u32 = 48;
+
+/// How far the paws are blended from the tint toward the text color
+pub const PATTERN_STRENGTH: f32 = 0.12;
+
+/// Centers of the paws covering a `width` x `height` area
+///
+/// Paws sit `spacing` apart in rows `spacing` apart, with every other row
+/// shifted by half a step so they don't line up in columns. Only paws whose
+/// center lies inside the area are returned.
+pub fn glyph_positions(width: i32, height: i32, spacing: u32) -> Vec<(i32, i32)> {
+    let step = spacing.max(MIN_SPACING) as i32;
+    let mut positions = Vec::new();
+    let mut y = step / 2;
+    let mut row = 0;
+    while y < height {
+        let mut x = if row % 2 == 0 { step / 2 } else { step };
+        while x < width {
+            positions.push((x, y));
+            x += step;
+        }
+        y += step;
+        row += 1;
+    }
+    positions
+}
+
+/// The pad and four toes of a paw centered on `center`, as ellipse bounds
+///
+/// `size` is the paw's overall height; the toes fan out above the pad.
+pub fn paw_shapes((x, y): (i32, i32), size: i32) -> [RECT; 5] {
+    let ellipse = |cx: i32, cy: i32, w: i32, h: i32| RECT {
+        left: cx - w / 2,
+        top: cy - h / 2,
+        right: cx + w / 2,
+        bottom: cy + h / 2,
+    };
+    let unit = size / 10;
+    [
+        // Pad
+        ellipse(x, y + unit * 2, unit * 6, unit * 5),
+        // Toes, outer ones lower
+        ellipse(x - unit * 4, y - unit, unit * 2, unit * 3),
+        ellipse(x - unit * 3 / 2, y - unit * 3, unit * 2, unit * 3),
+        ellipse(x + unit * 3 / 2, y - unit * 3, unit * 2, unit * 3),
+        ellipse(x + unit * 4, y - unit, unit * 2, unit * 3),
+    ]
+}
+
+/// Overall height of the paws for a given spacing
+pub fn paw_size(spacing: u32) -> i32 {
+    spacing.max(MIN_SPACING) as i32 * 2 / 5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions_cover_area_in_staggered_rows() {
+        /// WHY: The pattern must fill the screen evenly, with alternate rows offset.
+        let positions = glyph_positions(400, 250, 100);
+        assert_eq!(
+            positions,
+            vec![
+                (50, 50),
+                (150, 50),
+                (250, 50),
+                (350, 50),
+                (100, 150),
+                (200, 150),
+                (300, 150),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_positions_stay_inside_area() {
+        /// WHY: Paws centered off-screen would be wasted painting.
+        for &(width, height, spacing) in &[(1920, 1080, 160), (3840, 2160, 97), (800, 600, 300)] {
+            let positions = glyph_positions(width, height, spacing);
+            assert!(!positions.is_empty());
+            assert!(
+                positions.iter().all(|&(x, y)| (0..width).contains(&x) && (0..height).contains(&y)),
+                "{}x{} at {}",
+                width,
+                height,
+                spacing
+            );
+        }
+    }
+
+    #[test]
+    fn test_tiny_spacing_is_clamped() {
+        /// WHY: A spacing of 1 would mean millions of ellipses per repaint.
+        assert_eq!(glyph_positions(1920, 1080, 1), glyph_positions(1920, 1080, MIN_SPACING));
+        assert!(glyph_positions(1920, 1080, 0).len() < 1000);
+    }
+
+    #[test]
+    fn test_empty_area_has_no_positions() {
+        /// WHY: A zero-sized window (e.g. mid-creation) must not loop or panic.
+        assert!(glyph_positions(0, 0, 160).is_empty());
+        assert!(glyph_positions(-10, 500, 160).is_empty());
+    }
+
+    #[test]
+    fn test_paw_fits_its_cell() {
+        /// WHY: Neighboring paws must not overlap into one blob.
+        let spacing = 160;
+        let size = paw_size(spacing);
+        let shapes = paw_shapes((500, 500), size);
+        let left = shapes.iter().map(|r| r.left).min().unwrap();
+        let right = shapes.iter().map(|r| r.right).max().unwrap();
+        let top = shapes.iter().map(|r| r.top).min().unwrap();
+        let bottom = shapes.iter().map(|r| r.bottom).max().unwrap();
+        assert!(right - left < spacing as i32, "width {}", right - left);
+        assert!(bottom - top < spacing as i32, "height {}", bottom - top);
+        assert!(shapes.iter().all(|r| r.right > r.left && r.bottom > r.top));
+    }
+}
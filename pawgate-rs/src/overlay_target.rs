@@ -0,0 +1,142 @@
+//! Picking the monitor for a single-monitor `overlay_target`
+//!
+//! The overlay asks Windows for the connected monitors and, for
+//! `activemonitor`, for the bounds of the monitor under the foreground window
+//! (MonitorFromWindow). The choice itself is plain geometry, kept here so it
+//! can be tested without real displays.
+
+use crate::config::OverlayTarget;
+use windows::Win32::Foundation::RECT;
+
+/// A monitor's bounds and device name, as reported by EnumDisplayMonitors
+#[derive(Debug, Clone)]
+pub struct MonitorArea {
+    pub rect: RECT,
+    pub device_name: String,
+    /// Whether Windows reports this as the primary monitor
+    pub primary: bool,
+}
+
+/// Index into `monitors` of the one monitor `target` covers, or None to
+/// cover monitors as usual (`All`, or no monitors found)
+///
+/// `active` is the bounds of the monitor under the foreground window, if
+/// there is one. The monitor overlapping it most is chosen; without it,
+/// `ActiveMonitor` falls back to the primary monitor.
+pub fn target_monitor(target: OverlayTarget, monitors: &[MonitorArea], active: Option<RECT>) -> Option<usize> {
+    match target {
+        OverlayTarget::All => None,
+        OverlayTarget::PrimaryMonitor => primary_monitor(monitors),
+        OverlayTarget::ActiveMonitor => active
+            .and_then(|active| {
+                monitors
+                    .iter()
+                    .enumerate()
+                    .map(|(index, monitor)| (index, overlap(&monitor.rect, &active)))
+                    .filter(|&(_, area)| area > 0)
+                    .max_by_key(|&(_, area)| area)
+                    .map(|(index, _)| index)
+            })
+            .or_else(|| primary_monitor(monitors)),
+    }
+}
+
+/// The primary monitor, or failing that the one at the desktop origin, or the first
+fn primary_monitor(monitors: &[MonitorArea]) -> Option<usize> {
+    monitors
+        .iter()
+        .position(|monitor| monitor.primary)
+        .or_else(|| monitors.iter().position(|monitor| contains_origin(&monitor.rect)))
+        .or_else(|| (!monitors.is_empty()).then_some(0))
+}
+
+// WHY: The primary monitor always has its top-left corner at (0, 0)
+fn contains_origin(rect: &RECT) -> bool {
+    rect.left <= 0 && rect.top <= 0 && rect.right > 0 && rect.bottom > 0
+}
+
+/// Area shared by two rectangles, in square pixels
+fn overlap(a: &RECT, b: &RECT) -> i64 {
+    let width = i64::from(a.right.min(b.right)) - i64::from(a.left.max(b.left));
+    let height = i64::from(a.bottom.min(b.bottom)) - i64::from(a.top.max(b.top));
+    width.max(0) * height.max(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> RECT {
+        RECT {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// A laptop panel at the origin (primary) with a 4K screen to its left
+    fn monitors() -> Vec<MonitorArea> {
+        vec![
+            MonitorArea {
+                rect: rect(-3840, -600, 0, 1560),
+                device_name: r"\\.\DISPLAY2".to_string(),
+                primary: false,
+            },
+            MonitorArea {
+                rect: rect(0, 0, 1920, 1080),
+                device_name: r"\\.\DISPLAY1".to_string(),
+                primary: true,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_all_covers_as_usual() {
+        /// WHY: The default must keep the existing whole-desktop (or `monitors`) behavior.
+        assert_eq!(target_monitor(OverlayTarget::All, &monitors(), Some(rect(0, 0, 1920, 1080))), None);
+    }
+
+    #[test]
+    fn test_active_monitor_matches_foreground_monitor() {
+        /// WHY: Only the screen the user is working on should be covered.
+        let active = Some(rect(-3840, -600, 0, 1560));
+        assert_eq!(target_monitor(OverlayTarget::ActiveMonitor, &monitors(), active), Some(0));
+        let active = Some(rect(0, 0, 1920, 1080));
+        assert_eq!(target_monitor(OverlayTarget::ActiveMonitor, &monitors(), active), Some(1));
+    }
+
+    #[test]
+    fn test_active_monitor_picks_largest_overlap() {
+        /// WHY: A window straddling two screens belongs to the one showing most of it.
+        let active = Some(rect(-500, 100, 1500, 900));
+        assert_eq!(target_monitor(OverlayTarget::ActiveMonitor, &monitors(), active), Some(1));
+        let active = Some(rect(-1500, 100, 500, 900));
+        assert_eq!(target_monitor(OverlayTarget::ActiveMonitor, &monitors(), active), Some(0));
+    }
+
+    #[test]
+    fn test_active_monitor_falls_back_to_primary() {
+        /// WHY: With no foreground window (e.g. the desktop), the primary monitor is the best guess.
+        assert_eq!(target_monitor(OverlayTarget::ActiveMonitor, &monitors(), None), Some(1));
+        let offscreen = Some(rect(5000, 5000, 6000, 6000));
+        assert_eq!(target_monitor(OverlayTarget::ActiveMonitor, &monitors(), offscreen), Some(1));
+    }
+
+    #[test]
+    fn test_primary_monitor_by_flag_then_origin() {
+        /// WHY: The primary is found even if the flag is missing, since it always sits at the origin.
+        assert_eq!(target_monitor(OverlayTarget::PrimaryMonitor, &monitors(), None), Some(1));
+
+        let mut unflagged = monitors();
+        unflagged[1].primary = false;
+        assert_eq!(target_monitor(OverlayTarget::PrimaryMonitor, &unflagged, None), Some(1));
+    }
+
+    #[test]
+    fn test_no_monitors_covers_as_usual() {
+        /// WHY: If enumeration fails, falling back to the whole desktop beats no overlay.
+        assert_eq!(target_monitor(OverlayTarget::PrimaryMonitor, &[], None), None);
+        assert_eq!(target_monitor(OverlayTarget::ActiveMonitor, &[], Some(rect(0, 0, 10, 10))), None);
+    }
+}
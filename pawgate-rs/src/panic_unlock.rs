@@ -0,0 +1,81 @@
+//! The panic unlock hotkey (`panic_unlock_hotkey`)
+//!
+//! However unlocking is set up (a passphrase, a gesture, a tap/hold key),
+//! the panic hotkey unlocks straight away. The keyboard hook feeds it every
+//! key press before any other lock handling, so no unlock setting, and no
+//! half-typed passphrase, can leave the keyboard stuck.
+
+use crate::config::Config;
+use crate::sequence::{SequenceMatch, SequenceMatcher};
+use std::time::{Duration, Instant};
+
+/// Panic hotkey used when none (or an invalid one) is configured
+pub const DEFAULT_PANIC_HOTKEY: &str = "ctrl+alt+shift+u";
+
+/// Watches key presses for the panic hotkey
+pub struct PanicUnlock {
+    matcher: SequenceMatcher,
+}
+
+impl PanicUnlock {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            matcher: SequenceMatcher::new(
+                vec![config.parsed_panic_unlock_hotkey()],
+                Duration::from_millis(u64::from(config.sequence_timeout_ms)),
+            ),
+        }
+    }
+
+    /// Feed a (non-modifier) key press; returns true when it completes the
+    /// panic hotkey
+    ///
+    /// `modifiers_held` is the hook's modifier check, as for [`SequenceMatcher::feed`].
+    pub fn key_down(&mut self, vk: u32, now: Instant, modifiers_held: impl Fn(u32) -> bool) -> bool {
+        self.matcher.feed(vk, now, modifiers_held) == SequenceMatch::Complete
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse_hotkey_sequence;
+
+    /// Press `hotkey`'s steps in order, holding exactly each step's modifiers
+    fn press(panic: &mut PanicUnlock, hotkey: &str) -> bool {
+        let now = Instant::now();
+        parse_hotkey_sequence(hotkey)
+            .unwrap()
+            .into_iter()
+            .map(|(modifiers, vk)| panic.key_down(vk, now, |held| held == modifiers))
+            .last()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_default_hotkey_unlocks() {
+        /// WHY: Ctrl+Alt+Shift+U must work out of the box, with nothing configured.
+        assert!(press(&mut PanicUnlock::new(&Config::default()), DEFAULT_PANIC_HOTKEY));
+    }
+
+    #[test]
+    fn test_other_presses_do_not_unlock() {
+        /// WHY: A paw that finds U, or Ctrl+U, mustn't get the panic unlock.
+        let mut panic = PanicUnlock::new(&Config::default());
+        assert!(!press(&mut panic, "u"));
+        assert!(!press(&mut panic, "ctrl+u"));
+        assert!(!press(&mut panic, "ctrl+alt+shift+y"));
+    }
+
+    #[test]
+    fn test_configured_hotkey_replaces_default() {
+        /// WHY: Someone whose keyboard layout lacks U, or who uses the combo elsewhere, can move it.
+        let config = Config {
+            panic_unlock_hotkey: "ctrl+alt+shift+f12".to_string(),
+            ..Config::default()
+        };
+        let mut panic = PanicUnlock::new(&config);
+        assert!(press(&mut panic, "ctrl+alt+shift+f12"));
+        assert!(!press(&mut panic, DEFAULT_PANIC_HOTKEY));
+    }
+}
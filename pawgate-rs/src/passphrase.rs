@@ -0,0 +1,132 @@
+//! Typed-passphrase unlock
+//!
+//! When a passphrase is configured, the keyboard hook feeds every key typed
+//! while locked into a [`PassphraseMatcher`] and only unlocks on a full match.
+
+/// Tracks progress through a passphrase one keystroke at a time
+pub struct PassphraseMatcher {
+    passphrase: Vec<char>,
+    progress: usize,
+}
+
+impl PassphraseMatcher {
+    /// Create a matcher; returns None for an empty passphrase
+    ///
+    /// Matching is case-insensitive.
+    pub fn new(passphrase: &str) -> Option<Self> {
+        let passphrase: Vec<char> = passphrase.chars().map(|c| c.to_ascii_lowercase()).collect();
+        if passphrase.is_empty() {
+            return None;
+        }
+        Some(Self {
+            passphrase,
+            progress: 0,
+        })
+    }
+
+    /// Feed one typed character; returns true when the passphrase is complete
+    pub fn feed(&mut self, c: char) -> bool {
+        let c = c.to_ascii_lowercase();
+
+        if self.passphrase[self.progress] == c {
+            self.progress += 1;
+        } else {
+            // WHY: A wrong key restarts the match, but it may itself be the
+            // first character (e.g. "lletmein" should still unlock).
+            self.progress = usize::from(self.passphrase[0] == c);
+        }
+
+        if self.progress == self.passphrase.len() {
+            self.progress = 0;
+            return true;
+        }
+        false
+    }
+
+    /// Discard any partially typed passphrase
+    pub fn reset(&mut self) {
+        self.progress = 0;
+    }
+}
+
+/// Map a virtual key code to the character it contributes to a passphrase
+///
+/// Only letters, digits and space are supported, independent of keyboard
+/// layout and shift state.
+pub fn vk_to_passphrase_char(vk: u32) -> Option<char> {
+    match vk {
+        // 'A'-'Z' and '0'-'9' share their ASCII codes with the virtual keys
+        0x41..=0x5A | 0x30..=0x39 => char::from_u32(vk).map(|c| c.to_ascii_lowercase()),
+        0x20 => Some(' '), // VK_SPACE
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed_all(matcher: &mut PassphraseMatcher, text: &str) -> bool {
+        text.chars().fold(false, |_, c| matcher.feed(c))
+    }
+
+    #[test]
+    fn test_exact_sequence_unlocks() {
+        /// WHY: Typing the configured word is how the keyboard unlocks in this mode.
+        let mut matcher = PassphraseMatcher::new("letmein").unwrap();
+
+        assert!(!feed_all(&mut matcher, "letmei"), "Partial input must not unlock");
+        assert!(matcher.feed('n'), "Final character should complete the match");
+    }
+
+    #[test]
+    fn test_mismatch_resets_buffer() {
+        /// WHY: A cat mashing keys must not accumulate its way to a match.
+        let mut matcher = PassphraseMatcher::new("letmein").unwrap();
+
+        assert!(!feed_all(&mut matcher, "letxmein"));
+        assert!(feed_all(&mut matcher, "letmein"), "Fresh attempt should still work");
+    }
+
+    #[test]
+    fn test_mismatch_on_first_char_restarts() {
+        /// WHY: A repeated first letter ("lletmein") is a natural typo and should still match.
+        let mut matcher = PassphraseMatcher::new("letmein").unwrap();
+
+        assert!(feed_all(&mut matcher, "lletmein"));
+    }
+
+    #[test]
+    fn test_match_is_case_insensitive() {
+        /// WHY: Shift state isn't tracked by the hook, so case can't matter.
+        let mut matcher = PassphraseMatcher::new("LetMeIn").unwrap();
+
+        assert!(feed_all(&mut matcher, "letmein"));
+    }
+
+    #[test]
+    fn test_reset_discards_progress() {
+        /// WHY: A half-typed passphrase from a previous lock must not carry over.
+        let mut matcher = PassphraseMatcher::new("abc").unwrap();
+
+        assert!(!feed_all(&mut matcher, "ab"));
+        matcher.reset();
+        assert!(!matcher.feed('c'));
+    }
+
+    #[test]
+    fn test_empty_passphrase_is_rejected() {
+        /// WHY: An empty passphrase would unlock on any keystroke.
+        assert!(PassphraseMatcher::new("").is_none());
+    }
+
+    #[test]
+    fn test_vk_to_passphrase_char() {
+        /// WHY: Only layout-independent keys can be part of a passphrase.
+        assert_eq!(vk_to_passphrase_char(0x41), Some('a'));
+        assert_eq!(vk_to_passphrase_char(0x5A), Some('z'));
+        assert_eq!(vk_to_passphrase_char(0x37), Some('7'));
+        assert_eq!(vk_to_passphrase_char(0x20), Some(' '));
+        assert_eq!(vk_to_passphrase_char(0x70), None, "F1 is not a passphrase key");
+    }
+}
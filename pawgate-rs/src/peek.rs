@@ -0,0 +1,161 @@
+//! Peek: briefly let input through without unlocking
+//!
+//! Pressing the `peek_hotkey` while locked sets `AppState::peeking`, and the
+//! hooks stop blocking. The tray loop owns a [`Peek`] that dims the overlay,
+//! counts down `peek_duration_ms` and then re-engages the lock by clearing
+//! the flag. Pressing the peek hotkey again, or unlocking, ends it early.
+
+use std::time::{Duration, Instant};
+
+/// What happened to the peek on this frame
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PeekChange {
+    /// Input is let through from now on
+    Started,
+    /// The peek ran out and the lock is enforced again
+    Relocked,
+    /// The peek was ended early, by the hotkey or by unlocking
+    Cancelled,
+}
+
+/// State machine for a peek in progress
+#[derive(Debug)]
+pub struct Peek {
+    duration: Duration,
+    /// When the current peek ends
+    deadline: Option<Instant>,
+}
+
+impl Peek {
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            deadline: None,
+        }
+    }
+
+    /// Whether input is currently being let through
+    pub fn is_active(&self) -> bool {
+        self.deadline.is_some()
+    }
+
+    /// Time left before the lock re-engages, or None if not peeking
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.deadline.map(|deadline| deadline.saturating_duration_since(now))
+    }
+
+    /// Advance the peek
+    ///
+    /// `requested` is `AppState::peeking` as set by the hook. After
+    /// `Relocked` or `Cancelled`, the caller clears that flag so the hooks
+    /// block again.
+    pub fn update(&mut self, requested: bool, locked: bool, now: Instant) -> Option<PeekChange> {
+        match self.deadline {
+            // Nothing to peek through when unlocked
+            None if requested && locked => {
+                self.deadline = Some(now + self.duration);
+                Some(PeekChange::Started)
+            }
+            None => None,
+            Some(_) if !requested || !locked => {
+                self.deadline = None;
+                Some(PeekChange::Cancelled)
+            }
+            Some(deadline) if now >= deadline => {
+                self.deadline = None;
+                Some(PeekChange::Relocked)
+            }
+            Some(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn peek() -> Peek {
+        Peek::new(Duration::from_secs(5))
+    }
+
+    #[test]
+    fn test_peek_relocks_after_duration() {
+        /// WHY: A peek must end by itself, so forgetting about it can't leave the keyboard open.
+        let start = Instant::now();
+        let mut peek = peek();
+
+        assert_eq!(peek.update(true, true, start), Some(PeekChange::Started));
+        assert!(peek.is_active());
+        assert_eq!(peek.update(true, true, start + Duration::from_secs(2)), None);
+        assert_eq!(peek.remaining(start + Duration::from_secs(2)), Some(Duration::from_secs(3)));
+        assert_eq!(
+            peek.update(true, true, start + Duration::from_secs(5)),
+            Some(PeekChange::Relocked)
+        );
+        assert!(!peek.is_active());
+        assert_eq!(peek.remaining(start + Duration::from_secs(5)), None);
+    }
+
+    #[test]
+    fn test_relock_fires_once() {
+        /// WHY: The frame after the re-lock still sees the flag set until the caller clears it.
+        let start = Instant::now();
+        let mut peek = peek();
+        peek.update(true, true, start);
+        assert_eq!(
+            peek.update(true, true, start + Duration::from_secs(9)),
+            Some(PeekChange::Relocked),
+            "A stalled loop still re-locks"
+        );
+        assert_eq!(peek.update(false, true, start + Duration::from_secs(9)), None);
+    }
+
+    #[test]
+    fn test_hotkey_again_cancels_early() {
+        /// WHY: Once the user has typed their one thing, they shouldn't have to wait out the peek.
+        let start = Instant::now();
+        let mut peek = peek();
+        peek.update(true, true, start);
+
+        assert_eq!(
+            peek.update(false, true, start + Duration::from_secs(1)),
+            Some(PeekChange::Cancelled)
+        );
+        assert!(!peek.is_active());
+    }
+
+    #[test]
+    fn test_unlock_cancels_peek() {
+        /// WHY: After a real unlock there's nothing left to re-lock.
+        let start = Instant::now();
+        let mut peek = peek();
+        peek.update(true, true, start);
+
+        assert_eq!(
+            peek.update(true, false, start + Duration::from_secs(1)),
+            Some(PeekChange::Cancelled)
+        );
+        assert_eq!(peek.update(false, false, start + Duration::from_secs(6)), None);
+    }
+
+    #[test]
+    fn test_no_peek_while_unlocked() {
+        /// WHY: The peek hotkey means nothing when input already flows freely.
+        let mut peek = peek();
+        assert_eq!(peek.update(true, false, Instant::now()), None);
+        assert!(!peek.is_active());
+    }
+
+    #[test]
+    fn test_peek_can_start_again_after_relock() {
+        /// WHY: Each press of the peek hotkey grants a fresh window.
+        let start = Instant::now();
+        let mut peek = peek();
+        peek.update(true, true, start);
+        peek.update(true, true, start + Duration::from_secs(5));
+
+        let later = start + Duration::from_secs(10);
+        assert_eq!(peek.update(true, true, later), Some(PeekChange::Started));
+        assert_eq!(peek.remaining(later), Some(Duration::from_secs(5)));
+    }
+}
@@ -0,0 +1,167 @@
+//! What PawGate needs from the operating system, behind one trait
+//!
+//! A [`PawGate`](crate::PawGate) only flips shared state to lock and unlock;
+//! a [`Platform`] is what turns that state into blocked input.
+//! [`WindowsPlatform`] installs the real keyboard and mouse hooks, through
+//! the same [`SystemHooks`] a [`LockGuard`](crate::lock_guard::LockGuard) uses.
+//! [`SimulatedPlatform`] blocks nothing: it logs each lock and unlock and
+//! keeps them in memory, so tests can run the lock logic without hooking the
+//! real keyboard. The crate itself still only builds for Windows.
+
+use crate::config::Config;
+use crate::failsafe;
+use crate::lock_guard::{InputHooks, SystemHooks};
+use crate::AppState;
+use log::info;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Enforces the lock held in [`AppState`]
+pub trait Platform: Send {
+    /// Start enforcing `state`'s lock; called once, by `register_hotkeys`
+    ///
+    /// Fails if the lock can't be enforced, e.g. the keyboard hook wasn't
+    /// installed; the platform isn't running then.
+    fn start(&mut self, state: Arc<AppState>, config: &Config) -> Result<(), String>;
+    /// Whether `start` has run and `stop` hasn't
+    fn is_running(&self) -> bool;
+    /// Stop enforcing, returning once input can no longer be blocked
+    fn stop(&mut self);
+}
+
+/// The keyboard (and, per `lock_mode`, mouse) hooks, run on their own thread
+#[derive(Default)]
+pub struct WindowsPlatform {
+    hooks: Option<SystemHooks>,
+}
+
+impl Platform for WindowsPlatform {
+    fn start(&mut self, state: Arc<AppState>, config: &Config) -> Result<(), String> {
+        if self.hooks.is_some() {
+            return Ok(());
+        }
+        // A crash must not leave the keyboard blocked
+        failsafe::install_panic_hook();
+
+        let mut hooks = SystemHooks::new(state, config.clone());
+        hooks.install()?;
+        self.hooks = Some(hooks);
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        self.hooks.is_some()
+    }
+
+    fn stop(&mut self) {
+        // The keyboard thread unhooks as it exits
+        if let Some(mut hooks) = self.hooks.take() {
+            hooks.uninstall();
+        }
+    }
+}
+
+/// A platform that blocks nothing and records every lock and unlock
+///
+/// Clones share the record, so keep one to inspect after handing the other
+/// to [`PawGate::with_platform`](crate::PawGate::with_platform).
+#[derive(Clone, Default)]
+pub struct SimulatedPlatform {
+    transitions: Arc<Mutex<Vec<bool>>>,
+    running: Arc<AtomicBool>,
+    subscribed: Arc<AtomicBool>,
+}
+
+impl SimulatedPlatform {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every lock state change seen while running, oldest first (true = locked)
+    pub fn transitions(&self) -> Vec<bool> {
+        self.transitions.lock().unwrap().clone()
+    }
+
+    /// Whether the simulated input is blocked
+    pub fn is_locked(&self) -> bool {
+        self.transitions.lock().unwrap().last().copied().unwrap_or(false)
+    }
+}
+
+impl Platform for SimulatedPlatform {
+    fn start(&mut self, state: Arc<AppState>, _config: &Config) -> Result<(), String> {
+        self.running.store(true, Ordering::SeqCst);
+        // WHY: Callbacks can't be removed, so a restart reuses the first one
+        if self.subscribed.swap(true, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let transitions = Arc::clone(&self.transitions);
+        let running = Arc::clone(&self.running);
+        state.on_state_change(Box::new(move |locked| {
+            if running.load(Ordering::SeqCst) {
+                info!("Simulated input {}", if locked { "blocked" } else { "released" });
+                transitions.lock().unwrap().push(locked);
+            }
+        }));
+        Ok(())
+    }
+
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::Trigger;
+
+    fn started() -> (Arc<AppState>, SimulatedPlatform) {
+        let state = Arc::new(AppState::default());
+        let mut platform = SimulatedPlatform::new();
+        platform.start(Arc::clone(&state), &Config::default()).unwrap();
+        (state, platform)
+    }
+
+    #[test]
+    fn test_simulated_follows_lock_cycle() {
+        /// WHY: Without real hooks the simulation is the only witness that a lock would have blocked input.
+        let (state, platform) = started();
+        assert!(platform.is_running());
+        assert!(!platform.is_locked());
+
+        state.set_locked(true, Trigger::Hotkey);
+        assert!(platform.is_locked());
+        state.set_locked(true, Trigger::Api);
+        state.set_locked(false, Trigger::Menu);
+        assert!(!platform.is_locked());
+        assert_eq!(platform.transitions(), vec![true, false], "Only real transitions are recorded");
+    }
+
+    #[test]
+    fn test_simulated_ignores_changes_while_stopped() {
+        /// WHY: A stopped platform enforces nothing, so it mustn't report input as blocked.
+        let (state, mut platform) = started();
+        platform.stop();
+        assert!(!platform.is_running());
+        state.set_locked(true, Trigger::Api);
+        assert!(!platform.is_locked());
+
+        platform.start(Arc::clone(&state), &Config::default()).unwrap();
+        state.set_locked(false, Trigger::Api);
+        assert_eq!(platform.transitions(), vec![false], "A restart records once, not twice");
+    }
+
+    #[test]
+    fn test_simulated_clones_share_record() {
+        /// WHY: Tests hand one clone to PawGate and read the record through the other.
+        let (state, platform) = started();
+        let observer = platform.clone();
+        state.set_locked(true, Trigger::Api);
+        assert!(observer.is_locked());
+    }
+}
@@ -0,0 +1,103 @@
+//! Keeping the display awake while locked
+//!
+//! With `keep_awake` set, the tray thread asks Windows to keep the display on
+//! (ES_DISPLAY_REQUIRED | ES_CONTINUOUS) while locked, so the overlay stays
+//! visible, and clears the request on unlock and on exit.
+
+use windows::Win32::System::Power::{
+    SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, EXECUTION_STATE,
+};
+
+/// Change to make to the thread's execution state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionChange {
+    /// Keep the display on until cleared
+    KeepDisplayOn,
+    /// Let the display sleep normally again
+    Clear,
+}
+
+/// What to do for the current lock state, given whether the display is
+/// already being kept on; `None` if nothing needs to change
+pub fn execution_change(keep_awake: bool, locked: bool, active: bool) -> Option<ExecutionChange> {
+    match (keep_awake && locked, active) {
+        (true, false) => Some(ExecutionChange::KeepDisplayOn),
+        (false, true) => Some(ExecutionChange::Clear),
+        _ => None,
+    }
+}
+
+/// Holds the keep-display-on request across lock transitions
+pub struct DisplayKeeper {
+    enabled: bool,
+    /// Whether the request is currently set
+    active: bool,
+}
+
+impl DisplayKeeper {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled, active: false }
+    }
+
+    /// Update the request for a lock state change
+    pub fn set_locked(&mut self, locked: bool) {
+        if let Some(change) = execution_change(self.enabled, locked, self.active) {
+            self.apply(change);
+        }
+    }
+
+    fn apply(&mut self, change: ExecutionChange) {
+        let flags = match change {
+            ExecutionChange::KeepDisplayOn => ES_CONTINUOUS | ES_DISPLAY_REQUIRED,
+            ExecutionChange::Clear => ES_CONTINUOUS,
+        };
+        // Returns the previous state, or zero on failure
+        if unsafe { SetThreadExecutionState(flags) } == EXECUTION_STATE(0) {
+            log::warn!("Failed to set thread execution state ({:?})", change);
+            return;
+        }
+        self.active = change == ExecutionChange::KeepDisplayOn;
+    }
+}
+
+impl Drop for DisplayKeeper {
+    fn drop(&mut self) {
+        // WHY: Don't leave the display pinned on if we exit while locked
+        if self.active {
+            self.apply(ExecutionChange::Clear);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_sets_and_unlock_clears() {
+        /// WHY: The display stays on exactly while locked.
+        assert_eq!(execution_change(true, true, false), Some(ExecutionChange::KeepDisplayOn));
+        assert_eq!(execution_change(true, false, true), Some(ExecutionChange::Clear));
+    }
+
+    #[test]
+    fn test_no_change_when_already_in_state() {
+        /// WHY: Repeating the call each transition is harmless, but there's no reason to.
+        assert_eq!(execution_change(true, true, true), None);
+        assert_eq!(execution_change(true, false, false), None);
+    }
+
+    #[test]
+    fn test_disabled_never_sets() {
+        /// WHY: Without keep_awake the user's power settings apply even while locked.
+        assert_eq!(execution_change(false, true, false), None);
+        assert_eq!(execution_change(false, false, false), None);
+    }
+
+    #[test]
+    fn test_disabled_clears_leftover_request() {
+        /// WHY: Turning keep_awake off mid-lock must still release the display on unlock.
+        assert_eq!(execution_change(false, true, true), Some(ExecutionChange::Clear));
+        assert_eq!(execution_change(false, false, true), Some(ExecutionChange::Clear));
+    }
+}
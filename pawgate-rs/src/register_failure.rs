@@ -0,0 +1,142 @@
+//! What to do when no hotkey can be registered (`on_register_failure`)
+//!
+//! A hotkey can't be registered when it doesn't parse or is a combo Windows
+//! keeps for itself. As long as one configured hotkey works, the others are
+//! just skipped with a warning; this only decides what happens when none do.
+//! Each failed attempt goes through [`next_action`], so `fallbackdefault`
+//! tries `ctrl+b` and, should that fail too, notifies instead.
+
+use crate::config::{parse_hotkey_sequence, sequence_conflict, OnRegisterFailure};
+
+/// Hotkey tried by `fallbackdefault`
+pub const FALLBACK_HOTKEY: &str = "ctrl+b";
+
+/// Which registration attempt failed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Failure {
+    /// None of the configured hotkeys could be registered
+    Configured,
+    /// The fallback hotkey couldn't be registered either
+    Fallback,
+}
+
+/// What to do after a failed attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Try [`FALLBACK_HOTKEY`]
+    TryFallback,
+    /// Show a notification and run without a hotkey
+    Notify,
+    /// Show an error and quit
+    Exit,
+}
+
+/// The next step of the fallback chain for `policy` after `failure`
+pub fn next_action(policy: OnRegisterFailure, failure: Failure) -> Action {
+    match (policy, failure) {
+        (OnRegisterFailure::Exit, _) => Action::Exit,
+        (OnRegisterFailure::FallbackDefault, Failure::Configured) => Action::TryFallback,
+        (OnRegisterFailure::FallbackDefault, Failure::Fallback) | (OnRegisterFailure::Notify, _) => Action::Notify,
+    }
+}
+
+/// The hotkeys PawGate ends up with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The configured hotkeys (at least one worked)
+    Configured(Vec<Vec<(u32, u32)>>),
+    /// Only the fallback hotkey
+    Fallback(Vec<(u32, u32)>),
+    /// No hotkey; the user should be told
+    Hotkeyless,
+    /// No hotkey, and PawGate should quit
+    Exit,
+}
+
+/// Run the fallback chain for the hotkeys that parsed (`Config::parsed_hotkeys`)
+pub fn resolve(configured: Vec<Vec<(u32, u32)>>, policy: OnRegisterFailure) -> Outcome {
+    if !configured.is_empty() {
+        return Outcome::Configured(configured);
+    }
+    let mut action = next_action(policy, Failure::Configured);
+    loop {
+        match action {
+            Action::TryFallback => match parse_hotkey_sequence(FALLBACK_HOTKEY) {
+                Some(sequence) if sequence_conflict(&sequence).is_none() => return Outcome::Fallback(sequence),
+                _ => action = next_action(policy, Failure::Fallback),
+            },
+            Action::Notify => return Outcome::Hotkeyless,
+            Action::Exit => return Outcome::Exit,
+        }
+    }
+}
+
+/// Why PawGate was left without a hotkey, for the notification or error dialog
+pub fn failure_message(hotkeys: &[String]) -> String {
+    if hotkeys.is_empty() {
+        "No hotkey is configured.".to_string()
+    } else {
+        format!("None of the hotkeys ({}) could be registered.", hotkeys.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICIES: [OnRegisterFailure; 3] =
+        [OnRegisterFailure::FallbackDefault, OnRegisterFailure::Notify, OnRegisterFailure::Exit];
+
+    #[test]
+    fn test_fallback_default_chain() {
+        /// WHY: fallbackdefault must try ctrl+b first, and only notify if that fails too.
+        let policy = OnRegisterFailure::FallbackDefault;
+        assert_eq!(next_action(policy, Failure::Configured), Action::TryFallback);
+        assert_eq!(next_action(policy, Failure::Fallback), Action::Notify);
+    }
+
+    #[test]
+    fn test_notify_and_exit_skip_fallback() {
+        /// WHY: Users who chose notify or exit asked not to get a hotkey they didn't configure.
+        for failure in [Failure::Configured, Failure::Fallback] {
+            assert_eq!(next_action(OnRegisterFailure::Notify, failure), Action::Notify);
+            assert_eq!(next_action(OnRegisterFailure::Exit, failure), Action::Exit);
+        }
+    }
+
+    #[test]
+    fn test_chain_never_retries_fallback() {
+        /// WHY: A fallback that fails must end the chain, not loop trying it again.
+        for policy in POLICIES {
+            assert_ne!(next_action(policy, Failure::Fallback), Action::TryFallback, "{:?}", policy);
+        }
+    }
+
+    #[test]
+    fn test_working_hotkeys_are_kept() {
+        /// WHY: The policy only applies when nothing registered; a working hotkey is never replaced.
+        let configured = vec![vec![(0x0002, 0x4C)]];
+        for policy in POLICIES {
+            assert_eq!(resolve(configured.clone(), policy), Outcome::Configured(configured.clone()));
+        }
+    }
+
+    #[test]
+    fn test_resolve_without_hotkeys() {
+        /// WHY: Each policy must end somewhere definite when every hotkey failed.
+        assert_eq!(
+            resolve(Vec::new(), OnRegisterFailure::FallbackDefault),
+            Outcome::Fallback(parse_hotkey_sequence(FALLBACK_HOTKEY).unwrap())
+        );
+        assert_eq!(resolve(Vec::new(), OnRegisterFailure::Notify), Outcome::Hotkeyless);
+        assert_eq!(resolve(Vec::new(), OnRegisterFailure::Exit), Outcome::Exit);
+    }
+
+    #[test]
+    fn test_failure_message_lists_hotkeys() {
+        /// WHY: The user needs to see which hotkeys to fix.
+        let message = failure_message(&["win+l".to_string(), "ctrl+alt+del".to_string()]);
+        assert!(message.contains("win+l, ctrl+alt+del"), "{}", message);
+        assert!(failure_message(&[]).contains("No hotkey is configured"));
+    }
+}
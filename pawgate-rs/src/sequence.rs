@@ -0,0 +1,156 @@
+//! Matching of multi-step hotkey sequences such as "ctrl+b,l"
+//!
+//! Every configured hotkey is a sequence of (modifiers, vk) steps; a plain
+//! combo is just a one-step sequence. Steps must follow each other within the
+//! configured timeout.
+
+use std::time::{Duration, Instant};
+
+/// Outcome of feeding one key press to a [`SequenceMatcher`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequenceMatch {
+    /// A whole sequence was just completed
+    Complete,
+    /// The key advanced a sequence that isn't finished yet
+    InProgress,
+    /// The key isn't part of any sequence
+    NoMatch,
+}
+
+/// Tracks progress through each configured hotkey sequence
+pub struct SequenceMatcher {
+    sequences: Vec<Vec<(u32, u32)>>,
+    timeout: Duration,
+    /// Number of steps matched so far, per sequence
+    progress: Vec<usize>,
+    /// When the last step was matched
+    last_step: Option<Instant>,
+}
+
+impl SequenceMatcher {
+    pub fn new(sequences: Vec<Vec<(u32, u32)>>, timeout: Duration) -> Self {
+        let progress = vec![0; sequences.len()];
+        Self {
+            sequences,
+            timeout,
+            progress,
+            last_step: None,
+        }
+    }
+
+    /// Feed a key press
+    ///
+    /// `modifiers_held` reports whether exactly the given modifier flags are
+    /// currently down; the hook passes its GetAsyncKeyState check here.
+    pub fn feed(
+        &mut self,
+        vk: u32,
+        now: Instant,
+        modifiers_held: impl Fn(u32) -> bool,
+    ) -> SequenceMatch {
+        // A step that comes too late starts every sequence over
+        if self
+            .last_step
+            .is_some_and(|last| now.saturating_duration_since(last) > self.timeout)
+        {
+            self.reset();
+        }
+
+        let step_matches = |&(modifiers, step_vk): &(u32, u32)| step_vk == vk && modifiers_held(modifiers);
+
+        for (sequence, progress) in self.sequences.iter().zip(self.progress.iter_mut()) {
+            if step_matches(&sequence[*progress]) {
+                *progress += 1;
+            } else {
+                // WHY: A wrong key may itself be the first step of a fresh attempt
+                *progress = usize::from(step_matches(&sequence[0]));
+            }
+
+            if *progress == sequence.len() {
+                self.reset();
+                return SequenceMatch::Complete;
+            }
+        }
+
+        if self.progress.iter().any(|&p| p > 0) {
+            self.last_step = Some(now);
+            SequenceMatch::InProgress
+        } else {
+            self.last_step = None;
+            SequenceMatch::NoMatch
+        }
+    }
+
+    /// Discard progress on every sequence
+    pub fn reset(&mut self) {
+        self.progress.iter_mut().for_each(|p| *p = 0);
+        self.last_step = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CTRL: u32 = 0x0002;
+    const B: u32 = 0x42;
+    const L: u32 = 0x4C;
+
+    /// Feed `vk` with exactly `held` modifiers down
+    fn press(matcher: &mut SequenceMatcher, held: u32, vk: u32, at: Instant) -> SequenceMatch {
+        matcher.feed(vk, at, |modifiers| modifiers == held)
+    }
+
+    #[test]
+    fn test_single_combo_completes_immediately() {
+        /// WHY: Plain hotkeys are one-step sequences and must behave as before.
+        let mut matcher = SequenceMatcher::new(vec![vec![(CTRL, B)]], Duration::from_secs(1));
+        let now = Instant::now();
+
+        assert_eq!(press(&mut matcher, CTRL, B, now), SequenceMatch::Complete);
+        assert_eq!(press(&mut matcher, 0, B, now), SequenceMatch::NoMatch, "Modifiers must match");
+    }
+
+    #[test]
+    fn test_two_step_sequence() {
+        /// WHY: "ctrl+b,l" should fire only after both steps, in order.
+        let mut matcher = SequenceMatcher::new(vec![vec![(CTRL, B), (0, L)]], Duration::from_secs(1));
+        let now = Instant::now();
+
+        assert_eq!(press(&mut matcher, 0, L, now), SequenceMatch::NoMatch);
+        assert_eq!(press(&mut matcher, CTRL, B, now), SequenceMatch::InProgress);
+        assert_eq!(
+            press(&mut matcher, 0, L, now + Duration::from_millis(500)),
+            SequenceMatch::Complete
+        );
+    }
+
+    #[test]
+    fn test_sequence_times_out() {
+        /// WHY: A leader press long ago must not combine with an unrelated later key.
+        let mut matcher = SequenceMatcher::new(vec![vec![(CTRL, B), (0, L)]], Duration::from_secs(1));
+        let now = Instant::now();
+
+        press(&mut matcher, CTRL, B, now);
+        assert_eq!(
+            press(&mut matcher, 0, L, now + Duration::from_millis(1500)),
+            SequenceMatch::NoMatch
+        );
+    }
+
+    #[test]
+    fn test_wrong_key_restarts_sequence() {
+        /// WHY: Only the exact ordered sequence counts; a stray key breaks it.
+        let mut matcher = SequenceMatcher::new(vec![vec![(CTRL, B), (0, L)]], Duration::from_secs(1));
+        let now = Instant::now();
+
+        press(&mut matcher, CTRL, B, now);
+        assert_eq!(press(&mut matcher, 0, 0x58, now), SequenceMatch::NoMatch);
+        assert_eq!(press(&mut matcher, 0, L, now), SequenceMatch::NoMatch);
+
+        // Repeating the leader is a fresh start, not a failure
+        press(&mut matcher, CTRL, B, now);
+        assert_eq!(press(&mut matcher, CTRL, B, now), SequenceMatch::InProgress);
+        assert_eq!(press(&mut matcher, 0, L, now), SequenceMatch::Complete);
+    }
+}
@@ -0,0 +1,79 @@
+//! Recovering the lock after a Windows session switch
+//!
+//! Fast user switching, locking the session or an RDP reconnect can silently
+//! drop low-level hooks. The control window registers for
+//! WM_WTSSESSION_CHANGE and records whether this session is on screen in
+//! `AppState::session_active`; when it comes back while locked, the tray loop
+//! asks the keyboard thread to reinstall the hooks and re-shows the overlay.
+
+use log::warn;
+use windows::Win32::Foundation::HWND;
+use windows::Win32::System::RemoteDesktop::{WTSRegisterSessionNotification, NOTIFY_FOR_THIS_SESSION};
+use windows::Win32::UI::WindowsAndMessaging::{
+    WTS_CONSOLE_CONNECT, WTS_CONSOLE_DISCONNECT, WTS_REMOTE_CONNECT, WTS_REMOTE_DISCONNECT,
+    WTS_SESSION_LOCK, WTS_SESSION_LOGOFF, WTS_SESSION_LOGON, WTS_SESSION_UNLOCK,
+};
+
+/// Ask Windows to send WM_WTSSESSION_CHANGE for this session to `hwnd`
+pub fn register_notifications(hwnd: HWND) {
+    if let Err(e) = unsafe { WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) } {
+        warn!("Failed to register for session notifications: {}", e);
+    }
+}
+
+/// Whether this session is on screen after a WM_WTSSESSION_CHANGE event
+/// (its WPARAM), or None if the event doesn't change that
+pub fn active_after(event: u32) -> Option<bool> {
+    match event {
+        WTS_CONSOLE_CONNECT | WTS_REMOTE_CONNECT | WTS_SESSION_LOGON | WTS_SESSION_UNLOCK => Some(true),
+        WTS_CONSOLE_DISCONNECT | WTS_REMOTE_DISCONNECT | WTS_SESSION_LOGOFF | WTS_SESSION_LOCK => {
+            Some(false)
+        }
+        _ => None,
+    }
+}
+
+/// Whether the hooks and overlay must be restored, given whether the
+/// session was active before and is now, and whether PawGate is locked
+pub fn needs_reinstall(was_active: bool, is_active: bool, locked: bool) -> bool {
+    // WHY: Unlocked, nothing is being blocked, so there's nothing to lose;
+    // the hooks are still reinstalled on the next return while locked.
+    !was_active && is_active && locked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reinstall_when_session_returns_locked() {
+        /// WHY: The whole point: coming back to a locked PawGate must block input again.
+        assert!(needs_reinstall(false, true, true));
+    }
+
+    #[test]
+    fn test_no_reinstall_while_unlocked() {
+        /// WHY: Reinstalling while unlocked would churn hooks for nothing.
+        assert!(!needs_reinstall(false, true, false));
+    }
+
+    #[test]
+    fn test_no_reinstall_without_a_return() {
+        /// WHY: Only the switch back to this session can have dropped the hooks.
+        assert!(!needs_reinstall(true, true, true), "Still active");
+        assert!(!needs_reinstall(true, false, true), "Leaving the session");
+        assert!(!needs_reinstall(false, false, true), "Still away");
+    }
+
+    #[test]
+    fn test_session_events_map_to_activity() {
+        /// WHY: Every way of leaving and returning (local, RDP, lock screen) must be tracked.
+        for event in [WTS_CONSOLE_CONNECT, WTS_REMOTE_CONNECT, WTS_SESSION_LOGON, WTS_SESSION_UNLOCK] {
+            assert_eq!(active_after(event), Some(true), "event {}", event);
+        }
+        for event in [WTS_CONSOLE_DISCONNECT, WTS_REMOTE_DISCONNECT, WTS_SESSION_LOGOFF, WTS_SESSION_LOCK] {
+            assert_eq!(active_after(event), Some(false), "event {}", event);
+        }
+        assert_eq!(active_after(0xFFFF), None, "Unrelated events change nothing");
+    }
+}
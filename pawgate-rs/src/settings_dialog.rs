@@ -151,7 +151,7 @@ unsafe fn create_dialog_controls(hwnd: HWND, config: &Config) {
     let hotkey_edit = CreateWindowExW(
         WS_EX_CLIENTEDGE,
         w!("EDIT"),
-        PCWSTR(to_wide(&config.hotkey).as_ptr()),
+        PCWSTR(to_wide(config.hotkeys.first().map(String::as_str).unwrap_or("")).as_ptr()),
         WS_CHILD | WS_VISIBLE | WS_TABSTOP | WINDOW_STYLE(ES_AUTOHSCROLL as u32),
         control_x, y, control_width, 24,
         Some(hwnd),
@@ -441,11 +441,15 @@ unsafe fn gather_dialog_values(hwnd: HWND) -> Option<Config> {
     DIALOG_CONFIG.with(|c| {
         let mut config = c.borrow().clone()?;
 
-        // Get hotkey
+        // Get hotkey (the dialog edits the primary one; extra hotkeys are kept)
         if let Some(edit) = GetDlgItem(hwnd, ID_HOTKEY_EDIT) {
             let mut buffer = [0u16; 256];
             let len = GetWindowTextW(edit, &mut buffer) as usize;
-            config.hotkey = from_wide(&buffer[..len]);
+            let hotkey = from_wide(&buffer[..len]);
+            match config.hotkeys.first_mut() {
+                Some(primary) => *primary = hotkey,
+                None => config.hotkeys.push(hotkey),
+            }
         }
 
         // Get opacity from slider
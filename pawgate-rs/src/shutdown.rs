@@ -0,0 +1,101 @@
+//! Quitting cleanly, including while locked
+//!
+//! However PawGate is asked to quit, the tray loop tears down in one order:
+//! release the lock, remove the input hooks, remove the overlay, and only
+//! then return so the process can exit. A quit can't leave an overlay
+//! claiming a lock that nothing enforces, or hooks blocking input with no
+//! overlay to say why.
+//!
+//! With `confirm_quit_while_locked`, quitting from the tray menu while locked
+//! first asks on the overlay for a Y keypress; any other key stays locked.
+
+use log::info;
+
+/// Key that confirms a quit while locked ('Y')
+pub const CONFIRM_KEY: u32 = 0x59;
+
+/// Overlay text while a quit waits for confirmation
+pub const CONFIRM_TEXT: &str = "Quit PawGate? Press Y to unlock and quit, any other key to stay locked";
+
+/// The steps of tearing PawGate down; implemented by the tray loop and by
+/// a recording mock in tests
+pub trait Teardown {
+    /// Release the lock, and the window a `targetwindow` lock disabled
+    fn unlock(&mut self);
+    /// Remove the keyboard and mouse hooks, returning once they're gone
+    fn remove_hooks(&mut self);
+    /// Destroy the overlay windows
+    fn remove_overlay(&mut self);
+}
+
+/// Tear down in order; the caller exits once this returns
+///
+/// `locked` is the lock state when the quit was accepted. The lock is
+/// released first, so no later step runs while input is still blocked.
+pub fn shut_down(teardown: &mut impl Teardown, locked: bool) {
+    if locked {
+        info!("Unlocking before quitting");
+        teardown.unlock();
+    }
+    teardown.remove_hooks();
+    teardown.remove_overlay();
+}
+
+/// Whether a key pressed while a quit waits for confirmation confirms it
+pub fn confirms_quit(vk: u32) -> bool {
+    vk == CONFIRM_KEY
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the teardown steps instead of touching Windows
+    #[derive(Default)]
+    struct MockTeardown {
+        steps: Vec<&'static str>,
+    }
+
+    impl Teardown for MockTeardown {
+        fn unlock(&mut self) {
+            self.steps.push("unlock");
+        }
+
+        fn remove_hooks(&mut self) {
+            self.steps.push("remove_hooks");
+        }
+
+        fn remove_overlay(&mut self) {
+            self.steps.push("remove_overlay");
+        }
+    }
+
+    /// Shut down the mock, then "exit" the way the tray loop does: by returning
+    fn quit(locked: bool) -> Vec<&'static str> {
+        let mut teardown = MockTeardown::default();
+        shut_down(&mut teardown, locked);
+        teardown.steps.push("exit");
+        teardown.steps
+    }
+
+    #[test]
+    fn test_quit_while_locked_unlocks_first() {
+        /// WHY: Removing hooks or the overlay while still locked is the half-locked state quitting must avoid.
+        assert_eq!(quit(true), vec!["unlock", "remove_hooks", "remove_overlay", "exit"]);
+    }
+
+    #[test]
+    fn test_quit_while_unlocked_skips_unlock() {
+        /// WHY: There's nothing to release, and an unlock would log a transition that never happened.
+        assert_eq!(quit(false), vec!["remove_hooks", "remove_overlay", "exit"]);
+    }
+
+    #[test]
+    fn test_only_y_confirms() {
+        /// WHY: A paw mashing keys is what locked in the first place; only one specific key may quit.
+        assert!(confirms_quit(0x59));
+        for vk in [0x0D, 0x1B, 0x20, 0x4E, 0x5A] {
+            assert!(!confirms_quit(vk), "vk {:#x}", vk);
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Temporarily disabling the hotkey and auto-lock ("snooze")
+//!
+//! `pawgate snooze <minutes>` asks the running instance to ignore the lock
+//! hotkey for a while. The tray loop owns a [`Snooze`] and publishes whether
+//! it's active through `AppState::snoozed`, which the keyboard hook and idle
+//! monitor check.
+
+use std::time::{Duration, Instant};
+
+/// Value of `AppState::snooze_request` when no request is waiting
+pub const NO_REQUEST: u32 = u32::MAX;
+
+/// State machine for a snooze that re-arms the hotkey when it runs out
+#[derive(Debug, Default)]
+pub struct Snooze {
+    until: Option<Instant>,
+}
+
+impl Snooze {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Snooze for `duration` from `now`, replacing any running snooze
+    pub fn start(&mut self, now: Instant, duration: Duration) {
+        self.until = Some(now + duration);
+    }
+
+    /// End the snooze early
+    pub fn cancel(&mut self) {
+        self.until = None;
+    }
+
+    pub fn is_active(&self, now: Instant) -> bool {
+        self.remaining(now).is_some_and(|left| !left.is_zero())
+    }
+
+    /// Time left before the hotkey re-arms, or None if not snoozed
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.until.map(|until| until.saturating_duration_since(now))
+    }
+
+    /// Advance the snooze; returns true exactly once, when it runs out
+    pub fn tick(&mut self, now: Instant) -> bool {
+        if self.remaining(now) == Some(Duration::ZERO) {
+            self.until = None;
+            return true;
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snooze_remaining_time() {
+        /// WHY: The snooze must last exactly as long as requested.
+        let start = Instant::now();
+        let mut snooze = Snooze::new();
+        assert_eq!(snooze.remaining(start), None);
+        assert!(!snooze.is_active(start));
+
+        snooze.start(start, Duration::from_secs(600));
+        assert!(snooze.is_active(start));
+        assert_eq!(snooze.remaining(start + Duration::from_secs(60)), Some(Duration::from_secs(540)));
+        assert!(snooze.is_active(start + Duration::from_secs(599)));
+    }
+
+    #[test]
+    fn test_snooze_rearms_once() {
+        /// WHY: When the time is up the hotkey must come back, and the re-arm is reported once.
+        let start = Instant::now();
+        let mut snooze = Snooze::new();
+        snooze.start(start, Duration::from_secs(60));
+
+        assert!(!snooze.tick(start + Duration::from_secs(59)));
+        let end = start + Duration::from_secs(60);
+        assert!(!snooze.is_active(end));
+        assert!(snooze.tick(end), "Re-arms when the snooze runs out");
+        assert!(!snooze.tick(end + Duration::from_secs(1)), "Reported only once");
+        assert_eq!(snooze.remaining(end), None);
+    }
+
+    #[test]
+    fn test_snooze_restart_and_cancel() {
+        /// WHY: Snoozing again extends from now, and cancelling re-arms immediately.
+        let start = Instant::now();
+        let mut snooze = Snooze::new();
+        snooze.start(start, Duration::from_secs(60));
+        snooze.start(start + Duration::from_secs(50), Duration::from_secs(60));
+        assert!(snooze.is_active(start + Duration::from_secs(100)));
+
+        snooze.cancel();
+        assert!(!snooze.is_active(start));
+        assert!(!snooze.tick(start + Duration::from_secs(200)), "Cancelled snooze doesn't report a re-arm");
+    }
+}
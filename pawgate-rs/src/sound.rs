@@ -0,0 +1,38 @@
+//! Audible cues for lock and unlock
+//!
+//! Plays user-supplied WAV files with PlaySound. Problems are logged and
+//! otherwise ignored: a missing sound must never get in the way of locking.
+
+use log::warn;
+use std::path::Path;
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::HMODULE;
+use windows::Win32::Media::Audio::{PlaySoundW, SND_ASYNC, SND_FILENAME, SND_NODEFAULT};
+
+/// Start playing the WAV file at `path` without waiting for it to finish
+pub fn play_wav(path: &Path) {
+    if !path.is_file() {
+        warn!("Sound file not found: {}", path.display());
+        return;
+    }
+
+    let wide_path: Vec<u16> = path
+        .as_os_str()
+        .to_string_lossy()
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .collect();
+
+    // WHY: SND_NODEFAULT stops Windows from playing its default "ding" when
+    // the file can't be decoded, so a bad file is silent rather than confusing.
+    let played = unsafe {
+        PlaySoundW(
+            PCWSTR(wide_path.as_ptr()),
+            HMODULE::default(),
+            SND_FILENAME | SND_ASYNC | SND_NODEFAULT,
+        )
+    };
+    if !played.as_bool() {
+        warn!("Could not play sound file: {}", path.display());
+    }
+}
@@ -0,0 +1,129 @@
+//! Lifetime totals of blocked keystrokes
+//!
+//! The keyboard hook counts the keys it swallows in
+//! `AppState::blocked_keys`; on each unlock the tray loop adds that count to
+//! ~/.pawgate/stats.json and reports it in the unlock notification.
+
+use crate::config::{self, Config};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Totals across every lock since the stats file was created
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Stats {
+    /// Number of locks that have ended
+    pub locks: u64,
+    /// Keystrokes swallowed across all locks
+    pub keys_blocked: u64,
+    /// Most keystrokes swallowed during a single lock
+    pub most_in_one_lock: u64,
+}
+
+impl Stats {
+    /// Add one finished lock during which `blocked` keys were swallowed
+    pub fn record_lock(&mut self, blocked: u64) {
+        self.locks = self.locks.saturating_add(1);
+        self.keys_blocked = self.keys_blocked.saturating_add(blocked);
+        self.most_in_one_lock = self.most_in_one_lock.max(blocked);
+    }
+
+    /// Read the stats file; a missing or unreadable file starts from zero
+    ///
+    /// WHY: The totals are just for fun, so a damaged file shouldn't get in
+    /// the way of unlocking.
+    pub fn load(path: &Path) -> Self {
+        let Ok(contents) = fs::read_to_string(path) else {
+            return Self::default();
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            log::warn!("Ignoring unreadable stats file {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(self).expect("Stats is always serializable");
+        config::write_atomic(path, contents.as_bytes())
+    }
+}
+
+/// Path of the stats file (~/.pawgate/stats.json)
+pub fn stats_path() -> PathBuf {
+    Config::config_dir().join("stats.json")
+}
+
+/// Add a finished lock to the stats file at `path`, returning the new totals
+pub fn record_lock(path: &Path, blocked: u64) -> std::io::Result<Stats> {
+    let mut stats = Stats::load(path);
+    stats.record_lock(blocked);
+    stats.save(path)?;
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::ScratchDir;
+
+    #[test]
+    fn test_record_lock_accumulates() {
+        /// WHY: Totals must add up across locks and remember the busiest one.
+        let mut stats = Stats::default();
+        stats.record_lock(47);
+        stats.record_lock(0);
+        stats.record_lock(12);
+        assert_eq!(
+            stats,
+            Stats {
+                locks: 3,
+                keys_blocked: 59,
+                most_in_one_lock: 47,
+            }
+        );
+    }
+
+    #[test]
+    fn test_record_lock_saturates() {
+        /// WHY: A counter overflow must not panic the tray loop.
+        let mut stats = Stats {
+            keys_blocked: u64::MAX - 1,
+            ..Stats::default()
+        };
+        stats.record_lock(5);
+        assert_eq!(stats.keys_blocked, u64::MAX);
+    }
+
+    #[test]
+    fn test_stats_file_round_trip() {
+        /// WHY: Lifetime totals must survive restarts.
+        let dir = ScratchDir::new("stats-round-trip");
+        let path = dir.join("stats.json");
+        assert_eq!(Stats::load(&path), Stats::default(), "Missing file starts at zero");
+
+        record_lock(&path, 10).unwrap();
+        let stats = record_lock(&path, 4).unwrap();
+        assert_eq!(stats.keys_blocked, 14);
+        assert_eq!(Stats::load(&path), stats);
+
+        let json = fs::read_to_string(&path).unwrap();
+        assert!(json.contains(r#""keys_blocked": 14"#), "{}", json);
+    }
+
+    #[test]
+    fn test_damaged_stats_file_starts_over() {
+        /// WHY: A corrupt or older file must not stop the count, and missing fields default to zero.
+        let dir = ScratchDir::new("stats-damaged");
+        let path = dir.join("stats.json");
+        fs::write(&path, "{not json").unwrap();
+        assert_eq!(Stats::load(&path), Stats::default());
+
+        fs::write(&path, r#"{"locks": 2}"#).unwrap();
+        assert_eq!(Stats::load(&path).locks, 2);
+        assert_eq!(Stats::load(&path).keys_blocked, 0);
+    }
+}
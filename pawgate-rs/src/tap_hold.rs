@@ -0,0 +1,140 @@
+//! One key that locks when tapped and unlocks when held (`tap_hold_key`)
+//!
+//! Releasing the key sooner than `hold_threshold_ms` after pressing it is a
+//! tap, which toggles the lock like the hotkey. Holding it for at least that
+//! long is a hold, which always unlocks. Only the press and release times
+//! matter, so the discriminator itself never touches the lock.
+
+use std::time::{Duration, Instant};
+
+/// How a press of the tap/hold key was classified
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Press {
+    Tap,
+    Hold,
+}
+
+/// Classifies presses of one key by how long it was held down
+pub struct TapHoldDiscriminator {
+    /// Virtual keys that count as the key (e.g. left and right Ctrl)
+    keys: Vec<u32>,
+    threshold: Duration,
+    /// When the current press started; also ignores auto-repeat
+    pressed_at: Option<Instant>,
+}
+
+impl TapHoldDiscriminator {
+    pub fn new(keys: Vec<u32>, threshold: Duration) -> Self {
+        Self {
+            keys,
+            threshold,
+            pressed_at: None,
+        }
+    }
+
+    /// Whether `vk` is the tap/hold key
+    pub fn is_key(&self, vk: u32) -> bool {
+        self.keys.contains(&vk)
+    }
+
+    /// Feed a key press
+    pub fn key_down(&mut self, vk: u32, now: Instant) {
+        if self.is_key(vk) && self.pressed_at.is_none() {
+            self.pressed_at = Some(now);
+        }
+    }
+
+    /// Feed a key release; returns how the press that ended was held
+    ///
+    /// None for other keys, and for a release whose press wasn't seen
+    /// (e.g. the key was already down when PawGate started).
+    pub fn key_up(&mut self, vk: u32, now: Instant) -> Option<Press> {
+        if !self.is_key(vk) {
+            return None;
+        }
+        let pressed_at = self.pressed_at.take()?;
+        if now.saturating_duration_since(pressed_at) >= self.threshold {
+            Some(Press::Hold)
+        } else {
+            Some(Press::Tap)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PAUSE: u32 = 0x13;
+    const LCTRL: u32 = 0xA2;
+    const RCTRL: u32 = 0xA3;
+    const B: u32 = 0x42;
+
+    fn discriminator() -> TapHoldDiscriminator {
+        TapHoldDiscriminator::new(vec![PAUSE], Duration::from_millis(500))
+    }
+
+    /// Press `vk` at `start` ms and release it `hold` ms later
+    fn press(discriminator: &mut TapHoldDiscriminator, vk: u32, base: Instant, start: u64, hold: u64) -> Option<Press> {
+        discriminator.key_down(vk, base + Duration::from_millis(start));
+        discriminator.key_up(vk, base + Duration::from_millis(start + hold))
+    }
+
+    #[test]
+    fn test_short_press_is_tap() {
+        /// WHY: A quick press is the everyday toggle.
+        let mut discriminator = discriminator();
+        let base = Instant::now();
+        assert_eq!(press(&mut discriminator, PAUSE, base, 0, 80), Some(Press::Tap));
+        assert_eq!(press(&mut discriminator, PAUSE, base, 200, 499), Some(Press::Tap));
+    }
+
+    #[test]
+    fn test_long_press_is_hold() {
+        /// WHY: Holding past the threshold must unlock, however long the hold.
+        let mut discriminator = discriminator();
+        let base = Instant::now();
+        assert_eq!(press(&mut discriminator, PAUSE, base, 0, 500), Some(Press::Hold), "The threshold itself is a hold");
+        assert_eq!(press(&mut discriminator, PAUSE, base, 1000, 5000), Some(Press::Hold));
+    }
+
+    #[test]
+    fn test_auto_repeat_does_not_restart_press() {
+        /// WHY: A held key sends repeated key-downs; timing must run from the first.
+        let mut discriminator = discriminator();
+        let base = Instant::now();
+        discriminator.key_down(PAUSE, base);
+        for ms in (30..600).step_by(30) {
+            discriminator.key_down(PAUSE, base + Duration::from_millis(ms));
+        }
+        assert_eq!(discriminator.key_up(PAUSE, base + Duration::from_millis(610)), Some(Press::Hold));
+    }
+
+    #[test]
+    fn test_other_keys_ignored() {
+        /// WHY: Typing other keys mid-press must neither classify nor reset the press.
+        let mut discriminator = discriminator();
+        let base = Instant::now();
+        discriminator.key_down(PAUSE, base);
+        assert_eq!(press(&mut discriminator, B, base, 100, 50), None);
+        assert_eq!(discriminator.key_up(PAUSE, base + Duration::from_millis(700)), Some(Press::Hold));
+    }
+
+    #[test]
+    fn test_release_without_press() {
+        /// WHY: A key already down at startup releases without a press; guessing could unlock by mistake.
+        let mut discriminator = discriminator();
+        let base = Instant::now();
+        assert_eq!(discriminator.key_up(PAUSE, base), None);
+        assert_eq!(press(&mut discriminator, PAUSE, base, 100, 50), Some(Press::Tap), "The next press works");
+    }
+
+    #[test]
+    fn test_either_side_counts() {
+        /// WHY: "ctrl" means either Ctrl key, as it does in hotkeys.
+        let mut discriminator = TapHoldDiscriminator::new(vec![LCTRL, RCTRL], Duration::from_millis(500));
+        let base = Instant::now();
+        assert_eq!(press(&mut discriminator, RCTRL, base, 0, 50), Some(Press::Tap));
+        assert_eq!(press(&mut discriminator, LCTRL, base, 100, 800), Some(Press::Hold));
+    }
+}
@@ -0,0 +1,159 @@
+//! Locking a single window (`lock_mode: "targetwindow"`)
+//!
+//! Instead of blocking input everywhere, the window in the foreground at
+//! lock time is disabled with EnableWindow, so it ignores keyboard and mouse
+//! input while the rest of the desktop keeps working, and the overlay covers
+//! just that window. The disabled window is also recorded process-wide, so
+//! the fail-safes can enable it again if the tray loop crashes or hangs.
+
+use std::sync::atomic::{AtomicIsize, Ordering};
+use windows::Win32::Foundation::{HWND, RECT};
+use windows::Win32::Graphics::Gdi::{GetMonitorInfoW, MonitorFromWindow, MONITORINFO, MONITOR_DEFAULTTONEAREST};
+use windows::Win32::System::Threading::GetCurrentProcessId;
+use windows::Win32::UI::Input::KeyboardAndMouse::EnableWindow;
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, GetShellWindow, GetWindowRect, GetWindowThreadProcessId, IsWindow,
+};
+
+/// Raw handle of the window disabled by the current lock (0 = none)
+static DISABLED_WINDOW: AtomicIsize = AtomicIsize::new(0);
+
+/// Where the overlay goes over a window with bounds `window` on `monitor`
+///
+/// WHY: A maximized window's frame hangs a few pixels off its monitor, and
+/// an overlay that did too would show on the neighboring display. None when
+/// the window is off-screen (minimized windows sit at -32000, -32000).
+pub fn overlay_rect(window: &RECT, monitor: &RECT) -> Option<RECT> {
+    let rect = RECT {
+        left: window.left.max(monitor.left),
+        top: window.top.max(monitor.top),
+        right: window.right.min(monitor.right),
+        bottom: window.bottom.min(monitor.bottom),
+    };
+    (rect.right > rect.left && rect.bottom > rect.top).then_some(rect)
+}
+
+/// Disables the foreground window while locked; enables it again on unlock or drop
+#[derive(Default)]
+pub struct WindowLock {
+    hwnd: Option<HWND>,
+}
+
+impl WindowLock {
+    /// Disable the foreground window, returning where its overlay goes
+    ///
+    /// Returns None, disabling nothing, if there is no window to lock: the
+    /// desktop, one of PawGate's own windows, or one that's off-screen.
+    pub fn lock(&mut self) -> Option<RECT> {
+        self.unlock();
+        unsafe {
+            let hwnd = GetForegroundWindow();
+            if hwnd.0.is_null() || hwnd == GetShellWindow() {
+                return None;
+            }
+            let mut process_id = 0;
+            GetWindowThreadProcessId(hwnd, Some(&mut process_id));
+            if process_id == GetCurrentProcessId() {
+                return None;
+            }
+
+            let mut window = RECT::default();
+            GetWindowRect(hwnd, &mut window).ok()?;
+            let mut info = MONITORINFO {
+                cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+                ..Default::default()
+            };
+            if !GetMonitorInfoW(MonitorFromWindow(hwnd, MONITOR_DEFAULTTONEAREST), &mut info).as_bool() {
+                return None;
+            }
+            let rect = overlay_rect(&window, &info.rcMonitor)?;
+
+            let _ = EnableWindow(hwnd, false);
+            DISABLED_WINDOW.store(hwnd.0 as isize, Ordering::SeqCst);
+            self.hwnd = Some(hwnd);
+            Some(rect)
+        }
+    }
+
+    /// Enable the locked window again, if there is one
+    pub fn unlock(&mut self) {
+        if self.hwnd.take().is_some() {
+            release_disabled_window();
+        }
+    }
+}
+
+impl Drop for WindowLock {
+    fn drop(&mut self) {
+        self.unlock();
+    }
+}
+
+/// Enable the window a lock disabled, from any thread
+///
+/// Returns whether there was one. Called by the fail-safes as well as on
+/// unlock, so a crash never leaves a window that ignores all input.
+pub fn release_disabled_window() -> bool {
+    let raw = DISABLED_WINDOW.swap(0, Ordering::SeqCst);
+    if raw == 0 {
+        return false;
+    }
+    let hwnd = HWND(raw as *mut std::ffi::c_void);
+    unsafe {
+        // The window may have been closed while locked
+        if IsWindow(hwnd).as_bool() {
+            let _ = EnableWindow(hwnd, true);
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONITOR: RECT = RECT {
+        left: 0,
+        top: 0,
+        right: 1920,
+        bottom: 1080,
+    };
+
+    fn rect(left: i32, top: i32, right: i32, bottom: i32) -> RECT {
+        RECT { left, top, right, bottom }
+    }
+
+    #[test]
+    fn test_overlay_matches_window() {
+        /// WHY: The overlay should cover exactly the locked window and nothing else.
+        assert_eq!(overlay_rect(&rect(200, 150, 1000, 700), &MONITOR), Some(rect(200, 150, 1000, 700)));
+    }
+
+    #[test]
+    fn test_maximized_window_clipped_to_monitor() {
+        /// WHY: A maximized window's frame overhangs its monitor by a few pixels.
+        assert_eq!(overlay_rect(&rect(-8, -8, 1928, 1088), &MONITOR), Some(MONITOR));
+    }
+
+    #[test]
+    fn test_window_on_second_monitor() {
+        /// WHY: Windows on a monitor left of the primary have negative coordinates.
+        let left_monitor = rect(-1280, 0, 0, 1024);
+        assert_eq!(
+            overlay_rect(&rect(-1000, 100, -200, 600), &left_monitor),
+            Some(rect(-1000, 100, -200, 600))
+        );
+        assert_eq!(
+            overlay_rect(&rect(-300, 100, 400, 600), &left_monitor),
+            Some(rect(-300, 100, 0, 600)),
+            "Only the part on the window's own monitor is covered"
+        );
+    }
+
+    #[test]
+    fn test_offscreen_window_has_no_overlay() {
+        /// WHY: Minimized windows sit at -32000; covering "them" would put an overlay nowhere.
+        assert_eq!(overlay_rect(&rect(-32000, -32000, -31840, -31972), &MONITOR), None);
+        assert_eq!(overlay_rect(&rect(500, 500, 500, 800), &MONITOR), None, "Zero width");
+    }
+}
@@ -0,0 +1,312 @@
+//! PawGate's own corner toast, for `notification_position` other than "system"
+//!
+//! A small borderless, topmost window in a corner of the primary monitor's
+//! work area. It never takes focus, so it can't steal keystrokes from the
+//! app underneath, and the tray loop hides it again once
+//! `notification_duration_ms` has passed.
+
+use crate::config::NotificationPosition;
+use std::cell::RefCell;
+use std::time::{Duration, Instant};
+use windows::core::{w, PCWSTR};
+use windows::Win32::Foundation::*;
+use windows::Win32::Graphics::Gdi::*;
+use windows::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+const TOAST_CLASS_NAME: PCWSTR = w!("PawGateToast");
+
+/// Size of the toast window, in pixels
+const TOAST_SIZE: (i32, i32) = (340, 96);
+/// Gap between the toast and the edges of the work area
+const MARGIN: i32 = 16;
+/// Padding between the window edge and the text
+const PADDING: i32 = 14;
+
+const BACKGROUND: (u8, u8, u8) = (0x2B, 0x2B, 0x2B);
+const TEXT_COLOR: (u8, u8, u8) = (0xFF, 0xFF, 0xFF);
+
+/// Title and body the toast window paints
+thread_local! {
+    static TOAST_TEXT: RefCell<(String, String)> = const { RefCell::new((String::new(), String::new())) };
+}
+
+/// Top-left corner of a `width` x `height` toast at `position` inside `work_area`
+///
+/// Returns None for `System`, which leaves placement to Windows. A toast
+/// larger than the work area is pinned to its top-left rather than pushed
+/// off-screen.
+pub fn toast_origin(position: NotificationPosition, work_area: &RECT, (width, height): (i32, i32)) -> Option<(i32, i32)> {
+    let left = work_area.left + MARGIN;
+    let top = work_area.top + MARGIN;
+    let right = (work_area.right - MARGIN - width).max(work_area.left);
+    let bottom = (work_area.bottom - MARGIN - height).max(work_area.top);
+    match position {
+        NotificationPosition::System => None,
+        NotificationPosition::TopLeft => Some((left, top)),
+        NotificationPosition::TopRight => Some((right, top)),
+        NotificationPosition::BottomLeft => Some((left, bottom)),
+        NotificationPosition::BottomRight => Some((right, bottom)),
+    }
+}
+
+/// Shows notifications in a corner and hides them when they expire
+pub struct CornerToast {
+    position: NotificationPosition,
+    duration: Duration,
+    /// Created on first use
+    hwnd: Option<HWND>,
+    /// When the toast showing now should hide
+    hide_at: Option<Instant>,
+}
+
+impl CornerToast {
+    pub fn new(position: NotificationPosition, duration_ms: u32) -> Self {
+        Self {
+            position,
+            duration: Duration::from_millis(u64::from(duration_ms)),
+            hwnd: None,
+            hide_at: None,
+        }
+    }
+
+    /// Whether notifications go here rather than to a Windows toast
+    pub fn is_enabled(&self) -> bool {
+        self.position != NotificationPosition::System
+    }
+
+    /// Show `title` and `body`, replacing whatever the toast was showing
+    pub fn show(&mut self, title: &str, body: &str, now: Instant) {
+        let Some((x, y)) = toast_origin(self.position, &primary_work_area(), TOAST_SIZE) else {
+            return;
+        };
+        if self.hwnd.is_none() {
+            self.hwnd = create_toast_window();
+        }
+        let Some(hwnd) = self.hwnd else {
+            log::warn!("Failed to create notification window");
+            return;
+        };
+        TOAST_TEXT.with(|text| *text.borrow_mut() = (title.to_string(), body.to_string()));
+        unsafe {
+            let _ = SetWindowPos(
+                hwnd,
+                HWND_TOPMOST,
+                x,
+                y,
+                TOAST_SIZE.0,
+                TOAST_SIZE.1,
+                SWP_NOACTIVATE | SWP_SHOWWINDOW,
+            );
+            let _ = InvalidateRect(hwnd, None, true);
+        }
+        self.hide_at = Some(now + self.duration);
+    }
+
+    /// Hide the toast once its time is up; call every frame
+    pub fn update(&mut self, now: Instant) {
+        if self.hide_at.is_some_and(|hide_at| now >= hide_at) {
+            self.hide_at = None;
+            if let Some(hwnd) = self.hwnd {
+                unsafe {
+                    let _ = ShowWindow(hwnd, SW_HIDE);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for CornerToast {
+    fn drop(&mut self) {
+        if let Some(hwnd) = self.hwnd.take() {
+            unsafe {
+                let _ = DestroyWindow(hwnd);
+            }
+        }
+    }
+}
+
+/// The primary monitor's desktop area minus the taskbar
+fn primary_work_area() -> RECT {
+    let mut area = RECT::default();
+    unsafe {
+        if SystemParametersInfoW(
+            SPI_GETWORKAREA,
+            0,
+            Some(&mut area as *mut RECT as *mut std::ffi::c_void),
+            SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0),
+        )
+        .is_err()
+        {
+            area = RECT {
+                left: 0,
+                top: 0,
+                right: GetSystemMetrics(SM_CXSCREEN),
+                bottom: GetSystemMetrics(SM_CYSCREEN),
+            };
+        }
+    }
+    area
+}
+
+/// Create the (hidden) toast window
+fn create_toast_window() -> Option<HWND> {
+    unsafe {
+        let hinstance = GetModuleHandleW(None).ok()?;
+        let wc = WNDCLASSEXW {
+            cbSize: std::mem::size_of::<WNDCLASSEXW>() as u32,
+            lpfnWndProc: Some(toast_wnd_proc),
+            hInstance: hinstance.into(),
+            hCursor: LoadCursorW(None, IDC_ARROW).unwrap_or_default(),
+            lpszClassName: TOAST_CLASS_NAME,
+            ..Default::default()
+        };
+        RegisterClassExW(&wc);
+
+        // WHY: WS_EX_NOACTIVATE keeps focus (and typing) in the current app;
+        // WS_EX_TOOLWINDOW keeps the toast off the taskbar and Alt+Tab.
+        CreateWindowExW(
+            WS_EX_TOPMOST | WS_EX_TOOLWINDOW | WS_EX_NOACTIVATE,
+            TOAST_CLASS_NAME,
+            w!("PawGate Notification"),
+            WS_POPUP,
+            0,
+            0,
+            TOAST_SIZE.0,
+            TOAST_SIZE.1,
+            None,
+            None,
+            hinstance,
+            None,
+        )
+        .ok()
+    }
+}
+
+fn colorref((r, g, b): (u8, u8, u8)) -> COLORREF {
+    COLORREF((r as u32) | ((g as u32) << 8) | ((b as u32) << 16))
+}
+
+unsafe fn create_font(height: i32, weight: i32) -> HFONT {
+    CreateFontW(
+        height, 0, 0, 0,
+        weight,
+        0, 0, 0,
+        DEFAULT_CHARSET.0 as u32,
+        OUT_DEFAULT_PRECIS.0 as u32,
+        CLIP_DEFAULT_PRECIS.0 as u32,
+        CLEARTYPE_QUALITY.0 as u32,
+        DEFAULT_PITCH.0 as u32 | (FF_DONTCARE.0 as u32),
+        w!("Segoe UI"),
+    )
+}
+
+unsafe extern "system" fn toast_wnd_proc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    match msg {
+        WM_PAINT => {
+            let mut ps = PAINTSTRUCT::default();
+            let hdc = BeginPaint(hwnd, &mut ps);
+            let mut rect = RECT::default();
+            let _ = GetClientRect(hwnd, &mut rect);
+
+            let brush = CreateSolidBrush(colorref(BACKGROUND));
+            FillRect(hdc, &rect, brush);
+            let _ = DeleteObject(brush);
+
+            SetTextColor(hdc, colorref(TEXT_COLOR));
+            SetBkMode(hdc, TRANSPARENT);
+            let mut bounds = RECT {
+                left: rect.left + PADDING,
+                top: rect.top + PADDING,
+                right: rect.right - PADDING,
+                bottom: rect.bottom - PADDING,
+            };
+
+            TOAST_TEXT.with(|text| {
+                let (title, body) = &*text.borrow();
+
+                let title_font = create_font(22, FW_BOLD.0 as i32);
+                let old_font = SelectObject(hdc, title_font);
+                let mut wide_title: Vec<u16> = title.encode_utf16().collect();
+                let title_height = DrawTextW(hdc, &mut wide_title, &mut bounds.clone(), DT_SINGLELINE | DT_NOPREFIX | DT_END_ELLIPSIS);
+
+                let body_font = create_font(18, FW_NORMAL.0 as i32);
+                SelectObject(hdc, body_font);
+                bounds.top += title_height + 4;
+                let mut wide_body: Vec<u16> = body.encode_utf16().collect();
+                let _ = DrawTextW(hdc, &mut wide_body, &mut bounds, DT_WORDBREAK | DT_NOPREFIX | DT_END_ELLIPSIS);
+
+                SelectObject(hdc, old_font);
+                let _ = DeleteObject(title_font);
+                let _ = DeleteObject(body_font);
+            });
+
+            let _ = EndPaint(hwnd, &ps);
+            LRESULT(0)
+        }
+
+        // WHY: Clicking the toast must not activate it either
+        WM_MOUSEACTIVATE => LRESULT(MA_NOACTIVATE as isize),
+
+        _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: RECT = RECT {
+        left: 0,
+        top: 0,
+        right: 1920,
+        bottom: 1040,
+    };
+
+    #[test]
+    fn test_corner_origins() {
+        /// WHY: Each corner must keep the whole toast on screen with the same margin.
+        let size = (300, 80);
+        assert_eq!(toast_origin(NotificationPosition::TopLeft, &SCREEN, size), Some((16, 16)));
+        assert_eq!(toast_origin(NotificationPosition::TopRight, &SCREEN, size), Some((1604, 16)));
+        assert_eq!(toast_origin(NotificationPosition::BottomLeft, &SCREEN, size), Some((16, 944)));
+        assert_eq!(toast_origin(NotificationPosition::BottomRight, &SCREEN, size), Some((1604, 944)));
+    }
+
+    #[test]
+    fn test_work_area_offset() {
+        /// WHY: A taskbar on the top or left moves the work area away from (0, 0).
+        let area = RECT {
+            left: 60,
+            top: 40,
+            right: 1920,
+            bottom: 1080,
+        };
+        let size = (300, 80);
+        assert_eq!(toast_origin(NotificationPosition::TopLeft, &area, size), Some((76, 56)));
+        assert_eq!(toast_origin(NotificationPosition::BottomRight, &area, size), Some((1604, 984)));
+    }
+
+    #[test]
+    fn test_oversized_toast_stays_on_screen() {
+        /// WHY: On a tiny display the text must start on screen even if the rest is cut off.
+        let area = RECT {
+            left: 0,
+            top: 0,
+            right: 200,
+            bottom: 60,
+        };
+        assert_eq!(toast_origin(NotificationPosition::BottomRight, &area, (300, 80)), Some((0, 0)));
+    }
+
+    #[test]
+    fn test_system_position_has_no_origin() {
+        /// WHY: "system" hands the notification to Windows, which places it itself.
+        assert_eq!(toast_origin(NotificationPosition::System, &SCREEN, (300, 80)), None);
+    }
+}
@@ -0,0 +1,176 @@
+//! Keeping the overlay above other topmost windows while locked
+//!
+//! Topmost windows are only ordered among themselves by which was raised
+//! last, so a full-screen game or another always-on-top window can end up
+//! over the overlay. While locked, the tray loop puts the overlay back on
+//! top every `reassert_topmost_ms`, and straight away when the foreground
+//! window changes.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::UI::Accessibility::{SetWinEventHook, UnhookWinEvent, HWINEVENTHOOK};
+use windows::Win32::UI::WindowsAndMessaging::{
+    EVENT_SYSTEM_FOREGROUND, WINEVENT_OUTOFCONTEXT, WINEVENT_SKIPOWNPROCESS,
+};
+
+/// Set by the foreground event hook, cleared by [`ForegroundWatch::take_changed`]
+static FOREGROUND_CHANGED: AtomicBool = AtomicBool::new(false);
+
+/// Decides when the overlay should be raised again
+pub struct TopmostGuard {
+    /// None when `reassert_topmost_ms` is 0
+    interval: Option<Duration>,
+    /// When the overlay was last raised during this lock
+    last: Option<Instant>,
+}
+
+impl TopmostGuard {
+    pub fn new(interval_ms: u32) -> Self {
+        Self {
+            interval: (interval_ms > 0).then(|| Duration::from_millis(u64::from(interval_ms))),
+            last: None,
+        }
+    }
+
+    /// Whether re-raising is on at all
+    pub fn is_enabled(&self) -> bool {
+        self.interval.is_some()
+    }
+
+    /// Whether to raise the overlay this frame
+    ///
+    /// Locking shows the overlay on top already, so the first locked frame
+    /// only starts the clock. After that the overlay is raised once per
+    /// interval, or right away when `foreground_changed`.
+    pub fn due(&mut self, now: Instant, locked: bool, foreground_changed: bool) -> bool {
+        let Some(interval) = self.interval else {
+            return false;
+        };
+        if !locked {
+            self.last = None;
+            return false;
+        }
+        let Some(last) = self.last else {
+            self.last = Some(now);
+            return false;
+        };
+        if foreground_changed || now.saturating_duration_since(last) >= interval {
+            self.last = Some(now);
+            return true;
+        }
+        false
+    }
+}
+
+/// Notices when another window comes to the foreground; unhooks when dropped
+///
+/// Must be created on a thread that pumps messages (the tray loop), since
+/// out-of-context events are delivered through its message queue.
+pub struct ForegroundWatch {
+    hook: HWINEVENTHOOK,
+}
+
+impl ForegroundWatch {
+    pub fn new() -> Option<Self> {
+        let hook = unsafe {
+            SetWinEventHook(
+                EVENT_SYSTEM_FOREGROUND,
+                EVENT_SYSTEM_FOREGROUND,
+                None,
+                Some(foreground_event),
+                0,
+                0,
+                WINEVENT_OUTOFCONTEXT | WINEVENT_SKIPOWNPROCESS,
+            )
+        };
+        if hook.is_invalid() {
+            log::warn!("Failed to watch foreground changes; the overlay is only re-raised on its timer");
+            return None;
+        }
+        Some(Self { hook })
+    }
+
+    /// Whether the foreground window changed since the last call
+    pub fn take_changed(&self) -> bool {
+        FOREGROUND_CHANGED.swap(false, Ordering::SeqCst)
+    }
+}
+
+impl Drop for ForegroundWatch {
+    fn drop(&mut self) {
+        unsafe {
+            let _ = UnhookWinEvent(self.hook);
+        }
+    }
+}
+
+unsafe extern "system" fn foreground_event(
+    _hook: HWINEVENTHOOK,
+    _event: u32,
+    _hwnd: HWND,
+    _id_object: i32,
+    _id_child: i32,
+    _event_thread: u32,
+    _event_time: u32,
+) {
+    FOREGROUND_CHANGED.store(true, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(base: Instant, ms: u64) -> Instant {
+        base + Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn test_raises_once_per_interval_while_locked() {
+        /// WHY: Raising every frame would be wasteful; once per interval is enough to win back the top.
+        let mut guard = TopmostGuard::new(1000);
+        let base = Instant::now();
+        assert!(!guard.due(base, true, false), "Locking raised the overlay already");
+        assert!(!guard.due(at(base, 500), true, false));
+        assert!(guard.due(at(base, 1000), true, false));
+        assert!(!guard.due(at(base, 1500), true, false));
+        assert!(guard.due(at(base, 2100), true, false));
+        assert!(!guard.due(at(base, 3000), true, false), "The interval counts from the last raise");
+    }
+
+    #[test]
+    fn test_foreground_change_raises_immediately() {
+        /// WHY: A game taking the foreground covers the overlay at once, so waiting out the interval shows through.
+        let mut guard = TopmostGuard::new(1000);
+        let base = Instant::now();
+        guard.due(base, true, false);
+        assert!(guard.due(at(base, 100), true, true));
+        assert!(!guard.due(at(base, 200), true, false));
+    }
+
+    #[test]
+    fn test_unlocked_never_raises() {
+        /// WHY: The overlay is hidden while unlocked; raising it would do nothing useful.
+        let mut guard = TopmostGuard::new(1000);
+        let base = Instant::now();
+        assert!(!guard.due(base, false, true));
+        assert!(!guard.due(at(base, 5000), false, false));
+
+        // Unlocking restarts the clock for the next lock
+        guard.due(at(base, 6000), true, false);
+        guard.due(at(base, 6500), false, false);
+        assert!(!guard.due(at(base, 7200), true, false));
+        assert!(!guard.due(at(base, 8000), true, false));
+        assert!(guard.due(at(base, 8200), true, false));
+    }
+
+    #[test]
+    fn test_zero_interval_is_off() {
+        /// WHY: `reassert_topmost_ms: 0` turns re-raising off, foreground changes included.
+        let mut guard = TopmostGuard::new(0);
+        assert!(!guard.is_enabled());
+        let base = Instant::now();
+        assert!(!guard.due(base, true, false));
+        assert!(!guard.due(at(base, 60_000), true, true));
+    }
+}
@@ -2,9 +2,40 @@
 //!
 //! Provides a system tray icon with a context menu for settings, lock toggle, and exit.
 
-use crate::config::Config;
-use crate::overlay::{create_overlay, destroy_overlay, set_overlay_visible};
+use crate::animation::{
+    animation_alpha, effective_alpha, effective_animation, effective_fade, fade_alpha, SystemPreferences,
+};
+use crate::auto_unlock::{self, AutoUnlock};
+use crate::cli::{self, Command};
+use crate::config::{format_mode_overlays, step_opacity, Config, LockMode, OverlayTarget};
+use crate::countdown::LockCountdown;
+use crate::cursor::CursorHider;
+use crate::events::{self, Action, EventRecord, Trigger};
+use crate::icon_flash::{Frame, IconFlasher};
+use crate::led::KeyboardLed;
+use crate::lock_history::{self, SessionRecord};
+use crate::lock_state;
+use crate::overlay::{
+    create_overlay, create_overlay_over, destroy_overlay, overlay_alpha, raise_overlay,
+    set_overlay_alpha, set_overlay_image, set_overlay_status, set_overlay_visible,
+};
+use crate::overlay_image;
+use crate::peek::{Peek, PeekChange};
+use crate::platform::Platform;
+use crate::power::DisplayKeeper;
+use crate::register_failure;
+use crate::session;
 use crate::settings_dialog;
+use crate::shutdown::{self, Teardown};
+use crate::snooze::{self, Snooze};
+use crate::stats;
+use crate::target_window::WindowLock;
+use crate::toast::CornerToast;
+use crate::topmost::{ForegroundWatch, TopmostGuard};
+use crate::tray_menu::{
+    enable_item_text, lock_item_text, menu_action, menu_command, tooltip, MenuAction, MENU_ENABLE, MENU_LOCK,
+    MENU_OPEN_CONFIG, MENU_QUIT, MENU_SETTINGS,
+};
 use crate::AppState;
 use log::info;
 use muda::{Menu, MenuEvent, MenuItem, PredefinedMenuItem};
@@ -14,13 +45,21 @@ use tray_icon::{Icon, TrayIcon, TrayIconBuilder};
 use windows::Win32::Foundation::HWND;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
-/// Menu item IDs
-const MENU_LOCK: &str = "lock";
-const MENU_SETTINGS: &str = "settings";
-const MENU_EXIT: &str = "exit";
-
 /// Create the tray icon from embedded or generated icon
 fn create_tray_icon() -> Icon {
+    tray_icon_frame(Frame::Normal)
+}
+
+/// One frame of the tray icon; `Inverted` swaps the paw and background
+/// colors for `flash_while_locked`
+fn tray_icon_frame(frame: Frame) -> Icon {
+    const BLUE: [u8; 3] = [0x15, 0x65, 0xC0];
+    const ORANGE: [u8; 3] = [0xFF, 0x6D, 0x00];
+    let (background, paw) = match frame {
+        Frame::Normal => (BLUE, ORANGE),
+        Frame::Inverted => (ORANGE, BLUE),
+    };
+
     // Generate a simple colorblind-friendly icon programmatically
     // Using high contrast blue/orange which is distinguishable by most colorblind types
     let size = 64u32;
@@ -39,11 +78,9 @@ fn create_tray_icon() -> Icon {
             // Circular background
             let dist = ((cx * cx + cy * cy) as f32).sqrt();
             if dist < size as f32 / 2.0 - 2.0 {
-                // Deep blue background
-                rgba[idx] = 0x15;     // R
-                rgba[idx + 1] = 0x65; // G
-                rgba[idx + 2] = 0xC0; // B
-                rgba[idx + 3] = 255;  // A
+                // Background (deep blue, or orange when inverted)
+                rgba[idx..idx + 3].copy_from_slice(&background);
+                rgba[idx + 3] = 255; // A
 
                 // Draw paw pads in orange
                 // Main pad (center-bottom)
@@ -67,10 +104,8 @@ fn create_tray_icon() -> Icon {
                 }
 
                 if is_pad {
-                    // Bright orange for paw
-                    rgba[idx] = 0xFF;     // R
-                    rgba[idx + 1] = 0x6D; // G
-                    rgba[idx + 2] = 0x00; // B
+                    // Paw (bright orange, or blue when inverted)
+                    rgba[idx..idx + 3].copy_from_slice(&paw);
                 }
             } else {
                 // Transparent outside
@@ -86,35 +121,124 @@ fn create_tray_icon() -> Icon {
 }
 
 /// Run the main tray icon event loop
-pub fn run_tray_loop(state: Arc<AppState>, mut config: Config) -> Result<(), Box<dyn std::error::Error>> {
+///
+/// On quit, unlocks and removes the hooks by stopping `platform` before the
+/// overlay goes (see `shutdown.rs`).
+pub fn run_tray_loop(
+    state: Arc<AppState>,
+    mut config: Config,
+    platform: &mut dyn Platform,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // The kill switch is fixed at startup, like the hotkeys it turns off
+    let enabled = config.enabled;
+
     // Create menu
     let menu = Menu::new();
 
-    let lock_item = MenuItem::with_id(MENU_LOCK, "Lock Keyboard", true, None);
+    // Locking does nothing while disabled, so the item is greyed out
+    let lock_item = MenuItem::with_id(MENU_LOCK, lock_item_text(false), enabled, None);
+    let enable_item = MenuItem::with_id(MENU_ENABLE, enable_item_text(config.enabled), true, None);
+    let open_config_item = MenuItem::with_id(MENU_OPEN_CONFIG, "Open Config", true, None);
     let settings_item = MenuItem::with_id(MENU_SETTINGS, "Settings...", true, None);
-    let exit_item = MenuItem::with_id(MENU_EXIT, "Exit", true, None);
+    let quit_item = MenuItem::with_id(MENU_QUIT, "Quit", true, None);
 
     menu.append(&lock_item)?;
+    menu.append(&enable_item)?;
     menu.append(&PredefinedMenuItem::separator())?;
+    menu.append(&open_config_item)?;
     menu.append(&settings_item)?;
     menu.append(&PredefinedMenuItem::separator())?;
-    menu.append(&exit_item)?;
+    menu.append(&quit_item)?;
 
     // Create tray icon
     let icon = create_tray_icon();
-    let _tray_icon = TrayIconBuilder::new()
+    let mut shown_tooltip = tooltip(false, enabled, config.enabled);
+    let tray_icon = TrayIconBuilder::new()
         .with_menu(Box::new(menu))
-        .with_tooltip("PawGate - Keyboard Locker")
+        .with_tooltip(shown_tooltip)
         .with_icon(icon)
         .build()?;
+    // The config's `enabled` the enable item was last labeled for
+    let mut shown_enabled = config.enabled;
 
     info!("Tray icon created");
 
-    // Create overlay window (initially hidden)
-    let overlay_hwnd = create_overlay(Arc::clone(&state), &config);
+    // Hidden window that receives `pawgate lock/unlock/toggle` from the CLI
+    let _control_hwnd = crate::ipc::create_control_window(Arc::clone(&state));
+
+    // Create overlay windows (initially hidden)
+    let mut overlay_hwnds = create_overlay(Arc::clone(&state), &config);
+
+    // Pick up hand edits to the config file (config.json or --config) and the
+    // active profile without a restart
+    let (reload_tx, reload_rx) = std::sync::mpsc::channel();
+    let _config_watcher = Config::watch(Config::config_path(), move |mut new_config| {
+        new_config.apply_env_overrides();
+        let _ = reload_tx.send(new_config);
+    });
 
     // Track previous lock state to detect changes
     let mut prev_locked = false;
+    // Lock again once the loop is running if the last run was locked; the
+    // file is removed now, so a failed start doesn't restore it twice
+    let mut restore_lock = lock_state::should_restore(
+        config.restore_lock_state,
+        enabled,
+        lock_state::take(&lock_state::lock_state_path()),
+    );
+    // Whether this Windows session was on screen last frame
+    let mut prev_session_active = true;
+
+    // Grace period between the hotkey and the lock (delay is fixed at startup,
+    // like the hotkeys themselves)
+    let mut countdown = LockCountdown::new(config.lock_delay_ms);
+    // Last whole second painted on the overlay, to avoid repainting every frame
+    let mut shown_secs = None;
+
+    // Scroll Lock / Num Lock LED lit while locked
+    let mut led = KeyboardLed::new(config.led_indicator);
+    // Mouse pointer hidden while locked; restored when dropped
+    let mut cursor = CursorHider::new(config.hide_cursor);
+    // Display kept awake while locked; released when dropped
+    let mut display = DisplayKeeper::new(config.keep_awake);
+    // Window disabled by a `targetwindow` lock; enabled again when dropped
+    let mut window_lock = WindowLock::default();
+    // Corner notifications, when not left to Windows toasts
+    let mut toast = CornerToast::new(config.notification_position, config.notification_duration_ms);
+    // Tray icon flashing while locked
+    let mut flasher = IconFlasher::new(config.flash_while_locked, config.flash_interval_ms);
+    // Overlay raised again over other topmost windows while locked
+    let mut topmost = TopmostGuard::new(config.reassert_topmost_ms);
+    let mut foreground = topmost.is_enabled().then(ForegroundWatch::new).flatten();
+
+    // Hotkey/auto-lock snooze requested with `pawgate snooze`
+    let mut snooze = Snooze::new();
+
+    // Whether a quit while locked is waiting for its confirmation keypress
+    let mut awaiting_quit = false;
+
+    // Timed unlock of `pawgate test-lock` or `auto_unlock_secs`
+    let mut auto_unlock = AutoUnlock::new();
+    // Seconds of a test lock waiting for the lock to engage
+    let mut test_lock_secs = None;
+    // Last whole second of the auto-unlock shown on the overlay
+    let mut shown_unlock_secs = None;
+    // When the current lock began (Unix seconds) and what began it
+    let mut session_start: Option<(u64, Trigger)> = None;
+
+    // Input let through briefly with the peek hotkey (duration is fixed at
+    // startup, like the hotkey)
+    let mut peek = Peek::new(std::time::Duration::from_millis(u64::from(config.peek_duration_ms)));
+    // Last whole second of the peek shown on the overlay
+    let mut shown_peek_secs = None;
+
+    // When the current lock began, for the overlay animation
+    let mut lock_started: Option<std::time::Instant> = None;
+    let mut shown_alpha = overlay_alpha(&config);
+    // Reduced motion / high contrast, re-read at each lock
+    let mut system_prefs = SystemPreferences::default();
+    // Fade in progress after a lock state change: when it began and the alpha it started from
+    let mut fade: Option<(std::time::Instant, u8)> = None;
 
     // Main event loop
     let menu_receiver = MenuEvent::receiver();
@@ -127,18 +251,41 @@ pub fn run_tray_loop(state: Arc<AppState>, mut config: Config) -> Result<(), Box
 
         // Handle menu events (non-blocking)
         if let Ok(event) = menu_receiver.try_recv() {
-            match event.id.0.as_str() {
-                MENU_LOCK => {
-                    let current = state.locked.load(Ordering::SeqCst);
-                    state.locked.store(!current, Ordering::SeqCst);
-                    info!("Menu toggle lock: {}", !current);
+            let action = menu_action(event.id.0.as_str());
+            let locked = state.locked.load(Ordering::SeqCst);
+            // Items with a CLI twin do what that command does
+            match action.and_then(|action| menu_command(action, locked, config.enabled)) {
+                Some(Command::Lock) | Some(Command::Unlock) => {
+                    state.set_locked(!locked, Trigger::Menu);
+                    info!("Menu toggle lock: {}", !locked);
+                }
+                Some(command @ (Command::Enable | Command::Disable)) => {
+                    let enable = command == Command::Enable;
+                    match Config::set_saved_field("enabled", if enable { "true" } else { "false" }) {
+                        Ok(()) => {
+                            config.enabled = enable;
+                            info!("Menu set enabled={} (applies after restart)", enable);
+                        }
+                        Err(e) => log::error!("Failed to save enabled: {}", e),
+                    }
                 }
-                MENU_SETTINGS => {
+                Some(Command::ConfigEdit) => {
+                    if let Err(e) = cli::open_config() {
+                        log::warn!("Failed to open config: {}", e);
+                    }
+                }
+                _ => {}
+            }
+            match action {
+                Some(MenuAction::Settings) => {
                     info!("Opening settings dialog");
-                    // Show settings dialog
-                    if let Some(new_config) = settings_dialog::show_settings_dialog(&config) {
-                        // Save the new config
-                        if let Err(e) = new_config.save() {
+                    // The dialog blocks this loop until closed; that's not a hang
+                    state.heartbeat.suspend();
+                    let edited = settings_dialog::show_settings_dialog(&config);
+                    state.heartbeat.beat();
+                    if let Some(new_config) = edited {
+                        // Save only what was edited, not the env overrides
+                        if let Err(e) = config.save_changes(&new_config) {
                             log::error!("Failed to save config: {}", e);
                         } else {
                             config = new_config;
@@ -148,30 +295,449 @@ pub fn run_tray_loop(state: Arc<AppState>, mut config: Config) -> Result<(), Box
                         }
                     }
                 }
-                MENU_EXIT => {
+                Some(MenuAction::Quit) if locked && config.confirm_quit_while_locked => {
+                    info!("Exit requested while locked, waiting for confirmation");
+                    state.quit_pending.store(true, Ordering::SeqCst);
+                    awaiting_quit = true;
+                    set_overlay_status(&overlay_hwnds, Some(shutdown::CONFIRM_TEXT.to_string()));
+                }
+                // The background threads are stopped by the teardown below,
+                // after the lock is released
+                Some(MenuAction::Quit) => {
                     info!("Exit requested");
-                    state.should_quit.store(true, Ordering::SeqCst);
                     break;
                 }
                 _ => {}
             }
         }
 
+        // The keyboard hook answers a quit waiting for confirmation; an
+        // unlock by other means drops the question
+        if awaiting_quit {
+            if state.quit_confirmed.swap(false, Ordering::SeqCst) {
+                info!("Exit confirmed");
+                break;
+            }
+            if !state.quit_pending.load(Ordering::SeqCst) || !state.locked.load(Ordering::SeqCst) {
+                info!("Exit cancelled");
+                state.quit_pending.store(false, Ordering::SeqCst);
+                awaiting_quit = false;
+                set_overlay_status(&overlay_hwnds, None);
+            }
+        }
+
+        // Apply reloaded config. The hook's settings (hotkeys, passphrase,
+        // ...) keep their running values, since they need a restart.
+        // A save that changes nothing (e.g. the settings dialog's OK) is ignored
+        let reloaded = reload_rx.try_recv().ok().map(|mut new_config| {
+            let pending = new_config.keep_hook_fields(&config);
+            if !pending.is_empty() {
+                info!("Config changes to {} apply after a restart", pending.join(", "));
+            }
+            new_config
+        });
+        if let Some(new_config) = reloaded.filter(|new_config| *new_config != config) {
+            let changed = config.diff(&new_config);
+            if changed.iter().any(|field| Config::OVERLAY_FIELDS.contains(field)) {
+                // WHY: Destroy before creating, since WM_DESTROY clears the
+                // thread-local overlay state that create_overlay just filled in.
+                for hwnd in overlay_hwnds.drain(..) {
+                    destroy_overlay(hwnd);
+                }
+                overlay_hwnds = create_overlay(Arc::clone(&state), &new_config);
+                if prev_locked {
+                    set_overlay_image(&overlay_hwnds, load_overlay_image(&new_config));
+                }
+                for &hwnd in &overlay_hwnds {
+                    set_overlay_visible(hwnd, prev_locked);
+                }
+                shown_secs = None;
+                shown_unlock_secs = None;
+                shown_alpha = overlay_alpha(&new_config);
+                // The new windows were created fully shown or hidden
+                fade = None;
+            }
+            // Switching LEDs mid-lock would lose the state to restore
+            if !prev_locked {
+                led = KeyboardLed::new(new_config.led_indicator);
+                cursor = CursorHider::new(new_config.hide_cursor);
+                display = DisplayKeeper::new(new_config.keep_awake);
+            }
+            if changed.contains(&"notification_position") || changed.contains(&"notification_duration_ms") {
+                toast = CornerToast::new(new_config.notification_position, new_config.notification_duration_ms);
+            }
+            if changed.contains(&"flash_while_locked") || changed.contains(&"flash_interval_ms") {
+                flasher = IconFlasher::new(new_config.flash_while_locked, new_config.flash_interval_ms);
+                if let Err(e) = tray_icon.set_icon(Some(create_tray_icon())) {
+                    log::warn!("Failed to update tray icon: {}", e);
+                }
+            }
+            if changed.contains(&"reassert_topmost_ms") {
+                topmost = TopmostGuard::new(new_config.reassert_topmost_ms);
+                foreground = topmost.is_enabled().then(ForegroundWatch::new).flatten();
+            }
+            config = new_config;
+            info!("Config reloaded ({} changed)", changed.join(", "));
+        }
+
+        // Back from a session switch while locked: the hooks may have been
+        // dropped and the monitors may have changed (e.g. over RDP)
+        let session_active = state.session_active.load(Ordering::SeqCst);
+        if session::needs_reinstall(prev_session_active, session_active, prev_locked) {
+            info!("Session active again while locked, restoring the lock");
+            state.reinstall_hooks.store(true, Ordering::SeqCst);
+            for hwnd in overlay_hwnds.drain(..) {
+                destroy_overlay(hwnd);
+            }
+            overlay_hwnds = create_overlay(Arc::clone(&state), &config);
+            set_overlay_image(&overlay_hwnds, load_overlay_image(&config));
+            for &hwnd in &overlay_hwnds {
+                set_overlay_visible(hwnd, true);
+            }
+            shown_secs = None;
+            shown_unlock_secs = None;
+            shown_peek_secs = None;
+            shown_alpha = overlay_alpha(&config);
+            fade = None;
+        }
+        prev_session_active = session_active;
+
+        let now = std::time::Instant::now();
+        state.heartbeat.beat();
+        toast.update(now);
+
+        // WHY: Only now, with the heartbeat running, so the watchdog covers
+        // the restored lock from the start
+        if std::mem::take(&mut restore_lock) && !state.locked.load(Ordering::SeqCst) {
+            info!("PawGate was locked when it last stopped, locking again");
+            state.set_locked(true, Trigger::Restored);
+        }
+        // Whether the countdown had the overlay up right before this frame's lock
+        let mut counted_down = false;
+
+        // Tell the user once if `on_register_failure` left PawGate without a
+        // hotkey; shown even with notifications off, since they asked for it
+        if state.hotkey_unavailable.swap(false, Ordering::SeqCst) {
+            let text = format!(
+                "{}\nLock from the tray menu instead.",
+                register_failure::failure_message(&config.hotkeys)
+            );
+            if toast.is_enabled() {
+                toast.show("PawGate", &text, now);
+            } else if let Err(e) = crate::notify::show_toast("PawGate", &text, config.notification_duration_ms) {
+                log::warn!("Failed to show notification: {}", e);
+            }
+        }
+
+        // Start, replace or end a snooze requested from the CLI
+        let request = state.snooze_request.swap(snooze::NO_REQUEST, Ordering::SeqCst);
+        if request == 0 {
+            snooze.cancel();
+            info!("Snooze cancelled");
+        } else if request != snooze::NO_REQUEST {
+            snooze.start(now, std::time::Duration::from_secs(u64::from(request) * 60));
+            info!("Snoozed for {} minutes", request);
+        }
+        if snooze.tick(now) {
+            info!("Snooze over, hotkey re-armed");
+        }
+        state.snoozed.store(snooze.is_active(now), Ordering::SeqCst);
+
+        // Lock for a test lock requested from the CLI
+        let request = state.test_lock_request.swap(auto_unlock::NO_REQUEST, Ordering::SeqCst);
+        if request != auto_unlock::NO_REQUEST {
+            info!("Test lock for {} seconds", request);
+            if prev_locked {
+                // Already locked: just (re)start the timer
+                auto_unlock.start(now, std::time::Duration::from_secs(u64::from(request)));
+            } else {
+                test_lock_secs = Some(request);
+                state.lock_pending.store(false, Ordering::SeqCst);
+                state.set_locked(true, Trigger::Cli);
+            }
+        }
+
+        // Without hooks nothing would be blocked, so don't pretend to lock
+        // when the CLI or an embedding app asks
+        if !enabled && state.locked.load(Ordering::SeqCst) {
+            info!("Ignoring lock: PawGate is disabled");
+            test_lock_secs = None;
+            state.set_locked(false, Trigger::from_u8(state.lock_trigger.load(Ordering::SeqCst)));
+        }
+
+        // Drive the grace countdown requested by the keyboard hook
+        let mut lock_pending = state.lock_pending.load(Ordering::SeqCst);
+        if lock_pending && state.locked.load(Ordering::SeqCst) {
+            // Locked some other way (menu, idle) while counting down
+            state.lock_pending.store(false, Ordering::SeqCst);
+            lock_pending = false;
+        }
+        if lock_pending && !countdown.is_pending() {
+            countdown.start(now);
+            if config.overlay_target == OverlayTarget::ActiveMonitor {
+                recreate_overlay(&mut overlay_hwnds, &state, &config);
+            }
+            for &hwnd in &overlay_hwnds {
+                set_overlay_visible(hwnd, true);
+            }
+        } else if !lock_pending && countdown.is_pending() {
+            countdown.cancel();
+            shown_secs = None;
+            set_overlay_status(&overlay_hwnds, None);
+            for &hwnd in &overlay_hwnds {
+                set_overlay_visible(hwnd, prev_locked);
+            }
+            info!("Pending lock cancelled");
+        }
+        if countdown.tick(now) {
+            counted_down = true;
+            shown_secs = None;
+            set_overlay_status(&overlay_hwnds, None);
+            state.lock_pending.store(false, Ordering::SeqCst);
+            // The countdown was started by the hotkey
+            state.set_locked(true, Trigger::Hotkey);
+        } else if let Some(remaining) = countdown.remaining(now) {
+            // Round up so the display never shows "0" while still waiting
+            let secs = remaining.as_millis().div_ceil(1000);
+            if shown_secs != Some(secs) {
+                let text = format!("Locking in {}... Press hotkey to cancel", secs);
+                set_overlay_status(&overlay_hwnds, Some(text));
+                shown_secs = Some(secs);
+            }
+        }
+
         // Check for lock state changes
         let current_locked = state.locked.load(Ordering::SeqCst);
         if current_locked != prev_locked {
-            if let Some(hwnd) = overlay_hwnd {
-                set_overlay_visible(hwnd, current_locked);
+            if current_locked {
+                system_prefs = SystemPreferences::query();
+                if config.lock_mode == LockMode::TargetWindow {
+                    // Cover just the window being locked
+                    match window_lock.lock() {
+                        Some(area) => {
+                            for hwnd in overlay_hwnds.drain(..) {
+                                destroy_overlay(hwnd);
+                            }
+                            overlay_hwnds = create_overlay_over(Arc::clone(&state), &config, area);
+                            shown_alpha = overlay_alpha(&config);
+                        }
+                        None => log::warn!("No window to lock in the foreground; nothing is blocked"),
+                    }
+                } else if config.overlay_target == OverlayTarget::ActiveMonitor && !counted_down && fade.is_none() {
+                    // Follow the foreground window, unless the overlay is
+                    // already up from the countdown or a fade-out
+                    recreate_overlay(&mut overlay_hwnds, &state, &config);
+                    shown_alpha = overlay_alpha(&config);
+                }
+            } else {
+                window_lock.unlock();
+            }
+            let fade_duration = effective_fade(config.fade_ms, system_prefs);
 
-                // Update menu item text
-                let new_text = if current_locked {
-                    "Unlock Keyboard"
-                } else {
-                    "Lock Keyboard"
-                };
-                lock_item.set_text(new_text);
+            if current_locked || fade_duration.is_zero() {
+                // Decode the picture only while it's shown, so edits to the file
+                // are picked up on the next lock
+                let image = if current_locked { load_overlay_image(&config) } else { None };
+                set_overlay_image(&overlay_hwnds, image);
+
+                let fading_out = fade.take().is_some();
+                if current_locked && !fade_duration.is_zero() {
+                    // Pick up from wherever a countdown or fade-out left the overlay
+                    let from = if counted_down || fading_out { shown_alpha } else { 0 };
+                    set_overlay_alpha(&overlay_hwnds, from);
+                    shown_alpha = from;
+                    fade = Some((now, from));
+                }
+
+                for &hwnd in &overlay_hwnds {
+                    set_overlay_visible(hwnd, current_locked);
+                }
+            } else {
+                // Leave the overlay up until it has faded out
+                fade = Some((now, shown_alpha));
             }
+
+            lock_item.set_text(lock_item_text(current_locked));
             prev_locked = current_locked;
+            lock_started = current_locked.then(std::time::Instant::now);
+
+            led.set_locked(current_locked);
+            cursor.set_locked(current_locked);
+            display.set_locked(current_locked);
+
+            // A test lock, or safe mode, unlocks by itself after a while
+            let unlock_after = if current_locked {
+                test_lock_secs.take().or(config.auto_unlock_secs).filter(|&secs| secs > 0)
+            } else {
+                None
+            };
+            match unlock_after {
+                Some(secs) => auto_unlock.start(now, std::time::Duration::from_secs(u64::from(secs))),
+                None => auto_unlock.cancel(),
+            }
+            if shown_unlock_secs.take().is_some() {
+                set_overlay_status(&overlay_hwnds, None);
+            }
+
+            let sound = if current_locked { &config.lock_sound } else { &config.unlock_sound };
+            if let Some(path) = sound {
+                crate::sound::play_wav(path);
+            }
+
+            if config.restore_lock_state {
+                if let Err(e) = lock_state::save(&lock_state::lock_state_path(), current_locked) {
+                    log::warn!("Failed to save lock state: {}", e);
+                }
+            }
+
+            // Add this lock's blocked keystrokes to the lifetime totals
+            let mut text = config.notification_text(current_locked);
+            if current_locked {
+                let trigger = Trigger::from_u8(state.lock_trigger.load(Ordering::SeqCst));
+                session_start = Some((events::unix_now(), trigger));
+            } else {
+                let blocked = u64::from(state.blocked_keys.swap(0, Ordering::SeqCst));
+                if let Err(e) = stats::record_lock(&stats::stats_path(), blocked) {
+                    log::warn!("Failed to update stats: {}", e);
+                }
+                if let Some(start) = session_start.take() {
+                    record_session(&config, start, blocked);
+                }
+                if let Some(line) = config.blocked_keys_text(blocked) {
+                    text = format!("{}\n{}", text, line);
+                }
+            }
+
+            if config.notifications_enabled {
+                if toast.is_enabled() {
+                    toast.show("PawGate", &text, now);
+                } else if let Err(e) = crate::notify::show_toast("PawGate", &text, config.notification_duration_ms) {
+                    log::warn!("Failed to show notification: {}", e);
+                }
+            }
+
+            if config.log_events {
+                let action = if current_locked { Action::Lock } else { Action::Unlock };
+                let trigger = Trigger::from_u8(state.lock_trigger.load(Ordering::SeqCst));
+                let record = EventRecord::now(action, trigger);
+                if let Err(e) = events::append(&events::events_path(), &record, events::MAX_LOG_BYTES) {
+                    log::warn!("Failed to write event log: {}", e);
+                }
+            }
+        }
+
+        // Keep the tooltip and enable item in step with the lock and config
+        let text = tooltip(current_locked, enabled, config.enabled);
+        if text != shown_tooltip {
+            if let Err(e) = tray_icon.set_tooltip(Some(text)) {
+                log::warn!("Failed to update tray tooltip: {}", e);
+            }
+            shown_tooltip = text;
+        }
+        if let Some(frame) = flasher.update(current_locked, now) {
+            if let Err(e) = tray_icon.set_icon(Some(tray_icon_frame(frame))) {
+                log::warn!("Failed to update tray icon: {}", e);
+            }
+        }
+        if config.enabled != shown_enabled {
+            enable_item.set_text(enable_item_text(config.enabled));
+            shown_enabled = config.enabled;
+        }
+
+        // Let input through during a peek, then enforce the lock again
+        match peek.update(state.peeking.load(Ordering::SeqCst), current_locked, now) {
+            Some(PeekChange::Started) => info!("Peeking for {} ms", config.peek_duration_ms),
+            Some(change) => {
+                info!("Peek ended ({:?})", change);
+                state.peeking.store(false, Ordering::SeqCst);
+                shown_peek_secs = None;
+                shown_unlock_secs = None;
+                set_overlay_status(&overlay_hwnds, None);
+            }
+            None => {}
+        }
+        if let Some(remaining) = peek.remaining(now) {
+            let secs = remaining.as_millis().div_ceil(1000);
+            if shown_peek_secs != Some(secs) {
+                let text = format!("Peeking - locking again in {}...", secs);
+                set_overlay_status(&overlay_hwnds, Some(text));
+                shown_peek_secs = Some(secs);
+            }
+        }
+
+        // Opacity hotkeys pressed while locked; the animation below picks up
+        // the new value
+        let steps = state.opacity_steps.swap(0, Ordering::SeqCst);
+        if steps != 0 {
+            let opacity = step_opacity(config.effective_opacity(), steps);
+            config.set_effective_opacity(opacity);
+            info!("Overlay opacity set to {}", opacity);
+            if config.persist_opacity {
+                save_opacity(&config);
+            }
+        }
+
+        // Release a timed lock once its time is up
+        if auto_unlock.tick(now) {
+            info!("Auto-unlock time reached");
+            state.set_locked(false, Trigger::AutoUnlock);
+        } else if let Some(remaining) = auto_unlock.remaining(now).filter(|_| !peek.is_active()) {
+            let secs = remaining.as_millis().div_ceil(1000);
+            if shown_unlock_secs != Some(secs) {
+                let text = format!("Test lock - unlocking in {}...", secs);
+                set_overlay_status(&overlay_hwnds, Some(text));
+                shown_unlock_secs = Some(secs);
+            }
+        }
+
+        // Win the top back from games and other always-on-top windows
+        let foreground_changed = foreground.as_ref().is_some_and(ForegroundWatch::take_changed);
+        if topmost.due(now, lock_started.is_some(), foreground_changed) {
+            raise_overlay(&overlay_hwnds);
+        }
+
+        // Animate the overlay opacity while locked
+        if let Some(started) = lock_started {
+            let mut alpha = animation_alpha(
+                effective_animation(config.overlay_animation, system_prefs),
+                effective_alpha(overlay_alpha(&config), system_prefs),
+                started.elapsed(),
+                std::time::Duration::from_millis(u64::from(config.animation_period_ms)),
+            );
+            if let Some((fade_started, from)) = fade {
+                let duration = effective_fade(config.fade_ms, system_prefs);
+                alpha = fade_alpha(from, alpha, fade_started.elapsed(), duration);
+                if fade_started.elapsed() >= duration {
+                    fade = None;
+                }
+            }
+            // Dim the overlay while peeking, so it's clear input gets through
+            if peek.is_active() {
+                alpha /= 3;
+            }
+            if alpha != shown_alpha {
+                set_overlay_alpha(&overlay_hwnds, alpha);
+                shown_alpha = alpha;
+            }
+        } else if let Some((fade_started, from)) = fade {
+            // Fading out after an unlock
+            let duration = effective_fade(config.fade_ms, system_prefs);
+            let alpha = fade_alpha(from, 0, fade_started.elapsed(), duration);
+            if alpha != shown_alpha {
+                set_overlay_alpha(&overlay_hwnds, alpha);
+                shown_alpha = alpha;
+            }
+            if fade_started.elapsed() >= duration {
+                fade = None;
+                set_overlay_image(&overlay_hwnds, None);
+                for &hwnd in &overlay_hwnds {
+                    set_overlay_visible(hwnd, false);
+                }
+            }
+        } else if shown_alpha != overlay_alpha(&config) {
+            // Restore the static opacity for the next lock
+            set_overlay_alpha(&overlay_hwnds, overlay_alpha(&config));
+            shown_alpha = overlay_alpha(&config);
         }
 
         // Process Windows messages for proper event handling
@@ -193,10 +759,102 @@ pub fn run_tray_loop(state: Arc<AppState>, mut config: Config) -> Result<(), Box
         std::thread::sleep(std::time::Duration::from_millis(16)); // ~60fps
     }
 
-    // Cleanup
-    if let Some(hwnd) = overlay_hwnd {
+    // Cleanup: unlock first, then the hooks, then the overlay
+    let locked = state.locked.load(Ordering::SeqCst);
+    shutdown::shut_down(
+        &mut TrayTeardown {
+            state: &state,
+            config: &config,
+            window_lock: &mut window_lock,
+            platform,
+            overlay_hwnds,
+            session_start,
+        },
+        locked,
+    );
+
+    Ok(())
+}
+
+/// What [`shutdown::shut_down`] tears down when the tray loop exits
+struct TrayTeardown<'a> {
+    state: &'a AppState,
+    config: &'a Config,
+    window_lock: &'a mut WindowLock,
+    platform: &'a mut dyn Platform,
+    overlay_hwnds: Vec<HWND>,
+    session_start: Option<(u64, Trigger)>,
+}
+
+impl Teardown for TrayTeardown<'_> {
+    fn unlock(&mut self) {
+        self.state.lock_pending.store(false, Ordering::SeqCst);
+        self.state.set_locked(false, Trigger::Quit);
+        self.window_lock.unlock();
+        // WHY: The loop that logs transitions has ended, so log this one here.
+        // The lock state file is left alone, so restore_lock_state still
+        // brings the lock back at the next start.
+        if self.config.log_events {
+            let record = EventRecord::now(Action::Unlock, Trigger::Quit);
+            if let Err(e) = events::append(&events::events_path(), &record, events::MAX_LOG_BYTES) {
+                log::warn!("Failed to write event log: {}", e);
+            }
+        }
+        if let Some(start) = self.session_start.take() {
+            let blocked = u64::from(self.state.blocked_keys.load(Ordering::SeqCst));
+            record_session(self.config, start, blocked);
+        }
+    }
+
+    fn remove_hooks(&mut self) {
+        self.state.should_quit.store(true, Ordering::SeqCst);
+        self.platform.stop();
+    }
+
+    fn remove_overlay(&mut self) {
+        for hwnd in self.overlay_hwnds.drain(..) {
+            destroy_overlay(hwnd);
+        }
+    }
+}
+
+/// Add a lock that just ended to the session history
+fn record_session(config: &Config, (start, trigger): (u64, Trigger), blocked: u64) {
+    if config.session_history_limit == 0 {
+        return;
+    }
+    let record = SessionRecord::new(start, events::unix_now(), trigger, blocked);
+    let limit = config.session_history_limit as usize;
+    if let Err(e) = lock_history::append(&lock_history::sessions_path(), &record, limit) {
+        log::warn!("Failed to write session history: {}", e);
+    }
+}
+
+/// Replace the overlay windows with freshly placed ones (still hidden)
+///
+/// WHY: Destroy before creating, since WM_DESTROY clears the thread-local
+/// overlay state that create_overlay fills in.
+fn recreate_overlay(hwnds: &mut Vec<HWND>, state: &Arc<AppState>, config: &Config) {
+    for hwnd in hwnds.drain(..) {
         destroy_overlay(hwnd);
     }
+    *hwnds = create_overlay(Arc::clone(state), config);
+}
 
-    Ok(())
+/// Decode the configured overlay picture, if any
+fn load_overlay_image(config: &Config) -> Option<overlay_image::OverlayImage> {
+    config.overlay_image.as_deref().and_then(overlay_image::load)
+}
+
+/// Save an opacity changed with the opacity hotkeys to the config file
+fn save_opacity(config: &Config) {
+    // A per-mode opacity is the one the hotkeys changed
+    let result = if config.mode_overlay().is_some_and(|overlay| overlay.opacity.is_some()) {
+        Config::set_saved_field("lock_mode_overlays", &format_mode_overlays(&config.lock_mode_overlays))
+    } else {
+        Config::set_saved_field("opacity", &config.opacity.to_string())
+    };
+    if let Err(e) = result {
+        log::warn!("Failed to save opacity: {}", e);
+    }
 }
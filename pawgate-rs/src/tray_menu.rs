@@ -0,0 +1,142 @@
+//! Tray menu items and the actions behind them
+//!
+//! Kept apart from the Win32 menu in `tray.rs` so the mapping can be tested.
+//! Items that have a CLI equivalent map to that [`Command`], which the tray
+//! loop carries out in-process the same way the CLI would.
+
+use crate::cli::Command;
+
+/// Menu item IDs
+pub const MENU_LOCK: &str = "lock";
+pub const MENU_ENABLE: &str = "enable";
+pub const MENU_OPEN_CONFIG: &str = "open_config";
+pub const MENU_SETTINGS: &str = "settings";
+pub const MENU_QUIT: &str = "exit";
+
+/// What a tray menu item does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    /// Lock now, or unlock if locked
+    ToggleLock,
+    /// Flip `enabled` in the config
+    ToggleEnabled,
+    /// Open the config file in its default editor
+    OpenConfig,
+    /// Show the settings dialog
+    Settings,
+    /// Exit PawGate
+    Quit,
+}
+
+/// The action of the menu item with this ID, if it's one of ours
+pub fn menu_action(id: &str) -> Option<MenuAction> {
+    match id {
+        MENU_LOCK => Some(MenuAction::ToggleLock),
+        MENU_ENABLE => Some(MenuAction::ToggleEnabled),
+        MENU_OPEN_CONFIG => Some(MenuAction::OpenConfig),
+        MENU_SETTINGS => Some(MenuAction::Settings),
+        MENU_QUIT => Some(MenuAction::Quit),
+        _ => None,
+    }
+}
+
+/// The CLI command with the same effect as `action`, given whether PawGate
+/// is locked and whether the config has it enabled
+///
+/// None for actions only the tray has (Settings, Quit).
+pub fn menu_command(action: MenuAction, locked: bool, enabled: bool) -> Option<Command> {
+    match action {
+        MenuAction::ToggleLock if locked => Some(Command::Unlock),
+        MenuAction::ToggleLock => Some(Command::Lock),
+        MenuAction::ToggleEnabled if enabled => Some(Command::Disable),
+        MenuAction::ToggleEnabled => Some(Command::Enable),
+        MenuAction::OpenConfig => Some(Command::ConfigEdit),
+        MenuAction::Settings | MenuAction::Quit => None,
+    }
+}
+
+/// Label of the lock item
+pub fn lock_item_text(locked: bool) -> &'static str {
+    if locked {
+        "Unlock Keyboard"
+    } else {
+        "Lock Now"
+    }
+}
+
+/// Label of the enable/disable item, given whether the config has PawGate enabled
+pub fn enable_item_text(enabled: bool) -> &'static str {
+    if enabled {
+        "Disable"
+    } else {
+        "Enable"
+    }
+}
+
+/// Tray icon tooltip
+///
+/// `running` is whether this instance started enabled; `enabled` is what
+/// the config says now, which only takes effect at the next start.
+pub fn tooltip(locked: bool, running: bool, enabled: bool) -> &'static str {
+    match (running, enabled) {
+        (false, false) => "PawGate - Disabled",
+        (false, true) => "PawGate - Disabled (enabled after restart)",
+        (true, false) if locked => "PawGate - Locked (disabled after restart)",
+        (true, false) => "PawGate - Unlocked (disabled after restart)",
+        (true, true) if locked => "PawGate - Locked",
+        (true, true) => "PawGate - Unlocked",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_menu_ids_map_to_actions() {
+        /// WHY: A typo in an ID would leave a menu item silently doing nothing.
+        for (id, action) in [
+            (MENU_LOCK, MenuAction::ToggleLock),
+            (MENU_ENABLE, MenuAction::ToggleEnabled),
+            (MENU_OPEN_CONFIG, MenuAction::OpenConfig),
+            (MENU_SETTINGS, MenuAction::Settings),
+            (MENU_QUIT, MenuAction::Quit),
+        ] {
+            assert_eq!(menu_action(id), Some(action), "{}", id);
+        }
+        assert_eq!(menu_action("muda-generated-id"), None);
+    }
+
+    #[test]
+    fn test_lock_item_follows_state() {
+        /// WHY: The same item locks or unlocks, like `pawgate lock` and `pawgate unlock`.
+        assert_eq!(menu_command(MenuAction::ToggleLock, false, true), Some(Command::Lock));
+        assert_eq!(menu_command(MenuAction::ToggleLock, true, true), Some(Command::Unlock));
+    }
+
+    #[test]
+    fn test_enable_item_follows_config() {
+        /// WHY: The tray must flip the saved setting exactly like `pawgate enable`/`disable`.
+        assert_eq!(menu_command(MenuAction::ToggleEnabled, false, true), Some(Command::Disable));
+        assert_eq!(menu_command(MenuAction::ToggleEnabled, false, false), Some(Command::Enable));
+        assert_eq!(enable_item_text(true), "Disable");
+        assert_eq!(enable_item_text(false), "Enable");
+    }
+
+    #[test]
+    fn test_open_config_and_tray_only_actions() {
+        /// WHY: Open Config shares `pawgate config edit`; Settings and Quit have no CLI twin.
+        assert_eq!(menu_command(MenuAction::OpenConfig, false, true), Some(Command::ConfigEdit));
+        assert_eq!(menu_command(MenuAction::Settings, false, true), None);
+        assert_eq!(menu_command(MenuAction::Quit, true, true), None);
+    }
+
+    #[test]
+    fn test_tooltip_reflects_state() {
+        /// WHY: Hovering the icon is the quickest way to check what PawGate is doing.
+        assert_eq!(tooltip(false, true, true), "PawGate - Unlocked");
+        assert_eq!(tooltip(true, true, true), "PawGate - Locked");
+        assert_eq!(tooltip(false, false, false), "PawGate - Disabled");
+        assert!(tooltip(false, true, false).contains("after restart"), "Pending change is shown");
+    }
+}
@@ -0,0 +1,153 @@
+//! Integration tests for embedding PawGate as a library
+//!
+//! These only use the shared-state side of the API, which needs no hooks or
+//! message loop. Like the rest of the crate they build for Windows only;
+//! they just don't block the keyboard of the machine running them.
+
+use pawgate::config::{Config, LockMode};
+use pawgate::events::Trigger;
+use pawgate::platform::{Platform, SimulatedPlatform};
+use pawgate::{AppState, PawGate};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_lock_and_unlock_with_custom_config() {
+    /// WHY: Downstream apps drive the lock directly and read it back to update their own UI.
+    let config = Config {
+        hotkeys: vec!["ctrl+alt+p".to_string()],
+        lock_mode: LockMode::KeyboardOnly,
+        ..Config::default()
+    };
+    let gate = PawGate::with_config(config);
+    assert_eq!(gate.config().hotkeys, vec!["ctrl+alt+p"]);
+    assert!(!gate.is_locked(), "A new instance starts unlocked");
+
+    gate.lock();
+    assert!(gate.is_locked());
+    gate.lock();
+    assert!(gate.is_locked(), "Locking twice stays locked");
+
+    gate.unlock();
+    assert!(!gate.is_locked());
+}
+
+#[test]
+fn test_api_changes_are_attributed() {
+    /// WHY: The event log must tell library-driven locks apart from the hotkey.
+    let gate = PawGate::new();
+    gate.lock();
+    let trigger = Trigger::from_u8(gate.state().lock_trigger.load(Ordering::SeqCst));
+    assert_eq!(trigger, Trigger::Api);
+}
+
+#[test]
+fn test_lock_skips_pending_countdown() {
+    /// WHY: A programmatic lock takes effect immediately, even with lock_delay_ms set.
+    let gate = PawGate::with_config(Config {
+        lock_delay_ms: 3000,
+        ..Config::default()
+    });
+    gate.state().lock_pending.store(true, Ordering::SeqCst);
+    gate.lock();
+    assert!(gate.is_locked());
+    assert!(!gate.state().lock_pending.load(Ordering::SeqCst));
+}
+
+#[test]
+fn test_state_change_callback_follows_lock_cycle() {
+    /// WHY: External indicators rely on hearing every transition with the new state.
+    let gate = PawGate::new();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Arc::clone(&seen);
+    gate.on_state_change(Box::new(move |locked| recorder.lock().unwrap().push(locked)));
+
+    gate.lock();
+    gate.lock();
+    gate.unlock();
+    assert_eq!(*seen.lock().unwrap(), vec![true, false], "Repeated lock() isn't a transition");
+}
+
+#[test]
+fn test_disabled_config_skips_hotkey_registration() {
+    /// WHY: The kill switch must leave input alone: no hook thread, so nothing can be blocked.
+    let mut gate = PawGate::with_config(Config {
+        enabled: false,
+        ..Config::default()
+    });
+    gate.register_hotkeys().unwrap();
+    assert!(!gate.hotkeys_registered());
+}
+
+#[test]
+fn test_simulated_platform_follows_lock_and_unlock() {
+    /// WHY: In tests the simulated platform stands in for the hooks, so it must see every transition.
+    let platform = SimulatedPlatform::new();
+    let mut gate = PawGate::with_platform(Config::default(), platform.clone());
+    assert!(!gate.hotkeys_registered());
+
+    gate.register_hotkeys().unwrap();
+    assert!(gate.hotkeys_registered());
+    assert!(platform.is_running());
+
+    gate.lock();
+    assert!(gate.is_locked());
+    assert!(platform.is_locked());
+
+    gate.unlock();
+    assert!(!gate.is_locked());
+    assert!(!platform.is_locked());
+    assert_eq!(platform.transitions(), vec![true, false]);
+}
+
+#[test]
+fn test_simulated_platform_sees_other_triggers() {
+    /// WHY: Locks also come from the hotkey and idle timer, not just the API.
+    let platform = SimulatedPlatform::new();
+    let mut gate = PawGate::with_platform(Config::default(), platform.clone());
+    gate.register_hotkeys().unwrap();
+
+    gate.state().set_locked(true, Trigger::AutoIdle);
+    assert!(gate.is_locked());
+    assert!(platform.is_locked());
+}
+
+#[test]
+fn test_disabled_config_leaves_platform_stopped() {
+    /// WHY: The kill switch applies whichever platform enforces the lock.
+    let platform = SimulatedPlatform::new();
+    let mut gate = PawGate::with_platform(
+        Config {
+            enabled: false,
+            ..Config::default()
+        },
+        platform.clone(),
+    );
+    gate.register_hotkeys().unwrap();
+    assert!(!platform.is_running());
+    gate.lock();
+    assert!(!platform.is_locked(), "Nothing enforces the lock, so nothing is blocked");
+}
+
+/// A platform whose hooks never install
+struct UnhookablePlatform;
+
+impl Platform for UnhookablePlatform {
+    fn start(&mut self, _state: Arc<AppState>, _config: &Config) -> Result<(), String> {
+        Err("Failed to install keyboard hook".to_string())
+    }
+
+    fn is_running(&self) -> bool {
+        false
+    }
+
+    fn stop(&mut self) {}
+}
+
+#[test]
+fn test_failed_start_reaches_caller() {
+    /// WHY: An app that thinks the hook is in place would show a lock that blocks nothing.
+    let mut gate = PawGate::with_platform(Config::default(), UnhookablePlatform);
+    assert_eq!(gate.register_hotkeys(), Err("Failed to install keyboard hook".to_string()));
+    assert!(!gate.hotkeys_registered());
+}